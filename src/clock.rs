@@ -0,0 +1,57 @@
+// Source of "now" for scheduling, so time-dependent behaviour can be tested
+// without sleeping. The database uses SystemClock unless a test injects a
+// FixedClock.
+
+use chrono::{DateTime, Utc};
+
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// A clock that only moves when told to. Clones share the same time, so a test
+// can keep a handle and advance the clock a Database was given.
+#[cfg(test)]
+#[derive(Clone)]
+pub struct FixedClock {
+    now: std::rc::Rc<std::cell::Cell<DateTime<Utc>>>,
+}
+
+#[cfg(test)]
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: std::rc::Rc::new(std::cell::Cell::new(now)),
+        }
+    }
+
+    pub fn at(rfc3339: &str) -> Self {
+        Self::new(
+            DateTime::parse_from_rfc3339(rfc3339)
+                .expect("valid RFC 3339 timestamp")
+                .with_timezone(&Utc),
+        )
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.get()
+    }
+}
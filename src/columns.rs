@@ -0,0 +1,194 @@
+// `--columns`: which fields a list command shows. Rows are flattened into a
+// single JSON object; JSON output keeps only the chosen keys and tables show
+// them in the order they were given.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use unicode_width::UnicodeWidthStr;
+
+use crate::models::{Progress, Topic};
+use crate::tui::pad_to_width;
+
+// A `topic list` row: the topic's fields, then its progress (whose id and
+// topic_id would only repeat the topic's)
+pub const TOPIC_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "created_at",
+    "updated_at",
+    "tags",
+    "archived",
+    "suspended",
+    "mastery_level",
+    "times_reviewed",
+    "times_succeeded",
+    "last_reviewed",
+    "next_review",
+    "notes",
+    "skill_level",
+    "assessment_method",
+    "last_assessed",
+    "difficulty",
+];
+
+// A `sessions` row (SessionRow)
+pub const SESSION_COLUMNS: &[&str] = &[
+    "id",
+    "topic_id",
+    "session_type",
+    "started_at",
+    "ended_at",
+    "skill_level_at_start",
+    "outcome",
+    "summary",
+    "notes",
+    "duration_secs",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns(Vec<String>);
+
+impl Columns {
+    // Comma-separated names, each one of `known`; repeats are dropped
+    pub fn parse(spec: &str, known: &[&str]) -> Result<Self, String> {
+        let mut columns: Vec<String> = Vec::new();
+        for name in spec.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if !known.contains(&name) {
+                return Err(format!(
+                    "Unknown column '{}'. Valid columns: {}",
+                    name,
+                    known.join(", ")
+                ));
+            }
+            if !columns.iter().any(|c| c == name) {
+                columns.push(name.to_string());
+            }
+        }
+        if columns.is_empty() {
+            return Err("--columns needs at least one column name".to_string());
+        }
+        Ok(Self(columns))
+    }
+
+    // Just the chosen keys of `row`
+    pub fn project(&self, row: &Map<String, Value>) -> Value {
+        Value::Object(
+            self.0
+                .iter()
+                .map(|c| (c.clone(), row.get(c).cloned().unwrap_or(Value::Null)))
+                .collect(),
+        )
+    }
+
+    // Header, rule and one line per row, each column as wide as its widest cell
+    pub fn table(&self, rows: &[Map<String, Value>]) -> Vec<String> {
+        let header: Vec<String> = self.0.iter().map(|c| c.to_uppercase()).collect();
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                self.0
+                    .iter()
+                    .map(|c| cell(row.get(c).unwrap_or(&Value::Null)))
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = (0..self.0.len())
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|r| r[i].width())
+                    .chain([header[i].width()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                // format! pads by chars, which misaligns wide characters
+                .map(|(cell, &width)| pad_to_width(cell, width))
+                .collect();
+            padded.join(" ").trim_end().to_string()
+        };
+        let mut lines = vec![line(&header)];
+        lines.push("-".repeat(widths.iter().sum::<usize>() + widths.len() - 1));
+        lines.extend(cells.iter().map(|r| line(r)));
+        lines
+    }
+}
+
+// A topic and its progress as one flat row (see TOPIC_COLUMNS)
+pub fn topic_row(topic: &Topic, progress: &Progress) -> serde_json::Result<Map<String, Value>> {
+    let mut row = json_row(topic)?;
+    for (key, value) in json_row(progress)? {
+        if key != "id" && key != "topic_id" {
+            row.entry(key).or_insert(value);
+        }
+    }
+    Ok(row)
+}
+
+pub fn json_row(value: &impl Serialize) -> serde_json::Result<Map<String, Value>> {
+    match serde_json::to_value(value)? {
+        Value::Object(map) => Ok(map),
+        other => Ok(Map::from_iter([("value".to_string(), other)])),
+    }
+}
+
+// Table text for a JSON value: "-" for null, lists joined with commas
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.is_empty() => "-".to_string(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keeps_order_and_drops_repeats() {
+        let columns = Columns::parse(" name, id ,name,", TOPIC_COLUMNS).unwrap();
+        assert_eq!(columns, Columns(vec!["name".to_string(), "id".to_string()]));
+        assert!(Columns::parse(",", TOPIC_COLUMNS).is_err());
+    }
+
+    #[test]
+    fn unknown_columns_list_the_valid_ones() {
+        let e = Columns::parse("id,duration", &["id", "name"]).unwrap_err();
+        assert_eq!(e, "Unknown column 'duration'. Valid columns: id, name");
+    }
+
+    #[test]
+    fn table_cells_render_nulls_and_lists() {
+        let columns = Columns::parse("tags,notes", TOPIC_COLUMNS).unwrap();
+        let rows = [
+            json_row(&serde_json::json!({ "tags": ["a", "b"], "notes": null })).unwrap(),
+            json_row(&serde_json::json!({ "tags": [], "notes": "x" })).unwrap(),
+        ];
+        assert_eq!(
+            columns.table(&rows),
+            ["TAGS NOTES", "----------", "a, b -", "-    x"]
+        );
+    }
+
+    #[test]
+    fn table_aligns_wide_characters_by_display_width() {
+        let columns = Columns::parse("name,id", TOPIC_COLUMNS).unwrap();
+        let rows = [
+            json_row(&serde_json::json!({ "name": "日本語", "id": 1 })).unwrap(),
+            json_row(&serde_json::json!({ "name": "abc", "id": 2 })).unwrap(),
+        ];
+        assert_eq!(
+            columns.table(&rows),
+            ["NAME   ID", "---------", "日本語 1", "abc    2"]
+        );
+    }
+}
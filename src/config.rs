@@ -0,0 +1,185 @@
+// Optional startup config, read from <config dir>/feynman/config.toml (e.g.
+// ~/.config/feynman/config.toml) or the file named by FEYNMAN_CONFIG. It holds
+// what the settings table can't, starting with where that table lives. CLI
+// flags and environment variables take precedence; a missing file means the
+// defaults.
+//
+//   db_path = "~/notes/feynman.db"
+//   strategy = "lru"        # default for `next --strategy`
+//   timezone = "+02:00"     # "UTC", "local" or a fixed offset
+//   color = false           # TUI colors (NO_COLOR also turns them off)
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::models::SelectionStrategy;
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub db_path: Option<PathBuf>,
+    pub strategy: Option<SelectionStrategy>,
+    pub timezone: Timezone,
+    pub color: Option<bool>,
+}
+
+// Where plain dates (e.g. `review --at 2024-03-01`) start their day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Timezone {
+    #[default]
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "utc" | "z" => Ok(Timezone::Utc),
+            "local" => Ok(Timezone::Local),
+            offset => offset
+                .parse::<FixedOffset>()
+                .map(Timezone::Fixed)
+                .map_err(|_| {
+                    format!(
+                        "timezone must be \"UTC\", \"local\" or an offset like \"+02:00\", got \"{}\"",
+                        value
+                    )
+                }),
+        }
+    }
+}
+
+impl Timezone {
+    // The instant `date` begins in this timezone
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        match self {
+            Timezone::Utc => midnight.and_utc(),
+            // A skipped local midnight (DST) falls back to UTC
+            Timezone::Local => Local
+                .from_local_datetime(&midnight)
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|| midnight.and_utc()),
+            Timezone::Fixed(offset) => (midnight - *offset).and_utc(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let mut config: Config = toml::from_str(content).map_err(|e| e.message().to_string())?;
+        config.db_path = config.db_path.map(|path| expand_home(&path));
+        Ok(config)
+    }
+
+    // A missing file is the default config; an unreadable or invalid one is an error
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                Self::from_toml(&content).map_err(|e| format!("{}: {}", path.display(), e))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
+
+// FEYNMAN_CONFIG, else config.toml in the platform config dir
+pub fn config_path() -> PathBuf {
+    if let Ok(path) = std::env::var("FEYNMAN_CONFIG") {
+        return PathBuf::from(path);
+    }
+    config_dir().join(CONFIG_FILE_NAME)
+}
+
+// <platform config dir>/feynman, e.g. ~/.config/feynman
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("feynman")
+}
+
+// "~/x" is relative to the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_config_resolves_every_field() {
+        let config = Config::from_toml(
+            r#"
+            db_path = "/data/feynman.db"
+            strategy = "lru"
+            timezone = "+02:00"
+            color = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.db_path, Some(PathBuf::from("/data/feynman.db")));
+        assert_eq!(config.strategy, Some(SelectionStrategy::Lru));
+        assert_eq!(
+            config.timezone,
+            Timezone::Fixed(FixedOffset::east_opt(2 * 3600).unwrap())
+        );
+        assert_eq!(config.color, Some(false));
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(
+            config.timezone.start_of_day(date).to_rfc3339(),
+            "2024-02-29T22:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn empty_config_is_the_default() {
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(config, Config::default());
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(
+            config.timezone.start_of_day(date).to_rfc3339(),
+            "2024-03-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn missing_file_is_the_default() {
+        let path = std::env::temp_dir().join("feynman-no-such-config.toml");
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn db_path_expands_home() {
+        let config = Config::from_toml(r#"db_path = "~/notes/feynman.db""#).unwrap();
+        let expected = dirs::home_dir().unwrap().join("notes/feynman.db");
+        assert_eq!(config.db_path, Some(expected));
+    }
+
+    #[test]
+    fn invalid_values_and_unknown_keys_are_rejected() {
+        assert!(Config::from_toml(r#"strategy = "fastest""#).is_err());
+        assert!(Config::from_toml(r#"timezone = "Mars/Olympus""#)
+            .unwrap_err()
+            .contains("timezone"));
+        assert!(Config::from_toml("colour = true").is_err());
+        assert_eq!(
+            Config::from_toml(r#"timezone = "local""#).unwrap().timezone,
+            Timezone::Local
+        );
+    }
+}
@@ -2,23 +2,149 @@
 #![allow(dead_code)]
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
+use crate::clock::{Clock, SystemClock};
+use crate::error::{FeynmanError, Result};
+use crate::import::{ImportData, ImportValidation, TopicLine};
 use crate::models::{
-    AssessmentMethod, InterviewCategory, InterviewEntry, InterviewEntryType, LearningSession, Plan,
-    PlanStatus, Progress, ReviewOutcome, SessionGap, SessionOutcome, SessionType, SkillAssessment,
-    SkillLevel, Tag, Topic, TopicWithProgress,
+    normalize_tag, parse_timestamp, round_to_tenth, AssessmentMethod, CalibrationReport, DueFilter,
+    InterviewCategory, InterviewEntry, InterviewEntryType, LearningSession, NewTopicOptions,
+    OnConflict, Plan, PlanStatus, Progress, ReviewOptions, ReviewOutcome, ReviewRecord,
+    ScheduleReasoning, SelectionCandidate, SelectionExplanation, SelectionStrategy, SessionGap,
+    SessionOutcome, SessionQuery, SessionType, SkillAssessment, SkillLevel, Stats, StatusCounts,
+    StudySession, Tag, TagStats, TagTopics, Topic, TopicPool, TopicPrompt, TopicQuery, TopicRef,
+    TopicResource, TopicSort, TopicWithProgress, DEFAULT_DIFFICULTY,
 };
+use crate::scheduler;
+use crate::settings::{self, SelectionWeights, Settings, RECENT_PENALTY};
+
+// Stored as PRAGMA user_version by init; bump it with each new migration
+pub const SCHEMA_VERSION: i32 = 11;
+
+// How many of a topic's latest reviews must be free of fails before
+// auto_archive.after_days may archive it
+const AUTO_ARCHIVE_FAIL_LOOKBACK: i64 = 5;
+
+// Columns added since the first schema, as (table, column, SQL adding them);
+// migrate runs the SQL for any column an older database lacks
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    (
+        "progress",
+        "skill_level",
+        "ALTER TABLE progress ADD COLUMN skill_level INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE progress ADD COLUMN assessment_method TEXT NOT NULL DEFAULT 'none';
+         ALTER TABLE progress ADD COLUMN last_assessed TEXT;",
+    ),
+    // Archived/suspended flags on topics
+    (
+        "topics",
+        "archived",
+        "ALTER TABLE topics ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE topics ADD COLUMN suspended INTEGER NOT NULL DEFAULT 0;",
+    ),
+    // Per-topic difficulty scaling review intervals
+    (
+        "progress",
+        "difficulty",
+        "ALTER TABLE progress ADD COLUMN difficulty INTEGER NOT NULL DEFAULT 3;",
+    ),
+    // Self-rated confidence per review (nullable; older reviews have none)
+    (
+        "review_history",
+        "confidence",
+        "ALTER TABLE review_history ADD COLUMN confidence INTEGER;",
+    ),
+    // Full dialogue/explanation from a session (nullable)
+    (
+        "learning_sessions",
+        "transcript",
+        "ALTER TABLE learning_sessions ADD COLUMN transcript TEXT;",
+    ),
+    // Mastery a topic was added at, where replaying its history starts
+    (
+        "progress",
+        "initial_mastery",
+        "ALTER TABLE progress ADD COLUMN initial_mastery INTEGER NOT NULL DEFAULT 0;",
+    ),
+];
+
+// A topic's progress as a replay of its reviews builds it up: from the
+// mastery it was added at, or from its checkpoint once reviews were pruned
+// or its progress was imported
+#[derive(Default)]
+struct ReplayState {
+    mastery_level: i32,
+    times_reviewed: i32,
+    times_succeeded: i32,
+    last_reviewed: Option<String>,
+    next_review: Option<String>,
+    notes: Option<String>,
+}
 
 pub struct Database {
     conn: Connection,
+    clock: Box<dyn Clock>,
+}
+
+// Checkpoint on the way out so a clean exit leaves no WAL behind
+impl Drop for Database {
+    fn drop(&mut self) {
+        let _ = self.checkpoint();
+    }
 }
 
 impl Database {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let conn = Connection::open(path)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    // For readers that must never write (e.g. `serve`); fails if the file
+    // doesn't exist rather than creating it
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Ok(Self {
+            conn,
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    // Fold the write-ahead log back into the main file and truncate it so the
+    // -wal sidecar doesn't grow. Returns false (doing nothing) unless the
+    // database is file-backed and in WAL mode.
+    pub fn checkpoint(&self) -> Result<bool> {
+        let mode: String = self
+            .conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            return Ok(false);
+        }
+
+        self.conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(true)
+    }
+
+    #[cfg(test)]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    // Current time in SQLite's datetime() format, for comparisons in queries
+    fn sql_now(&self) -> String {
+        self.now().format("%Y-%m-%d %H:%M:%S").to_string()
     }
 
     pub fn init(&self) -> Result<()> {
@@ -29,7 +155,9 @@ impl Database {
                 name TEXT NOT NULL UNIQUE,
                 description TEXT,
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                archived INTEGER NOT NULL DEFAULT 0,
+                suspended INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS tags (
@@ -57,6 +185,8 @@ impl Database {
                 skill_level INTEGER NOT NULL DEFAULT 0,
                 assessment_method TEXT NOT NULL DEFAULT 'none',
                 last_assessed TEXT,
+                difficulty INTEGER NOT NULL DEFAULT 3,
+                initial_mastery INTEGER NOT NULL DEFAULT 0,
                 FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
             );
 
@@ -66,6 +196,20 @@ impl Database {
                 outcome TEXT NOT NULL,
                 reviewed_at TEXT NOT NULL DEFAULT (datetime('now')),
                 notes TEXT,
+                confidence INTEGER,
+                FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
+            );
+
+            -- Progress folded from pruned reviews (or imported), where
+            -- replays start; reviews up to its last_reviewed are counted in it
+            CREATE TABLE IF NOT EXISTS history_checkpoints (
+                topic_id INTEGER PRIMARY KEY,
+                mastery_level INTEGER NOT NULL,
+                times_reviewed INTEGER NOT NULL,
+                times_succeeded INTEGER NOT NULL,
+                last_reviewed TEXT,
+                next_review TEXT,
+                notes TEXT,
                 FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
             );
 
@@ -80,6 +224,7 @@ impl Database {
                 outcome TEXT CHECK(outcome IN ('success', 'partial', 'fail', 'abandoned')),
                 summary TEXT,
                 notes TEXT,
+                transcript TEXT,
                 FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
             );
 
@@ -104,6 +249,24 @@ impl Database {
                 FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
             );
 
+            -- Questions to answer when reviewing a topic, asked in ord order
+            CREATE TABLE IF NOT EXISTS topic_prompts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                topic_id INTEGER NOT NULL,
+                prompt TEXT NOT NULL,
+                ord INTEGER NOT NULL,
+                FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
+            );
+
+            -- Links and references for a topic, listed in the order added
+            CREATE TABLE IF NOT EXISTS topic_resources (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                topic_id INTEGER NOT NULL,
+                label TEXT,
+                url TEXT NOT NULL,
+                FOREIGN KEY (topic_id) REFERENCES topics(id) ON DELETE CASCADE
+            );
+
             -- Plans (interview mode)
             CREATE TABLE IF NOT EXISTS plans (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -127,14 +290,23 @@ impl Database {
                 FOREIGN KEY (plan_id) REFERENCES plans(id) ON DELETE CASCADE
             );
 
+            -- User settings (see settings.rs for the recognised keys)
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
             CREATE INDEX IF NOT EXISTS idx_progress_next_review ON progress(next_review);
             CREATE INDEX IF NOT EXISTS idx_progress_mastery ON progress(mastery_level);
             CREATE INDEX IF NOT EXISTS idx_topic_tags_topic ON topic_tags(topic_id);
             CREATE INDEX IF NOT EXISTS idx_topic_tags_tag ON topic_tags(tag_id);
+            CREATE INDEX IF NOT EXISTS idx_review_history_time ON review_history(julianday(reviewed_at));
             CREATE INDEX IF NOT EXISTS idx_sessions_topic ON learning_sessions(topic_id);
             CREATE INDEX IF NOT EXISTS idx_sessions_type ON learning_sessions(session_type);
             CREATE INDEX IF NOT EXISTS idx_session_gaps_session ON session_gaps(session_id);
             CREATE INDEX IF NOT EXISTS idx_skill_assessments_topic ON skill_assessments(topic_id);
+            CREATE INDEX IF NOT EXISTS idx_topic_prompts_topic ON topic_prompts(topic_id, ord);
+            CREATE INDEX IF NOT EXISTS idx_topic_resources_topic ON topic_resources(topic_id);
             CREATE INDEX IF NOT EXISTS idx_plans_status ON plans(status);
             CREATE INDEX IF NOT EXISTS idx_plan_entries_plan ON plan_interview_entries(plan_id);
             CREATE INDEX IF NOT EXISTS idx_plan_entries_category ON plan_interview_entries(category);
@@ -142,40 +314,114 @@ impl Database {
         )?;
 
         // Run migrations for existing databases
-        self.migrate()?;
-
-        // Create indexes on migrated columns (after migration ensures columns exist)
-        self.conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_progress_skill ON progress(skill_level);",
-        )?;
+        self.migrate()
+    }
 
-        Ok(())
+    // Schema version recorded by the last init (0 if never initialized)
+    pub fn schema_version(&self) -> Result<i32> {
+        Ok(self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?)
     }
 
-    // Handle schema migrations for existing databases
+    // Bring an existing database up to SCHEMA_VERSION. Every step runs in one
+    // transaction, so a failure leaves the database as it was and names the
+    // step that failed.
     fn migrate(&self) -> Result<()> {
-        // Check if skill_level column exists in progress table
-        let has_skill_level: bool = self
-            .conn
-            .prepare("SELECT skill_level FROM progress LIMIT 1")
-            .is_ok();
+        let tx = self.conn.unchecked_transaction()?;
+        let failed = |step: String| {
+            move |e: FeynmanError| match e {
+                FeynmanError::Db(source) => FeynmanError::Migration {
+                    version: SCHEMA_VERSION,
+                    step,
+                    source,
+                },
+                other => other,
+            }
+        };
 
-        if !has_skill_level {
-            // Add new columns to progress table
-            self.conn.execute_batch(
-                r#"
-                ALTER TABLE progress ADD COLUMN skill_level INTEGER NOT NULL DEFAULT 0;
-                ALTER TABLE progress ADD COLUMN assessment_method TEXT NOT NULL DEFAULT 'none';
-                ALTER TABLE progress ADD COLUMN last_assessed TEXT;
-                "#,
-            )?;
+        for &(table, column, sql) in ADDED_COLUMNS {
+            let exists = self
+                .conn
+                .prepare(&format!("SELECT {} FROM {} LIMIT 1", column, table))
+                .is_ok();
+            if !exists {
+                self.conn
+                    .execute_batch(sql)
+                    .map_err(FeynmanError::from)
+                    .map_err(failed(format!("adding {}.{}", table, column)))?;
+            }
+        }
+
+        // Tags became case- and whitespace-insensitive in version 7
+        if self.schema_version()? < 7 {
+            self.merge_tag_variants()
+                .map_err(failed("merging tag variants".to_string()))?;
         }
 
+        // Indexes on migrated columns (after migration ensures columns exist)
+        self.conn
+            .execute_batch(
+                "CREATE INDEX IF NOT EXISTS idx_progress_skill ON progress(skill_level);",
+            )
+            .map_err(FeynmanError::from)
+            .map_err(failed("indexing progress.skill_level".to_string()))?;
+
+        self.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(tx.commit()?)
+    }
+
+    // Fold tags that normalize to the same name (e.g. "Rust" and "rust") into
+    // the oldest of them, renamed to the normalized form. Runs inside
+    // migrate's transaction.
+    fn merge_tag_variants(&self) -> Result<()> {
+        let tags: Vec<(i64, String)> = {
+            let mut stmt = self.conn.prepare("SELECT id, name FROM tags ORDER BY id")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        let mut kept: Vec<(i64, String)> = Vec::new();
+        for (id, name) in tags {
+            let normalized = normalize_tag(&name);
+            match kept.iter().find(|(_, n)| *n == normalized) {
+                Some(&(keep_id, _)) => {
+                    self.conn.execute(
+                        "INSERT OR IGNORE INTO topic_tags (topic_id, tag_id)
+                         SELECT topic_id, ?1 FROM topic_tags WHERE tag_id = ?2",
+                        params![keep_id, id],
+                    )?;
+                    self.conn
+                        .execute("DELETE FROM topic_tags WHERE tag_id = ?1", params![id])?;
+                    self.conn
+                        .execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+                }
+                None => kept.push((id, normalized)),
+            }
+        }
+        for (id, name) in &kept {
+            self.conn
+                .execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+        }
         Ok(())
     }
 
     // Topic operations
     pub fn add_topic(&self, name: &str, description: Option<&str>, tags: &[String]) -> Result<i64> {
+        self.add_topic_with(name, description, tags, &NewTopicOptions::default())
+    }
+
+    // add_topic starting from what the learner already knows: a mastery above
+    // 0 is scheduled as if a successful review had just reached it, and a
+    // skill level is recorded as a self-assessment
+    pub fn add_topic_with(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        tags: &[String],
+        options: &NewTopicOptions,
+    ) -> Result<i64> {
         self.conn.execute(
             "INSERT INTO topics (name, description) VALUES (?1, ?2)",
             params![name, description],
@@ -183,10 +429,32 @@ impl Database {
         let topic_id = self.conn.last_insert_rowid();
 
         // Initialize progress for this topic
+        let now = self.now();
+        let mastery = options.mastery.clamp(0, settings::MAX_MASTERY);
+        let next_review = if mastery > 0 {
+            let settings = self.settings()?;
+            let interval = scheduler::review_interval(
+                &settings,
+                mastery,
+                ReviewOutcome::Success,
+                DEFAULT_DIFFICULTY,
+            );
+            now + scheduler::fuzz_interval(
+                &settings,
+                interval,
+                &mut scheduler::review_rng(topic_id, now),
+            )
+        } else {
+            now
+        };
         self.conn.execute(
-            "INSERT INTO progress (topic_id) VALUES (?1)",
-            params![topic_id],
+            "INSERT INTO progress (topic_id, next_review, mastery_level, initial_mastery)
+             VALUES (?1, ?2, ?3, ?3)",
+            params![topic_id, next_review.to_rfc3339(), mastery],
         )?;
+        if let Some(skill) = options.skill {
+            self.update_skill_level(topic_id, skill, AssessmentMethod::SelfAssessed, None)?;
+        }
 
         // Add tags
         for tag in tags {
@@ -200,21 +468,73 @@ impl Database {
         Ok(topic_id)
     }
 
+    // Add several topics at once; all or none are created
+    pub fn add_topics(&self, topics: &[TopicLine]) -> Result<Vec<i64>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let ids = topics
+            .iter()
+            .map(|t| self.add_topic(&t.name, t.description.as_deref(), &t.tags))
+            .collect::<Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    // Add topics from `topic import-json` in one transaction. A name already
+    // in the database (or earlier in the list) is handled per `on_conflict`;
+    // with OnConflict::Fail the insert errors and nothing is kept.
+    pub fn import_topic_lines(
+        &self,
+        topics: &[TopicLine],
+        on_conflict: OnConflict,
+    ) -> Result<TopicImportSummary> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut summary = TopicImportSummary::default();
+        for t in topics {
+            let existing = match self.conn.query_row(
+                "SELECT id FROM topics WHERE name = ?1",
+                params![t.name],
+                |row| row.get::<_, i64>(0),
+            ) {
+                Ok(id) => Some(id),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e.into()),
+            };
+
+            match (existing, on_conflict) {
+                (Some(_), OnConflict::Skip) => summary.skipped += 1,
+                (Some(id), OnConflict::Update) => {
+                    if let Some(description) = &t.description {
+                        self.conn.execute(
+                            "UPDATE topics SET description = ?1, updated_at = ?2 WHERE id = ?3",
+                            params![description, self.now().to_rfc3339(), id],
+                        )?;
+                    }
+                    for tag in &t.tags {
+                        let tag_id = self.get_or_create_tag(tag)?;
+                        self.conn.execute(
+                            "INSERT OR IGNORE INTO topic_tags (topic_id, tag_id) VALUES (?1, ?2)",
+                            params![id, tag_id],
+                        )?;
+                    }
+                    summary.updated += 1;
+                }
+                _ => {
+                    self.add_topic(&t.name, t.description.as_deref(), &t.tags)?;
+                    summary.added += 1;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(summary)
+    }
+
     pub fn get_topic(&self, id: i64) -> Result<Option<Topic>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, created_at, updated_at FROM topics WHERE id = ?1",
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM topics t WHERE t.id = ?1",
+            TOPIC_COLUMNS
+        ))?;
 
-        let topic = stmt.query_row(params![id], |row| {
-            Ok(Topic {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
-                tags: vec![],
-            })
-        });
+        let topic = stmt.query_row(params![id], |row| topic_from_row(row, 0));
 
         match topic {
             Ok(mut t) => {
@@ -222,50 +542,34 @@ impl Database {
                 Ok(Some(t))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
     pub fn list_topics(&self, tag_filter: Option<&str>) -> Result<Vec<Topic>> {
         let mut topics: Vec<Topic> = if let Some(tag) = tag_filter {
-            let mut stmt = self.conn.prepare(
+            let mut stmt = self.conn.prepare(&format!(
                 r#"
-                SELECT DISTINCT t.id, t.name, t.description, t.created_at, t.updated_at
+                SELECT DISTINCT {}
                 FROM topics t
                 JOIN topic_tags tt ON t.id = tt.topic_id
                 JOIN tags tg ON tt.tag_id = tg.id
                 WHERE tg.name = ?1
                 ORDER BY t.name
                 "#,
-            )?;
+                TOPIC_COLUMNS
+            ))?;
 
-            let rows = stmt.query_map(params![tag], |row| {
-                Ok(Topic {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                    tags: vec![],
-                })
-            })?;
-            rows.collect::<Result<Vec<_>>>()?
+            let rows = stmt.query_map(params![normalize_tag(tag)], |row| topic_from_row(row, 0))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
         } else {
-            let mut stmt = self.conn.prepare(
-                "SELECT id, name, description, created_at, updated_at FROM topics ORDER BY name",
-            )?;
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT {} FROM topics t ORDER BY t.name",
+                TOPIC_COLUMNS
+            ))?;
 
-            let rows = stmt.query_map([], |row| {
-                Ok(Topic {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                    tags: vec![],
-                })
-            })?;
-            rows.collect::<Result<Vec<_>>>()?
+            let rows = stmt.query_map([], |row| topic_from_row(row, 0))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
         };
 
         for topic in &mut topics {
@@ -282,6 +586,189 @@ impl Database {
         Ok(rows > 0)
     }
 
+    // Bumped by SQLite when another connection commits to the database, so a
+    // long-lived reader like the TUI can notice writes from other processes
+    pub fn data_version(&self) -> Result<i64> {
+        Ok(self
+            .conn
+            .query_row("PRAGMA data_version", [], |row| row.get(0))?)
+    }
+
+    // Delete a topic and, explicitly, everything recorded against it, so
+    // nothing is left behind whether or not foreign keys are enforced.
+    // Returns the rows removed per table, or None if the topic doesn't exist.
+    pub fn purge_topic(&self, id: i64) -> Result<Option<PurgeSummary>> {
+        if self.get_topic(id)?.is_none() {
+            return Ok(None);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let summary = self.delete_topic_rows(id)?;
+        tx.commit()?;
+        Ok(Some(summary))
+    }
+
+    // Topics carrying `tag` (matched normalized), including archived and suspended ones
+    pub fn topics_with_tag(&self, tag: &str) -> Result<Vec<Topic>> {
+        Ok(self
+            .get_topics_with_progress(&TopicQuery {
+                tag: Some(tag.to_string()),
+                include_archived: true,
+                include_suspended: true,
+                ..TopicQuery::default()
+            })?
+            .into_iter()
+            .map(|twp| twp.topic)
+            .collect())
+    }
+
+    // Purge every topic carrying `tag` in one transaction, as purge_topic
+    // does for one. Returns the number of topics deleted.
+    pub fn delete_topics_by_tag(&self, tag: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let topics = self.topics_with_tag(tag)?;
+        for topic in &topics {
+            self.delete_topic_rows(topic.id)?;
+        }
+        tx.commit()?;
+        Ok(topics.len())
+    }
+
+    // The deletes behind purge_topic; the caller owns the transaction
+    fn delete_topic_rows(&self, id: i64) -> Result<PurgeSummary> {
+        let delete_rows = |table: &str| {
+            self.conn.execute(
+                &format!("DELETE FROM {} WHERE topic_id = ?1", table),
+                params![id],
+            )
+        };
+        let summary = PurgeSummary {
+            reviews: delete_rows("review_history")?,
+            gaps: self.conn.execute(
+                "DELETE FROM session_gaps
+                 WHERE session_id IN (SELECT id FROM learning_sessions WHERE topic_id = ?1)",
+                params![id],
+            )?,
+            sessions: delete_rows("learning_sessions")?,
+            assessments: delete_rows("skill_assessments")?,
+            prompts: delete_rows("topic_prompts")?,
+            resources: delete_rows("topic_resources")?,
+            tags: delete_rows("topic_tags")?,
+            progress: delete_rows("progress")?,
+        };
+        delete_rows("history_checkpoints")?;
+        self.conn
+            .execute("DELETE FROM topics WHERE id = ?1", params![id])?;
+        Ok(summary)
+    }
+
+    // Fold `source` into `target`: move its reviews, sessions (gaps follow
+    // their session), skill assessments, prompts (after the target's own) and
+    // resources, add its tags, rebuild the target's progress from the
+    // combined history and delete the source. Returns None if the ids are
+    // equal or either topic doesn't exist. A checkpoint can't be combined
+    // with other history: a source with one, or with reviews no later than
+    // the target's, is refused.
+    pub fn merge_topics(&self, source: i64, target: i64) -> Result<Option<MergeSummary>> {
+        if source == target
+            || self.get_topic(source)?.is_none()
+            || self.get_topic(target)?.is_none()
+        {
+            return Ok(None);
+        }
+        if self.history_checkpoint(source)?.is_some() {
+            return Err(format!(
+                "Topic {}'s progress comes from pruned or imported history, so it can't be \
+                 merged into another topic",
+                source
+            )
+            .into());
+        }
+        if let Some(checkpoint) = self.checkpoint_time(target)? {
+            let older: i64 = self.conn.query_row(
+                "SELECT COUNT(*) FROM review_history
+                 WHERE topic_id = ?1 AND julianday(reviewed_at) <= julianday(?2)",
+                params![source, checkpoint.to_rfc3339()],
+                |row| row.get(0),
+            )?;
+            if older > 0 {
+                return Err(format!(
+                    "Topic {} has reviews from before topic {}'s pruned or imported \
+                     history ends ({}), so they can't be replayed into it",
+                    source,
+                    target,
+                    checkpoint.format("%Y-%m-%d %H:%M")
+                )
+                .into());
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let move_rows = |table: &str| {
+            self.conn.execute(
+                &format!("UPDATE {} SET topic_id = ?1 WHERE topic_id = ?2", table),
+                params![target, source],
+            )
+        };
+        let summary = MergeSummary {
+            reviews: move_rows("review_history")?,
+            sessions: move_rows("learning_sessions")?,
+            assessments: move_rows("skill_assessments")?,
+            prompts: self.conn.execute(
+                "UPDATE topic_prompts SET topic_id = ?1, ord = ord +
+                   (SELECT COALESCE(MAX(ord), 0) FROM topic_prompts WHERE topic_id = ?1)
+                 WHERE topic_id = ?2",
+                params![target, source],
+            )?,
+            resources: move_rows("topic_resources")?,
+            tags: self.conn.execute(
+                "INSERT OR IGNORE INTO topic_tags (topic_id, tag_id)
+                 SELECT ?1, tag_id FROM topic_tags WHERE topic_id = ?2",
+                params![target, source],
+            )?,
+        };
+
+        self.conn.execute(
+            "DELETE FROM topic_tags WHERE topic_id = ?1",
+            params![source],
+        )?;
+        self.conn
+            .execute("DELETE FROM progress WHERE topic_id = ?1", params![source])?;
+        self.conn
+            .execute("DELETE FROM topics WHERE id = ?1", params![source])?;
+
+        let settings = self.settings()?;
+        self.replay_topic_history(&settings, target)?;
+
+        tx.commit()?;
+        Ok(Some(summary))
+    }
+
+    pub fn set_topic_archived(&self, id: i64, archived: bool) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE topics SET archived = ?1, updated_at = ?2 WHERE id = ?3",
+            params![archived as i32, self.now().to_rfc3339(), id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // Difficulty 1 (easy) to 5 (hard); harder topics get shorter intervals
+    pub fn set_topic_difficulty(&self, topic_id: i64, difficulty: i32) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE progress SET difficulty = ?1 WHERE topic_id = ?2",
+            params![difficulty.clamp(1, 5), topic_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    pub fn set_topic_suspended(&self, id: i64, suspended: bool) -> Result<bool> {
+        let rows = self.conn.execute(
+            "UPDATE topics SET suspended = ?1, updated_at = ?2 WHERE id = ?3",
+            params![suspended as i32, self.now().to_rfc3339(), id],
+        )?;
+        Ok(rows > 0)
+    }
+
     pub fn update_topic_tags(&self, topic_id: i64, tags: &[String]) -> Result<()> {
         // Remove existing tags
         self.conn.execute(
@@ -303,8 +790,10 @@ impl Database {
 
     // Tag operations
     fn get_or_create_tag(&self, name: &str) -> Result<i64> {
+        let name = normalize_tag(name);
+
         // Try to get existing tag
-        let existing: Result<i64> = self.conn.query_row(
+        let existing: rusqlite::Result<i64> = self.conn.query_row(
             "SELECT id FROM tags WHERE name = ?1",
             params![name],
             |row| row.get(0),
@@ -317,7 +806,7 @@ impl Database {
                     .execute("INSERT INTO tags (name) VALUES (?1)", params![name])?;
                 Ok(self.conn.last_insert_rowid())
             }
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -333,7 +822,7 @@ impl Database {
         )?;
 
         let rows = stmt.query_map(params![topic_id], |row| row.get(0))?;
-        let tags = rows.collect::<Result<Vec<String>>>()?;
+        let tags = rows.collect::<rusqlite::Result<Vec<String>>>()?;
 
         Ok(tags)
     }
@@ -356,44 +845,113 @@ impl Database {
                 topic_count: row.get(2)?,
             })
         })?;
-        let tags = rows.collect::<Result<Vec<_>>>()?;
+        let tags = rows.collect::<rusqlite::Result<Vec<_>>>()?;
 
         Ok(tags)
     }
 
-    // Progress operations
-    pub fn get_progress(&self, topic_id: i64) -> Result<Option<Progress>> {
+    // Every tag, sorted by name, with the topics carrying it sorted by id.
+    // One query for all of them; tags no topic uses have an empty list.
+    pub fn list_tags_with_topics(&self) -> Result<Vec<TagTopics>> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, topic_id, mastery_level, times_reviewed, times_succeeded,
-                   last_reviewed, next_review, notes, skill_level, assessment_method, last_assessed
-            FROM progress
-            WHERE topic_id = ?1
+            SELECT tg.name, t.id, t.name
+            FROM tags tg
+            LEFT JOIN topic_tags tt ON tg.id = tt.tag_id
+            LEFT JOIN topics t ON tt.topic_id = t.id
+            ORDER BY tg.name, t.id
             "#,
         )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut tags: Vec<TagTopics> = Vec::new();
+        for (tag, id, name) in rows {
+            if tags.last().is_none_or(|last| last.tag != tag) {
+                tags.push(TagTopics {
+                    tag,
+                    topics: Vec::new(),
+                });
+            }
+            if let (Some(id), Some(name), Some(last)) = (id, name, tags.last_mut()) {
+                last.topics.push(TopicRef { id, name });
+            }
+        }
+        Ok(tags)
+    }
 
-        let progress = stmt.query_row(params![topic_id], |row| {
-            let skill_level_int: i32 = row.get(8)?;
-            let assessment_str: String = row.get(9)?;
-            Ok(Progress {
-                id: row.get(0)?,
-                topic_id: row.get(1)?,
-                mastery_level: row.get(2)?,
-                times_reviewed: row.get(3)?,
-                times_succeeded: row.get(4)?,
-                last_reviewed: row.get(5)?,
-                next_review: row.get(6)?,
-                notes: row.get(7)?,
-                skill_level: SkillLevel::from_i32(skill_level_int),
-                assessment_method: AssessmentMethod::from_str(&assessment_str),
-                last_assessed: row.get(10)?,
-            })
-        });
+    // Existing tag names starting with `prefix` (case-insensitive), sorted
+    pub fn tags_matching_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT name FROM tags
+            WHERE lower(substr(name, 1, length(?1))) = lower(?1)
+            ORDER BY name
+            "#,
+        )?;
+        let rows = stmt.query_map(params![prefix], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // Add `tag` to every topic in `topic_ids` in one transaction. Returns the
+    // number of topics that didn't already have it.
+    pub fn bulk_add_tag(&self, topic_ids: &[i64], tag: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let tag_id = self.get_or_create_tag(tag)?;
+
+        let mut affected = 0;
+        for topic_id in topic_ids {
+            affected += self.conn.execute(
+                "INSERT OR IGNORE INTO topic_tags (topic_id, tag_id) VALUES (?1, ?2)",
+                params![topic_id, tag_id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(affected)
+    }
+
+    // Remove `tag` from every topic in `topic_ids` in one transaction. Returns
+    // the number of topics that had it.
+    pub fn bulk_remove_tag(&self, topic_ids: &[i64], tag: &str) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let tag = normalize_tag(tag);
+
+        let mut affected = 0;
+        for topic_id in topic_ids {
+            affected += self.conn.execute(
+                r#"
+                DELETE FROM topic_tags
+                WHERE topic_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)
+                "#,
+                params![topic_id, tag],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(affected)
+    }
+
+    // Progress operations
+    pub fn get_progress(&self, topic_id: i64) -> Result<Option<Progress>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM progress p WHERE p.topic_id = ?1",
+            PROGRESS_COLUMNS
+        ))?;
+
+        let progress = stmt.query_row(params![topic_id], |row| progress_from_row(row, 0));
 
         match progress {
             Ok(p) => Ok(Some(p)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -403,39 +961,89 @@ impl Database {
         outcome: ReviewOutcome,
         notes: Option<&str>,
     ) -> Result<()> {
-        let now = Utc::now();
+        self.record_review_with(topic_id, outcome, notes, &ReviewOptions::default())?;
+        Ok(())
+    }
+
+    // Returns the scheduling step applied, or None for a backfill older than
+    // the latest review (progress is rebuilt from the whole history instead)
+    pub fn record_review_with(
+        &self,
+        topic_id: i64,
+        outcome: ReviewOutcome,
+        notes: Option<&str>,
+        options: &ReviewOptions,
+    ) -> Result<Option<ScheduleReasoning>> {
+        let reviewed_at = options.at.unwrap_or_else(|| self.now());
         let outcome_str = outcome.as_str();
 
+        // Get current progress; a missing topic records nothing
+        let progress = self
+            .get_progress(topic_id)?
+            .ok_or(FeynmanError::NotFound("Topic"))?;
+
+        // Older reviews were pruned or imported into the checkpoint, leaving
+        // nothing to replay a backfill among
+        if let Some(checkpoint) = self.checkpoint_time(topic_id)? {
+            if reviewed_at <= checkpoint {
+                return Err(format!(
+                    "Progress up to {} comes from pruned or imported history, so a review \
+                     can't be backfilled before then",
+                    checkpoint.format("%Y-%m-%d %H:%M")
+                )
+                .into());
+            }
+        }
+
         // Record in history
         self.conn.execute(
-            "INSERT INTO review_history (topic_id, outcome, reviewed_at, notes) VALUES (?1, ?2, ?3, ?4)",
-            params![topic_id, outcome_str, now.to_rfc3339(), notes],
+            "INSERT INTO review_history (topic_id, outcome, reviewed_at, notes, confidence) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                topic_id,
+                outcome_str,
+                reviewed_at.to_rfc3339(),
+                notes,
+                options.confidence
+            ],
         )?;
+        let settings = self.settings()?;
+
+        // A backfill older than the latest review can't be applied on top of
+        // it; replay the history in date order instead so last_reviewed and
+        // next_review never go backwards
+        let out_of_order = progress
+            .last_reviewed
+            .as_deref()
+            .and_then(parse_timestamp)
+            .is_some_and(|last| reviewed_at < last);
+        if out_of_order {
+            self.replay_topic_history(&settings, topic_id)?;
+            return Ok(None);
+        }
 
-        // Get current progress
-        let progress = self
-            .get_progress(topic_id)?
-            .ok_or_else(|| rusqlite::Error::QueryReturnedNoRows)?;
+        // Calculate new mastery level and next review. Only a date set by an
+        // earlier review counts as a due date for the on-time bonus.
+        let due = progress
+            .last_reviewed
+            .as_ref()
+            .and(progress.next_review_dt());
+        let on_time = scheduler::earns_on_time_bonus(&settings, outcome, due, reviewed_at);
+        let (new_mastery, interval) = scheduler::schedule_review(
+            &settings,
+            progress.mastery_level,
+            outcome,
+            progress.difficulty,
+            on_time,
+            &mut scheduler::review_rng(topic_id, reviewed_at),
+        );
 
-        // Calculate new mastery level and next review
-        let (new_mastery, days_until_next) = match outcome {
-            ReviewOutcome::Success => {
-                let new_level = (progress.mastery_level + 1).min(5);
-                let days = Self::calculate_interval(new_level);
-                (new_level, days)
-            }
-            ReviewOutcome::Partial => {
-                let new_level = progress.mastery_level; // Stay same
-                let days = Self::calculate_interval(new_level) / 2;
-                (new_level, days.max(1))
-            }
-            ReviewOutcome::Fail => {
-                let new_level = (progress.mastery_level - 1).max(0);
-                (new_level, 1) // Review again tomorrow
-            }
+        // Cramming: put missed topics straight back into today's queue
+        let retry_now = options.retry_now && !matches!(outcome, ReviewOutcome::Success);
+        let next_review = if retry_now {
+            reviewed_at
+        } else {
+            reviewed_at + interval
         };
-
-        let next_review = now + chrono::Duration::days(days_until_next as i64);
         let times_succeeded = if matches!(outcome, ReviewOutcome::Success) {
             progress.times_succeeded + 1
         } else {
@@ -456,152 +1064,838 @@ impl Database {
             params![
                 new_mastery,
                 times_succeeded,
-                now.to_rfc3339(),
+                reviewed_at.to_rfc3339(),
                 next_review.to_rfc3339(),
                 notes,
                 topic_id
             ],
         )?;
 
-        Ok(())
+        let previous_interval = progress
+            .last_reviewed
+            .as_deref()
+            .and_then(parse_timestamp)
+            .zip(progress.next_review_dt())
+            .map(|(last, next)| (next - last).num_minutes());
+        Ok(Some(ScheduleReasoning {
+            previous_level: progress.mastery_level,
+            new_level: new_mastery,
+            grading_delta: settings.grading.delta(outcome),
+            level_delta: new_mastery - progress.mastery_level,
+            previous_interval_minutes: previous_interval,
+            difficulty: progress.difficulty,
+            interval_minutes: (next_review - reviewed_at).num_minutes(),
+            on_time_bonus: on_time.then_some(settings.on_time_bonus),
+            retry_now,
+            next_review: next_review.to_rfc3339(),
+        }))
     }
 
-    // Spaced repetition intervals (in days) based on mastery level
-    fn calculate_interval(mastery_level: i32) -> i32 {
-        match mastery_level {
-            0 => 1,
-            1 => 2,
-            2 => 4,
-            3 => 7,
-            4 => 14,
-            5 => 30,
-            _ => 30,
+    // Settings operations
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let value = self.conn.query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        );
+        match value {
+            Ok(v) => Ok(Some(v)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
         }
     }
 
-    // Stochastic selection for next topic to review
-    pub fn get_next_topic(&self, tag_filter: Option<&str>) -> Result<Option<TopicWithProgress>> {
-        // Get topics due for review, weighted by priority
-        let topics = self.get_due_topics(tag_filter)?;
-
-        if topics.is_empty() {
-            return Ok(None);
-        }
-
-        // Stochastic selection: weight by overdue-ness and lower mastery
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-
-        let now = Utc::now();
-        let weights: Vec<f64> = topics
-            .iter()
-            .map(|t| {
-                let overdue_days = if let Some(next) = &t.progress.next_review {
-                    if let Ok(next_dt) = DateTime::parse_from_rfc3339(next) {
-                        let diff = now.signed_duration_since(next_dt.with_timezone(&Utc));
-                        diff.num_days().max(0) as f64 + 1.0
-                    } else {
-                        1.0
-                    }
-                } else {
-                    1.0
-                };
-
-                // Lower mastery = higher weight, overdue = higher weight
-                let mastery_weight = 6.0 - t.progress.mastery_level as f64;
-                overdue_days * mastery_weight
-            })
-            .collect();
+    // Rejects a value the setting wouldn't accept (see settings::validate)
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        settings::validate(&self.settings()?, key, value).map_err(FeynmanError::Validation)?;
+        let value = value.trim();
+        self.conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
 
-        let total_weight: f64 = weights.iter().sum();
-        let mut random_point = rng.gen::<f64>() * total_weight;
+    // Returns false if the key wasn't set
+    pub fn unset_setting(&self, key: &str) -> Result<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+        Ok(rows > 0)
+    }
 
-        for (i, weight) in weights.iter().enumerate() {
-            random_point -= weight;
-            if random_point <= 0.0 {
-                return Ok(Some(topics[i].clone()));
-            }
-        }
+    // Explicitly set key/value pairs, sorted by key
+    pub fn list_settings(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 
-        // Fallback to first
-        Ok(topics.into_iter().next())
+    // Stored settings layered over the defaults
+    pub fn settings(&self) -> Result<Settings> {
+        let pairs = self.list_settings()?;
+        Ok(Settings::from_pairs(
+            pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+        ))
     }
 
-    fn get_due_topics(&self, tag_filter: Option<&str>) -> Result<Vec<TopicWithProgress>> {
-        let base_query = r#"
-            SELECT t.id, t.name, t.description, t.created_at, t.updated_at,
-                   p.id, p.topic_id, p.mastery_level, p.times_reviewed, p.times_succeeded,
-                   p.last_reviewed, p.next_review, p.notes, p.skill_level, p.assessment_method, p.last_assessed
-            FROM topics t
-            JOIN progress p ON t.id = p.topic_id
-        "#;
+    // Review history ordered by id, optionally for one topic. `after` and
+    // `limit` give keyset pagination: pass the last id seen to get the next page.
+    pub fn get_review_history(
+        &self,
+        topic_id: Option<i64>,
+        after: Option<i64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ReviewRecord>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, topic_id, outcome, reviewed_at, notes, confidence
+            FROM review_history
+            WHERE (?1 IS NULL OR topic_id = ?1) AND id > ?2
+            ORDER BY id
+            LIMIT ?3
+            "#,
+        )?;
 
-        let (query, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) =
-            if let Some(tag) = tag_filter {
-                let q = format!(
-                    r#"{}
-                JOIN topic_tags tt ON t.id = tt.topic_id
-                JOIN tags tg ON tt.tag_id = tg.id
-                WHERE tg.name = ?1
-                ORDER BY p.next_review ASC, p.mastery_level ASC
-                "#,
-                    base_query
-                );
-                (q, vec![Box::new(tag.to_string())])
-            } else {
-                let q = format!(
-                    "{} ORDER BY p.next_review ASC, p.mastery_level ASC",
-                    base_query
-                );
-                (q, vec![])
+        let rows = stmt.query_map(
+            params![
+                topic_id,
+                after.unwrap_or(0),
+                limit.map(|l| l as i64).unwrap_or(-1)
+            ],
+            review_from_row,
+        )?;
+
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // Whole days between consecutive reviews of a topic, oldest first.
+    // Timestamps that can't be parsed are skipped.
+    pub fn get_topic_intervals(&self, topic_id: i64) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT reviewed_at FROM review_history WHERE topic_id = ?1")?;
+        let timestamps = stmt
+            .query_map(params![topic_id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut reviewed: Vec<DateTime<Utc>> = timestamps
+            .iter()
+            .filter_map(|s| parse_timestamp(s))
+            .collect();
+        reviewed.sort();
+        Ok(interval_days(&reviewed))
+    }
+
+    // The mastery level after each of a topic's reviews, oldest first, from
+    // replaying its outcomes through the current grading scale (as
+    // rebuild_progress does) starting at the level it was added with, or
+    // from its checkpoint if reviews were pruned or progress imported
+    pub fn get_mastery_trajectory(&self, topic_id: i64) -> Result<Vec<i32>> {
+        let (_, start) = self.replay_start(topic_id)?;
+        let outcomes = self.review_rows(topic_id, &start, None)?;
+
+        let grading = self.settings()?.grading;
+        Ok(outcomes
+            .iter()
+            .filter_map(|(s, _, _)| ReviewOutcome::from_str(s))
+            .scan(start.mastery_level, |level, outcome| {
+                *level = grading.apply(*level, outcome);
+                Some(*level)
+            })
+            .collect())
+    }
+
+    // Confidence ratings against outcomes, over every rated review
+    pub fn get_calibration(&self) -> Result<CalibrationReport> {
+        let reviews: Vec<ReviewRecord> = self
+            .get_review_history(None, None, None)?
+            .into_iter()
+            .filter(|r| r.confidence.is_some())
+            .collect();
+        Ok(CalibrationReport::from_reviews(&reviews))
+    }
+
+    // Review history grouped into implicit study sessions, newest first:
+    // consecutive reviews at most `gap_minutes` apart share a session
+    pub fn get_study_sessions(&self, gap_minutes: i64) -> Result<Vec<StudySession>> {
+        let reviews = self.get_review_history(None, None, None)?;
+        Ok(StudySession::cluster(
+            &reviews,
+            chrono::Duration::minutes(gap_minutes),
+        ))
+    }
+
+    // The latest `limit` of get_study_sessions, read newest review first and
+    // stopping once the sessions are complete, so a long history isn't
+    // loaded to show a few sessions
+    pub fn get_recent_study_sessions(
+        &self,
+        gap_minutes: i64,
+        limit: usize,
+    ) -> Result<Vec<StudySession>> {
+        let gap = chrono::Duration::minutes(gap_minutes);
+        let mut stmt = self.conn.prepare(
+            "SELECT id, topic_id, outcome, reviewed_at, notes, confidence
+             FROM review_history
+             ORDER BY julianday(reviewed_at) DESC, id DESC",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut reviews = Vec::new();
+        let mut sessions = 0;
+        let mut earliest: Option<DateTime<Utc>> = None;
+        while let Some(row) = rows.next()? {
+            let review = review_from_row(row)?;
+            let Some(at) = parse_timestamp(&review.reviewed_at) else {
+                continue;
             };
+            if earliest.is_none_or(|earliest| earliest - at > gap) {
+                if sessions == limit {
+                    break;
+                }
+                sessions += 1;
+            }
+            earliest = Some(at);
+            reviews.push(review);
+        }
+        Ok(StudySession::cluster(&reviews, gap))
+    }
 
-        let mut stmt = self.conn.prepare(&query)?;
+    // Reviews prune_review_history(before) would delete
+    pub fn count_reviews_before(&self, before: DateTime<Utc>) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM review_history WHERE julianday(reviewed_at) < julianday(?1)",
+            params![before.to_rfc3339()],
+            |row| row.get(0),
+        )?)
+    }
 
-        let params_refs: Vec<&dyn rusqlite::ToSql> =
-            params_vec.iter().map(|b| b.as_ref()).collect();
+    // Delete reviews from before the cutoff. Each topic's deleted reviews are
+    // first folded into its checkpoint (the progress they add up to, under
+    // the current settings), which every replay starts from, so rebuilding a
+    // pruned topic still reproduces its progress. Returns the number of
+    // reviews deleted.
+    pub fn prune_review_history(&self, before: DateTime<Utc>) -> Result<usize> {
+        if self.schema_version()? < SCHEMA_VERSION {
+            return Err("Run `feynman init` to upgrade the database before pruning".into());
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let topic_ids: Vec<i64> = self
+            .conn
+            .prepare(
+                "SELECT DISTINCT topic_id FROM review_history
+                 WHERE julianday(reviewed_at) < julianday(?1)",
+            )?
+            .query_map(params![before.to_rfc3339()], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let settings = self.settings()?;
+        for topic_id in topic_ids {
+            let (difficulty, mut state) = self.replay_start(topic_id)?;
+            let history = self.review_rows(topic_id, &state, Some(before))?;
+            self.replay_reviews(&settings, topic_id, difficulty, &mut state, history);
+            self.conn.execute(
+                "INSERT OR REPLACE INTO history_checkpoints
+                   (topic_id, mastery_level, times_reviewed, times_succeeded,
+                    last_reviewed, next_review, notes)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    topic_id,
+                    state.mastery_level,
+                    state.times_reviewed,
+                    state.times_succeeded,
+                    state.last_reviewed,
+                    state.next_review,
+                    state.notes
+                ],
+            )?;
+        }
 
-        let rows = stmt.query_map(params_refs.as_slice(), |row| {
-            let skill_level_int: i32 = row.get(13)?;
-            let assessment_str: String = row.get(14)?;
-            Ok(TopicWithProgress {
-                topic: Topic {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                    tags: vec![],
-                },
-                progress: Progress {
-                    id: row.get(5)?,
-                    topic_id: row.get(6)?,
-                    mastery_level: row.get(7)?,
-                    times_reviewed: row.get(8)?,
-                    times_succeeded: row.get(9)?,
-                    last_reviewed: row.get(10)?,
-                    next_review: row.get(11)?,
-                    notes: row.get(12)?,
-                    skill_level: SkillLevel::from_i32(skill_level_int),
-                    assessment_method: AssessmentMethod::from_str(&assessment_str),
-                    last_assessed: row.get(15)?,
-                },
+        let deleted = self.conn.execute(
+            "DELETE FROM review_history WHERE julianday(reviewed_at) < julianday(?1)",
+            params![before.to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    // Apply history.retention_days, if set. A database that hasn't been
+    // initialized (or upgraded by init to hold checkpoints) yet is left
+    // alone.
+    pub fn prune_expired_history(&self) -> Result<usize> {
+        if self.schema_version()? < SCHEMA_VERSION {
+            return Ok(0);
+        }
+        match self.settings()?.retention_days {
+            Some(days) => {
+                self.prune_review_history(self.now() - chrono::Duration::days(days as i64))
+            }
+            None => Ok(0),
+        }
+    }
+
+    // Apply auto_archive.after_days, if set: archive every active topic at
+    // full mastery whose last review is older than the threshold and whose
+    // last AUTO_ARCHIVE_FAIL_LOOKBACK reviews include no fail. Returns the
+    // archived topics' ids and names. A database that hasn't been
+    // initialized yet is left alone.
+    pub fn auto_archive_mastered(&self) -> Result<Vec<(i64, String)>> {
+        if self.schema_version()? == 0 {
+            return Ok(Vec::new());
+        }
+        let Some(days) = self.settings()?.auto_archive_days else {
+            return Ok(Vec::new());
+        };
+        let cutoff = (self.now() - chrono::Duration::days(days as i64)).to_rfc3339();
+
+        let tx = self.conn.unchecked_transaction()?;
+        let topics = self
+            .conn
+            .prepare(
+                "SELECT t.id, t.name FROM topics t
+                 JOIN progress p ON p.topic_id = t.id
+                 WHERE t.archived = 0
+                   AND p.mastery_level >= ?1
+                   AND julianday(p.last_reviewed) < julianday(?2)
+                   AND NOT EXISTS (
+                       SELECT 1 FROM (
+                           SELECT r.outcome FROM review_history r
+                           WHERE r.topic_id = t.id
+                           ORDER BY julianday(r.reviewed_at) DESC, r.id DESC
+                           LIMIT ?3
+                       )
+                       WHERE outcome = 'fail'
+                   )
+                 ORDER BY t.id",
+            )?
+            .query_map(
+                params![settings::MAX_MASTERY, cutoff, AUTO_ARCHIVE_FAIL_LOOKBACK],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect::<rusqlite::Result<Vec<(i64, String)>>>()?;
+        for (id, _) in &topics {
+            self.set_topic_archived(*id, true)?;
+        }
+        tx.commit()?;
+        Ok(topics)
+    }
+
+    // Topics without a progress row, e.g. after an interrupted add or a
+    // botched import. They never come up as due and are left out of stats.
+    pub fn topics_missing_progress(&self) -> Result<Vec<i64>> {
+        self.conn
+            .prepare(
+                "SELECT t.id FROM topics t
+                 WHERE NOT EXISTS (SELECT 1 FROM progress p WHERE p.topic_id = t.id)
+                 ORDER BY t.id",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(Into::into)
+    }
+
+    // How each scheduling timestamp column is stored (see TIMESTAMP_COLUMNS)
+    pub fn timestamp_formats(&self) -> Result<Vec<TimestampFormats>> {
+        TIMESTAMP_COLUMNS
+            .iter()
+            .map(|&(table, column)| {
+                let mut formats = TimestampFormats {
+                    column: format!("{}.{}", table, column),
+                    ..Default::default()
+                };
+                for (_, value) in self.timestamp_values(table, column)? {
+                    match TimestampFormat::of(&value) {
+                        TimestampFormat::Rfc3339 => formats.rfc3339 += 1,
+                        TimestampFormat::Sqlite => formats.sqlite += 1,
+                        TimestampFormat::Invalid => formats.invalid += 1,
+                    }
+                }
+                Ok(formats)
             })
+            .collect()
+    }
+
+    // Rewrite every SQLite-style ("YYYY-MM-DD HH:MM:SS", UTC) timestamp in
+    // TIMESTAMP_COLUMNS as RFC 3339, in one transaction. Values that parse as
+    // neither are left for the user. Returns the number of values rewritten.
+    pub fn normalize_timestamps(&self) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut rewritten = 0;
+        for &(table, column) in TIMESTAMP_COLUMNS {
+            for (id, value) in self.timestamp_values(table, column)? {
+                if TimestampFormat::of(&value) != TimestampFormat::Sqlite {
+                    continue;
+                }
+                let Some(dt) = parse_timestamp(&value) else {
+                    continue;
+                };
+                self.conn.execute(
+                    &format!("UPDATE {} SET {} = ?1 WHERE id = ?2", table, column),
+                    params![dt.to_rfc3339(), id],
+                )?;
+                rewritten += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(rewritten)
+    }
+
+    // (id, value) for each non-null value of `table.column`
+    fn timestamp_values(&self, table: &str, column: &str) -> Result<Vec<(i64, String)>> {
+        self.conn
+            .prepare(&format!(
+                "SELECT id, {0} FROM {1} WHERE {0} IS NOT NULL ORDER BY id",
+                column, table
+            ))?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()
+            .map_err(Into::into)
+    }
+
+    // Give every topic lacking a progress row a fresh one (new, due now), as
+    // add_topic would have. Returns the number of rows inserted.
+    pub fn backfill_missing_progress(&self) -> Result<usize> {
+        Ok(self.conn.execute(
+            "INSERT INTO progress (topic_id, next_review)
+             SELECT t.id, ?1 FROM topics t
+             WHERE NOT EXISTS (SELECT 1 FROM progress p WHERE p.topic_id = t.id)",
+            params![self.now().to_rfc3339()],
+        )?)
+    }
+
+    // Recompute progress from review_history for one topic (or all topics)
+    // by resetting to level 0 and replaying each review through the
+    // scheduler. Returns the number of topics rebuilt.
+    pub fn rebuild_progress(&self, topic_id: Option<i64>) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let topic_ids: Vec<i64> = match topic_id {
+            Some(id) => self
+                .conn
+                .prepare("SELECT id FROM topics WHERE id = ?1")?
+                .query_map(params![id], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?,
+            None => self
+                .conn
+                .prepare("SELECT id FROM topics ORDER BY id")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?,
+        };
+
+        let settings = self.settings()?;
+        for id in &topic_ids {
+            self.replay_topic_history(&settings, *id)?;
+        }
+
+        tx.commit()?;
+        Ok(topic_ids.len())
+    }
+
+    // Recompute next_review for every topic (or those tagged `tag`) from its
+    // current mastery, difficulty, latest outcome (and whether it was on
+    // time) and last_reviewed, using the current settings. Topics never
+    // reviewed keep their date (due from creation). Returns the number of
+    // topics whose next_review changed.
+    pub fn reschedule(&self, tag: Option<&str>) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let settings = self.settings()?;
+        let topics = self.get_topics_with_progress(&TopicQuery {
+            tag: tag.map(|t| t.to_string()),
+            include_archived: true,
+            include_suspended: true,
+            ..TopicQuery::default()
         })?;
-        let topics = rows.collect::<Result<Vec<_>>>()?;
 
-        // Fill in tags
-        let mut result = topics;
-        for twp in &mut result {
-            twp.topic.tags = self.get_topic_tags(twp.topic.id)?;
+        let mut moved = 0;
+        for twp in topics {
+            let progress = &twp.progress;
+            let Some(last_reviewed) = progress.last_reviewed.as_deref().and_then(parse_timestamp)
+            else {
+                continue;
+            };
+            // Progress without history (e.g. imported) counts as a success
+            let last_outcome = self.conn.query_row(
+                "SELECT outcome FROM review_history WHERE topic_id = ?1
+                 ORDER BY julianday(reviewed_at) DESC, id DESC LIMIT 1",
+                params![twp.topic.id],
+                |row| row.get::<_, String>(0),
+            );
+            let outcome = match last_outcome {
+                Ok(o) => ReviewOutcome::from_str(&o).unwrap_or(ReviewOutcome::Success),
+                Err(rusqlite::Error::QueryReturnedNoRows) => ReviewOutcome::Success,
+                Err(e) => return Err(e.into()),
+            };
+            let on_time = self.last_review_on_time(&settings, twp.topic.id)?;
+
+            let interval = scheduler::scheduled_interval(
+                &settings,
+                progress.mastery_level,
+                outcome,
+                progress.difficulty,
+                on_time,
+            );
+            let interval = scheduler::fuzz_interval(
+                &settings,
+                interval,
+                &mut scheduler::review_rng(twp.topic.id, last_reviewed),
+            );
+            let next_review = last_reviewed + interval;
+            if progress.next_review.as_deref().and_then(parse_timestamp) == Some(next_review) {
+                continue;
+            }
+            self.conn.execute(
+                "UPDATE progress SET next_review = ?1 WHERE topic_id = ?2",
+                params![next_review.to_rfc3339(), twp.topic.id],
+            )?;
+            moved += 1;
         }
 
-        Ok(result)
+        tx.commit()?;
+        Ok(moved)
+    }
+
+    // Whether a topic's latest review earned the on-time bonus, judged as
+    // replay_reviews judges it: against the due date the reviews before it
+    // set. A review only counted in a checkpoint can't be judged, so it isn't.
+    fn last_review_on_time(&self, settings: &Settings, topic_id: i64) -> Result<bool> {
+        let (difficulty, mut state) = self.replay_start(topic_id)?;
+        let mut history = self.review_rows(topic_id, &state, None)?;
+        let Some((outcome, reviewed_at, _)) = history.pop() else {
+            return Ok(false);
+        };
+        let (Some(outcome), Some(at)) = (
+            ReviewOutcome::from_str(&outcome),
+            parse_timestamp(&reviewed_at),
+        ) else {
+            return Ok(false);
+        };
+        self.replay_reviews(settings, topic_id, difficulty, &mut state, history);
+        let due = state.next_review.as_deref().and_then(parse_timestamp);
+        Ok(scheduler::earns_on_time_bonus(settings, outcome, due, at))
+    }
+
+    // The latest review counted in `topic_id`'s checkpoint, if any; reviews
+    // can't be added before it
+    fn checkpoint_time(&self, topic_id: i64) -> Result<Option<DateTime<Utc>>> {
+        let at = match self.history_checkpoint(topic_id)? {
+            Some(state) => state.last_reviewed,
+            None => None,
+        };
+        Ok(at.as_deref().and_then(parse_timestamp))
+    }
+
+    fn history_checkpoint(&self, topic_id: i64) -> Result<Option<ReplayState>> {
+        let checkpoint = self.conn.query_row(
+            "SELECT mastery_level, times_reviewed, times_succeeded, last_reviewed,
+                    next_review, notes
+             FROM history_checkpoints WHERE topic_id = ?1",
+            params![topic_id],
+            |row| {
+                Ok(ReplayState {
+                    mastery_level: row.get(0)?,
+                    times_reviewed: row.get(1)?,
+                    times_succeeded: row.get(2)?,
+                    last_reviewed: row.get(3)?,
+                    next_review: row.get(4)?,
+                    notes: row.get(5)?,
+                })
+            },
+        );
+        match checkpoint {
+            Ok(state) => Ok(Some(state)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Where replaying a topic's reviews starts (its checkpoint, or else the
+    // mastery it was added at), with the topic's difficulty
+    fn replay_start(&self, topic_id: i64) -> Result<(i32, ReplayState)> {
+        let start = self.conn.query_row(
+            "SELECT difficulty, initial_mastery FROM progress WHERE topic_id = ?1",
+            params![topic_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        );
+        let (difficulty, initial_mastery): (i32, i32) = match start {
+            Ok(start) => start,
+            Err(rusqlite::Error::QueryReturnedNoRows) => (DEFAULT_DIFFICULTY, 0),
+            Err(e) => return Err(e.into()),
+        };
+        let state = self.history_checkpoint(topic_id)?.unwrap_or(ReplayState {
+            mastery_level: initial_mastery,
+            ..ReplayState::default()
+        });
+        Ok((difficulty, state))
+    }
+
+    // A topic's reviews still to replay on top of `start` as (outcome,
+    // reviewed_at, notes) in time order: those after the last one it counts,
+    // and only those before `before` if given
+    fn review_rows(
+        &self,
+        topic_id: i64,
+        start: &ReplayState,
+        before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<(String, String, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            // julianday so SQLite-format and RFC 3339 timestamps interleave by time
+            "SELECT outcome, reviewed_at, notes FROM review_history
+             WHERE topic_id = ?1
+               AND (julianday(?2) IS NULL OR julianday(reviewed_at) > julianday(?2))
+               AND (?3 IS NULL OR julianday(reviewed_at) < julianday(?3))
+             ORDER BY julianday(reviewed_at) ASC, id ASC",
+        )?;
+        let params = params![
+            topic_id,
+            start.last_reviewed,
+            before.map(|b| b.to_rfc3339())
+        ];
+        let rows = stmt
+            .query_map(params, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    // Run `history` through the scheduler on top of `state`
+    fn replay_reviews(
+        &self,
+        settings: &Settings,
+        topic_id: i64,
+        difficulty: i32,
+        state: &mut ReplayState,
+        history: Vec<(String, String, Option<String>)>,
+    ) {
+        for (outcome_str, reviewed_at, review_notes) in history {
+            let Some(outcome) = ReviewOutcome::from_str(&outcome_str) else {
+                continue;
+            };
+            let at = parse_timestamp(&reviewed_at).unwrap_or_else(|| self.now());
+            let due = state.next_review.as_deref().and_then(parse_timestamp);
+            let on_time = scheduler::earns_on_time_bonus(settings, outcome, due, at);
+            let (new_mastery, interval) = scheduler::schedule_review(
+                settings,
+                state.mastery_level,
+                outcome,
+                difficulty,
+                on_time,
+                &mut scheduler::review_rng(topic_id, at),
+            );
+
+            state.mastery_level = new_mastery;
+            state.times_reviewed += 1;
+            if matches!(outcome, ReviewOutcome::Success) {
+                state.times_succeeded += 1;
+            }
+            state.next_review = Some((at + interval).to_rfc3339());
+            state.last_reviewed = Some(reviewed_at);
+            state.notes = review_notes.or(state.notes.take());
+        }
+    }
+
+    fn replay_topic_history(&self, settings: &Settings, topic_id: i64) -> Result<()> {
+        let (difficulty, mut state) = self.replay_start(topic_id)?;
+        let history = self.review_rows(topic_id, &state, None)?;
+        self.replay_reviews(settings, topic_id, difficulty, &mut state, history);
+
+        // Topics that lost their progress row get a fresh one
+        self.conn.execute(
+            "INSERT OR IGNORE INTO progress (topic_id) VALUES (?1)",
+            params![topic_id],
+        )?;
+
+        // With no history left, a topic added at a mastery above 0 keeps its
+        // first review date; any other goes back to being due now
+        self.conn.execute(
+            r#"
+            UPDATE progress
+            SET mastery_level = ?1,
+                times_reviewed = ?2,
+                times_succeeded = ?3,
+                last_reviewed = ?4,
+                next_review = COALESCE(?5, CASE WHEN initial_mastery > 0 THEN next_review ELSE ?8 END),
+                notes = ?6
+            WHERE topic_id = ?7
+            "#,
+            params![
+                state.mastery_level,
+                state.times_reviewed,
+                state.times_succeeded,
+                state.last_reviewed,
+                state.next_review,
+                state.notes,
+                topic_id,
+                self.now().to_rfc3339()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    // Spaced repetition intervals (in days) based on mastery level
+    fn calculate_interval(mastery_level: i32) -> i32 {
+        Settings::default().interval(mastery_level)
+    }
+
+    // Stochastic selection for next topic to review
+    pub fn get_next_topic(&self, tag_filter: Option<&str>) -> Result<Option<TopicWithProgress>> {
+        Ok(self
+            .explain_next_topic(tag_filter, SelectionStrategy::Weighted)?
+            .map(|e| e.selected))
+    }
+
+    // The same selection as get_next_topic, with the weights and draw behind it
+    pub fn explain_next_topic(
+        &self,
+        tag_filter: Option<&str>,
+        strategy: SelectionStrategy,
+    ) -> Result<Option<SelectionExplanation>> {
+        self.explain_next_topic_with_rng(tag_filter, strategy, &mut rand::thread_rng())
+    }
+
+    pub fn explain_next_topic_with_rng(
+        &self,
+        tag_filter: Option<&str>,
+        strategy: SelectionStrategy,
+        rng: &mut impl rand::Rng,
+    ) -> Result<Option<SelectionExplanation>> {
+        let topics = self.get_due_topics(tag_filter)?;
+        self.explain_pick(topics, strategy, rng)
+    }
+
+    // As explain_next_topic, but first choose between never-reviewed topics
+    // (with probability `new_ratio`) and ones already in review, then pick
+    // within that pool. Once selection.new_per_day topics have had their
+    // first review today no new ones are offered. 0 and 1 pick only from one
+    // pool; in between, an empty pool falls back to the other.
+    pub fn explain_next_topic_mixed(
+        &self,
+        tag_filter: Option<&str>,
+        strategy: SelectionStrategy,
+        new_ratio: f64,
+        rng: &mut impl rand::Rng,
+    ) -> Result<Option<SelectionExplanation>> {
+        let (new, review): (Vec<_>, Vec<_>) = self
+            .get_due_topics(tag_filter)?
+            .into_iter()
+            .partition(|t| t.progress.times_reviewed == 0);
+        let settings = self.settings()?;
+        let under_cap = match settings.new_per_day {
+            Some(limit) => self.count_new_reviewed_today()? < limit as i64,
+            None => true,
+        };
+        let (new_available, review_available) = (
+            !new.is_empty() && under_cap && new_ratio > 0.0,
+            !review.is_empty() && new_ratio < 1.0,
+        );
+        // Deterministic selection goes with whichever pool the ratio favours
+        let pool = if settings.deterministic && new_available && review_available {
+            Some(if new_ratio >= 0.5 {
+                TopicPool::New
+            } else {
+                TopicPool::Review
+            })
+        } else {
+            TopicPool::choose(new_available, review_available, new_ratio, rng)
+        };
+        let topics = match pool {
+            Some(TopicPool::New) => new,
+            Some(TopicPool::Review) => review,
+            None => return Ok(None),
+        };
+        Ok(self
+            .explain_pick(topics, strategy, rng)?
+            .map(|explanation| SelectionExplanation {
+                pool,
+                ..explanation
+            }))
+    }
+
+    // Topics whose first review happened today
+    fn count_new_reviewed_today(&self) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FROM (
+               SELECT MIN(reviewed_at) AS first FROM review_history GROUP BY topic_id
+             ) WHERE date(first) = date(?1)",
+            params![self.sql_now()],
+            |row| row.get(0),
+        )?)
+    }
+
+    // Choose among `topics` by `strategy`, with the weights behind the pick
+    fn explain_pick(
+        &self,
+        mut topics: Vec<TopicWithProgress>,
+        strategy: SelectionStrategy,
+        rng: &mut impl rand::Rng,
+    ) -> Result<Option<SelectionExplanation>> {
+        if topics.is_empty() {
+            return Ok(None);
+        }
+
+        // Weighted: by overdue-ness and lower mastery. Random: equal weights.
+        let (now, settings) = (self.now(), self.settings()?);
+        let mut candidates: Vec<SelectionCandidate> = topics
+            .iter()
+            .map(|t| selection_candidate(t, now, &settings.selection))
+            .collect();
+        if strategy == SelectionStrategy::Random {
+            candidates.iter_mut().for_each(|c| c.weight = 1.0);
+        }
+        let total_weight: f64 = candidates.iter().map(|c| c.weight).sum();
+
+        let (index, draw) = match strategy {
+            // selection.deterministic: no draw, the heaviest topic wins
+            SelectionStrategy::Weighted | SelectionStrategy::Random if settings.deterministic => {
+                (max_weight_index(&candidates), None)
+            }
+            SelectionStrategy::Weighted | SelectionStrategy::Random => {
+                let draw = rng.gen::<f64>() * total_weight;
+                (pick_weighted(&candidates, draw), Some(draw))
+            }
+            // min_by_key keeps the first of equal keys, and None sorts first
+            SelectionStrategy::Overdue => (
+                min_index_by_key(&topics, |t| t.progress.next_review_dt()),
+                None,
+            ),
+            SelectionStrategy::Lru => (
+                min_index_by_key(&topics, |t| {
+                    t.progress
+                        .last_reviewed
+                        .as_deref()
+                        .and_then(parse_timestamp)
+                }),
+                None,
+            ),
+        };
+
+        Ok(Some(SelectionExplanation {
+            selected: topics.swap_remove(index),
+            strategy,
+            candidates,
+            total_weight,
+            draw,
+            pool: None,
+        }))
+    }
+
+    // Topics missing their progress row aren't scheduled, so they never come
+    // up until doctor --fix gives them one
+    fn get_due_topics(&self, tag_filter: Option<&str>) -> Result<Vec<TopicWithProgress>> {
+        let mut topics = self.get_topics_with_progress(&TopicQuery {
+            tag: tag_filter.map(|t| t.to_string()),
+            sort: TopicSort::NextReview,
+            ..TopicQuery::default()
+        })?;
+        topics.retain(|t| t.progress.next_review.is_some());
+        Ok(topics)
     }
 
     // Learning session operations
     pub fn start_session(&self, topic_id: i64, session_type: SessionType) -> Result<i64> {
-        let now = Utc::now();
+        let now = self.now();
         let progress = self.get_progress(topic_id)?;
         let skill_at_start = progress.map(|p| p.skill_level.as_i32());
 
@@ -627,22 +1921,116 @@ impl Database {
         summary: Option<&str>,
         notes: Option<&str>,
     ) -> Result<()> {
-        let now = Utc::now();
+        self.end_session_with_gaps(session_id, outcome, summary, notes, None, &[])?;
+        Ok(())
+    }
+
+    // End a session and record the gaps found in it, all in one transaction.
+    // A transcript replaces any stored one; None keeps it. Returns the new gap IDs.
+    pub fn end_session_with_gaps(
+        &self,
+        session_id: i64,
+        outcome: SessionOutcome,
+        summary: Option<&str>,
+        notes: Option<&str>,
+        transcript: Option<&str>,
+        gaps: &[String],
+    ) -> Result<Vec<i64>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let now = self.now();
         self.conn.execute(
             r#"
             UPDATE learning_sessions
-            SET ended_at = ?1, outcome = ?2, summary = ?3, notes = ?4
-            WHERE id = ?5
+            SET ended_at = ?1, outcome = ?2, summary = ?3, notes = ?4,
+                transcript = COALESCE(?5, transcript)
+            WHERE id = ?6
             "#,
             params![
                 now.to_rfc3339(),
                 outcome.as_str(),
                 summary,
                 notes,
+                transcript,
                 session_id
             ],
         )?;
-        Ok(())
+
+        let gap_ids = gaps
+            .iter()
+            .map(|gap| self.add_session_gap(session_id, gap))
+            .collect::<Result<Vec<_>>>()?;
+
+        tx.commit()?;
+        Ok(gap_ids)
+    }
+
+    // Start a session for every active topic tagged `tag`, in one
+    // transaction. Returns (topic ID, session ID) pairs in topic order.
+    pub fn start_sessions_for_tag(
+        &self,
+        tag: &str,
+        session_type: SessionType,
+    ) -> Result<Vec<(i64, i64)>> {
+        let topics = self.get_topics(&TopicQuery {
+            tag: Some(tag.to_string()),
+            sort: TopicSort::Id,
+            ..TopicQuery::default()
+        })?;
+        let tx = self.conn.unchecked_transaction()?;
+        let started = topics
+            .iter()
+            .map(|t| Ok((t.id, self.start_session(t.id, session_type)?)))
+            .collect::<Result<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(started)
+    }
+
+    // Active sessions started at or after `since`, oldest first
+    pub fn active_session_ids_since(&self, since: DateTime<Utc>) -> Result<Vec<i64>> {
+        let mut sessions = self.query_sessions(&SessionQuery {
+            active: true,
+            ..SessionQuery::default()
+        })?;
+        sessions.retain(|s| parse_timestamp(&s.started_at).is_some_and(|t| t >= since));
+        let mut ids: Vec<i64> = sessions.iter().map(|s| s.id).collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    // End each of `session_ids` that is still active with `outcome`, in one
+    // transaction. Missing and already-ended sessions are skipped; returns
+    // the IDs actually ended.
+    pub fn end_sessions(&self, session_ids: &[i64], outcome: SessionOutcome) -> Result<Vec<i64>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let now = self.now().to_rfc3339();
+        let mut ended = Vec::new();
+        for &id in session_ids {
+            let changed = self.conn.execute(
+                "UPDATE learning_sessions SET ended_at = ?1, outcome = ?2
+                 WHERE id = ?3 AND ended_at IS NULL",
+                params![now, outcome.as_str(), id],
+            )?;
+            if changed > 0 {
+                ended.push(id);
+            }
+        }
+        tx.commit()?;
+        Ok(ended)
+    }
+
+    // Kept out of LearningSession so session lists stay light; None if the
+    // session has no transcript or doesn't exist
+    pub fn get_session_transcript(&self, session_id: i64) -> Result<Option<String>> {
+        let transcript = self.conn.query_row(
+            "SELECT transcript FROM learning_sessions WHERE id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        );
+        match transcript {
+            Ok(t) => Ok(t),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
     }
 
     pub fn get_session(&self, session_id: i64) -> Result<Option<LearningSession>> {
@@ -675,38 +2063,45 @@ impl Database {
         match session {
             Ok(s) => Ok(Some(s)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
     pub fn list_sessions(&self, topic_id: Option<i64>) -> Result<Vec<LearningSession>> {
-        let (query, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) =
-            if let Some(tid) = topic_id {
-                (
-                    r#"
-                SELECT id, topic_id, session_type, started_at, ended_at,
-                       skill_level_at_start, outcome, summary, notes
-                FROM learning_sessions
-                WHERE topic_id = ?1
-                ORDER BY started_at DESC
-                "#
-                    .to_string(),
-                    vec![Box::new(tid)],
-                )
-            } else {
-                (
-                    r#"
-                SELECT id, topic_id, session_type, started_at, ended_at,
-                       skill_level_at_start, outcome, summary, notes
-                FROM learning_sessions
-                ORDER BY started_at DESC
-                "#
-                    .to_string(),
-                    vec![],
-                )
-            };
+        self.query_sessions(&SessionQuery {
+            topic_id,
+            ..SessionQuery::default()
+        })
+    }
 
-        let mut stmt = self.conn.prepare(&query)?;
+    pub fn query_sessions(&self, query: &SessionQuery) -> Result<Vec<LearningSession>> {
+        let mut sql = String::from(
+            "SELECT id, topic_id, session_type, started_at, ended_at,
+                    skill_level_at_start, outcome, summary, notes
+             FROM learning_sessions",
+        );
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(topic_id) = query.topic_id {
+            conditions.push("topic_id = ?");
+            params_vec.push(Box::new(topic_id));
+        }
+        if query.active {
+            conditions.push("ended_at IS NULL");
+        }
+        if let Some(outcome) = query.outcome {
+            conditions.push("outcome = ?");
+            params_vec.push(Box::new(outcome.as_str()));
+        }
+
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY started_at DESC");
+
+        let mut stmt = self.conn.prepare(&sql)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> =
             params_vec.iter().map(|b| b.as_ref()).collect();
 
@@ -727,7 +2122,7 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
     // Session gap operations
@@ -761,7 +2156,81 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // Append a prompt after the topic's others; returns its id
+    pub fn add_prompt(&self, topic_id: i64, prompt: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO topic_prompts (topic_id, prompt, ord)
+             SELECT ?1, ?2, COALESCE(MAX(ord), 0) + 1 FROM topic_prompts WHERE topic_id = ?1",
+            params![topic_id, prompt],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_prompts(&self, topic_id: i64) -> Result<Vec<TopicPrompt>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, topic_id, prompt, ord FROM topic_prompts WHERE topic_id = ?1
+             ORDER BY ord, id",
+        )?;
+        let rows = stmt.query_map(params![topic_id], |row| {
+            Ok(TopicPrompt {
+                id: row.get(0)?,
+                topic_id: row.get(1)?,
+                prompt: row.get(2)?,
+                ord: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn delete_prompt(&self, prompt_id: i64) -> Result<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM topic_prompts WHERE id = ?1",
+            params![prompt_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // Attach a link to a topic; returns its id
+    pub fn add_resource(&self, topic_id: i64, label: Option<&str>, url: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO topic_resources (topic_id, label, url) VALUES (?1, ?2, ?3)",
+            params![topic_id, label, url],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_resources(&self, topic_id: i64) -> Result<Vec<TopicResource>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, topic_id, label, url FROM topic_resources WHERE topic_id = ?1
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![topic_id], |row| {
+            Ok(TopicResource {
+                id: row.get(0)?,
+                topic_id: row.get(1)?,
+                label: row.get(2)?,
+                url: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn delete_resource(&self, resource_id: i64) -> Result<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM topic_resources WHERE id = ?1",
+            params![resource_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // The prompt to show at the topic's next review (see TopicPrompt::for_review)
+    pub fn get_review_prompt(&self, topic_id: i64) -> Result<Option<TopicPrompt>> {
+        let prompts = self.get_prompts(topic_id)?;
+        let times_reviewed = self.get_progress(topic_id)?.map_or(0, |p| p.times_reviewed);
+        Ok(TopicPrompt::for_review(&prompts, times_reviewed).cloned())
     }
 
     pub fn get_unaddressed_gaps(&self, topic_id: i64) -> Result<Vec<SessionGap>> {
@@ -784,7 +2253,31 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // All gaps across topics, optionally filtered by addressed state
+    pub fn get_all_gaps(&self, addressed: Option<bool>) -> Result<Vec<SessionGap>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT sg.id, sg.session_id, sg.gap_description, sg.addressed
+            FROM session_gaps sg
+            JOIN learning_sessions ls ON sg.session_id = ls.id
+            WHERE ?1 IS NULL OR sg.addressed = ?1
+            ORDER BY ls.started_at DESC, sg.id
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![addressed.map(i32::from)], |row| {
+            Ok(SessionGap {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                gap_description: row.get(2)?,
+                addressed: row.get::<_, i32>(3)? != 0,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
     // Skill assessment operations
@@ -795,7 +2288,7 @@ impl Database {
         method: AssessmentMethod,
         notes: Option<&str>,
     ) -> Result<()> {
-        let now = Utc::now();
+        let now = self.now();
 
         // Get current level for history
         let current = self.get_progress(topic_id)?;
@@ -858,12 +2351,12 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
     // Plan operations
     pub fn create_plan(&self, title: &str, initial_description: &str) -> Result<i64> {
-        let now = Utc::now();
+        let now = self.now();
         self.conn.execute(
             r#"
             INSERT INTO plans (title, initial_description, created_at, updated_at)
@@ -900,7 +2393,7 @@ impl Database {
         match plan {
             Ok(p) => Ok(Some(p)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -946,11 +2439,36 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
+    // Only moves PlanStatus::can_transition_to allows; setting the current
+    // status again is a no-op
     pub fn update_plan_status(&self, plan_id: i64, status: PlanStatus) -> Result<()> {
-        let now = Utc::now();
+        let plan = self
+            .get_plan(plan_id)?
+            .ok_or(FeynmanError::NotFound("Plan"))?;
+        if plan.status == status {
+            return Ok(());
+        }
+        if !plan.status.can_transition_to(status) {
+            let allowed: Vec<&str> = plan
+                .status
+                .allowed_transitions()
+                .iter()
+                .map(PlanStatus::as_str)
+                .collect();
+            let allowed = if allowed.is_empty() {
+                "none".to_string()
+            } else {
+                allowed.join(", ")
+            };
+            return Err(FeynmanError::Validation(format!(
+                "Can't move a plan from {} to {} (allowed: {})",
+                plan.status, status, allowed
+            )));
+        }
+        let now = self.now();
         self.conn.execute(
             "UPDATE plans SET status = ?1, updated_at = ?2 WHERE id = ?3",
             params![status.as_str(), now.to_rfc3339(), plan_id],
@@ -959,7 +2477,7 @@ impl Database {
     }
 
     pub fn update_plan_engineer_level(&self, plan_id: i64, level: &str) -> Result<()> {
-        let now = Utc::now();
+        let now = self.now();
         self.conn.execute(
             "UPDATE plans SET engineer_level = ?1, updated_at = ?2 WHERE id = ?3",
             params![level, now.to_rfc3339(), plan_id],
@@ -968,7 +2486,7 @@ impl Database {
     }
 
     pub fn update_plan_spec_path(&self, plan_id: i64, path: &str) -> Result<()> {
-        let now = Utc::now();
+        let now = self.now();
         self.conn.execute(
             "UPDATE plans SET spec_file_path = ?1, status = 'spec_ready', updated_at = ?2 WHERE id = ?3",
             params![path, now.to_rfc3339(), plan_id],
@@ -991,7 +2509,7 @@ impl Database {
         content: &str,
         category: InterviewCategory,
     ) -> Result<i64> {
-        let now = Utc::now();
+        let now = self.now();
         self.conn.execute(
             r#"
             INSERT INTO plan_interview_entries (plan_id, entry_type, content, category, created_at)
@@ -1021,7 +2539,7 @@ impl Database {
             SELECT id, plan_id, entry_type, content, category, created_at
             FROM plan_interview_entries
             WHERE plan_id = ?1
-            ORDER BY created_at ASC
+            ORDER BY created_at ASC, id ASC
             "#,
         )?;
 
@@ -1040,7 +2558,7 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
     pub fn get_interview_entries_by_category(
@@ -1053,7 +2571,7 @@ impl Database {
             SELECT id, plan_id, entry_type, content, category, created_at
             FROM plan_interview_entries
             WHERE plan_id = ?1 AND category = ?2
-            ORDER BY created_at ASC
+            ORDER BY created_at ASC, id ASC
             "#,
         )?;
 
@@ -1072,74 +2590,74 @@ impl Database {
             })
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // Append a freeform note to a plan's running log (a Note entry under Other)
+    pub fn add_plan_note(&self, plan_id: i64, content: &str) -> Result<i64> {
+        self.add_interview_entry(
+            plan_id,
+            InterviewEntryType::Note,
+            content,
+            InterviewCategory::Other,
+        )
+    }
+
+    // Every note entry added with add_plan_note, oldest first
+    pub fn get_plan_notes(&self, plan_id: i64) -> Result<Vec<InterviewEntry>> {
+        Ok(self
+            .get_interview_entries_by_category(plan_id, InterviewCategory::Other)?
+            .into_iter()
+            .filter(|e| e.entry_type == InterviewEntryType::Note)
+            .collect())
     }
 
     // TUI helper methods
-    pub fn get_topics_with_progress(
-        &self,
-        tag_filter: Option<&str>,
-    ) -> Result<Vec<TopicWithProgress>> {
-        let base_query = r#"
-            SELECT t.id, t.name, t.description, t.created_at, t.updated_at,
-                   p.id, p.topic_id, p.mastery_level, p.times_reviewed, p.times_succeeded,
-                   p.last_reviewed, p.next_review, p.notes, p.skill_level, p.assessment_method, p.last_assessed
-            FROM topics t
-            JOIN progress p ON t.id = p.topic_id
-        "#;
+    pub fn get_topics_with_progress(&self, query: &TopicQuery) -> Result<Vec<TopicWithProgress>> {
+        let (filter, mut params_vec) = self.topic_filter_sql(query);
+        // LEFT JOIN so a topic missing its progress row is still listed, as
+        // get_topics lists it
+        let mut sql = format!(
+            "SELECT {}, {} FROM topics t LEFT JOIN progress p ON t.id = p.topic_id{}",
+            TOPIC_COLUMNS, PROGRESS_COLUMNS, filter
+        );
 
-        let (query, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) =
-            if let Some(tag) = tag_filter {
-                let q = format!(
-                    r#"{}
-                JOIN topic_tags tt ON t.id = tt.topic_id
-                JOIN tags tg ON tt.tag_id = tg.id
-                WHERE tg.name = ?1
-                ORDER BY t.name
-                "#,
-                    base_query
-                );
-                (q, vec![Box::new(tag.to_string())])
-            } else {
-                let q = format!("{} ORDER BY t.name", base_query);
-                (q, vec![])
-            };
+        sql.push_str(match query.sort {
+            TopicSort::Name => " ORDER BY t.name",
+            TopicSort::NextReview => " ORDER BY p.next_review ASC, p.mastery_level ASC",
+            TopicSort::Mastery => " ORDER BY p.mastery_level ASC, t.name",
+            TopicSort::Health => " ORDER BY t.name",
+            TopicSort::Id => " ORDER BY t.id",
+        });
 
-        let mut stmt = self.conn.prepare(&query)?;
+        // Health is sorted below, so the limit has to wait until then
+        let sql_limit = query.limit.filter(|_| query.sort != TopicSort::Health);
+        if let Some(limit) = sql_limit {
+            sql.push_str(" LIMIT ?");
+            params_vec.push(Box::new(limit as i64));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> =
             params_vec.iter().map(|b| b.as_ref()).collect();
 
-        let rows = stmt.query_map(params_refs.as_slice(), |row| {
-            let skill_level_int: i32 = row.get(13)?;
-            let assessment_str: String = row.get(14)?;
-            Ok(TopicWithProgress {
-                topic: Topic {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                    tags: vec![],
-                },
-                progress: Progress {
-                    id: row.get(5)?,
-                    topic_id: row.get(6)?,
-                    mastery_level: row.get(7)?,
-                    times_reviewed: row.get(8)?,
-                    times_succeeded: row.get(9)?,
-                    last_reviewed: row.get(10)?,
-                    next_review: row.get(11)?,
-                    notes: row.get(12)?,
-                    skill_level: SkillLevel::from_i32(skill_level_int),
-                    assessment_method: AssessmentMethod::from_str(&assessment_str),
-                    last_assessed: row.get(15)?,
-                },
-            })
-        })?;
-        let topics = rows.collect::<Result<Vec<_>>>()?;
+        let rows = stmt.query_map(params_refs.as_slice(), topic_with_progress_from_row)?;
+        let mut result = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        if query.sort == TopicSort::Health {
+            let (now, weights) = (self.now(), self.settings()?.health);
+            // Stable sort keeps name order among equal scores
+            result.sort_by(|a, b| {
+                let a = a.progress.health_score_with(now, &weights);
+                let b = b.progress.health_score_with(now, &weights);
+                a.total_cmp(&b)
+            });
+            if let Some(limit) = query.limit {
+                result.truncate(limit);
+            }
+        }
 
         // Fill in tags
-        let mut result = topics;
         for twp in &mut result {
             twp.topic.tags = self.get_topic_tags(twp.topic.id)?;
         }
@@ -1147,50 +2665,135 @@ impl Database {
         Ok(result)
     }
 
+    // The topics get_topics_with_progress would return, without loading
+    // progress: no progress join, and tags come from one query rather than
+    // one per topic. For listing large decks. Sorts that need progress
+    // (next review, mastery, health) and due filters go through the full query.
+    pub fn get_topics(&self, query: &TopicQuery) -> Result<Vec<Topic>> {
+        let needs_progress = query.due != DueFilter::Any;
+        let order = match query.sort {
+            TopicSort::Name if !needs_progress => " ORDER BY t.name",
+            TopicSort::Id if !needs_progress => " ORDER BY t.id",
+            _ => {
+                return Ok(self
+                    .get_topics_with_progress(query)?
+                    .into_iter()
+                    .map(|twp| twp.topic)
+                    .collect());
+            }
+        };
+
+        let (filter, mut params_vec) = self.topic_filter_sql(query);
+        let mut sql = format!("SELECT {} FROM topics t{}{}", TOPIC_COLUMNS, filter, order);
+        if let Some(limit) = query.limit {
+            sql.push_str(" LIMIT ?");
+            params_vec.push(Box::new(limit as i64));
+        }
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params_vec.iter().map(|b| b.as_ref()).collect();
+        let rows = stmt.query_map(params_refs.as_slice(), |row| topic_from_row(row, 0))?;
+        let mut topics = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut tags = self.get_tags_by_topic()?;
+        for topic in &mut topics {
+            topic.tags = tags.remove(&topic.id).unwrap_or_default();
+        }
+        Ok(topics)
+    }
+
+    // Joins and WHERE clause for a TopicQuery's filters, with their params.
+    // Due filters refer to the progress table, so need it joined as `p`.
+    fn topic_filter_sql(&self, query: &TopicQuery) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut sql = String::new();
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(tag) = &query.tag {
+            sql.push_str(
+                " JOIN topic_tags tt ON t.id = tt.topic_id JOIN tags tg ON tt.tag_id = tg.id",
+            );
+            conditions.push("tg.name = ?1");
+            params_vec.push(Box::new(normalize_tag(tag)));
+        }
+        if !query.include_archived {
+            conditions.push("t.archived = 0");
+        }
+        if !query.include_suspended {
+            conditions.push("t.suspended = 0");
+        }
+        if let Some(after) = query.after {
+            conditions.push("t.id > ?");
+            params_vec.push(Box::new(after));
+        }
+        match query.due {
+            DueFilter::Any => {}
+            DueFilter::Due => {
+                conditions.push("datetime(p.next_review) <= datetime(?)");
+                params_vec.push(Box::new(self.sql_now()));
+            }
+            DueFilter::Overdue => {
+                conditions.push("datetime(p.next_review) < date(?)");
+                params_vec.push(Box::new(self.sql_now()));
+            }
+        }
+
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        (sql, params_vec)
+    }
+
+    // Every topic's tag names, sorted, in one query
+    fn get_tags_by_topic(&self) -> Result<HashMap<i64, Vec<String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tt.topic_id, tg.name FROM topic_tags tt JOIN tags tg ON tg.id = tt.tag_id
+             ORDER BY tg.name",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))?;
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (topic_id, name) = row?;
+            tags.entry(topic_id).or_default().push(name);
+        }
+        Ok(tags)
+    }
+
+    // Convenience for the common case: neither archived nor suspended
+    pub fn get_active_topics_with_progress(
+        &self,
+        tag_filter: Option<&str>,
+    ) -> Result<Vec<TopicWithProgress>> {
+        self.get_topics_with_progress(&TopicQuery {
+            tag: tag_filter.map(|t| t.to_string()),
+            ..TopicQuery::default()
+        })
+    }
+
     pub fn get_due_topics_limited(&self, limit: usize) -> Result<Vec<TopicWithProgress>> {
-        let query = r#"
-            SELECT t.id, t.name, t.description, t.created_at, t.updated_at,
-                   p.id, p.topic_id, p.mastery_level, p.times_reviewed, p.times_succeeded,
-                   p.last_reviewed, p.next_review, p.notes, p.skill_level, p.assessment_method, p.last_assessed
+        let query = format!(
+            r#"
+            SELECT {}, {}
             FROM topics t
             JOIN progress p ON t.id = p.topic_id
-            WHERE p.next_review <= datetime('now')
+            WHERE datetime(p.next_review) <= datetime(?2)
+              AND t.archived = 0 AND t.suspended = 0
             ORDER BY p.next_review ASC, p.mastery_level ASC
             LIMIT ?1
-        "#;
+            "#,
+            TOPIC_COLUMNS, PROGRESS_COLUMNS
+        );
 
-        let mut stmt = self.conn.prepare(query)?;
-        let rows = stmt.query_map(params![limit as i64], |row| {
-            let skill_level_int: i32 = row.get(13)?;
-            let assessment_str: String = row.get(14)?;
-            Ok(TopicWithProgress {
-                topic: Topic {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                    tags: vec![],
-                },
-                progress: Progress {
-                    id: row.get(5)?,
-                    topic_id: row.get(6)?,
-                    mastery_level: row.get(7)?,
-                    times_reviewed: row.get(8)?,
-                    times_succeeded: row.get(9)?,
-                    last_reviewed: row.get(10)?,
-                    next_review: row.get(11)?,
-                    notes: row.get(12)?,
-                    skill_level: SkillLevel::from_i32(skill_level_int),
-                    assessment_method: AssessmentMethod::from_str(&assessment_str),
-                    last_assessed: row.get(15)?,
-                },
-            })
-        })?;
-        let topics = rows.collect::<Result<Vec<_>>>()?;
+        let mut stmt = self.conn.prepare(&query)?;
+        let rows = stmt.query_map(
+            params![limit as i64, self.sql_now()],
+            topic_with_progress_from_row,
+        )?;
+        let mut result = rows.collect::<rusqlite::Result<Vec<_>>>()?;
 
         // Fill in tags
-        let mut result = topics;
         for twp in &mut result {
             twp.topic.tags = self.get_topic_tags(twp.topic.id)?;
         }
@@ -1198,9 +2801,12 @@ impl Database {
         Ok(result)
     }
 
+    // Sessions newest first with their topic names, `limit` of them after
+    // skipping the newest `offset`
     pub fn get_recent_sessions_with_topics(
         &self,
         limit: usize,
+        offset: usize,
     ) -> Result<Vec<(LearningSession, String)>> {
         let query = r#"
             SELECT ls.id, ls.topic_id, ls.session_type, ls.started_at, ls.ended_at,
@@ -1208,12 +2814,12 @@ impl Database {
                    t.name
             FROM learning_sessions ls
             JOIN topics t ON ls.topic_id = t.id
-            ORDER BY ls.started_at DESC
-            LIMIT ?1
+            ORDER BY julianday(ls.started_at) DESC, ls.id DESC
+            LIMIT ?1 OFFSET ?2
         "#;
 
         let mut stmt = self.conn.prepare(query)?;
-        let rows = stmt.query_map(params![limit as i64], |row| {
+        let rows = stmt.query_map(params![limit as i64, offset as i64], |row| {
             let session_type_str: String = row.get(2)?;
             let outcome_str: Option<String> = row.get(6)?;
             let topic_name: String = row.get(9)?;
@@ -1234,7 +2840,22 @@ impl Database {
             ))
         })?;
 
-        rows.collect()
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // Number of topics at each mastery level, for every level 0-5
+    pub fn get_mastery_histogram(&self) -> Result<Vec<(i32, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.mastery_level, COUNT(*) FROM progress p
+             JOIN topics t ON t.id = p.topic_id
+             GROUP BY p.mastery_level",
+        )?;
+        let counts = stmt
+            .query_map([], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok((0..=settings::MAX_MASTERY)
+            .map(|level| (level, counts.get(&level).copied().unwrap_or(0)))
+            .collect())
     }
 
     pub fn get_stats(&self) -> Result<Stats> {
@@ -1253,8 +2874,11 @@ impl Database {
         )?;
 
         let due_now: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM progress WHERE next_review <= datetime('now')",
-            [],
+            r#"
+            SELECT COUNT(*) FROM progress p JOIN topics t ON t.id = p.topic_id
+            WHERE datetime(p.next_review) <= datetime(?1) AND t.archived = 0 AND t.suspended = 0
+            "#,
+            params![self.sql_now()],
             |row| row.get(0),
         )?;
 
@@ -1267,632 +2891,4281 @@ impl Database {
             )
             .unwrap_or(0.0);
 
+        let (total_gaps, open_gaps): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(addressed = 0), 0) FROM session_gaps",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
         Ok(Stats {
             total_topics,
             total_reviews,
             mastered,
             due_now,
             avg_mastery,
+            avg_mastery_rounded: round_to_tenth(avg_mastery),
+            total_gaps,
+            open_gaps,
         })
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct Stats {
-    pub total_topics: i64,
-    pub total_reviews: i64,
-    pub mastered: i64,
-    pub due_now: i64,
-    pub avg_mastery: f64,
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn setup_db() -> Database {
-        let db = Database::open(":memory:").expect("Failed to create in-memory database");
-        db.init().expect("Failed to initialize database");
-        db
+    // get_stats for each tag, by tag name. Due counts leave out archived and
+    // suspended topics, as the deck-wide count does.
+    pub fn get_tag_stats(&self) -> Result<Vec<TagStats>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT tg.name,
+                   COUNT(*),
+                   COALESCE(SUM(datetime(p.next_review) <= datetime(?1)
+                                AND t.archived = 0 AND t.suspended = 0), 0),
+                   COALESCE(AVG(p.mastery_level), 0)
+            FROM tags tg
+            JOIN topic_tags tt ON tt.tag_id = tg.id
+            JOIN topics t ON t.id = tt.topic_id
+            LEFT JOIN progress p ON p.topic_id = t.id
+            GROUP BY tg.id
+            ORDER BY tg.name
+            "#,
+        )?;
+        let rows = stmt.query_map(params![self.sql_now()], |row| {
+            Ok(TagStats {
+                tag: row.get(0)?,
+                topics: row.get(1)?,
+                due_now: row.get(2)?,
+                avg_mastery: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
     }
 
-    mod init_tests {
-        use super::*;
-
-        #[test]
-        fn init_creates_tables() {
-            let db = setup_db();
-            // Verify tables exist by querying them
-            let topics: i64 = db
-                .conn
-                .query_row("SELECT COUNT(*) FROM topics", [], |row| row.get(0))
-                .expect("topics table should exist");
-            assert_eq!(topics, 0);
+    // Import operations
+    //
+    // Writes every row `validation` did not reject, remapping file-local IDs
+    // to new rows. Runs in a single transaction.
+    pub fn import_data(
+        &self,
+        data: &ImportData,
+        validation: &ImportValidation,
+    ) -> Result<ImportSummary> {
+        let tx = self.conn.unchecked_transaction()?;
+        let (summary, _) = self.insert_import_rows(data, validation)?;
+        tx.commit()?;
+        Ok(summary)
+    }
 
-            let tags: i64 = db
-                .conn
-                .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0))
-                .expect("tags table should exist");
-            assert_eq!(tags, 0);
+    // As import_data, then each imported topic's progress is rebuilt from its
+    // imported reviews (for sources like Anki that bring history but no
+    // feynman progress)
+    pub fn import_and_replay(
+        &self,
+        data: &ImportData,
+        validation: &ImportValidation,
+    ) -> Result<ImportSummary> {
+        let tx = self.conn.unchecked_transaction()?;
+        let (summary, topic_map) = self.insert_import_rows(data, validation)?;
+        let settings = self.settings()?;
+        for id in topic_map.values() {
+            self.replay_topic_history(&settings, *id)?;
+        }
+        tx.commit()?;
+        Ok(summary)
+    }
 
-            let progress: i64 = db
-                .conn
-                .query_row("SELECT COUNT(*) FROM progress", [], |row| row.get(0))
-                .expect("progress table should exist");
-            assert_eq!(progress, 0);
+    // The inserts behind import_data; the caller owns the transaction.
+    // Returns the summary and the file-local to new topic id map.
+    fn insert_import_rows(
+        &self,
+        data: &ImportData,
+        validation: &ImportValidation,
+    ) -> Result<(ImportSummary, HashMap<i64, i64>)> {
+        let mut summary = ImportSummary {
+            skipped: validation.skipped(),
+            ..Default::default()
+        };
 
-            let history: i64 = db
-                .conn
-                .query_row("SELECT COUNT(*) FROM review_history", [], |row| row.get(0))
-                .expect("review_history table should exist");
-            assert_eq!(history, 0);
+        let mut topic_map: HashMap<i64, i64> = HashMap::new();
+        for (i, topic) in data.topics.iter().enumerate() {
+            if validation.skip_topics.contains(&i) {
+                continue;
+            }
+            let new_id =
+                self.add_topic(topic.name.trim(), topic.description.as_deref(), &topic.tags)?;
+            if let Some(p) = &topic.progress {
+                self.conn.execute(
+                    r#"
+                    UPDATE progress
+                    SET mastery_level = ?1, times_reviewed = ?2, times_succeeded = ?3,
+                        last_reviewed = ?4, next_review = COALESCE(?5, next_review)
+                    WHERE topic_id = ?6
+                    "#,
+                    params![
+                        p.mastery_level,
+                        p.times_reviewed,
+                        p.times_succeeded,
+                        p.last_reviewed,
+                        p.next_review,
+                        new_id
+                    ],
+                )?;
+                // Replays start from the imported progress rather than from
+                // scratch: once reviewed it's a checkpoint (covering the
+                // imported reviews up to its last_reviewed), otherwise just
+                // the mastery the topic starts at
+                if p.last_reviewed.is_some() || p.times_reviewed > 0 {
+                    self.conn.execute(
+                        "INSERT INTO history_checkpoints
+                           (topic_id, mastery_level, times_reviewed, times_succeeded,
+                            last_reviewed, next_review)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            new_id,
+                            p.mastery_level,
+                            p.times_reviewed,
+                            p.times_succeeded,
+                            p.last_reviewed,
+                            p.next_review
+                        ],
+                    )?;
+                } else {
+                    self.conn.execute(
+                        "UPDATE progress SET initial_mastery = mastery_level WHERE topic_id = ?1",
+                        params![new_id],
+                    )?;
+                }
+            }
+            topic_map.insert(topic.id, new_id);
+            summary.topics += 1;
         }
 
-        #[test]
-        fn init_is_idempotent() {
-            let db = setup_db();
-            // Add some data
-            db.add_topic("Test", None, &[]).unwrap();
+        for (i, review) in data.reviews.iter().enumerate() {
+            if validation.skip_reviews.contains(&i) {
+                continue;
+            }
+            let Some(outcome) = ReviewOutcome::from_str(&review.outcome) else {
+                continue;
+            };
+            self.conn.execute(
+                "INSERT INTO review_history (topic_id, outcome, reviewed_at, notes) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    topic_map[&review.topic_id],
+                    outcome.as_str(),
+                    review.reviewed_at,
+                    review.notes
+                ],
+            )?;
+            summary.reviews += 1;
+        }
 
-            // Re-init should not fail or clear data
-            db.init().expect("Re-init should succeed");
+        let mut session_map: HashMap<i64, i64> = HashMap::new();
+        for (i, session) in data.sessions.iter().enumerate() {
+            if validation.skip_sessions.contains(&i) {
+                continue;
+            }
+            let Some(session_type) = SessionType::from_str(&session.session_type) else {
+                continue;
+            };
+            let outcome = session
+                .outcome
+                .as_deref()
+                .and_then(SessionOutcome::from_str)
+                .map(|o| o.as_str());
+            self.conn.execute(
+                r#"
+                INSERT INTO learning_sessions (topic_id, session_type, started_at, ended_at, outcome, summary, notes)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                "#,
+                params![
+                    topic_map[&session.topic_id],
+                    session_type.as_str(),
+                    session.started_at,
+                    session.ended_at,
+                    outcome,
+                    session.summary,
+                    session.notes
+                ],
+            )?;
+            session_map.insert(session.id, self.conn.last_insert_rowid());
+            summary.sessions += 1;
+        }
 
-            let topics = db.list_topics(None).unwrap();
-            assert_eq!(topics.len(), 1);
+        for (i, gap) in data.gaps.iter().enumerate() {
+            if validation.skip_gaps.contains(&i) {
+                continue;
+            }
+            self.conn.execute(
+                "INSERT INTO session_gaps (session_id, gap_description, addressed) VALUES (?1, ?2, ?3)",
+                params![
+                    session_map[&gap.session_id],
+                    gap.gap_description,
+                    gap.addressed as i32
+                ],
+            )?;
+            summary.gaps += 1;
         }
+
+        Ok((summary, topic_map))
     }
 
-    mod topic_tests {
-        use super::*;
+    // Lightweight counts for the one-line status output (no row loads)
+    pub fn get_status_counts(&self) -> Result<StatusCounts> {
+        let due: i64 = self.conn.query_row(
+            r#"
+            SELECT COUNT(*) FROM progress p JOIN topics t ON t.id = p.topic_id
+            WHERE datetime(p.next_review) <= datetime(?1) AND t.archived = 0 AND t.suspended = 0
+            "#,
+            params![self.sql_now()],
+            |row| row.get(0),
+        )?;
 
-        #[test]
-        fn add_topic_basic() {
-            let db = setup_db();
+        // Overdue = was already due before the start of today
+        let overdue: i64 = self.conn.query_row(
+            r#"
+            SELECT COUNT(*) FROM progress p JOIN topics t ON t.id = p.topic_id
+            WHERE datetime(p.next_review) < date(?1) AND t.archived = 0 AND t.suspended = 0
+            "#,
+            params![self.sql_now()],
+            |row| row.get(0),
+        )?;
+
+        let avg_mastery: f64 = self.conn.query_row(
+            "SELECT COALESCE(AVG(mastery_level), 0) FROM progress",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT date(reviewed_at) FROM review_history WHERE date(reviewed_at) IS NOT NULL ORDER BY 1 DESC",
+        )?;
+        let days = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(StatusCounts {
+            due,
+            overdue,
+            streak: Self::calculate_streak(&days, self.now().date_naive()),
+            avg_mastery,
+            avg_mastery_rounded: round_to_tenth(avg_mastery),
+        })
+    }
+
+    // Consecutive review days ending today (or yesterday, so the streak
+    // survives until the end of the current day). `days` must be sorted newest first.
+    fn calculate_streak(days: &[String], today: chrono::NaiveDate) -> i64 {
+        let mut expected = today;
+        let mut streak = 0;
+
+        for (i, day) in days.iter().enumerate() {
+            let Ok(date) = chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+                continue;
+            };
+            if i == 0 && date == today - chrono::Duration::days(1) {
+                expected = date;
+            }
+            if date != expected {
+                break;
+            }
+            streak += 1;
+            expected = date - chrono::Duration::days(1);
+        }
+
+        streak
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub topics: usize,
+    pub reviews: usize,
+    pub sessions: usize,
+    pub gaps: usize,
+    pub skipped: usize,
+}
+
+// What `topic import-json` did with each entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+// Columns the scheduler reads and compares as timestamps. feynman writes
+// RFC 3339, but older versions and SQL defaults left "YYYY-MM-DD HH:MM:SS",
+// which sorts and compares differently as text.
+const TIMESTAMP_COLUMNS: &[(&str, &str)] = &[
+    ("progress", "next_review"),
+    ("progress", "last_reviewed"),
+    ("review_history", "reviewed_at"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimestampFormat {
+    Rfc3339,
+    Sqlite,
+    Invalid,
+}
+
+impl TimestampFormat {
+    fn of(value: &str) -> Self {
+        if DateTime::parse_from_rfc3339(value).is_ok() {
+            TimestampFormat::Rfc3339
+        } else if parse_timestamp(value).is_some() {
+            TimestampFormat::Sqlite
+        } else {
+            TimestampFormat::Invalid
+        }
+    }
+}
+
+// How many values of one column are in each format, for `doctor`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampFormats {
+    // e.g. "progress.next_review"
+    pub column: String,
+    pub rfc3339: usize,
+    pub sqlite: usize,
+    pub invalid: usize,
+}
+
+impl TimestampFormats {
+    // Anything not already RFC 3339
+    pub fn mismatched(&self) -> usize {
+        self.sqlite + self.invalid
+    }
+}
+
+// Rows moved onto the target by merge_topics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeSummary {
+    pub reviews: usize,
+    pub sessions: usize,
+    pub assessments: usize,
+    pub prompts: usize,
+    pub resources: usize,
+    pub tags: usize,
+}
+
+// Rows deleted by purge_topic, per table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeSummary {
+    pub reviews: usize,
+    pub sessions: usize,
+    pub gaps: usize,
+    pub assessments: usize,
+    pub prompts: usize,
+    pub resources: usize,
+    pub tags: usize,
+    pub progress: usize,
+}
+
+fn selection_candidate(
+    twp: &TopicWithProgress,
+    now: DateTime<Utc>,
+    weights: &SelectionWeights,
+) -> SelectionCandidate {
+    let overdue_days = twp.progress.overdue_days(now);
+
+    // Lower mastery = higher weight, overdue = higher weight
+    let mastery_gap = (weights.baseline - twp.progress.mastery_level).max(1);
+    let mastery_factor = (mastery_gap as f64).powf(weights.mastery);
+
+    // Played down if it was reviewed within the recency window
+    let recent = twp
+        .progress
+        .last_reviewed
+        .as_deref()
+        .and_then(parse_timestamp)
+        .is_some_and(|last| {
+            weights.recent_hours > 0
+                && now - last < chrono::Duration::hours(weights.recent_hours as i64)
+        });
+    let recency_factor = if recent { RECENT_PENALTY } else { 1.0 };
+    SelectionCandidate {
+        topic_id: twp.topic.id,
+        weight: (overdue_days as f64 + 1.0).powf(weights.overdue) * mastery_factor * recency_factor,
+        overdue_days,
+        mastery_factor,
+        recency_factor,
+    }
+}
+
+// Index of the first candidate whose cumulative weight reaches `draw`
+// (the first candidate if rounding leaves nothing)
+fn pick_weighted(candidates: &[SelectionCandidate], draw: f64) -> usize {
+    let mut remaining = draw;
+    for (i, candidate) in candidates.iter().enumerate() {
+        remaining -= candidate.weight;
+        if remaining <= 0.0 {
+            return i;
+        }
+    }
+    0
+}
+
+// The heaviest candidate, ties going to the lowest topic id
+fn max_weight_index(candidates: &[SelectionCandidate]) -> usize {
+    candidates
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| {
+            a.weight
+                .total_cmp(&b.weight)
+                .then(b.topic_id.cmp(&a.topic_id))
+        })
+        .map_or(0, |(i, _)| i)
+}
+
+// Gaps in whole days between consecutive timestamps (expects them sorted)
+fn interval_days(timestamps: &[DateTime<Utc>]) -> Vec<i64> {
+    timestamps
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_days())
+        .collect()
+}
+
+fn min_index_by_key<K: Ord>(
+    topics: &[TopicWithProgress],
+    key: impl Fn(&TopicWithProgress) -> K,
+) -> usize {
+    topics
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, t)| key(t))
+        .map_or(0, |(i, _)| i)
+}
+
+const TOPIC_COLUMNS: &str =
+    "t.id, t.name, t.description, t.created_at, t.updated_at, t.archived, t.suspended";
+
+const PROGRESS_COLUMNS: &str =
+    "p.id, p.topic_id, p.mastery_level, p.times_reviewed, p.times_succeeded, \
+     p.last_reviewed, p.next_review, p.notes, p.skill_level, p.assessment_method, p.last_assessed, \
+     p.difficulty";
+
+// Number of columns in TOPIC_COLUMNS, i.e. where progress columns start
+const TOPIC_COLUMN_COUNT: usize = 7;
+
+fn topic_from_row(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Topic> {
+    Ok(Topic {
+        id: row.get(offset)?,
+        name: row.get(offset + 1)?,
+        description: row.get(offset + 2)?,
+        created_at: row.get(offset + 3)?,
+        updated_at: row.get(offset + 4)?,
+        archived: row.get::<_, i32>(offset + 5)? != 0,
+        suspended: row.get::<_, i32>(offset + 6)? != 0,
+        tags: vec![],
+    })
+}
+
+fn progress_from_row(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Progress> {
+    let skill_level_int: i32 = row.get(offset + 8)?;
+    let assessment_str: String = row.get(offset + 9)?;
+    Ok(Progress {
+        id: row.get(offset)?,
+        topic_id: row.get(offset + 1)?,
+        mastery_level: row.get(offset + 2)?,
+        times_reviewed: row.get(offset + 3)?,
+        times_succeeded: row.get(offset + 4)?,
+        last_reviewed: row.get(offset + 5)?,
+        next_review: row.get(offset + 6)?,
+        notes: row.get(offset + 7)?,
+        skill_level: SkillLevel::from_i32(skill_level_int),
+        assessment_method: AssessmentMethod::from_str(&assessment_str),
+        last_assessed: row.get(offset + 10)?,
+        difficulty: row.get(offset + 11)?,
+    })
+}
+
+// Maps a review_history row selected as
+// `id, topic_id, outcome, reviewed_at, notes, confidence`
+fn review_from_row(row: &rusqlite::Row) -> rusqlite::Result<ReviewRecord> {
+    let outcome_str: String = row.get(2)?;
+    Ok(ReviewRecord {
+        id: row.get(0)?,
+        topic_id: row.get(1)?,
+        outcome: ReviewOutcome::from_str(&outcome_str).unwrap_or(ReviewOutcome::Fail),
+        reviewed_at: row.get(3)?,
+        notes: row.get(4)?,
+        confidence: row.get(5)?,
+    })
+}
+
+// Maps a row selected as `TOPIC_COLUMNS, PROGRESS_COLUMNS`
+fn topic_with_progress_from_row(row: &rusqlite::Row) -> rusqlite::Result<TopicWithProgress> {
+    let topic = topic_from_row(row, 0)?;
+    // A LEFT JOIN leaves the progress columns NULL when the row is missing
+    let progress = match row.get::<_, Option<i64>>(TOPIC_COLUMN_COUNT)? {
+        Some(_) => progress_from_row(row, TOPIC_COLUMN_COUNT)?,
+        None => missing_progress(topic.id),
+    };
+    Ok(TopicWithProgress { topic, progress })
+}
+
+// Stands in for a progress row that's missing (see topics_missing_progress):
+// new, never reviewed and not scheduled
+fn missing_progress(topic_id: i64) -> Progress {
+    Progress {
+        id: 0,
+        topic_id,
+        mastery_level: 0,
+        times_reviewed: 0,
+        times_succeeded: 0,
+        last_reviewed: None,
+        next_review: None,
+        notes: None,
+        skill_level: SkillLevel::from_i32(0),
+        assessment_method: AssessmentMethod::None,
+        last_assessed: None,
+        difficulty: DEFAULT_DIFFICULTY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::open(":memory:").expect("Failed to create in-memory database");
+        db.init().expect("Failed to initialize database");
+        db
+    }
+
+    fn progress_fields(db: &Database, id: i64) -> (i32, i32, i32, Option<String>, Option<String>) {
+        let p = db.get_progress(id).unwrap().unwrap();
+        (
+            p.mastery_level,
+            p.times_reviewed,
+            p.times_succeeded,
+            p.last_reviewed,
+            p.next_review,
+        )
+    }
+
+    // Averages come out of SQLite as floats, so compare with some slack
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    mod init_tests {
+        use super::*;
+
+        #[test]
+        fn init_creates_tables() {
+            let db = setup_db();
+            // Verify tables exist by querying them
+            let topics: i64 = db
+                .conn
+                .query_row("SELECT COUNT(*) FROM topics", [], |row| row.get(0))
+                .expect("topics table should exist");
+            assert_eq!(topics, 0);
+
+            let tags: i64 = db
+                .conn
+                .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0))
+                .expect("tags table should exist");
+            assert_eq!(tags, 0);
+
+            let progress: i64 = db
+                .conn
+                .query_row("SELECT COUNT(*) FROM progress", [], |row| row.get(0))
+                .expect("progress table should exist");
+            assert_eq!(progress, 0);
+
+            let history: i64 = db
+                .conn
+                .query_row("SELECT COUNT(*) FROM review_history", [], |row| row.get(0))
+                .expect("review_history table should exist");
+            assert_eq!(history, 0);
+        }
+
+        #[test]
+        fn init_records_schema_version() {
+            let db = Database::open(":memory:").unwrap();
+            assert_eq!(db.schema_version().unwrap(), 0);
+            db.init().unwrap();
+            assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        }
+
+        #[test]
+        fn init_is_idempotent() {
+            let db = setup_db();
+            // Add some data
+            db.add_topic("Test", None, &[]).unwrap();
+
+            // Re-init should not fail or clear data
+            db.init().expect("Re-init should succeed");
+
+            let topics = db.list_topics(None).unwrap();
+            assert_eq!(topics.len(), 1);
+        }
+
+        #[test]
+        fn migrate_merges_case_variant_tags() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            // Tags as stored before normalization
+            db.conn
+                .execute_batch(&format!(
+                    "INSERT INTO tags (id, name) VALUES (1, 'Rust'), (2, 'rust'), (3, 'Go  Lang');
+                     INSERT INTO topic_tags (topic_id, tag_id)
+                     VALUES ({a}, 1), ({a}, 2), ({b}, 2), ({b}, 3);
+                     PRAGMA user_version = 6;"
+                ))
+                .unwrap();
+
+            db.init().unwrap();
+
+            let tags: Vec<(i64, String, i64)> = db
+                .list_tags()
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.id, t.name, t.topic_count))
+                .collect();
+            assert_eq!(
+                tags,
+                [(3, "go lang".to_string(), 1), (1, "rust".to_string(), 2)]
+            );
+            assert_eq!(db.get_topic(a).unwrap().unwrap().tags, ["rust"]);
+            assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        }
+
+        #[test]
+        fn failed_migration_names_the_step_and_changes_nothing() {
+            let db = Database::open(":memory:").unwrap();
+            // A half-upgraded progress table: adding skill_level works, but
+            // assessment_method is already there
+            db.conn
+                .execute_batch(
+                    "CREATE TABLE progress (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        topic_id INTEGER NOT NULL UNIQUE,
+                        mastery_level INTEGER NOT NULL DEFAULT 0,
+                        next_review TEXT NOT NULL DEFAULT (datetime('now')),
+                        assessment_method TEXT
+                    );",
+                )
+                .unwrap();
+
+            let e = db.init().unwrap_err();
+            match &e {
+                FeynmanError::Migration { version, step, .. } => {
+                    assert_eq!(*version, SCHEMA_VERSION);
+                    assert_eq!(step, "adding progress.skill_level");
+                }
+                other => panic!("expected a migration error, got {:?}", other),
+            }
+            assert!(e
+                .to_string()
+                .contains("failed at adding progress.skill_level"));
+            assert!(db.conn.prepare("SELECT skill_level FROM progress").is_err());
+            assert_eq!(db.schema_version().unwrap(), 0);
+        }
+
+        #[test]
+        fn migrate_adds_confidence_to_old_review_history() {
+            let db = Database::open(":memory:").unwrap();
+            db.conn
+                .execute_batch(
+                    "CREATE TABLE review_history (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        topic_id INTEGER NOT NULL,
+                        outcome TEXT NOT NULL,
+                        reviewed_at TEXT NOT NULL DEFAULT (datetime('now')),
+                        notes TEXT
+                    );
+                    INSERT INTO review_history (topic_id, outcome) VALUES (1, 'success');",
+                )
+                .unwrap();
+            db.init().unwrap();
+
+            let history = db.get_review_history(None, None, None).unwrap();
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].confidence, None);
+        }
+
+        #[test]
+        fn migrate_adds_transcript_to_old_sessions() {
+            let db = Database::open(":memory:").unwrap();
+            db.conn
+                .execute_batch(
+                    "CREATE TABLE learning_sessions (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        topic_id INTEGER NOT NULL,
+                        session_type TEXT NOT NULL,
+                        started_at TEXT NOT NULL DEFAULT (datetime('now')),
+                        ended_at TEXT,
+                        skill_level_at_start INTEGER,
+                        outcome TEXT,
+                        summary TEXT,
+                        notes TEXT
+                    );
+                    INSERT INTO learning_sessions (topic_id, session_type) VALUES (1, 'feynman');",
+                )
+                .unwrap();
+            db.init().unwrap();
+
+            assert_eq!(db.get_session_transcript(1).unwrap(), None);
+        }
+    }
+
+    mod checkpoint_tests {
+        use super::*;
+
+        fn temp_db_path(name: &str) -> std::path::PathBuf {
+            std::env::temp_dir().join(format!(
+                "feynman-{}-{}-{}.db",
+                name,
+                std::process::id(),
+                Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ))
+        }
+
+        fn wal_len(db_path: &Path) -> u64 {
+            let mut wal = db_path.as_os_str().to_owned();
+            wal.push("-wal");
+            std::fs::metadata(wal).map(|m| m.len()).unwrap_or(0)
+        }
+
+        #[test]
+        fn checkpoint_truncates_wal_after_writes() {
+            let path = temp_db_path("wal");
+            let db = Database::open(&path).unwrap();
+            let mode: String = db
+                .conn
+                .query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(mode, "wal");
+            db.init().unwrap();
+
+            for i in 0..200 {
+                let id = db.add_topic(&format!("Topic {}", i), None, &[]).unwrap();
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            }
+            assert!(wal_len(&path) > 0);
+
+            assert!(db.checkpoint().unwrap());
+            assert_eq!(wal_len(&path), 0);
+            assert_eq!(db.get_stats().unwrap().total_topics, 200);
+
+            drop(db);
+            for suffix in ["", "-wal", "-shm"] {
+                let mut file = path.as_os_str().to_owned();
+                file.push(suffix);
+                let _ = std::fs::remove_file(file);
+            }
+        }
+
+        #[test]
+        fn data_version_moves_only_for_other_connections() {
+            let path = temp_db_path("data-version");
+            let db = Database::open(&path).unwrap();
+            db.init().unwrap();
+            let before = db.data_version().unwrap();
+
+            db.add_topic("Own write", None, &[]).unwrap();
+            assert_eq!(db.data_version().unwrap(), before);
+
+            let other = Database::open(&path).unwrap();
+            other.add_topic("Other write", None, &[]).unwrap();
+            assert_ne!(db.data_version().unwrap(), before);
+
+            drop(other);
+            drop(db);
+            let _ = std::fs::remove_file(path);
+        }
+
+        #[test]
+        fn read_only_connection_reads_but_never_writes() {
+            let path = temp_db_path("read-only");
+            assert!(Database::open_read_only(&path).is_err());
+
+            let db = Database::open(&path).unwrap();
+            db.init().unwrap();
+            db.add_topic("Written", None, &[]).unwrap();
+            drop(db);
+
+            let reader = Database::open_read_only(&path).unwrap();
+            assert_eq!(reader.get_stats().unwrap().total_topics, 1);
+            assert!(reader.add_topic("Refused", None, &[]).is_err());
+            assert_eq!(reader.get_stats().unwrap().total_topics, 1);
+
+            drop(reader);
+            let _ = std::fs::remove_file(path);
+        }
+
+        #[test]
+        fn checkpoint_is_noop_in_memory() {
+            let db = setup_db();
+            db.add_topic("Test", None, &[]).unwrap();
+            assert!(!db.checkpoint().unwrap());
+        }
+    }
+
+    mod purge_tests {
+        use super::*;
+
+        #[test]
+        fn purge_reports_and_removes_everything_for_the_topic() {
+            let db = setup_db();
+            let id = db
+                .add_topic(
+                    "Lifetimes",
+                    None,
+                    &["rust".to_string(), "memory".to_string()],
+                )
+                .unwrap();
+            let keep = db.add_topic("Traits", None, &["rust".to_string()]).unwrap();
+
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            db.record_review(keep, ReviewOutcome::Success, None)
+                .unwrap();
+            let first = db.start_session(id, SessionType::Feynman).unwrap();
+            db.add_session_gap(first, "Variance").unwrap();
+            db.add_session_gap(first, "Elision rules").unwrap();
+            let second = db.start_session(id, SessionType::Socratic).unwrap();
+            db.add_session_gap(second, "'static bounds").unwrap();
+            let kept_session = db.start_session(keep, SessionType::Feynman).unwrap();
+            db.add_session_gap(kept_session, "Object safety").unwrap();
+            db.update_skill_level(
+                id,
+                SkillLevel::Intermediate,
+                AssessmentMethod::SelfAssessed,
+                None,
+            )
+            .unwrap();
+            db.add_prompt(id, "Why can't a struct outlive its borrow?")
+                .unwrap();
+            db.add_prompt(keep, "What makes a trait object safe?")
+                .unwrap();
+            db.add_resource(id, None, "https://doc.rust-lang.org/nomicon/")
+                .unwrap();
+
+            let summary = db.purge_topic(id).unwrap().unwrap();
+            assert_eq!(summary.reviews, 2);
+            assert_eq!(summary.sessions, 2);
+            assert_eq!(summary.gaps, 3);
+            assert_eq!(summary.assessments, 1);
+            assert_eq!(summary.prompts, 1);
+            assert_eq!(summary.resources, 1);
+            assert_eq!(summary.tags, 2);
+            assert_eq!(summary.progress, 1);
+
+            assert!(db.get_topic(id).unwrap().is_none());
+            assert!(db
+                .get_review_history(Some(id), None, None)
+                .unwrap()
+                .is_empty());
+            assert!(db.list_sessions(Some(id)).unwrap().is_empty());
+            assert!(db.get_skill_assessments(id).unwrap().is_empty());
+
+            // The other topic is untouched
+            assert_eq!(
+                db.get_review_history(Some(keep), None, None).unwrap().len(),
+                1
+            );
+            assert_eq!(db.get_session_gaps(kept_session).unwrap().len(), 1);
+            assert_eq!(db.get_prompts(keep).unwrap().len(), 1);
+            assert_eq!(db.get_topic(keep).unwrap().unwrap().tags, ["rust"]);
+        }
+
+        #[test]
+        fn purge_missing_topic_is_none() {
+            let db = setup_db();
+            assert!(db.purge_topic(999).unwrap().is_none());
+        }
+
+        #[test]
+        fn delete_by_tag_removes_exactly_the_tagged_topics() {
+            let db = setup_db();
+            let rust = vec!["rust".to_string()];
+            let a = db.add_topic("Lifetimes", None, &rust).unwrap();
+            let b = db
+                .add_topic("Traits", None, &["rust".to_string(), "types".to_string()])
+                .unwrap();
+            let archived = db.add_topic("Macros", None, &rust).unwrap();
+            db.set_topic_archived(archived, true).unwrap();
+            let keep = db
+                .add_topic("Goroutines", None, &["go".to_string()])
+                .unwrap();
+            let untagged = db.add_topic("Untagged", None, &[]).unwrap();
+            for id in [a, keep] {
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+                let session = db.start_session(id, SessionType::Feynman).unwrap();
+                db.add_session_gap(session, "Gap").unwrap();
+            }
+
+            assert_eq!(db.topics_with_tag("rust").unwrap().len(), 3);
+            assert_eq!(db.delete_topics_by_tag("rust").unwrap(), 3);
+
+            for id in [a, b, archived] {
+                assert!(db.get_topic(id).unwrap().is_none());
+                assert!(db.get_progress(id).unwrap().is_none());
+            }
+            assert!(db
+                .get_review_history(Some(a), None, None)
+                .unwrap()
+                .is_empty());
+            assert!(db.list_sessions(Some(a)).unwrap().is_empty());
+
+            assert_eq!(db.get_topic(keep).unwrap().unwrap().tags, ["go"]);
+            assert!(db.get_topic(untagged).unwrap().is_some());
+            assert_eq!(
+                db.get_review_history(Some(keep), None, None).unwrap().len(),
+                1
+            );
+            assert_eq!(db.list_sessions(Some(keep)).unwrap().len(), 1);
+
+            // Nothing left to delete
+            assert_eq!(db.delete_topics_by_tag("rust").unwrap(), 0);
+        }
+    }
+
+    mod merge_tests {
+        use super::*;
+
+        #[test]
+        fn merge_combines_history_and_removes_source() {
+            let db = setup_db();
+            let source = db
+                .add_topic(
+                    "Borrowing",
+                    None,
+                    &["rust".to_string(), "memory".to_string()],
+                )
+                .unwrap();
+            let target = db
+                .add_topic("Borrow checker", None, &["rust".to_string()])
+                .unwrap();
+
+            db.record_review(source, ReviewOutcome::Success, None)
+                .unwrap();
+            db.record_review(source, ReviewOutcome::Success, None)
+                .unwrap();
+            db.record_review(target, ReviewOutcome::Partial, None)
+                .unwrap();
+            let session = db.start_session(source, SessionType::Feynman).unwrap();
+            db.add_session_gap(session, "Reborrows").unwrap();
+            db.add_prompt(target, "When does a borrow end?").unwrap();
+            db.add_prompt(source, "What is a reborrow?").unwrap();
+            db.add_resource(
+                source,
+                Some("Rustonomicon"),
+                "https://doc.rust-lang.org/nomicon/",
+            )
+            .unwrap();
+
+            let summary = db.merge_topics(source, target).unwrap().unwrap();
+            assert_eq!(summary.reviews, 2);
+            assert_eq!(summary.sessions, 1);
+            assert_eq!(summary.prompts, 1);
+            assert_eq!(summary.resources, 1);
+            assert_eq!(summary.tags, 1);
+
+            assert!(db.get_topic(source).unwrap().is_none());
+            assert!(db.get_progress(source).unwrap().is_none());
+
+            let history = db.get_review_history(Some(target), None, None).unwrap();
+            assert_eq!(history.len(), 3);
+            let progress = db.get_progress(target).unwrap().unwrap();
+            assert_eq!(progress.times_reviewed, 3);
+            assert_eq!(progress.times_succeeded, 2);
+
+            let mut tags = db.get_topic(target).unwrap().unwrap().tags;
+            tags.sort();
+            assert_eq!(tags, ["memory", "rust"]);
+
+            let sessions = db.list_sessions(Some(target)).unwrap();
+            assert_eq!(sessions.len(), 1);
+            assert_eq!(db.get_session_gaps(sessions[0].id).unwrap().len(), 1);
+
+            // The source's prompts are asked after the target's own
+            let prompts: Vec<String> = db
+                .get_prompts(target)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.prompt)
+                .collect();
+            assert_eq!(prompts, ["When does a borrow end?", "What is a reborrow?"]);
+            assert_eq!(db.get_resources(target).unwrap().len(), 1);
+        }
+
+        #[test]
+        fn merge_rejects_self_and_missing_topics() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+            assert!(db.merge_topics(id, id).unwrap().is_none());
+            assert!(db.merge_topics(id, 999).unwrap().is_none());
+            assert!(db.merge_topics(999, id).unwrap().is_none());
+            assert!(db.get_topic(id).unwrap().is_some());
+            assert_eq!(db.get_progress(id).unwrap().unwrap().times_reviewed, 1);
+        }
+    }
+
+    mod topic_tests {
+        use super::*;
+
+        #[test]
+        fn add_topic_basic() {
+            let db = setup_db();
             let id = db.add_topic("Rust Basics", None, &[]).unwrap();
             assert!(id > 0);
 
-            let topic = db.get_topic(id).unwrap().unwrap();
-            assert_eq!(topic.name, "Rust Basics");
-            assert!(topic.description.is_none());
-            assert!(topic.tags.is_empty());
+            let topic = db.get_topic(id).unwrap().unwrap();
+            assert_eq!(topic.name, "Rust Basics");
+            assert!(topic.description.is_none());
+            assert!(topic.tags.is_empty());
+        }
+
+        #[test]
+        fn add_topic_with_description() {
+            let db = setup_db();
+            let id = db
+                .add_topic("Rust Basics", Some("Learn the fundamentals"), &[])
+                .unwrap();
+
+            let topic = db.get_topic(id).unwrap().unwrap();
+            assert_eq!(
+                topic.description,
+                Some("Learn the fundamentals".to_string())
+            );
+        }
+
+        #[test]
+        fn add_topic_with_tags() {
+            let db = setup_db();
+            let tags = vec!["rust".to_string(), "programming".to_string()];
+            let id = db.add_topic("Rust Basics", None, &tags).unwrap();
+
+            let topic = db.get_topic(id).unwrap().unwrap();
+            assert_eq!(topic.tags.len(), 2);
+            assert!(topic.tags.contains(&"rust".to_string()));
+            assert!(topic.tags.contains(&"programming".to_string()));
+        }
+
+        #[test]
+        fn add_topic_creates_progress() {
+            let db = setup_db();
+            let id = db.add_topic("Test Topic", None, &[]).unwrap();
+
+            let progress = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(progress.topic_id, id);
+            assert_eq!(progress.mastery_level, 0);
+            assert_eq!(progress.times_reviewed, 0);
+            assert_eq!(progress.times_succeeded, 0);
+            assert_eq!(progress.skill_level, SkillLevel::Unknown);
+            assert_eq!(progress.assessment_method, AssessmentMethod::None);
+            assert!(progress.last_assessed.is_none());
+        }
+
+        #[test]
+        fn add_topic_duplicate_name_fails() {
+            let db = setup_db();
+            db.add_topic("Unique Name", None, &[]).unwrap();
+            let result = db.add_topic("Unique Name", None, &[]);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn get_topic_not_found() {
+            let db = setup_db();
+            let topic = db.get_topic(999).unwrap();
+            assert!(topic.is_none());
+        }
+
+        #[test]
+        fn list_topics_empty() {
+            let db = setup_db();
+            let topics = db.list_topics(None).unwrap();
+            assert!(topics.is_empty());
+        }
+
+        #[test]
+        fn list_topics_returns_all() {
+            let db = setup_db();
+            db.add_topic("Topic A", None, &[]).unwrap();
+            db.add_topic("Topic B", None, &[]).unwrap();
+            db.add_topic("Topic C", None, &[]).unwrap();
+
+            let topics = db.list_topics(None).unwrap();
+            assert_eq!(topics.len(), 3);
+        }
+
+        #[test]
+        fn list_topics_sorted_by_name() {
+            let db = setup_db();
+            db.add_topic("Zebra", None, &[]).unwrap();
+            db.add_topic("Alpha", None, &[]).unwrap();
+            db.add_topic("Middle", None, &[]).unwrap();
+
+            let topics = db.list_topics(None).unwrap();
+            assert_eq!(topics[0].name, "Alpha");
+            assert_eq!(topics[1].name, "Middle");
+            assert_eq!(topics[2].name, "Zebra");
+        }
+
+        #[test]
+        fn list_topics_filter_by_tag() {
+            let db = setup_db();
+            db.add_topic("Rust Topic", None, &["rust".to_string()])
+                .unwrap();
+            db.add_topic("Go Topic", None, &["go".to_string()]).unwrap();
+            db.add_topic("Both", None, &["rust".to_string(), "go".to_string()])
+                .unwrap();
+
+            let rust_topics = db.list_topics(Some("rust")).unwrap();
+            assert_eq!(rust_topics.len(), 2);
+
+            let go_topics = db.list_topics(Some("go")).unwrap();
+            assert_eq!(go_topics.len(), 2);
+
+            let python_topics = db.list_topics(Some("python")).unwrap();
+            assert!(python_topics.is_empty());
+        }
+
+        #[test]
+        fn delete_topic_success() {
+            let db = setup_db();
+            let id = db.add_topic("To Delete", None, &[]).unwrap();
+
+            let deleted = db.delete_topic(id).unwrap();
+            assert!(deleted);
+
+            let topic = db.get_topic(id).unwrap();
+            assert!(topic.is_none());
+        }
+
+        #[test]
+        fn delete_topic_not_found() {
+            let db = setup_db();
+            let deleted = db.delete_topic(999).unwrap();
+            assert!(!deleted);
+        }
+
+        #[test]
+        fn delete_topic_cascades_progress() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            // Verify progress exists
+            assert!(db.get_progress(id).unwrap().is_some());
+
+            db.delete_topic(id).unwrap();
+
+            // Progress should be gone too
+            assert!(db.get_progress(id).unwrap().is_none());
+        }
+
+        #[test]
+        fn update_topic_tags() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &["old".to_string()]).unwrap();
+
+            db.update_topic_tags(id, &["new1".to_string(), "new2".to_string()])
+                .unwrap();
+
+            let topic = db.get_topic(id).unwrap().unwrap();
+            assert_eq!(topic.tags.len(), 2);
+            assert!(topic.tags.contains(&"new1".to_string()));
+            assert!(topic.tags.contains(&"new2".to_string()));
+            assert!(!topic.tags.contains(&"old".to_string()));
+        }
+
+        #[test]
+        fn update_topic_tags_to_empty() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &["tag1".to_string()]).unwrap();
+
+            db.update_topic_tags(id, &[]).unwrap();
+
+            let topic = db.get_topic(id).unwrap().unwrap();
+            assert!(topic.tags.is_empty());
+        }
+
+        #[test]
+        fn tag_case_and_spacing_variants_resolve_to_one_row() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &["Rust".to_string()]).unwrap();
+            db.add_topic("B", None, &["rust".to_string()]).unwrap();
+            let c = db.add_topic("C", None, &[]).unwrap();
+            db.update_topic_tags(c, &[" RUST ".to_string(), "rust".to_string()])
+                .unwrap();
+
+            let tags = db.list_tags().unwrap();
+            assert_eq!(tags.len(), 1);
+            assert_eq!((tags[0].name.as_str(), tags[0].topic_count), ("rust", 3));
+            assert_eq!(db.get_topic(c).unwrap().unwrap().tags, ["rust"]);
+
+            assert_eq!(db.list_topics(Some("Rust")).unwrap().len(), 3);
+            assert_eq!(db.topics_with_tag(" rust").unwrap().len(), 3);
+            assert_eq!(db.bulk_remove_tag(&[a], "RUST").unwrap(), 1);
+        }
+    }
+
+    mod topic_query_tests {
+        use super::*;
+
+        // active, archived, suspended, archived+suspended
+        fn setup_flagged() -> Database {
+            let db = setup_db();
+            db.add_topic("Active", None, &[]).unwrap();
+            let archived = db.add_topic("Archived", None, &[]).unwrap();
+            let suspended = db.add_topic("Suspended", None, &[]).unwrap();
+            let both = db.add_topic("Both", None, &[]).unwrap();
+            db.set_topic_archived(archived, true).unwrap();
+            db.set_topic_suspended(suspended, true).unwrap();
+            db.set_topic_archived(both, true).unwrap();
+            db.set_topic_suspended(both, true).unwrap();
+            db
+        }
+
+        fn names(db: &Database, include_archived: bool, include_suspended: bool) -> Vec<String> {
+            db.get_topics_with_progress(&TopicQuery {
+                include_archived,
+                include_suspended,
+                ..TopicQuery::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|t| t.topic.name)
+            .collect()
+        }
+
+        #[test]
+        fn lightweight_list_returns_the_same_topics_as_the_detailed_one() {
+            let db = setup_flagged();
+            db.add_topic("Tagged", None, &["rust".to_string(), "db".to_string()])
+                .unwrap();
+            db.add_topic("Also tagged", None, &["rust".to_string()])
+                .unwrap();
+
+            let queries = [
+                TopicQuery::default(),
+                TopicQuery {
+                    include_archived: true,
+                    include_suspended: true,
+                    ..TopicQuery::default()
+                },
+                TopicQuery {
+                    tag: Some("Rust".to_string()),
+                    ..TopicQuery::default()
+                },
+                TopicQuery {
+                    include_archived: true,
+                    sort: TopicSort::Id,
+                    after: Some(2),
+                    limit: Some(2),
+                    ..TopicQuery::default()
+                },
+                TopicQuery {
+                    sort: TopicSort::Mastery,
+                    ..TopicQuery::default()
+                },
+            ];
+            for query in &queries {
+                let detailed: Vec<(i64, Vec<String>)> = db
+                    .get_topics_with_progress(query)
+                    .unwrap()
+                    .into_iter()
+                    .map(|twp| (twp.topic.id, twp.topic.tags))
+                    .collect();
+                let light: Vec<(i64, Vec<String>)> = db
+                    .get_topics(query)
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| (t.id, t.tags))
+                    .collect();
+                assert!(!light.is_empty(), "{:?}", query);
+                assert_eq!(light, detailed, "{:?}", query);
+            }
+        }
+
+        #[test]
+        fn topics_missing_progress_are_listed_with_default_progress() {
+            let db = setup_db();
+            let kept = db.add_topic("Kept", None, &[]).unwrap();
+            let orphan = db.add_topic("Orphan", None, &[]).unwrap();
+            db.conn
+                .execute("DELETE FROM progress WHERE topic_id = ?1", params![orphan])
+                .unwrap();
+
+            let query = TopicQuery::default();
+            let detailed = db.get_topics_with_progress(&query).unwrap();
+            let ids: Vec<i64> = detailed.iter().map(|t| t.topic.id).collect();
+            let light: Vec<i64> = db
+                .get_topics(&query)
+                .unwrap()
+                .iter()
+                .map(|t| t.id)
+                .collect();
+            assert_eq!(ids, [kept, orphan]);
+            assert_eq!(light, ids);
+
+            let progress = &detailed[1].progress;
+            assert_eq!(progress.topic_id, orphan);
+            assert_eq!(progress.mastery_level, 0);
+            assert_eq!(progress.times_reviewed, 0);
+            assert!(progress.next_review.is_none());
+
+            // Still never selected for review
+            assert_eq!(db.get_next_topic(None).unwrap().unwrap().topic.id, kept);
+        }
+
+        #[test]
+        fn health_sort_puts_weakest_first_and_limits_after_sorting() {
+            let db = setup_db();
+            let strong = db.add_topic("A strong", None, &[]).unwrap();
+            let weak = db.add_topic("B weak", None, &[]).unwrap();
+            db.add_topic("C new", None, &[]).unwrap();
+            db.record_review(strong, ReviewOutcome::Success, None)
+                .unwrap();
+            db.record_review(strong, ReviewOutcome::Success, None)
+                .unwrap();
+            db.record_review(weak, ReviewOutcome::Fail, None).unwrap();
+
+            let query = TopicQuery {
+                sort: TopicSort::Health,
+                ..TopicQuery::default()
+            };
+            let names: Vec<String> = db
+                .get_topics_with_progress(&query)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.topic.name)
+                .collect();
+            // Weak and new tie on score; name order breaks the tie
+            assert_eq!(names, ["B weak", "C new", "A strong"]);
+
+            let top = db
+                .get_topics_with_progress(&TopicQuery {
+                    limit: Some(1),
+                    ..query
+                })
+                .unwrap();
+            assert_eq!(top[0].topic.name, "B weak");
+        }
+
+        #[test]
+        fn default_excludes_archived_and_suspended() {
+            let db = setup_flagged();
+            assert_eq!(names(&db, false, false), vec!["Active"]);
+        }
+
+        #[test]
+        fn include_archived_only() {
+            let db = setup_flagged();
+            assert_eq!(names(&db, true, false), vec!["Active", "Archived"]);
+        }
+
+        #[test]
+        fn include_suspended_only() {
+            let db = setup_flagged();
+            assert_eq!(names(&db, false, true), vec!["Active", "Suspended"]);
+        }
+
+        #[test]
+        fn include_both() {
+            let db = setup_flagged();
+            assert_eq!(
+                names(&db, true, true),
+                vec!["Active", "Archived", "Both", "Suspended"]
+            );
+        }
+
+        #[test]
+        fn active_wrapper_matches_default_query() {
+            let db = setup_flagged();
+            let active: Vec<String> = db
+                .get_active_topics_with_progress(None)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.topic.name)
+                .collect();
+            assert_eq!(active, vec!["Active"]);
+        }
+
+        #[test]
+        fn query_combines_tag_and_flags() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &["rust".to_string()]).unwrap();
+            db.add_topic("B", None, &["rust".to_string()]).unwrap();
+            db.add_topic("C", None, &[]).unwrap();
+            db.set_topic_archived(a, true).unwrap();
+
+            let query = TopicQuery {
+                tag: Some("rust".to_string()),
+                include_archived: true,
+                ..TopicQuery::default()
+            };
+            let result = db.get_topics_with_progress(&query).unwrap();
+            assert_eq!(result.len(), 2);
+            assert!(result[0].topic.archived);
+        }
+
+        #[test]
+        fn sort_by_mastery() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            db.add_topic("B", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Success, None).unwrap();
+
+            let query = TopicQuery {
+                sort: TopicSort::Mastery,
+                ..TopicQuery::default()
+            };
+            let result = db.get_topics_with_progress(&query).unwrap();
+            assert_eq!(result[0].topic.name, "B");
+        }
+
+        #[test]
+        fn next_skips_archived_and_suspended() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            db.set_topic_archived(a, true).unwrap();
+            db.set_topic_suspended(b, true).unwrap();
+
+            assert!(db.get_next_topic(None).unwrap().is_none());
+            assert_eq!(db.get_stats().unwrap().due_now, 0);
+        }
+    }
+
+    mod pagination_tests {
+        use super::*;
+
+        fn page(db: &Database, after: Option<i64>, limit: usize) -> Vec<i64> {
+            db.get_topics_with_progress(&TopicQuery {
+                sort: TopicSort::Id,
+                after,
+                limit: Some(limit),
+                ..TopicQuery::default()
+            })
+            .unwrap()
+            .into_iter()
+            .map(|t| t.topic.id)
+            .collect()
+        }
+
+        #[test]
+        fn topics_page_in_two_chunks_without_overlap_or_gaps() {
+            let db = setup_db();
+            let mut ids = Vec::new();
+            // Names out of id order so the id sort is what's tested
+            for name in ["e", "d", "c", "b", "a"] {
+                ids.push(db.add_topic(name, None, &[]).unwrap());
+            }
+
+            let first = page(&db, None, 3);
+            assert_eq!(first, ids[..3]);
+
+            let second = page(&db, first.last().copied(), 3);
+            assert_eq!(second, ids[3..]);
+
+            assert!(page(&db, second.last().copied(), 3).is_empty());
+        }
+
+        #[test]
+        fn topics_after_combines_with_tag_filter() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &["rust".into()]).unwrap();
+            db.add_topic("B", None, &["go".into()]).unwrap();
+            let c = db.add_topic("C", None, &["rust".into()]).unwrap();
+
+            let result = db
+                .get_topics_with_progress(&TopicQuery {
+                    tag: Some("rust".to_string()),
+                    sort: TopicSort::Id,
+                    after: Some(a),
+                    ..TopicQuery::default()
+                })
+                .unwrap();
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0].topic.id, c);
+        }
+
+        #[test]
+        fn review_history_pages_in_two_chunks_without_overlap_or_gaps() {
+            let db = setup_db();
+            let rust = db.add_topic("Rust", None, &[]).unwrap();
+            let go = db.add_topic("Go", None, &[]).unwrap();
+            for topic_id in [rust, go, rust, rust, go] {
+                db.record_review(topic_id, ReviewOutcome::Success, None)
+                    .unwrap();
+            }
+
+            let all: Vec<i64> = db
+                .get_review_history(None, None, None)
+                .unwrap()
+                .iter()
+                .map(|r| r.id)
+                .collect();
+            assert_eq!(all.len(), 5);
+
+            let first = db.get_review_history(None, None, Some(2)).unwrap();
+            let last_id = first.last().map(|r| r.id);
+            let second = db.get_review_history(None, last_id, Some(10)).unwrap();
+
+            let paged: Vec<i64> = first.iter().chain(&second).map(|r| r.id).collect();
+            assert_eq!(paged, all);
+        }
+
+        #[test]
+        fn review_history_filters_by_topic() {
+            let db = setup_db();
+            let rust = db.add_topic("Rust", None, &[]).unwrap();
+            let go = db.add_topic("Go", None, &[]).unwrap();
+            db.record_review(rust, ReviewOutcome::Success, None)
+                .unwrap();
+            db.record_review(go, ReviewOutcome::Fail, Some("channels"))
+                .unwrap();
+
+            let history = db.get_review_history(Some(go), None, None).unwrap();
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].outcome, ReviewOutcome::Fail);
+            assert_eq!(history[0].notes.as_deref(), Some("channels"));
+        }
+    }
+
+    mod tag_tests {
+        use super::*;
+
+        #[test]
+        fn list_tags_empty() {
+            let db = setup_db();
+            let tags = db.list_tags().unwrap();
+            assert!(tags.is_empty());
+        }
+
+        #[test]
+        fn list_tags_with_counts() {
+            let db = setup_db();
+            db.add_topic("T1", None, &["common".to_string()]).unwrap();
+            db.add_topic("T2", None, &["common".to_string()]).unwrap();
+            db.add_topic("T3", None, &["rare".to_string()]).unwrap();
+
+            let tags = db.list_tags().unwrap();
+            assert_eq!(tags.len(), 2);
+
+            let common = tags.iter().find(|t| t.name == "common").unwrap();
+            assert_eq!(common.topic_count, 2);
+
+            let rare = tags.iter().find(|t| t.name == "rare").unwrap();
+            assert_eq!(rare.topic_count, 1);
+        }
+
+        #[test]
+        fn tags_with_topics_list_every_carrier() {
+            let db = setup_db();
+            let t1 = db.add_topic("T1", None, &["common".to_string()]).unwrap();
+            let t2 = db
+                .add_topic("T2", None, &["common".to_string(), "rare".to_string()])
+                .unwrap();
+            db.add_topic("T3", None, &[]).unwrap();
+
+            let tags = db.list_tags_with_topics().unwrap();
+            assert_eq!(
+                tags,
+                [
+                    TagTopics {
+                        tag: "common".to_string(),
+                        topics: vec![
+                            TopicRef {
+                                id: t1,
+                                name: "T1".to_string()
+                            },
+                            TopicRef {
+                                id: t2,
+                                name: "T2".to_string()
+                            },
+                        ],
+                    },
+                    TagTopics {
+                        tag: "rare".to_string(),
+                        topics: vec![TopicRef {
+                            id: t2,
+                            name: "T2".to_string()
+                        }],
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn tags_are_reused() {
+            let db = setup_db();
+            db.add_topic("T1", None, &["shared".to_string()]).unwrap();
+            db.add_topic("T2", None, &["shared".to_string()]).unwrap();
+
+            let tags = db.list_tags().unwrap();
+            assert_eq!(tags.len(), 1);
+            assert_eq!(tags[0].topic_count, 2);
+        }
+    }
+
+    mod tag_prefix_tests {
+        use super::*;
+
+        #[test]
+        fn matches_prefix_case_insensitively() {
+            let db = setup_db();
+            db.add_topic(
+                "A",
+                None,
+                &["rust".to_string(), "Rustc".to_string(), "go".to_string()],
+            )
+            .unwrap();
+
+            assert_eq!(db.tags_matching_prefix("ru").unwrap(), ["rust", "rustc"]);
+            assert_eq!(db.tags_matching_prefix("RUST").unwrap(), ["rust", "rustc"]);
+            assert_eq!(db.tags_matching_prefix("g").unwrap(), ["go"]);
+            assert!(db.tags_matching_prefix("py").unwrap().is_empty());
+            assert_eq!(db.tags_matching_prefix("").unwrap().len(), 3);
+        }
+
+        #[test]
+        fn percent_and_underscore_are_literal() {
+            let db = setup_db();
+            db.add_topic("A", None, &["c_lang".to_string(), "cpp".to_string()])
+                .unwrap();
+            assert_eq!(db.tags_matching_prefix("c_").unwrap(), ["c_lang"]);
+            assert!(db.tags_matching_prefix("%").unwrap().is_empty());
+        }
+    }
+
+    mod bulk_tag_tests {
+        use super::*;
+
+        fn ids_tagged(db: &Database, tag: &str) -> Vec<i64> {
+            db.list_topics(Some(tag))
+                .unwrap()
+                .into_iter()
+                .map(|t| t.id)
+                .collect()
+        }
+
+        #[test]
+        fn apply_only_touches_filtered_topics() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &["rust".into()]).unwrap();
+            db.add_topic("B", None, &["go".into()]).unwrap();
+            let c = db.add_topic("C", None, &["rust".into()]).unwrap();
+
+            let rust = ids_tagged(&db, "rust");
+            assert_eq!(db.bulk_add_tag(&rust, "priority").unwrap(), 2);
+
+            let mut priority = ids_tagged(&db, "priority");
+            priority.sort();
+            assert_eq!(priority, vec![a, c]);
+        }
+
+        #[test]
+        fn reapplying_is_idempotent() {
+            let db = setup_db();
+            db.add_topic("A", None, &["rust".into()]).unwrap();
+            db.add_topic("B", None, &["rust".into(), "priority".into()])
+                .unwrap();
+
+            let rust = ids_tagged(&db, "rust");
+            assert_eq!(db.bulk_add_tag(&rust, "priority").unwrap(), 1);
+            assert_eq!(db.bulk_add_tag(&rust, "priority").unwrap(), 0);
+            assert_eq!(ids_tagged(&db, "priority").len(), 2);
+        }
+
+        #[test]
+        fn strip_removes_tag_from_filtered_topics_only() {
+            let db = setup_db();
+            db.add_topic("A", None, &["rust".into(), "priority".into()])
+                .unwrap();
+            let b = db
+                .add_topic("B", None, &["go".into(), "priority".into()])
+                .unwrap();
+
+            let rust = ids_tagged(&db, "rust");
+            assert_eq!(db.bulk_remove_tag(&rust, "priority").unwrap(), 1);
+            assert_eq!(db.bulk_remove_tag(&rust, "priority").unwrap(), 0);
+            assert_eq!(ids_tagged(&db, "priority"), vec![b]);
+        }
+
+        #[test]
+        fn strip_unknown_tag_affects_nothing() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &["rust".into()]).unwrap();
+            assert_eq!(db.bulk_remove_tag(&[a], "nope").unwrap(), 0);
+        }
+    }
+
+    mod review_tests {
+        use super::*;
+        use crate::clock::FixedClock;
+
+        #[test]
+        fn record_review_success_increases_mastery() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            // Initial mastery is 0
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 0);
+
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 1);
+            assert_eq!(p.times_reviewed, 1);
+            assert_eq!(p.times_succeeded, 1);
+        }
+
+        #[test]
+        fn record_review_of_a_missing_topic_is_not_found() {
+            let db = setup_db();
+            let e = db
+                .record_review(99, ReviewOutcome::Success, None)
+                .unwrap_err();
+            assert!(matches!(e, FeynmanError::NotFound("Topic")), "{:?}", e);
+            // Nothing was written for it
+            assert!(db
+                .get_review_history(Some(99), None, None)
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn record_review_success_caps_at_5() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            // Do 10 successful reviews
+            for _ in 0..10 {
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            }
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 5); // Should cap at 5
+            assert_eq!(p.times_reviewed, 10);
+            assert_eq!(p.times_succeeded, 10);
+        }
+
+        #[test]
+        fn record_review_partial_maintains_level() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            // Get to level 2
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 2);
+
+            // Partial should maintain level
+            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 2);
+            assert_eq!(p.times_reviewed, 3);
+            assert_eq!(p.times_succeeded, 2); // Only successes count
+        }
+
+        #[test]
+        fn record_review_fail_decreases_mastery() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            // Get to level 3
+            for _ in 0..3 {
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            }
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 3);
+
+            // Fail should decrease
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 2);
+        }
+
+        #[test]
+        fn record_review_fail_floors_at_0() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            // Fail multiple times at level 0
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 0); // Should not go negative
+        }
+
+        #[test]
+        fn record_review_with_notes() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            db.record_review(id, ReviewOutcome::Success, Some("Great session!"))
+                .unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.notes, Some("Great session!".to_string()));
+        }
+
+        #[test]
+        fn backfilled_review_schedules_from_its_own_date() {
+            let db = setup_db().with_clock(FixedClock::at("2024-03-10T12:00:00Z"));
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            let at = parse_timestamp("2024-03-01T09:00:00Z").unwrap();
+
+            db.record_review_with(
+                id,
+                ReviewOutcome::Success,
+                None,
+                &ReviewOptions {
+                    at: Some(at),
+                    ..ReviewOptions::default()
+                },
+            )
+            .unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.last_reviewed.as_deref(), Some(at.to_rfc3339().as_str()));
+            assert_eq!(p.next_review_dt(), Some(at + chrono::Duration::days(2)));
+            let history = db.get_review_history(Some(id), None, None).unwrap();
+            assert_eq!(history[0].reviewed_at, at.to_rfc3339());
+        }
+
+        #[test]
+        fn out_of_order_backfill_does_not_regress_last_reviewed() {
+            let db = setup_db().with_clock(FixedClock::at("2024-03-10T12:00:00Z"));
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            let latest = db.get_progress(id).unwrap().unwrap();
+
+            // A fail from before the success arrives late
+            db.record_review_with(
+                id,
+                ReviewOutcome::Fail,
+                Some("offline"),
+                &ReviewOptions {
+                    at: parse_timestamp("2024-03-01T09:00:00Z"),
+                    ..ReviewOptions::default()
+                },
+            )
+            .unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.last_reviewed, latest.last_reviewed);
+            assert_eq!(p.next_review, latest.next_review);
+            assert_eq!(p.times_reviewed, 2);
+            assert_eq!(p.times_succeeded, 1);
+            // fail (0 -> 0) then success (0 -> 1), in date order
+            assert_eq!(p.mastery_level, 1);
+        }
+
+        #[test]
+        fn record_review_updates_timestamps() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            let p_before = db.get_progress(id).unwrap().unwrap();
+            assert!(p_before.last_reviewed.is_none());
+
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+            let p_after = db.get_progress(id).unwrap().unwrap();
+            assert!(p_after.last_reviewed.is_some());
+            assert!(p_after.next_review.is_some());
+        }
+
+        #[test]
+        fn record_review_retry_now_leaves_topic_due() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+            let options = ReviewOptions {
+                retry_now: true,
+                ..ReviewOptions::default()
+            };
+            db.record_review_with(id, ReviewOutcome::Fail, None, &options)
+                .unwrap();
+
+            let progress = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(progress.mastery_level, 0);
+            let next = DateTime::parse_from_rfc3339(&progress.next_review.unwrap()).unwrap();
+            assert!(next.with_timezone(&Utc) <= Utc::now());
+
+            let due = db.get_due_topics_limited(10).unwrap();
+            assert_eq!(due.len(), 1);
+        }
+
+        #[test]
+        fn record_review_retry_now_ignored_on_success() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            let options = ReviewOptions {
+                retry_now: true,
+                ..ReviewOptions::default()
+            };
+            db.record_review_with(id, ReviewOutcome::Success, None, &options)
+                .unwrap();
+
+            let progress = db.get_progress(id).unwrap().unwrap();
+            let next = DateTime::parse_from_rfc3339(&progress.next_review.unwrap()).unwrap();
+            assert!(next.with_timezone(&Utc) > Utc::now());
+        }
+
+        #[test]
+        fn record_review_creates_history() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+
+            db.record_review(id, ReviewOutcome::Success, Some("note1"))
+                .unwrap();
+            db.record_review(id, ReviewOutcome::Fail, Some("note2"))
+                .unwrap();
+
+            let count: i64 = db
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) FROM review_history WHERE topic_id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(count, 2);
+        }
+    }
+
+    mod interval_history_tests {
+        use super::*;
+        use crate::clock::FixedClock;
+        use chrono::Duration;
+
+        fn at(s: &str) -> DateTime<Utc> {
+            parse_timestamp(s).unwrap()
+        }
+
+        #[test]
+        fn interval_days_are_gaps_between_consecutive_reviews() {
+            let series = [
+                at("2024-03-01T09:00:00Z"),
+                at("2024-03-02T10:00:00Z"),
+                at("2024-03-04T08:00:00Z"),
+                at("2024-03-11T09:00:00Z"),
+                at("2024-03-11T18:00:00Z"),
+            ];
+            // A gap just short of a full day rounds down
+            assert_eq!(interval_days(&series), vec![1, 1, 7, 0]);
+        }
+
+        #[test]
+        fn fewer_than_two_reviews_have_no_intervals() {
+            assert!(interval_days(&[]).is_empty());
+            assert!(interval_days(&[at("2024-03-01T09:00:00Z")]).is_empty());
+        }
+
+        #[test]
+        fn topic_intervals_follow_review_dates_not_insert_order() {
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            let other = db.add_topic("Borrowing", None, &[]).unwrap();
+
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            clock.advance(Duration::days(4));
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.record_review(other, ReviewOutcome::Success, None)
+                .unwrap();
+            // Backfilled between the two
+            let options = ReviewOptions {
+                at: Some(at("2024-03-02T12:00:00Z")),
+                ..ReviewOptions::default()
+            };
+            db.record_review_with(id, ReviewOutcome::Success, None, &options)
+                .unwrap();
+
+            assert_eq!(db.get_topic_intervals(id).unwrap(), vec![1, 3]);
+            assert!(db.get_topic_intervals(other).unwrap().is_empty());
+        }
+    }
+
+    mod mastery_trajectory_tests {
+        use super::*;
+        use crate::clock::FixedClock;
+        use chrono::Duration;
+
+        #[test]
+        fn trajectory_replays_setbacks() {
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = setup_db().with_clock(clock.clone());
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            assert!(db.get_mastery_trajectory(id).unwrap().is_empty());
+
+            for outcome in [
+                ReviewOutcome::Success,
+                ReviewOutcome::Success,
+                ReviewOutcome::Fail,
+                ReviewOutcome::Success,
+            ] {
+                db.record_review(id, outcome, None).unwrap();
+                clock.advance(Duration::days(1));
+            }
+            assert_eq!(db.get_mastery_trajectory(id).unwrap(), vec![1, 2, 1, 2]);
+            assert_eq!(db.get_progress(id).unwrap().unwrap().mastery_level, 2);
+        }
+
+        #[test]
+        fn trajectory_starts_from_the_added_mastery() {
+            let db = setup_db();
+            let id = db
+                .add_topic_with(
+                    "Big-O",
+                    None,
+                    &[],
+                    &NewTopicOptions {
+                        mastery: 3,
+                        skill: None,
+                    },
+                )
+                .unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            assert_eq!(db.get_mastery_trajectory(id).unwrap(), vec![2]);
+        }
+    }
+
+    mod rebuild_tests {
+        use super::*;
+
+        #[test]
+        fn fuzz_spreads_a_batch_and_replays_exactly() {
+            let db = setup_db();
+            db.set_setting(settings::SCHEDULE_FUZZ_PERCENT, "25")
+                .unwrap();
+            let options = ReviewOptions {
+                at: parse_timestamp("2024-03-01T09:00:00Z"),
+                ..ReviewOptions::default()
+            };
+            let mut ids = Vec::new();
+            for i in 0..20 {
+                let id = db.add_topic(&format!("Topic {}", i), None, &[]).unwrap();
+                db.record_review_with(id, ReviewOutcome::Success, None, &options)
+                    .unwrap();
+                ids.push(id);
+            }
+            let next_reviews: Vec<Option<String>> = ids
+                .iter()
+                .map(|&id| db.get_progress(id).unwrap().unwrap().next_review)
+                .collect();
+            let distinct: std::collections::HashSet<&Option<String>> =
+                next_reviews.iter().collect();
+            assert!(distinct.len() > 1, "every review landed on one date");
+
+            // Level 1 waits 2 days, so 25% is ±1. The seed is per review, so
+            // reschedule and a replay land on the same dates.
+            assert_eq!(db.reschedule(None).unwrap(), 0);
+            let before: Vec<_> = ids.iter().map(|&id| progress_fields(&db, id)).collect();
+            db.rebuild_progress(Some(ids[0])).unwrap();
+            assert_eq!(progress_fields(&db, ids[0]), before[0]);
+        }
+
+        #[test]
+        fn rebuild_matches_incremental_progress() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            for outcome in [
+                ReviewOutcome::Success,
+                ReviewOutcome::Success,
+                ReviewOutcome::Fail,
+                ReviewOutcome::Partial,
+                ReviewOutcome::Success,
+            ] {
+                db.record_review(id, outcome, None).unwrap();
+            }
+            let expected = progress_fields(&db, id);
+
+            // Corrupt the progress row, then rebuild from history
+            db.conn
+                .execute(
+                    "UPDATE progress SET mastery_level = 5, times_reviewed = 0, next_review = 'garbage' WHERE topic_id = ?1",
+                    params![id],
+                )
+                .unwrap();
+            assert_eq!(db.rebuild_progress(Some(id)).unwrap(), 1);
+
+            assert_eq!(progress_fields(&db, id), expected);
+        }
+
+        #[test]
+        fn rebuild_without_history_resets_to_new() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.conn
+                .execute(
+                    "UPDATE progress SET mastery_level = 3, times_reviewed = 4 WHERE topic_id = ?1",
+                    params![id],
+                )
+                .unwrap();
+
+            db.rebuild_progress(None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 0);
+            assert_eq!(p.times_reviewed, 0);
+            assert!(p.last_reviewed.is_none());
+        }
+
+        #[test]
+        fn rebuild_recreates_missing_progress_row() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.conn
+                .execute("DELETE FROM progress WHERE topic_id = ?1", params![id])
+                .unwrap();
+
+            db.rebuild_progress(None).unwrap();
+
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 1);
+            assert_eq!(p.times_reviewed, 1);
+        }
+
+        #[test]
+        fn backfill_restores_missing_progress_and_makes_topic_selectable() {
+            let db = setup_db();
+            let id = db.add_topic("Orphan", None, &[]).unwrap();
+            db.conn
+                .execute("DELETE FROM progress WHERE topic_id = ?1", params![id])
+                .unwrap();
+            assert_eq!(db.topics_missing_progress().unwrap(), [id]);
+            assert!(db.get_next_topic(None).unwrap().is_none());
+
+            assert_eq!(db.backfill_missing_progress().unwrap(), 1);
+
+            assert!(db.topics_missing_progress().unwrap().is_empty());
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 0);
+            assert_eq!(db.get_next_topic(None).unwrap().unwrap().topic.id, id);
+            assert_eq!(db.backfill_missing_progress().unwrap(), 0);
+        }
+
+        #[test]
+        fn rebuild_replays_mixed_timestamp_formats_in_time_order() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            // As text the later SQLite timestamp sorts first (' ' < 'T')
+            for (outcome, at) in [
+                ("fail", "2024-03-01 13:00:00"),
+                ("success", "2024-03-01T12:00:00Z"),
+            ] {
+                db.conn
+                    .execute(
+                        "INSERT INTO review_history (topic_id, outcome, reviewed_at)
+                         VALUES (?1, ?2, ?3)",
+                        params![id, outcome, at],
+                    )
+                    .unwrap();
+            }
+
+            db.rebuild_progress(Some(id)).unwrap();
+            let p = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(p.mastery_level, 0);
+            assert_eq!(
+                p.last_reviewed.as_deref().and_then(parse_timestamp),
+                parse_timestamp("2024-03-01T13:00:00Z")
+            );
+        }
+
+        #[test]
+        fn rebuild_unknown_topic_is_noop() {
+            let db = setup_db();
+            assert_eq!(db.rebuild_progress(Some(999)).unwrap(), 0);
+        }
+
+        #[test]
+        fn rebuild_surfaces_errors_reading_the_starting_progress() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.conn
+                .execute(
+                    "UPDATE progress SET initial_mastery = 'high' WHERE topic_id = ?1",
+                    params![id],
+                )
+                .unwrap();
+
+            assert!(db.rebuild_progress(Some(id)).is_err());
+        }
+    }
+
+    mod history_pruning_tests {
+        use super::*;
+
+        #[test]
+        fn pruning_removes_only_older_reviews_and_keeps_progress() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            for at in [
+                "2024-01-01T08:00:00Z",
+                "2024-01-05T08:00:00.123456+00:00",
+                "2024-02-01T09:00:00+02:00",
+                "2024-03-01T08:00:00Z",
+            ] {
+                let options = ReviewOptions {
+                    at: parse_timestamp(at),
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(id, ReviewOutcome::Success, None, &options)
+                    .unwrap();
+            }
+            let before = progress_fields(&db, id);
+
+            // 09:00+02:00 is 07:00 UTC, so it falls before the cutoff
+            let cutoff = parse_timestamp("2024-02-01T08:00:00Z").unwrap();
+            assert_eq!(db.count_reviews_before(cutoff).unwrap(), 3);
+            assert_eq!(db.prune_review_history(cutoff).unwrap(), 3);
+
+            let left: Vec<String> = db
+                .get_review_history(Some(id), None, None)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.reviewed_at)
+                .collect();
+            assert_eq!(left, ["2024-03-01T08:00:00+00:00"]);
+            assert_eq!(progress_fields(&db, id), before);
+            assert_eq!(db.prune_review_history(cutoff).unwrap(), 0);
+        }
+
+        // Record each (time, outcome) review as a backfill at that time
+        fn review_at(db: &Database, id: i64, reviews: &[(&str, ReviewOutcome)]) {
+            for &(at, outcome) in reviews {
+                let options = ReviewOptions {
+                    at: parse_timestamp(at),
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(id, outcome, None, &options).unwrap();
+            }
+        }
+
+        #[test]
+        fn replays_after_pruning_start_from_the_checkpoint() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            review_at(
+                &db,
+                id,
+                &[
+                    ("2024-01-01T08:00:00Z", ReviewOutcome::Success),
+                    ("2024-01-03T08:00:00Z", ReviewOutcome::Success),
+                    ("2024-01-08T08:00:00Z", ReviewOutcome::Fail),
+                    ("2024-01-09T08:00:00Z", ReviewOutcome::Success),
+                    ("2024-01-12T08:00:00Z", ReviewOutcome::Success),
+                ],
+            );
+            let expected = progress_fields(&db, id);
+            let trajectory = db.get_mastery_trajectory(id).unwrap();
+
+            // Pruning twice folds the second batch on top of the first
+            for cutoff in ["2024-01-02T00:00:00Z", "2024-01-09T00:00:00Z"] {
+                db.prune_review_history(parse_timestamp(cutoff).unwrap())
+                    .unwrap();
+                db.rebuild_progress(Some(id)).unwrap();
+                assert_eq!(progress_fields(&db, id), expected, "after {}", cutoff);
+            }
+            assert_eq!(db.get_mastery_trajectory(id).unwrap(), trajectory[3..]);
+
+            // A later review still builds on the pruned ones
+            review_at(&db, id, &[("2024-01-20T08:00:00Z", ReviewOutcome::Success)]);
+            let incremental = progress_fields(&db, id);
+            db.rebuild_progress(Some(id)).unwrap();
+            assert_eq!(progress_fields(&db, id), incremental);
+            assert_eq!(incremental.1, 6);
+        }
+
+        #[test]
+        fn pruned_history_refuses_early_backfills_and_merges() {
+            let db = setup_db();
+            let pruned = db.add_topic("Pruned", None, &[]).unwrap();
+            let other = db.add_topic("Other", None, &[]).unwrap();
+            let reviews = [
+                ("2024-01-01T08:00:00Z", ReviewOutcome::Success),
+                ("2024-03-01T08:00:00Z", ReviewOutcome::Success),
+            ];
+            review_at(&db, pruned, &reviews);
+            review_at(&db, other, &reviews[..1]);
+            db.prune_review_history(parse_timestamp("2024-02-01T00:00:00Z").unwrap())
+                .unwrap();
+            // Other's only review went into its checkpoint; give it a new one
+            review_at(&db, other, &reviews[1..]);
+            let before = progress_fields(&db, pruned);
+
+            let backfill = ReviewOptions {
+                at: parse_timestamp("2023-12-01T08:00:00Z"),
+                ..ReviewOptions::default()
+            };
+            let e = db
+                .record_review_with(pruned, ReviewOutcome::Fail, None, &backfill)
+                .unwrap_err();
+            assert!(e.to_string().contains("2024-01-01 08:00"), "{}", e);
+            assert!(matches!(
+                db.merge_topics(pruned, other),
+                Err(FeynmanError::Validation(_))
+            ));
+
+            let fresh = db.add_topic("Fresh", None, &[]).unwrap();
+            review_at(&db, fresh, &[("2023-06-01T08:00:00Z", ReviewOutcome::Fail)]);
+            assert!(matches!(
+                db.merge_topics(fresh, pruned),
+                Err(FeynmanError::Validation(_))
+            ));
+            assert_eq!(progress_fields(&db, pruned), before);
+            assert_eq!(
+                db.get_review_history(Some(pruned), None, None)
+                    .unwrap()
+                    .len(),
+                1
+            );
+
+            // Reviews after the checkpoint merge in on top of it
+            let late = db.add_topic("Late", None, &[]).unwrap();
+            review_at(
+                &db,
+                late,
+                &[("2024-04-01T08:00:00Z", ReviewOutcome::Success)],
+            );
+            db.merge_topics(late, pruned).unwrap().unwrap();
+            let merged = db.get_progress(pruned).unwrap().unwrap();
+            assert_eq!(merged.times_reviewed, 3);
+            assert_eq!(merged.mastery_level, 3);
+        }
+
+        #[test]
+        fn pruning_needs_the_checkpoint_table() {
+            let db = setup_db();
+            db.conn
+                .pragma_update(None, "user_version", SCHEMA_VERSION - 1)
+                .unwrap();
+            assert!(db.prune_review_history(Utc::now()).is_err());
+            db.set_setting(settings::HISTORY_RETENTION_DAYS, "1")
+                .unwrap();
+            assert_eq!(db.prune_expired_history().unwrap(), 0);
+        }
+
+        #[test]
+        fn retention_setting_prunes_relative_to_now() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            for days_ago in [400, 100, 1] {
+                let options = ReviewOptions {
+                    at: Some(Utc::now() - chrono::Duration::days(days_ago)),
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(id, ReviewOutcome::Success, None, &options)
+                    .unwrap();
+            }
+
+            assert_eq!(db.prune_expired_history().unwrap(), 0);
+            db.set_setting(settings::HISTORY_RETENTION_DAYS, "365")
+                .unwrap();
+            assert_eq!(db.prune_expired_history().unwrap(), 1);
+            db.set_setting(settings::HISTORY_RETENTION_DAYS, "30")
+                .unwrap();
+            assert_eq!(db.prune_expired_history().unwrap(), 1);
+            assert_eq!(db.get_review_history(None, None, None).unwrap().len(), 1);
+
+            let fresh = Database::open(":memory:").unwrap();
+            assert_eq!(fresh.prune_expired_history().unwrap(), 0);
+        }
+    }
+
+    mod auto_archive_tests {
+        use super::*;
+
+        #[test]
+        fn auto_archive_takes_only_long_mastered_topics() {
+            use crate::clock::FixedClock;
+
+            let clock = FixedClock::at("2024-06-01T12:00:00Z");
+            let db = setup_db().with_clock(clock.clone());
+            let mastered = |name: &str, days_ago: i64| {
+                let id = db
+                    .add_topic_with(
+                        name,
+                        None,
+                        &[],
+                        &NewTopicOptions {
+                            mastery: 4,
+                            skill: None,
+                        },
+                    )
+                    .unwrap();
+                let options = ReviewOptions {
+                    at: Some(clock.now() - chrono::Duration::days(days_ago)),
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(id, ReviewOutcome::Success, None, &options)
+                    .unwrap();
+                id
+            };
+            let old = mastered("Old", 200);
+            let recent = mastered("Recent", 10);
+            // Back at full mastery long ago, but failed two reviews before that
+            let shaky = mastered("Shaky", 260);
+            for (outcome, days_ago) in [
+                (ReviewOutcome::Fail, 250),
+                (ReviewOutcome::Success, 240),
+                (ReviewOutcome::Success, 230),
+            ] {
+                let options = ReviewOptions {
+                    at: Some(clock.now() - chrono::Duration::days(days_ago)),
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(shaky, outcome, None, &options)
+                    .unwrap();
+            }
+            assert_eq!(
+                db.get_progress(shaky).unwrap().unwrap().mastery_level,
+                settings::MAX_MASTERY
+            );
+            let learning = db.add_topic("Learning", None, &[]).unwrap();
+            db.record_review_with(
+                learning,
+                ReviewOutcome::Success,
+                None,
+                &ReviewOptions {
+                    at: Some(clock.now() - chrono::Duration::days(200)),
+                    ..ReviewOptions::default()
+                },
+            )
+            .unwrap();
+
+            // Off by default
+            assert!(db.auto_archive_mastered().unwrap().is_empty());
+
+            db.set_setting(settings::AUTO_ARCHIVE_AFTER_DAYS, "90")
+                .unwrap();
+            assert_eq!(
+                db.auto_archive_mastered().unwrap(),
+                [(old, "Old".to_string())]
+            );
+            assert!(db.get_topic(old).unwrap().unwrap().archived);
+            assert!(!db.get_topic(recent).unwrap().unwrap().archived);
+            assert!(!db.get_topic(shaky).unwrap().unwrap().archived);
+            assert!(!db.get_topic(learning).unwrap().unwrap().archived);
+            // Already archived topics aren't reported again
+            assert!(db.auto_archive_mastered().unwrap().is_empty());
+
+            let fresh = Database::open(":memory:").unwrap();
+            assert!(fresh.auto_archive_mastered().unwrap().is_empty());
+        }
+    }
+
+    mod timestamp_format_tests {
+        use super::*;
+
+        #[test]
+        fn doctor_counts_and_normalizes_timestamp_formats() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            db.add_topic("C", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Success, None).unwrap();
+            db.conn
+                .execute(
+                    "UPDATE progress SET next_review = '2024-03-01 09:30:00',
+                        last_reviewed = '2024-02-28 09:30:00' WHERE topic_id = ?1",
+                    params![b],
+                )
+                .unwrap();
+            db.conn
+                .execute(
+                    "INSERT INTO review_history (topic_id, outcome, reviewed_at)
+                     VALUES (?1, 'success', '2024-02-28 09:30:00')",
+                    params![b],
+                )
+                .unwrap();
+            db.conn
+                .execute(
+                    "INSERT INTO review_history (topic_id, outcome, reviewed_at)
+                     VALUES (?1, 'fail', 'last tuesday')",
+                    params![b],
+                )
+                .unwrap();
+
+            let report = db.timestamp_formats().unwrap();
+            let counts: Vec<(&str, usize, usize, usize)> = report
+                .iter()
+                .map(|f| (f.column.as_str(), f.rfc3339, f.sqlite, f.invalid))
+                .collect();
+            assert_eq!(
+                counts,
+                [
+                    ("progress.next_review", 2, 1, 0),
+                    ("progress.last_reviewed", 1, 1, 0),
+                    ("review_history.reviewed_at", 1, 1, 1),
+                ]
+            );
+
+            assert_eq!(db.normalize_timestamps().unwrap(), 3);
+            let p = db.get_progress(b).unwrap().unwrap();
+            assert_eq!(p.next_review.as_deref(), Some("2024-03-01T09:30:00+00:00"));
+            for column in ["next_review", "last_reviewed"] {
+                for (_, value) in db.timestamp_values("progress", column).unwrap() {
+                    assert!(DateTime::parse_from_rfc3339(&value).is_ok(), "{}", value);
+                }
+            }
+            // Unparseable values are reported, not guessed at
+            let report = db.timestamp_formats().unwrap();
+            assert!(report.iter().all(|f| f.sqlite == 0));
+            assert_eq!(report[2].invalid, 1);
+            assert_eq!(db.normalize_timestamps().unwrap(), 0);
+        }
+    }
+
+    mod settings_tests {
+        use super::*;
+        use crate::settings;
+
+        #[test]
+        fn set_get_unset_round_trip() {
+            let db = setup_db();
+            assert_eq!(db.get_setting(settings::GRADING_FAIL_DELTA).unwrap(), None);
+
+            db.set_setting(settings::GRADING_FAIL_DELTA, "-2").unwrap();
+            db.set_setting(settings::GRADING_FAIL_DELTA, "-3").unwrap();
+            assert_eq!(
+                db.get_setting(settings::GRADING_FAIL_DELTA)
+                    .unwrap()
+                    .as_deref(),
+                Some("-3")
+            );
+            assert_eq!(db.settings().unwrap().grading.fail, -3);
+            assert_eq!(db.list_settings().unwrap().len(), 1);
+
+            assert!(db.unset_setting(settings::GRADING_FAIL_DELTA).unwrap());
+            assert!(!db.unset_setting(settings::GRADING_FAIL_DELTA).unwrap());
+            assert_eq!(db.settings().unwrap(), Settings::default());
+        }
+
+        #[test]
+        fn invalid_values_are_rejected_before_saving() {
+            let db = setup_db();
+            let e = db
+                .set_setting(settings::GRADING_FAIL_DELTA, "lots")
+                .unwrap_err();
+            match e {
+                FeynmanError::Validation(message) => {
+                    assert!(message.contains("whole number"), "{}", message)
+                }
+                other => panic!("expected a validation error, got {:?}", other),
+            }
+            assert_eq!(db.get_setting(settings::GRADING_FAIL_DELTA).unwrap(), None);
+        }
+    }
+
+    mod interval_tests {
+        use super::*;
+        use crate::settings;
+
+        #[test]
+        fn calculate_interval_level_0() {
+            assert_eq!(Database::calculate_interval(0), 1);
+        }
+
+        #[test]
+        fn calculate_interval_level_1() {
+            assert_eq!(Database::calculate_interval(1), 2);
+        }
+
+        #[test]
+        fn calculate_interval_level_2() {
+            assert_eq!(Database::calculate_interval(2), 4);
+        }
+
+        #[test]
+        fn calculate_interval_level_3() {
+            assert_eq!(Database::calculate_interval(3), 7);
+        }
+
+        #[test]
+        fn calculate_interval_level_4() {
+            assert_eq!(Database::calculate_interval(4), 14);
+        }
+
+        #[test]
+        fn calculate_interval_level_5() {
+            assert_eq!(Database::calculate_interval(5), 30);
+        }
+
+        #[test]
+        fn calculate_interval_above_max() {
+            assert_eq!(Database::calculate_interval(10), 30);
+        }
+
+        #[test]
+        fn interval_ladder_matches_calculate_interval() {
+            let ladder = scheduler::interval_ladder(&Settings::default());
+            assert_eq!(ladder.len(), 6);
+            for (level, days) in ladder {
+                assert_eq!(days, Database::calculate_interval(level));
+            }
+        }
+
+        #[test]
+        fn interval_ladder_and_reviews_honor_overrides() {
+            let db = setup_db();
+            db.set_setting("schedule.interval.1", "3").unwrap();
+            let ladder = scheduler::interval_ladder(&db.settings().unwrap());
+            assert_eq!(ladder[1], (1, 3));
+            assert_eq!(ladder[2], (2, 4));
+
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            let progress = db.get_progress(id).unwrap().unwrap();
+            let last = parse_timestamp(progress.last_reviewed.as_deref().unwrap()).unwrap();
+            assert_eq!((progress.next_review_dt().unwrap() - last).num_days(), 3);
+        }
+
+        #[test]
+        fn custom_grading_changes_level_transitions() {
+            let db = setup_db();
+            db.set_setting(settings::GRADING_SUCCESS_DELTA, "+2")
+                .unwrap();
+            db.set_setting(settings::GRADING_FAIL_DELTA, "-2").unwrap();
+            let id = db.add_topic("Harsh", None, &[]).unwrap();
+            let level = || db.get_progress(id).unwrap().unwrap().mastery_level;
+
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            assert_eq!(level(), 2);
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            assert_eq!(level(), 4);
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            assert_eq!(level(), 5);
+            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
+            assert_eq!(level(), 5);
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            assert_eq!(level(), 3);
+
+            // Replaying history uses the same scale
+            db.rebuild_progress(Some(id)).unwrap();
+            assert_eq!(level(), 3);
+        }
+
+        #[test]
+        fn recorded_review_uses_topic_difficulty() {
+            let db = setup_db();
+            let easy = db.add_topic("Easy", None, &[]).unwrap();
+            let hard = db.add_topic("Hard", None, &[]).unwrap();
+            assert!(db.set_topic_difficulty(easy, 1).unwrap());
+            assert!(db.set_topic_difficulty(hard, 5).unwrap());
+
+            for id in [easy, hard] {
+                for _ in 0..4 {
+                    db.record_review(id, ReviewOutcome::Success, None).unwrap();
+                }
+            }
+
+            let next = |id| {
+                let p = db.get_progress(id).unwrap().unwrap();
+                parse_timestamp(p.next_review.as_deref().unwrap()).unwrap()
+            };
+            assert_eq!(db.get_progress(hard).unwrap().unwrap().difficulty, 5);
+            assert!(next(hard) < next(easy));
+        }
+
+        #[test]
+        fn set_difficulty_clamps_and_reports_missing_topic() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            assert_eq!(
+                db.get_progress(id).unwrap().unwrap().difficulty,
+                DEFAULT_DIFFICULTY
+            );
+
+            db.set_topic_difficulty(id, 9).unwrap();
+            assert_eq!(db.get_progress(id).unwrap().unwrap().difficulty, 5);
+            assert!(!db.set_topic_difficulty(999, 2).unwrap());
+        }
+    }
+
+    mod clock_tests {
+        use super::*;
+        use crate::clock::FixedClock;
+        use crate::settings;
+        use chrono::Duration;
+
+        fn setup_at(now: &str) -> (Database, FixedClock) {
+            let clock = FixedClock::at(now);
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            (db, clock)
+        }
+
+        fn due_ids(db: &Database) -> Vec<i64> {
+            db.get_due_topics_limited(10)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.topic.id)
+                .collect()
+        }
+
+        #[test]
+        fn new_topic_is_due_at_creation_time() {
+            let (db, _clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            assert_eq!(due_ids(&db), vec![id]);
+            assert_eq!(db.get_stats().unwrap().due_now, 1);
+        }
+
+        #[test]
+        fn review_reasoning_matches_the_stored_transition() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            let first = db
+                .record_review_with(id, ReviewOutcome::Success, None, &ReviewOptions::default())
+                .unwrap()
+                .unwrap();
+            assert_eq!(first.previous_interval_minutes, None);
+
+            clock.advance(Duration::days(2));
+            let reasoning = db
+                .record_review_with(id, ReviewOutcome::Success, None, &ReviewOptions::default())
+                .unwrap()
+                .unwrap();
+            let progress = db.get_progress(id).unwrap().unwrap();
+            let last = parse_timestamp(progress.last_reviewed.as_deref().unwrap()).unwrap();
+            let next = progress.next_review_dt().unwrap();
+
+            assert_eq!((reasoning.previous_level, reasoning.new_level), (1, 2));
+            assert_eq!(reasoning.new_level, progress.mastery_level);
+            assert_eq!((reasoning.grading_delta, reasoning.level_delta), (1, 1));
+            assert_eq!(
+                reasoning.previous_interval_minutes,
+                Some(first.interval_minutes)
+            );
+            assert_eq!(reasoning.interval_minutes, (next - last).num_minutes());
+            assert_eq!(parse_timestamp(&reasoning.next_review), Some(next));
+            assert_eq!(reasoning.difficulty, DEFAULT_DIFFICULTY);
+            assert!(!reasoning.retry_now);
+
+            // A fail at level 0 can't go lower than the grading delta says
+            for _ in 0..3 {
+                db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            }
+            let floor = db
+                .record_review_with(id, ReviewOutcome::Fail, None, &ReviewOptions::default())
+                .unwrap()
+                .unwrap();
+            assert_eq!((floor.grading_delta, floor.level_delta), (-1, 0));
+
+            // A backfill before the latest review is a full replay, not one step
+            let backfill = ReviewOptions {
+                at: Some(parse_timestamp("2024-03-02T00:00:00Z").unwrap()),
+                ..ReviewOptions::default()
+            };
+            assert_eq!(
+                db.record_review_with(id, ReviewOutcome::Success, None, &backfill)
+                    .unwrap(),
+                None
+            );
         }
 
         #[test]
-        fn add_topic_with_description() {
-            let db = setup_db();
-            let id = db
-                .add_topic("Rust Basics", Some("Learn the fundamentals"), &[])
+        fn on_time_reviews_earn_a_longer_interval() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            db.set_setting(settings::SCHEDULE_ON_TIME_BONUS, "1.5")
                 .unwrap();
+            let on_time = db.add_topic("On time", None, &[]).unwrap();
+            let late = db.add_topic("Late", None, &[]).unwrap();
+            let success = |id| {
+                db.record_review_with(id, ReviewOutcome::Success, None, &ReviewOptions::default())
+                    .unwrap()
+                    .unwrap()
+            };
+            // No earlier review set a due date, so no bonus yet: 2 days each
+            for id in [on_time, late] {
+                let first = success(id);
+                assert_eq!(first.interval_minutes, Duration::days(2).num_minutes());
+                assert_eq!(first.on_time_bonus, None);
+            }
 
-            let topic = db.get_topic(id).unwrap().unwrap();
+            // Level 2 is 4 days, 6 with the bonus
+            clock.advance(Duration::days(2) + Duration::hours(6));
+            let reasoning = success(on_time);
+            assert_eq!(reasoning.interval_minutes, Duration::days(6).num_minutes());
+            assert_eq!(reasoning.on_time_bonus, Some(1.5));
+
+            clock.advance(Duration::days(8));
+            let reasoning = success(late);
+            assert_eq!(reasoning.interval_minutes, Duration::days(4).num_minutes());
+            assert_eq!(reasoning.on_time_bonus, None);
+
+            // A replay reaches the same schedule, and so does rescheduling
+            // under the same settings
+            let before = db.get_progress(on_time).unwrap().unwrap().next_review;
+            assert_eq!(before.as_deref(), Some("2024-03-09T18:00:00+00:00"));
+            db.rebuild_progress(Some(on_time)).unwrap();
             assert_eq!(
-                topic.description,
-                Some("Learn the fundamentals".to_string())
+                db.get_progress(on_time).unwrap().unwrap().next_review,
+                before
+            );
+            assert_eq!(db.reschedule(None).unwrap(), 0);
+            assert_eq!(
+                db.get_progress(on_time).unwrap().unwrap().next_review,
+                before
             );
         }
 
         #[test]
-        fn add_topic_with_tags() {
-            let db = setup_db();
-            let tags = vec!["rust".to_string(), "programming".to_string()];
-            let id = db.add_topic("Rust Basics", None, &tags).unwrap();
-
-            let topic = db.get_topic(id).unwrap().unwrap();
-            assert_eq!(topic.tags.len(), 2);
-            assert!(topic.tags.contains(&"rust".to_string()));
-            assert!(topic.tags.contains(&"programming".to_string()));
+        fn on_time_bonus_is_off_by_default() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            clock.advance(Duration::days(2));
+            let reasoning = db
+                .record_review_with(id, ReviewOutcome::Success, None, &ReviewOptions::default())
+                .unwrap()
+                .unwrap();
+            assert_eq!(reasoning.interval_minutes, Duration::days(4).num_minutes());
+            assert_eq!(reasoning.on_time_bonus, None);
         }
 
         #[test]
-        fn add_topic_creates_progress() {
-            let db = setup_db();
-            let id = db.add_topic("Test Topic", None, &[]).unwrap();
+        fn topic_becomes_due_exactly_at_next_review() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
 
+            // Level 1 → 2 days
             let progress = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(progress.topic_id, id);
-            assert_eq!(progress.mastery_level, 0);
-            assert_eq!(progress.times_reviewed, 0);
-            assert_eq!(progress.times_succeeded, 0);
-            assert_eq!(progress.skill_level, SkillLevel::Unknown);
-            assert_eq!(progress.assessment_method, AssessmentMethod::None);
-            assert!(progress.last_assessed.is_none());
-        }
+            assert_eq!(
+                parse_timestamp(progress.next_review.as_deref().unwrap()),
+                Some(clock.now() + Duration::days(2))
+            );
 
-        #[test]
-        fn add_topic_duplicate_name_fails() {
-            let db = setup_db();
-            db.add_topic("Unique Name", None, &[]).unwrap();
-            let result = db.add_topic("Unique Name", None, &[]);
-            assert!(result.is_err());
+            clock.advance(Duration::days(2) - Duration::seconds(1));
+            assert!(due_ids(&db).is_empty());
+            assert_eq!(db.get_stats().unwrap().due_now, 0);
+            assert_eq!(db.get_status_counts().unwrap().due, 0);
+
+            clock.advance(Duration::seconds(1));
+            assert_eq!(due_ids(&db), vec![id]);
+            assert_eq!(db.get_stats().unwrap().due_now, 1);
+            assert_eq!(db.get_status_counts().unwrap().due, 1);
         }
 
         #[test]
-        fn get_topic_not_found() {
-            let db = setup_db();
-            let topic = db.get_topic(999).unwrap();
-            assert!(topic.is_none());
+        fn advancing_across_successive_intervals() {
+            let (db, clock) = setup_at("2024-03-01T08:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+
+            // Levels 1..4 → 2, 4, 7, 14 days
+            for days in [2, 4, 7, 14] {
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+                assert!(due_ids(&db).is_empty());
+
+                clock.advance(Duration::days(days) - Duration::hours(1));
+                assert!(due_ids(&db).is_empty(), "due early at {} days", days);
+
+                clock.advance(Duration::hours(1));
+                assert_eq!(due_ids(&db), vec![id], "not due after {} days", days);
+            }
         }
 
         #[test]
-        fn list_topics_empty() {
-            let db = setup_db();
-            let topics = db.list_topics(None).unwrap();
-            assert!(topics.is_empty());
+        fn reschedule_moves_reviews_earlier_after_lowering_an_interval() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let rust = vec!["rust".to_string()];
+            let a = db.add_topic("A", None, &rust).unwrap();
+            let b = db.add_topic("B", None, &["go".to_string()]).unwrap();
+            for id in [a, b] {
+                for _ in 0..3 {
+                    db.record_review(id, ReviewOutcome::Success, None).unwrap();
+                }
+            }
+            clock.advance(Duration::days(1));
+            let fresh = db.add_topic("Fresh", None, &rust).unwrap();
+            let next_review = |id| db.get_progress(id).unwrap().unwrap().next_review_dt();
+            let reviewed_at = clock.now() - Duration::days(1);
+            assert_eq!(next_review(a), Some(reviewed_at + Duration::days(7)));
+
+            db.set_setting("schedule.interval.3", "3").unwrap();
+            assert_eq!(db.reschedule(Some("rust")).unwrap(), 1);
+
+            assert_eq!(next_review(a), Some(reviewed_at + Duration::days(3)));
+            // Outside the tag, untouched
+            assert_eq!(next_review(b), Some(reviewed_at + Duration::days(7)));
+            // Never reviewed: still due from creation
+            assert_eq!(next_review(fresh), Some(clock.now()));
+
+            // Already in line with the settings: nothing to move
+            assert_eq!(db.reschedule(Some("rust")).unwrap(), 0);
+            assert_eq!(db.reschedule(None).unwrap(), 1);
+            assert_eq!(next_review(b), Some(reviewed_at + Duration::days(3)));
         }
 
         #[test]
-        fn list_topics_returns_all() {
-            let db = setup_db();
-            db.add_topic("Topic A", None, &[]).unwrap();
-            db.add_topic("Topic B", None, &[]).unwrap();
-            db.add_topic("Topic C", None, &[]).unwrap();
+        fn reschedule_reads_the_latest_outcome_across_timestamp_formats() {
+            let (db, _clock) = setup_at("2024-03-01T12:00:00Z");
+            // A fail retries in minutes, a success waits days
+            db.set_setting(settings::SCHEDULE_FAIL_MINUTES, "10")
+                .unwrap();
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            // The real last review, stored in SQLite format: a fail that the
+            // RFC 3339 success would outrank if compared as text
+            db.conn
+                .execute(
+                    "UPDATE review_history SET reviewed_at = '2024-03-01T11:00:00Z'
+                     WHERE topic_id = ?1",
+                    params![id],
+                )
+                .unwrap();
+            db.conn
+                .execute(
+                    "INSERT INTO review_history (topic_id, outcome, reviewed_at)
+                     VALUES (?1, 'fail', '2024-03-01 12:00:00')",
+                    params![id],
+                )
+                .unwrap();
+            db.conn
+                .execute(
+                    "UPDATE progress SET mastery_level = 0, next_review = '2030-01-01T00:00:00Z',
+                        last_reviewed = '2024-03-01T12:00:00Z' WHERE topic_id = ?1",
+                    params![id],
+                )
+                .unwrap();
 
-            let topics = db.list_topics(None).unwrap();
-            assert_eq!(topics.len(), 3);
+            assert_eq!(db.reschedule(None).unwrap(), 1);
+            let settings = db.settings().unwrap();
+            let reviewed_at = parse_timestamp("2024-03-01T12:00:00Z").unwrap();
+            let p = db.get_progress(id).unwrap().unwrap();
+            let expected =
+                scheduler::review_interval(&settings, 0, ReviewOutcome::Fail, p.difficulty);
+            assert_eq!(p.next_review_dt(), Some(reviewed_at + expected));
         }
 
         #[test]
-        fn list_topics_sorted_by_name() {
-            let db = setup_db();
-            db.add_topic("Zebra", None, &[]).unwrap();
-            db.add_topic("Alpha", None, &[]).unwrap();
-            db.add_topic("Middle", None, &[]).unwrap();
+        fn short_retry_brings_failed_topic_back_within_the_hour() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            db.set_setting(settings::SCHEDULE_FAIL_MINUTES, "10")
+                .unwrap();
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
 
-            let topics = db.list_topics(None).unwrap();
-            assert_eq!(topics[0].name, "Alpha");
-            assert_eq!(topics[1].name, "Middle");
-            assert_eq!(topics[2].name, "Zebra");
+            let progress = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(progress.mastery_level, 0);
+            assert_eq!(
+                progress.next_review_dt(),
+                Some(clock.now() + Duration::minutes(10))
+            );
+
+            clock.advance(Duration::minutes(9));
+            assert!(due_ids(&db).is_empty());
+            clock.advance(Duration::minutes(1));
+            assert_eq!(due_ids(&db), vec![id]);
+
+            // Replaying the history lands on the same time
+            db.rebuild_progress(Some(id)).unwrap();
+            assert_eq!(
+                db.get_progress(id).unwrap().unwrap().next_review_dt(),
+                Some(clock.now())
+            );
         }
 
         #[test]
-        fn list_topics_filter_by_tag() {
-            let db = setup_db();
-            db.add_topic("Rust Topic", None, &["rust".to_string()])
-                .unwrap();
-            db.add_topic("Go Topic", None, &["go".to_string()]).unwrap();
-            db.add_topic("Both", None, &["rust".to_string(), "go".to_string()])
+        fn short_retry_applies_to_partial_only_when_set() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            db.set_setting(settings::SCHEDULE_FAIL_MINUTES, "10")
                 .unwrap();
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
+            assert_eq!(
+                db.get_progress(id).unwrap().unwrap().next_review_dt(),
+                Some(clock.now() + Duration::days(1))
+            );
 
-            let rust_topics = db.list_topics(Some("rust")).unwrap();
-            assert_eq!(rust_topics.len(), 2);
+            db.set_setting(settings::SCHEDULE_PARTIAL_MINUTES, "30")
+                .unwrap();
+            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
+            assert_eq!(
+                db.get_progress(id).unwrap().unwrap().next_review_dt(),
+                Some(clock.now() + Duration::minutes(30))
+            );
+        }
 
-            let go_topics = db.list_topics(Some("go")).unwrap();
-            assert_eq!(go_topics.len(), 2);
+        #[test]
+        fn failed_topic_waits_a_day_without_short_retry() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
 
-            let python_topics = db.list_topics(Some("python")).unwrap();
-            assert!(python_topics.is_empty());
+            clock.advance(Duration::hours(1));
+            assert!(due_ids(&db).is_empty());
         }
 
         #[test]
-        fn delete_topic_success() {
-            let db = setup_db();
-            let id = db.add_topic("To Delete", None, &[]).unwrap();
+        fn overdue_starts_the_day_after_due() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
 
-            let deleted = db.delete_topic(id).unwrap();
-            assert!(deleted);
+            // Due 2024-03-02 12:00: due but not overdue that day
+            clock.set(parse_timestamp("2024-03-02T23:59:00Z").unwrap());
+            let status = db.get_status_counts().unwrap();
+            assert_eq!((status.due, status.overdue), (1, 0));
 
-            let topic = db.get_topic(id).unwrap();
-            assert!(topic.is_none());
+            clock.advance(Duration::minutes(1));
+            let status = db.get_status_counts().unwrap();
+            assert_eq!((status.due, status.overdue), (1, 1));
         }
 
         #[test]
-        fn delete_topic_not_found() {
-            let db = setup_db();
-            let deleted = db.delete_topic(999).unwrap();
-            assert!(!deleted);
+        fn topic_added_at_mastery_3_waits_the_level_3_interval() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let options = NewTopicOptions {
+                mastery: 3,
+                skill: None,
+            };
+            let id = db.add_topic_with("Known", None, &[], &options).unwrap();
+            let fresh = db.add_topic("Fresh", None, &[]).unwrap();
+
+            let settings = db.settings().unwrap();
+            let progress = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(progress.mastery_level, 3);
+            assert_eq!(progress.times_reviewed, 0);
+            assert_eq!(
+                progress.next_review_dt(),
+                Some(clock.now() + Duration::days(settings.interval(3) as i64))
+            );
+            assert_eq!(due_ids(&db), [fresh]);
+
+            // A review carries on from the starting level
+            clock.advance(Duration::days(settings.interval(3) as i64));
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            assert_eq!(db.get_progress(id).unwrap().unwrap().mastery_level, 4);
         }
 
         #[test]
-        fn delete_topic_cascades_progress() {
-            let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
-
-            // Verify progress exists
-            assert!(db.get_progress(id).unwrap().is_some());
+        fn rebuild_keeps_the_starting_mastery() {
+            let (db, _clock) = setup_at("2024-03-01T12:00:00Z");
+            let options = NewTopicOptions {
+                mastery: 2,
+                skill: None,
+            };
+            let unreviewed = db
+                .add_topic_with("Unreviewed", None, &[], &options)
+                .unwrap();
+            let reviewed = db.add_topic_with("Reviewed", None, &[], &options).unwrap();
+            db.record_review(reviewed, ReviewOutcome::Success, None)
+                .unwrap();
+            let before = |id| db.get_progress(id).unwrap().unwrap();
+            let (unreviewed_before, reviewed_before) = (before(unreviewed), before(reviewed));
+
+            db.rebuild_progress(None).unwrap();
+            for (id, expected) in [(unreviewed, unreviewed_before), (reviewed, reviewed_before)] {
+                let progress = db.get_progress(id).unwrap().unwrap();
+                assert_eq!(progress.mastery_level, expected.mastery_level);
+                assert_eq!(progress.next_review_dt(), expected.next_review_dt());
+            }
+            assert_eq!(db.get_progress(reviewed).unwrap().unwrap().mastery_level, 3);
+        }
 
-            db.delete_topic(id).unwrap();
+        #[test]
+        fn starting_skill_is_a_self_assessment() {
+            let (db, _clock) = setup_at("2024-03-01T12:00:00Z");
+            let options = NewTopicOptions {
+                mastery: 0,
+                skill: Some(SkillLevel::Advanced),
+            };
+            let id = db.add_topic_with("Known", None, &[], &options).unwrap();
 
-            // Progress should be gone too
-            assert!(db.get_progress(id).unwrap().is_none());
+            let progress = db.get_progress(id).unwrap().unwrap();
+            assert_eq!(progress.skill_level, SkillLevel::Advanced);
+            assert_eq!(progress.mastery_level, 0);
+            let assessments = db.get_skill_assessments(id).unwrap();
+            assert_eq!(assessments.len(), 1);
+            assert_eq!(assessments[0].method, AssessmentMethod::SelfAssessed);
+            assert_eq!(assessments[0].new_level, SkillLevel::Advanced.as_i32());
+            // Still a new topic, so due straight away
+            assert_eq!(due_ids(&db), [id]);
         }
 
         #[test]
-        fn update_topic_tags() {
-            let db = setup_db();
-            let id = db.add_topic("Test", None, &["old".to_string()]).unwrap();
-
-            db.update_topic_tags(id, &["new1".to_string(), "new2".to_string()])
+        fn due_filters_split_due_today_from_overdue() {
+            let (db, clock) = setup_at("2024-02-29T12:00:00Z");
+            // A failed review is due again a day later
+            let overdue = db
+                .add_topic("Overdue", None, &["rust".to_string()])
                 .unwrap();
+            db.record_review(overdue, ReviewOutcome::Fail, None)
+                .unwrap();
+            clock.set(parse_timestamp("2024-03-01T09:00:00Z").unwrap());
+            let due_today = db
+                .add_topic("Due today", None, &["go".to_string()])
+                .unwrap();
+            db.record_review(due_today, ReviewOutcome::Fail, None)
+                .unwrap();
+            clock.set(parse_timestamp("2024-03-02T12:00:00Z").unwrap());
+            let later = db.add_topic("Later", None, &["rust".to_string()]).unwrap();
+            db.record_review(later, ReviewOutcome::Fail, None).unwrap();
+
+            clock.set(parse_timestamp("2024-03-02T18:00:00Z").unwrap());
+            let names = |due, tag: Option<&str>| -> Vec<String> {
+                let query = TopicQuery {
+                    due,
+                    tag: tag.map(str::to_string),
+                    ..TopicQuery::default()
+                };
+                let topics: Vec<String> = db
+                    .get_topics(&query)
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| t.name)
+                    .collect();
+                let with_progress: Vec<String> = db
+                    .get_topics_with_progress(&query)
+                    .unwrap()
+                    .into_iter()
+                    .map(|twp| twp.topic.name)
+                    .collect();
+                assert_eq!(topics, with_progress);
+                topics
+            };
 
-            let topic = db.get_topic(id).unwrap().unwrap();
-            assert_eq!(topic.tags.len(), 2);
-            assert!(topic.tags.contains(&"new1".to_string()));
-            assert!(topic.tags.contains(&"new2".to_string()));
-            assert!(!topic.tags.contains(&"old".to_string()));
+            assert_eq!(
+                names(DueFilter::Any, None),
+                ["Due today", "Later", "Overdue"]
+            );
+            assert_eq!(names(DueFilter::Due, None), ["Due today", "Overdue"]);
+            assert_eq!(names(DueFilter::Overdue, None), ["Overdue"]);
+            assert_eq!(names(DueFilter::Due, Some("rust")), ["Overdue"]);
+            assert!(names(DueFilter::Overdue, Some("go")).is_empty());
         }
 
         #[test]
-        fn update_topic_tags_to_empty() {
-            let db = setup_db();
-            let id = db.add_topic("Test", None, &["tag1".to_string()]).unwrap();
+        fn streak_follows_the_clock() {
+            let (db, clock) = setup_at("2024-03-01T12:00:00Z");
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            for _ in 0..3 {
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+                clock.advance(Duration::days(1));
+            }
 
-            db.update_topic_tags(id, &[]).unwrap();
+            // Last review was yesterday: streak still alive
+            assert_eq!(db.get_status_counts().unwrap().streak, 3);
 
-            let topic = db.get_topic(id).unwrap().unwrap();
-            assert!(topic.tags.is_empty());
+            clock.advance(Duration::days(1));
+            assert_eq!(db.get_status_counts().unwrap().streak, 0);
         }
     }
 
-    mod tag_tests {
+    mod selection_tests {
         use super::*;
+        use crate::clock::FixedClock;
+        use chrono::Duration;
 
         #[test]
-        fn list_tags_empty() {
-            let db = setup_db();
-            let tags = db.list_tags().unwrap();
-            assert!(tags.is_empty());
+        fn candidate_weights_sum_to_total_weight() {
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            db.add_topic("C", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Success, None).unwrap();
+            db.record_review(b, ReviewOutcome::Fail, None).unwrap();
+            clock.advance(Duration::days(5));
+
+            let explanation = db
+                .explain_next_topic(None, SelectionStrategy::Weighted)
+                .unwrap()
+                .unwrap();
+            assert_eq!(explanation.candidates.len(), 3);
+            let sum: f64 = explanation.candidates.iter().map(|c| c.weight).sum();
+            assert!((sum - explanation.total_weight).abs() < 1e-9);
+            let draw = explanation.draw.unwrap();
+            assert!(draw >= 0.0 && draw < explanation.total_weight);
+
+            // A: level 1, due 2 days after its review → 3 days overdue
+            let a_candidate = explanation
+                .candidates
+                .iter()
+                .find(|c| c.topic_id == a)
+                .unwrap();
+            assert_eq!(a_candidate.overdue_days, 3);
+            assert_eq!(a_candidate.mastery_factor, 5.0);
+            assert_eq!(a_candidate.weight, 20.0);
         }
 
         #[test]
-        fn list_tags_with_counts() {
-            let db = setup_db();
-            db.add_topic("T1", None, &["common".to_string()]).unwrap();
-            db.add_topic("T2", None, &["common".to_string()]).unwrap();
-            db.add_topic("T3", None, &["rare".to_string()]).unwrap();
+        fn zero_mastery_weight_selects_on_overdue_days_alone() {
+            use rand::rngs::StdRng;
+            use rand::SeedableRng;
+
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            // (mastery, days overdue): A and B differ only in mastery
+            let mut ids = Vec::new();
+            for (name, mastery, overdue) in [("A", 0, 0), ("B", 4, 0), ("C", 2, 4)] {
+                let id = db.add_topic(name, None, &[]).unwrap();
+                let next_review = (clock.now() - Duration::days(overdue)).to_rfc3339();
+                db.conn
+                    .execute(
+                        "UPDATE progress SET mastery_level = ?1, next_review = ?2 WHERE topic_id = ?3",
+                        params![mastery, next_review, id],
+                    )
+                    .unwrap();
+                ids.push(id);
+            }
+            let weights = |db: &Database| {
+                let e = db
+                    .explain_next_topic_with_rng(
+                        None,
+                        SelectionStrategy::Weighted,
+                        &mut StdRng::seed_from_u64(7),
+                    )
+                    .unwrap()
+                    .unwrap();
+                ids.iter()
+                    .map(|id| {
+                        e.candidates
+                            .iter()
+                            .find(|c| c.topic_id == *id)
+                            .unwrap()
+                            .weight
+                    })
+                    .collect::<Vec<f64>>()
+            };
 
-            let tags = db.list_tags().unwrap();
-            assert_eq!(tags.len(), 2);
+            // Defaults: (overdue_days + 1) * (6 - mastery)
+            assert_eq!(weights(&db), vec![6.0, 2.0, 20.0]);
 
-            let common = tags.iter().find(|t| t.name == "common").unwrap();
-            assert_eq!(common.topic_count, 2);
+            db.set_setting(settings::SELECTION_MASTERY_WEIGHT, "0")
+                .unwrap();
+            assert_eq!(weights(&db), vec![1.0, 1.0, 5.0]);
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut counts = HashMap::new();
+            for _ in 0..350 {
+                let e = db
+                    .explain_next_topic_with_rng(None, SelectionStrategy::Weighted, &mut rng)
+                    .unwrap()
+                    .unwrap();
+                *counts.entry(e.selected.topic.id).or_insert(0) += 1;
+            }
+            // A and B now share 1/7 each; C takes 5/7
+            let (a, b, c) = (counts[&ids[0]], counts[&ids[1]], counts[&ids[2]]);
+            assert!((30..=70).contains(&a), "A picked {} times", a);
+            assert!((30..=70).contains(&b), "B picked {} times", b);
+            assert!((210..=290).contains(&c), "C picked {} times", c);
+        }
 
-            let rare = tags.iter().find(|t| t.name == "rare").unwrap();
-            assert_eq!(rare.topic_count, 1);
+        #[test]
+        fn lru_picks_never_reviewed_then_oldest_review() {
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            let c = db.add_topic("C", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Success, None).unwrap();
+            clock.advance(Duration::days(1));
+            db.record_review(b, ReviewOutcome::Fail, None).unwrap();
+
+            let pick = |db: &Database| {
+                let e = db
+                    .explain_next_topic(None, SelectionStrategy::Lru)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(e.strategy, SelectionStrategy::Lru);
+                assert!(e.draw.is_none());
+                e.selected.topic.id
+            };
+            assert_eq!(pick(&db), c);
+
+            clock.advance(Duration::days(1));
+            db.record_review(c, ReviewOutcome::Success, None).unwrap();
+            assert_eq!(pick(&db), a);
         }
 
         #[test]
-        fn tags_are_reused() {
+        fn overdue_picks_earliest_next_review() {
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Success, None).unwrap(); // due in 2 days
+            db.record_review(b, ReviewOutcome::Fail, None).unwrap(); // due in 1 day
+            clock.advance(Duration::days(5));
+
+            let e = db
+                .explain_next_topic(None, SelectionStrategy::Overdue)
+                .unwrap()
+                .unwrap();
+            assert_eq!(e.selected.topic.id, b);
+        }
+
+        #[test]
+        fn random_is_uniform_and_reproducible_with_a_seed() {
+            use rand::rngs::StdRng;
+            use rand::SeedableRng;
+
             let db = setup_db();
-            db.add_topic("T1", None, &["shared".to_string()]).unwrap();
-            db.add_topic("T2", None, &["shared".to_string()]).unwrap();
+            let ids: Vec<i64> = ["A", "B", "C"]
+                .iter()
+                .map(|name| db.add_topic(name, None, &[]).unwrap())
+                .collect();
+            // Very different weighted odds shouldn't matter to `random`
+            db.record_review(ids[0], ReviewOutcome::Success, None)
+                .unwrap();
 
-            let tags = db.list_tags().unwrap();
-            assert_eq!(tags.len(), 1);
-            assert_eq!(tags[0].topic_count, 2);
-        }
-    }
+            let pick = |rng: &mut StdRng| {
+                db.explain_next_topic_with_rng(None, SelectionStrategy::Random, rng)
+                    .unwrap()
+                    .unwrap()
+            };
 
-    mod review_tests {
-        use super::*;
+            let e = pick(&mut StdRng::seed_from_u64(7));
+            assert!(e.candidates.iter().all(|c| c.weight == 1.0));
+            assert_eq!(e.total_weight, 3.0);
+            assert_eq!(
+                e.selected.topic.id,
+                pick(&mut StdRng::seed_from_u64(7)).selected.topic.id
+            );
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut counts = HashMap::new();
+            for _ in 0..300 {
+                *counts.entry(pick(&mut rng).selected.topic.id).or_insert(0) += 1;
+            }
+            for id in &ids {
+                let n = counts.get(id).copied().unwrap_or(0);
+                assert!((60..=140).contains(&n), "topic {} picked {} times", id, n);
+            }
+        }
 
         #[test]
-        fn record_review_success_increases_mastery() {
+        fn new_ratio_sets_the_share_of_new_topics() {
+            use rand::rngs::StdRng;
+            use rand::SeedableRng;
+
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
+            for name in ["A", "B", "C"] {
+                let id = db.add_topic(name, None, &[]).unwrap();
+                db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            }
+            let new_ids: Vec<i64> = ["D", "E"]
+                .iter()
+                .map(|name| db.add_topic(name, None, &[]).unwrap())
+                .collect();
+
+            let mut rng = StdRng::seed_from_u64(42);
+            let draws = 2000;
+            let mut new_picks = 0;
+            for _ in 0..draws {
+                let e = db
+                    .explain_next_topic_mixed(None, SelectionStrategy::Weighted, 0.3, &mut rng)
+                    .unwrap()
+                    .unwrap();
+                let is_new = new_ids.contains(&e.selected.topic.id);
+                assert_eq!(e.pool == Some(TopicPool::New), is_new);
+                if is_new {
+                    new_picks += 1;
+                }
+            }
+            let observed = new_picks as f64 / draws as f64;
+            assert!((observed - 0.3).abs() < 0.03, "observed {}", observed);
+        }
 
-            // Initial mastery is 0
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 0);
+        #[test]
+        fn new_ratio_extremes_and_daily_cap() {
+            use rand::rngs::StdRng;
+            use rand::SeedableRng;
 
-            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            let db = setup_db();
+            let reviewed = db.add_topic("Reviewed", None, &[]).unwrap();
+            db.record_review(reviewed, ReviewOutcome::Fail, None)
+                .unwrap();
+            let fresh = db.add_topic("Fresh", None, &[]).unwrap();
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut pick = |ratio: f64| {
+                db.explain_next_topic_mixed(None, SelectionStrategy::Weighted, ratio, &mut rng)
+                    .unwrap()
+                    .map(|e| e.selected.topic.id)
+            };
+            assert_eq!(pick(1.0), Some(fresh));
+            assert_eq!(pick(0.0), Some(reviewed));
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 1);
-            assert_eq!(p.times_reviewed, 1);
-            assert_eq!(p.times_succeeded, 1);
+            // "Reviewed" was first reviewed today, which uses up a cap of 1
+            db.set_setting(settings::SELECTION_NEW_PER_DAY, "1")
+                .unwrap();
+            assert_eq!(pick(0.5), Some(reviewed));
+            assert_eq!(pick(1.0), None);
         }
 
         #[test]
-        fn record_review_success_caps_at_5() {
+        fn deterministic_mode_always_takes_the_heaviest_topic() {
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
+            let ids: Vec<i64> = ["A", "B", "C"]
+                .iter()
+                .map(|name| db.add_topic(name, None, &[]).unwrap())
+                .collect();
+            // B and C tie on the lowest mastery, so B (the lower id) wins
+            db.conn
+                .execute(
+                    "UPDATE progress SET mastery_level = 3 WHERE topic_id = ?1",
+                    params![ids[0]],
+                )
+                .unwrap();
+            db.set_setting(settings::SELECTION_DETERMINISTIC, "true")
+                .unwrap();
 
-            // Do 10 successful reviews
-            for _ in 0..10 {
-                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            for strategy in [SelectionStrategy::Weighted, SelectionStrategy::Random] {
+                let expected = match strategy {
+                    SelectionStrategy::Random => ids[0],
+                    _ => ids[1],
+                };
+                for _ in 0..20 {
+                    let e = db.explain_next_topic(None, strategy).unwrap().unwrap();
+                    assert_eq!(e.selected.topic.id, expected);
+                    assert_eq!(e.draw, None);
+                }
             }
-
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 5); // Should cap at 5
-            assert_eq!(p.times_reviewed, 10);
-            assert_eq!(p.times_succeeded, 10);
         }
 
         #[test]
-        fn record_review_partial_maintains_level() {
-            let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
-
-            // Get to level 2
-            db.record_review(id, ReviewOutcome::Success, None).unwrap();
-            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+        fn topic_reviewed_minutes_ago_is_rarely_reselected() {
+            use rand::rngs::StdRng;
+            use rand::SeedableRng;
+
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            db.set_setting(settings::SELECTION_RECENT_HOURS, "4")
+                .unwrap();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            db.add_topic("B", None, &[]).unwrap();
+            db.add_topic("C", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Success, None).unwrap();
+            clock.advance(Duration::minutes(10));
+            // Still due, so only the recent review tells it apart
+            db.conn
+                .execute(
+                    "UPDATE progress SET next_review = ?1 WHERE topic_id = ?2",
+                    params![clock.now().to_rfc3339(), a],
+                )
+                .unwrap();
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 2);
+            let picks_of_a = |db: &Database| {
+                let mut rng = StdRng::seed_from_u64(11);
+                (0..500)
+                    .filter(|_| {
+                        let e = db
+                            .explain_next_topic_with_rng(
+                                None,
+                                SelectionStrategy::Weighted,
+                                &mut rng,
+                            )
+                            .unwrap()
+                            .unwrap();
+                        e.selected.topic.id == a
+                    })
+                    .count()
+            };
 
-            // Partial should maintain level
-            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
+            // 0.5 of 12.5 total weight, against 5 of 17 without the penalty
+            let penalised = picks_of_a(&db);
+            assert!(penalised < 50, "picked {} times", penalised);
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 2);
-            assert_eq!(p.times_reviewed, 3);
-            assert_eq!(p.times_succeeded, 2); // Only successes count
+            db.set_setting(settings::SELECTION_RECENT_HOURS, "0")
+                .unwrap();
+            let unpenalised = picks_of_a(&db);
+            assert!(unpenalised > 100, "picked {} times", unpenalised);
         }
 
         #[test]
-        fn record_review_fail_decreases_mastery() {
-            let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
+        fn recency_penalty_lapses_after_the_window() {
+            let clock = FixedClock::at("2024-03-01T12:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            db.record_review(a, ReviewOutcome::Fail, None).unwrap();
+            let factor = |db: &Database| {
+                db.explain_next_topic(None, SelectionStrategy::Weighted)
+                    .unwrap()
+                    .unwrap()
+                    .candidates[0]
+                    .recency_factor
+            };
+            // Off by default
+            assert_eq!(factor(&db), 1.0);
+            db.set_setting(settings::SELECTION_RECENT_HOURS, "4")
+                .unwrap();
 
-            // Get to level 3
-            for _ in 0..3 {
-                db.record_review(id, ReviewOutcome::Success, None).unwrap();
-            }
+            clock.advance(Duration::hours(3) + Duration::minutes(59));
+            assert_eq!(factor(&db), settings::RECENT_PENALTY);
+            clock.advance(Duration::minutes(1));
+            assert_eq!(factor(&db), 1.0);
+        }
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 3);
+        #[test]
+        fn pick_weighted_uses_cumulative_weights() {
+            let candidates: Vec<SelectionCandidate> = [1.0, 2.0, 3.0]
+                .iter()
+                .enumerate()
+                .map(|(i, &weight)| SelectionCandidate {
+                    topic_id: i as i64,
+                    weight,
+                    overdue_days: 0,
+                    mastery_factor: weight,
+                    recency_factor: 1.0,
+                })
+                .collect();
+
+            assert_eq!(pick_weighted(&candidates, 0.0), 0);
+            assert_eq!(pick_weighted(&candidates, 1.0), 0);
+            assert_eq!(pick_weighted(&candidates, 1.5), 1);
+            assert_eq!(pick_weighted(&candidates, 3.0), 1);
+            assert_eq!(pick_weighted(&candidates, 5.9), 2);
+            assert_eq!(pick_weighted(&candidates, 7.0), 0);
+        }
+    }
 
-            // Fail should decrease
-            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+    mod next_topic_tests {
+        use super::*;
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 2);
+        #[test]
+        fn get_next_topic_empty_db() {
+            let db = setup_db();
+            let next = db.get_next_topic(None).unwrap();
+            assert!(next.is_none());
         }
 
         #[test]
-        fn record_review_fail_floors_at_0() {
+        fn get_next_topic_returns_something() {
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
-
-            // Fail multiple times at level 0
-            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
-            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            db.add_topic("Topic A", None, &[]).unwrap();
+            db.add_topic("Topic B", None, &[]).unwrap();
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.mastery_level, 0); // Should not go negative
+            let next = db.get_next_topic(None).unwrap();
+            assert!(next.is_some());
         }
 
         #[test]
-        fn record_review_with_notes() {
+        fn get_next_topic_with_tag_filter() {
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
-
-            db.record_review(id, ReviewOutcome::Success, Some("Great session!"))
+            db.add_topic("Rust Topic", None, &["rust".to_string()])
                 .unwrap();
+            db.add_topic("Go Topic", None, &["go".to_string()]).unwrap();
 
-            let p = db.get_progress(id).unwrap().unwrap();
-            assert_eq!(p.notes, Some("Great session!".to_string()));
+            // Filter by rust should return rust topic
+            let next = db.get_next_topic(Some("rust")).unwrap().unwrap();
+            assert_eq!(next.topic.name, "Rust Topic");
+
+            // Filter by go should return go topic
+            let next = db.get_next_topic(Some("go")).unwrap().unwrap();
+            assert_eq!(next.topic.name, "Go Topic");
         }
 
         #[test]
-        fn record_review_updates_timestamps() {
+        fn get_next_topic_filter_no_match() {
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.add_topic("Topic", None, &["rust".to_string()]).unwrap();
 
-            let p_before = db.get_progress(id).unwrap().unwrap();
-            assert!(p_before.last_reviewed.is_none());
+            let next = db.get_next_topic(Some("python")).unwrap();
+            assert!(next.is_none());
+        }
 
+        #[test]
+        fn get_next_topic_includes_progress() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
             db.record_review(id, ReviewOutcome::Success, None).unwrap();
 
-            let p_after = db.get_progress(id).unwrap().unwrap();
-            assert!(p_after.last_reviewed.is_some());
-            assert!(p_after.next_review.is_some());
+            let next = db.get_next_topic(None).unwrap().unwrap();
+            assert_eq!(next.progress.mastery_level, 1);
+            assert_eq!(next.progress.times_reviewed, 1);
         }
 
         #[test]
-        fn record_review_creates_history() {
+        fn get_next_topic_includes_tags() {
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
-
-            db.record_review(id, ReviewOutcome::Success, Some("note1"))
-                .unwrap();
-            db.record_review(id, ReviewOutcome::Fail, Some("note2"))
+            db.add_topic("Test", None, &["tag1".to_string(), "tag2".to_string()])
                 .unwrap();
 
-            let count: i64 = db
-                .conn
-                .query_row(
-                    "SELECT COUNT(*) FROM review_history WHERE topic_id = ?1",
-                    params![id],
-                    |row| row.get(0),
-                )
-                .unwrap();
-            assert_eq!(count, 2);
+            let next = db.get_next_topic(None).unwrap().unwrap();
+            assert_eq!(next.topic.tags.len(), 2);
         }
     }
 
-    mod interval_tests {
+    mod stats_tests {
         use super::*;
 
         #[test]
-        fn calculate_interval_level_0() {
-            assert_eq!(Database::calculate_interval(0), 1);
+        fn tag_stats_break_the_totals_down_by_tag() {
+            let db = setup_db();
+            let tags = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+            let rust = db.add_topic("Ownership", None, &tags(&["rust"])).unwrap();
+            db.add_topic("Traits", None, &tags(&["rust", "types"]))
+                .unwrap();
+            let paused = db.add_topic("Macros", None, &tags(&["rust"])).unwrap();
+            db.set_topic_suspended(paused, true).unwrap();
+            db.record_review(rust, ReviewOutcome::Success, None)
+                .unwrap();
+
+            let stats = db.get_tag_stats().unwrap();
+            assert_eq!(
+                stats,
+                vec![
+                    TagStats {
+                        tag: "rust".to_string(),
+                        topics: 3,
+                        due_now: 1,
+                        avg_mastery: 1.0 / 3.0,
+                    },
+                    TagStats {
+                        tag: "types".to_string(),
+                        topics: 1,
+                        due_now: 1,
+                        avg_mastery: 0.0,
+                    },
+                ]
+            );
         }
 
         #[test]
-        fn calculate_interval_level_1() {
-            assert_eq!(Database::calculate_interval(1), 2);
+        fn calibration_flags_high_confidence_fails() {
+            let db = setup_db();
+            let id = db.add_topic("Lifetimes", None, &[]).unwrap();
+            let review = |outcome, confidence| {
+                let options = ReviewOptions {
+                    confidence,
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(id, outcome, None, &options).unwrap();
+            };
+            review(ReviewOutcome::Fail, Some(5));
+            review(ReviewOutcome::Success, Some(5));
+            review(ReviewOutcome::Fail, None);
+
+            let history = db.get_review_history(Some(id), None, None).unwrap();
+            let confidences: Vec<Option<i32>> = history.iter().map(|r| r.confidence).collect();
+            assert_eq!(confidences, vec![Some(5), Some(5), None]);
+
+            let report = db.get_calibration().unwrap();
+            assert_eq!(report.rated_reviews(), 2);
+            assert_eq!(report.levels[4].fail, 1);
+            assert_eq!(report.overconfident.len(), 1);
+            assert_eq!(report.overconfident[0].id, history[0].id);
         }
 
         #[test]
-        fn calculate_interval_level_2() {
-            assert_eq!(Database::calculate_interval(2), 4);
+        fn study_sessions_cluster_reviews_across_topics() {
+            let db = setup_db();
+            let rust = db.add_topic("Rust", None, &[]).unwrap();
+            let go = db.add_topic("Go", None, &[]).unwrap();
+            let review = |id, outcome, at: &str| {
+                let options = ReviewOptions {
+                    at: parse_timestamp(at),
+                    ..ReviewOptions::default()
+                };
+                db.record_review_with(id, outcome, None, &options).unwrap();
+            };
+            review(rust, ReviewOutcome::Success, "2024-03-01T09:00:00Z");
+            review(go, ReviewOutcome::Fail, "2024-03-01T09:20:00Z");
+            review(rust, ReviewOutcome::Success, "2024-03-01T18:00:00Z");
+            // Backfilled into the morning session
+            review(go, ReviewOutcome::Success, "2024-03-01T09:45:00Z");
+
+            let sessions = db.get_study_sessions(30).unwrap();
+            let counts: Vec<i64> = sessions.iter().map(|s| s.reviews).collect();
+            assert_eq!(counts, [1, 3]);
+            assert_eq!(sessions[1].successes, 2);
+            assert_eq!(sessions[1].duration(), Some(chrono::Duration::minutes(45)));
+
+            // A shorter gap splits the morning at the 25-minute pause
+            let counts: Vec<i64> = db
+                .get_study_sessions(20)
+                .unwrap()
+                .iter()
+                .map(|s| s.reviews)
+                .collect();
+            assert_eq!(counts, [1, 1, 2]);
+
+            // The newest sessions alone match the front of the full list
+            let all = db.get_study_sessions(20).unwrap();
+            for limit in 0..=4 {
+                let recent = db.get_recent_study_sessions(20, limit).unwrap();
+                let expected = &all[..limit.min(all.len())];
+                assert_eq!(
+                    recent.iter().map(|s| &s.start).collect::<Vec<_>>(),
+                    expected.iter().map(|s| &s.start).collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    recent.iter().map(|s| s.reviews).collect::<Vec<_>>(),
+                    expected.iter().map(|s| s.reviews).collect::<Vec<_>>()
+                );
+            }
         }
 
         #[test]
-        fn calculate_interval_level_3() {
-            assert_eq!(Database::calculate_interval(3), 7);
+        fn stats_empty_db() {
+            let db = setup_db();
+            let stats = db.get_stats().unwrap();
+
+            assert_eq!(stats.total_topics, 0);
+            assert_eq!(stats.total_reviews, 0);
+            assert_eq!(stats.mastered, 0);
+            assert_eq!(stats.due_now, 0);
+            assert_close(stats.avg_mastery, 0.0);
         }
 
         #[test]
-        fn calculate_interval_level_4() {
-            assert_eq!(Database::calculate_interval(4), 14);
+        fn stats_counts_topics() {
+            let db = setup_db();
+            db.add_topic("A", None, &[]).unwrap();
+            db.add_topic("B", None, &[]).unwrap();
+
+            let stats = db.get_stats().unwrap();
+            assert_eq!(stats.total_topics, 2);
         }
 
         #[test]
-        fn calculate_interval_level_5() {
-            assert_eq!(Database::calculate_interval(5), 30);
+        fn stats_counts_reviews() {
+            let db = setup_db();
+            let id = db.add_topic("Test", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
+            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+
+            let stats = db.get_stats().unwrap();
+            assert_eq!(stats.total_reviews, 3);
         }
 
         #[test]
-        fn calculate_interval_above_max() {
-            assert_eq!(Database::calculate_interval(10), 30);
-        }
-    }
+        fn stats_counts_mastered() {
+            let db = setup_db();
+            let id1 = db.add_topic("Master", None, &[]).unwrap();
+            let id2 = db.add_topic("Novice", None, &[]).unwrap();
 
-    mod next_topic_tests {
-        use super::*;
+            // Get id1 to level 4 (proficient)
+            for _ in 0..4 {
+                db.record_review(id1, ReviewOutcome::Success, None).unwrap();
+            }
+
+            // id2 stays at level 0
+            let _ = id2;
+
+            let stats = db.get_stats().unwrap();
+            assert_eq!(stats.mastered, 1);
+        }
 
         #[test]
-        fn get_next_topic_empty_db() {
+        fn stats_calculates_avg_mastery() {
             let db = setup_db();
-            let next = db.get_next_topic(None).unwrap();
-            assert!(next.is_none());
+            let id1 = db.add_topic("Topic 1", None, &[]).unwrap();
+            let id2 = db.add_topic("Topic 2", None, &[]).unwrap();
+
+            // id1 to level 2
+            db.record_review(id1, ReviewOutcome::Success, None).unwrap();
+            db.record_review(id1, ReviewOutcome::Success, None).unwrap();
+
+            // id2 stays at level 0
+            let _ = id2;
+
+            let stats = db.get_stats().unwrap();
+            assert_close(stats.avg_mastery, 1.0); // (2 + 0) / 2 = 1.0
+            assert_close(stats.avg_mastery_rounded, 1.0);
         }
 
         #[test]
-        fn get_next_topic_returns_something() {
+        fn avg_mastery_that_does_not_terminate_is_rounded_to_a_tenth() {
             let db = setup_db();
-            db.add_topic("Topic A", None, &[]).unwrap();
-            db.add_topic("Topic B", None, &[]).unwrap();
+            let id = db.add_topic("Topic 1", None, &[]).unwrap();
+            db.add_topic("Topic 2", None, &[]).unwrap();
+            db.add_topic("Topic 3", None, &[]).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            db.record_review(id, ReviewOutcome::Success, None).unwrap();
 
-            let next = db.get_next_topic(None).unwrap();
-            assert!(next.is_some());
+            // (2 + 0 + 0) / 3
+            let stats = db.get_stats().unwrap();
+            assert_close(stats.avg_mastery, 2.0 / 3.0);
+            assert_close(stats.avg_mastery_rounded, 0.7);
+            let status = db.get_status_counts().unwrap();
+            assert_close(status.avg_mastery, 2.0 / 3.0);
+            assert_close(status.avg_mastery_rounded, 0.7);
         }
 
         #[test]
-        fn get_next_topic_with_tag_filter() {
+        fn stats_due_now_counts_new_topics() {
             let db = setup_db();
-            db.add_topic("Rust Topic", None, &["rust".to_string()])
-                .unwrap();
-            db.add_topic("Go Topic", None, &["go".to_string()]).unwrap();
+            // New topics are immediately due
+            db.add_topic("A", None, &[]).unwrap();
+            db.add_topic("B", None, &[]).unwrap();
 
-            // Filter by rust should return rust topic
-            let next = db.get_next_topic(Some("rust")).unwrap().unwrap();
-            assert_eq!(next.topic.name, "Rust Topic");
+            let stats = db.get_stats().unwrap();
+            assert_eq!(stats.due_now, 2);
+        }
 
-            // Filter by go should return go topic
-            let next = db.get_next_topic(Some("go")).unwrap().unwrap();
-            assert_eq!(next.topic.name, "Go Topic");
+        #[test]
+        fn mastery_histogram_counts_topics_per_level() {
+            let db = setup_db();
+            // Successes to reach each topic's level
+            for (i, successes) in [0, 0, 2, 2, 2, 5].iter().enumerate() {
+                let id = db.add_topic(&format!("Topic {}", i), None, &[]).unwrap();
+                for _ in 0..*successes {
+                    db.record_review(id, ReviewOutcome::Success, None).unwrap();
+                }
+            }
+
+            assert_eq!(
+                db.get_mastery_histogram().unwrap(),
+                vec![(0, 2), (1, 0), (2, 3), (3, 0), (4, 0), (5, 1)]
+            );
         }
 
         #[test]
-        fn get_next_topic_filter_no_match() {
+        fn status_counts_empty_db() {
             let db = setup_db();
-            db.add_topic("Topic", None, &["rust".to_string()]).unwrap();
+            let status = db.get_status_counts().unwrap();
 
-            let next = db.get_next_topic(Some("python")).unwrap();
-            assert!(next.is_none());
+            assert_eq!(status.due, 0);
+            assert_eq!(status.overdue, 0);
+            assert_eq!(status.streak, 0);
+            assert_close(status.avg_mastery, 0.0);
         }
 
         #[test]
-        fn get_next_topic_includes_progress() {
+        fn status_counts_streak_includes_today() {
             let db = setup_db();
             let id = db.add_topic("Test", None, &[]).unwrap();
             db.record_review(id, ReviewOutcome::Success, None).unwrap();
 
-            let next = db.get_next_topic(None).unwrap().unwrap();
-            assert_eq!(next.progress.mastery_level, 1);
-            assert_eq!(next.progress.times_reviewed, 1);
+            let status = db.get_status_counts().unwrap();
+            assert_eq!(status.streak, 1);
+            assert_eq!(status.due, 0);
         }
 
         #[test]
-        fn get_next_topic_includes_tags() {
-            let db = setup_db();
-            db.add_topic("Test", None, &["tag1".to_string(), "tag2".to_string()])
-                .unwrap();
+        fn calculate_streak_consecutive_days() {
+            let today = chrono::NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+            let days = vec![
+                "2024-03-10".to_string(),
+                "2024-03-09".to_string(),
+                "2024-03-08".to_string(),
+                "2024-03-05".to_string(),
+            ];
+            assert_eq!(Database::calculate_streak(&days, today), 3);
+        }
 
-            let next = db.get_next_topic(None).unwrap().unwrap();
-            assert_eq!(next.topic.tags.len(), 2);
+        #[test]
+        fn calculate_streak_alive_from_yesterday() {
+            let today = chrono::NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+            let days = vec!["2024-03-09".to_string(), "2024-03-08".to_string()];
+            assert_eq!(Database::calculate_streak(&days, today), 2);
+        }
+
+        #[test]
+        fn calculate_streak_broken() {
+            let today = chrono::NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+            let days = vec!["2024-03-07".to_string()];
+            assert_eq!(Database::calculate_streak(&days, today), 0);
         }
     }
 
-    mod stats_tests {
+    mod import_tests {
         use super::*;
+        use std::collections::HashSet;
+
+        fn parse(json: &str) -> ImportData {
+            serde_json::from_str(json).unwrap()
+        }
 
         #[test]
-        fn stats_empty_db() {
+        fn import_data_remaps_ids() {
             let db = setup_db();
-            let stats = db.get_stats().unwrap();
-
-            assert_eq!(stats.total_topics, 0);
-            assert_eq!(stats.total_reviews, 0);
-            assert_eq!(stats.mastered, 0);
-            assert_eq!(stats.due_now, 0);
-            assert_eq!(stats.avg_mastery, 0.0);
+            let data = parse(
+                r#"{
+                    "topics": [{"id": 7, "name": "Rust", "tags": ["rust"], "progress": {"mastery_level": 3}}],
+                    "reviews": [{"topic_id": 7, "outcome": "success", "reviewed_at": "2024-01-01T00:00:00Z"}],
+                    "sessions": [{"id": 42, "topic_id": 7, "session_type": "socratic", "started_at": "2024-01-01T00:00:00Z"}],
+                    "gaps": [{"session_id": 42, "gap_description": "Lifetimes"}]
+                }"#,
+            );
+            let validation = data.validate(&HashSet::new());
+            let summary = db.import_data(&data, &validation).unwrap();
+            assert_eq!(summary.topics, 1);
+            assert_eq!(summary.reviews, 1);
+            assert_eq!(summary.sessions, 1);
+            assert_eq!(summary.gaps, 1);
+
+            let topic = &db.list_topics(None).unwrap()[0];
+            assert_eq!(topic.tags, vec!["rust"]);
+            assert_eq!(db.get_progress(topic.id).unwrap().unwrap().mastery_level, 3);
+            let sessions = db.list_sessions(Some(topic.id)).unwrap();
+            assert_eq!(sessions.len(), 1);
+            assert_eq!(db.get_unaddressed_gaps(topic.id).unwrap().len(), 1);
         }
 
         #[test]
-        fn stats_counts_topics() {
+        fn import_data_skips_rejected_rows() {
             let db = setup_db();
-            db.add_topic("A", None, &[]).unwrap();
-            db.add_topic("B", None, &[]).unwrap();
+            let data = parse(
+                r#"{
+                    "topics": [{"id": 1, "name": "Rust"}],
+                    "gaps": [{"session_id": 99, "gap_description": "Orphan"}]
+                }"#,
+            );
+            let validation = data.validate(&HashSet::new());
+            assert!(!validation.is_ok());
 
-            let stats = db.get_stats().unwrap();
-            assert_eq!(stats.total_topics, 2);
+            let summary = db.import_data(&data, &validation).unwrap();
+            assert_eq!(summary.topics, 1);
+            assert_eq!(summary.gaps, 0);
+            assert_eq!(summary.skipped, 1);
         }
 
         #[test]
-        fn stats_counts_reviews() {
+        fn import_and_replay_rebuilds_progress_from_the_reviews() {
             let db = setup_db();
-            let id = db.add_topic("Test", None, &[]).unwrap();
-            db.record_review(id, ReviewOutcome::Success, None).unwrap();
-            db.record_review(id, ReviewOutcome::Partial, None).unwrap();
-            db.record_review(id, ReviewOutcome::Fail, None).unwrap();
+            let data = parse(
+                r#"{
+                    "topics": [{"id": 1, "name": "Ownership"}, {"id": 2, "name": "Traits"}],
+                    "reviews": [
+                        {"topic_id": 1, "outcome": "success", "reviewed_at": "2024-01-01T00:00:00Z"},
+                        {"topic_id": 1, "outcome": "success", "reviewed_at": "2024-01-03T00:00:00Z"},
+                        {"topic_id": 1, "outcome": "partial", "reviewed_at": "2024-01-02T00:00:00Z"}
+                    ]
+                }"#,
+            );
+            let validation = data.validate(&HashSet::new());
+            let summary = db.import_and_replay(&data, &validation).unwrap();
+            assert_eq!(summary.topics, 2);
+            assert_eq!(summary.reviews, 3);
 
-            let stats = db.get_stats().unwrap();
-            assert_eq!(stats.total_reviews, 3);
+            let topics = db.list_topics(None).unwrap();
+            let id = |name: &str| topics.iter().find(|t| t.name == name).unwrap().id;
+            let progress = db.get_progress(id("Ownership")).unwrap().unwrap();
+            assert_eq!(progress.times_reviewed, 3);
+            assert_eq!(progress.times_succeeded, 2);
+            assert!(progress.mastery_level > 0);
+            assert!(progress
+                .last_reviewed
+                .as_deref()
+                .unwrap()
+                .starts_with("2024-01-03"));
+            let untouched = db.get_progress(id("Traits")).unwrap().unwrap();
+            assert_eq!(untouched.times_reviewed, 0);
         }
 
         #[test]
-        fn stats_counts_mastered() {
+        fn imported_progress_survives_backfills_merges_and_rebuilds() {
             let db = setup_db();
-            let id1 = db.add_topic("Master", None, &[]).unwrap();
-            let id2 = db.add_topic("Novice", None, &[]).unwrap();
+            let data = parse(
+                r#"{
+                    "topics": [
+                        {"id": 1, "name": "Imported", "progress": {"mastery_level": 3,
+                            "times_reviewed": 6, "times_succeeded": 5,
+                            "last_reviewed": "2024-01-01T00:00:00Z",
+                            "next_review": "2024-01-08T00:00:00Z"}},
+                        {"id": 2, "name": "Started", "progress": {"mastery_level": 2}}
+                    ],
+                    "reviews": [
+                        {"topic_id": 1, "outcome": "success", "reviewed_at": "2023-12-25T00:00:00Z"}
+                    ]
+                }"#,
+            );
+            let validation = data.validate(&HashSet::new());
+            db.import_data(&data, &validation).unwrap();
+            let topics = db.list_topics(None).unwrap();
+            let id = |name: &str| topics.iter().find(|t| t.name == name).unwrap().id;
+            let (imported, started) = (id("Imported"), id("Started"));
+            let fields = |id| {
+                let p = db.get_progress(id).unwrap().unwrap();
+                (p.mastery_level, p.times_reviewed, p.last_reviewed)
+            };
+            let as_imported = (3, 6, Some("2024-01-01T00:00:00Z".to_string()));
 
-            // Get id1 to level 4 (proficient)
-            for _ in 0..4 {
-                db.record_review(id1, ReviewOutcome::Success, None).unwrap();
-            }
+            // The imported review is already counted in the progress
+            db.rebuild_progress(None).unwrap();
+            assert_eq!(fields(imported), as_imported);
+            assert_eq!(fields(started).0, 2);
 
-            // id2 stays at level 0
-            let _ = id2;
+            let backfill = ReviewOptions {
+                at: parse_timestamp("2023-12-01T00:00:00Z"),
+                ..ReviewOptions::default()
+            };
+            assert!(matches!(
+                db.record_review_with(imported, ReviewOutcome::Fail, None, &backfill),
+                Err(FeynmanError::Validation(_))
+            ));
+            assert_eq!(fields(imported), as_imported);
+
+            let older = db.add_topic("Older", None, &[]).unwrap();
+            db.record_review_with(older, ReviewOutcome::Fail, None, &backfill)
+                .unwrap();
+            assert!(matches!(
+                db.merge_topics(older, imported),
+                Err(FeynmanError::Validation(_))
+            ));
+            assert!(matches!(
+                db.merge_topics(imported, older),
+                Err(FeynmanError::Validation(_))
+            ));
+            assert_eq!(fields(imported), as_imported);
+
+            // Newer reviews build on the imported progress
+            let newer = db.add_topic("Newer", None, &[]).unwrap();
+            let later = ReviewOptions {
+                at: parse_timestamp("2024-01-08T00:00:00Z"),
+                ..ReviewOptions::default()
+            };
+            db.record_review_with(newer, ReviewOutcome::Success, None, &later)
+                .unwrap();
+            db.merge_topics(newer, imported).unwrap().unwrap();
+            assert_eq!(
+                fields(imported),
+                (4, 7, Some("2024-01-08T00:00:00+00:00".to_string()))
+            );
+        }
 
-            let stats = db.get_stats().unwrap();
-            assert_eq!(stats.mastered, 1);
+        fn import_json(db: &Database, json: &str, on_conflict: OnConflict) -> TopicImportSummary {
+            let topics = crate::import::parse_topic_array(json).unwrap();
+            db.import_topic_lines(&topics, on_conflict).unwrap()
+        }
+
+        const WITH_DUPLICATE: &str = r#"[
+            {"name": "Rust", "description": "Systems language", "tags": ["lang"]},
+            {"name": "Go"}
+        ]"#;
+
+        fn setup_existing_rust() -> Database {
+            let db = setup_db();
+            db.add_topic("Rust", Some("Old"), &["rust".to_string()])
+                .unwrap();
+            db
         }
 
         #[test]
-        fn stats_calculates_avg_mastery() {
+        fn import_topic_array_adds_every_topic() {
             let db = setup_db();
-            let id1 = db.add_topic("Topic 1", None, &[]).unwrap();
-            let id2 = db.add_topic("Topic 2", None, &[]).unwrap();
+            let summary = import_json(
+                &db,
+                r#"[{"name": "Rust", "tags": ["lang", "systems"]}, {"name": "Go", "description": "Gophers"}]"#,
+                OnConflict::Fail,
+            );
+            assert_eq!(
+                summary,
+                TopicImportSummary {
+                    added: 2,
+                    updated: 0,
+                    skipped: 0
+                }
+            );
+            let topics = db.list_topics(None).unwrap();
+            assert_eq!(topics[0].name, "Go");
+            assert_eq!(topics[0].description.as_deref(), Some("Gophers"));
+            assert_eq!(topics[1].tags, ["lang", "systems"]);
+            assert!(db.get_progress(topics[1].id).unwrap().is_some());
+        }
 
-            // id1 to level 2
-            db.record_review(id1, ReviewOutcome::Success, None).unwrap();
-            db.record_review(id1, ReviewOutcome::Success, None).unwrap();
+        #[test]
+        fn import_topic_array_duplicate_fails_and_keeps_nothing() {
+            let db = setup_existing_rust();
+            let topics = crate::import::parse_topic_array(WITH_DUPLICATE).unwrap();
+            assert!(db.import_topic_lines(&topics, OnConflict::Fail).is_err());
+            assert_eq!(db.get_stats().unwrap().total_topics, 1);
+        }
 
-            // id2 stays at level 0
-            let _ = id2;
+        #[test]
+        fn import_topic_array_duplicate_skipped() {
+            let db = setup_existing_rust();
+            let summary = import_json(&db, WITH_DUPLICATE, OnConflict::Skip);
+            assert_eq!((summary.added, summary.updated, summary.skipped), (1, 0, 1));
+            let rust = db.get_topic(1).unwrap().unwrap();
+            assert_eq!(rust.description.as_deref(), Some("Old"));
+            assert_eq!(rust.tags, ["rust"]);
+        }
 
-            let stats = db.get_stats().unwrap();
-            assert_eq!(stats.avg_mastery, 1.0); // (2 + 0) / 2 = 1.0
+        #[test]
+        fn import_topic_array_duplicate_updated() {
+            let db = setup_existing_rust();
+            let summary = import_json(&db, WITH_DUPLICATE, OnConflict::Update);
+            assert_eq!((summary.added, summary.updated, summary.skipped), (1, 1, 0));
+            let rust = db.get_topic(1).unwrap().unwrap();
+            assert_eq!(rust.description.as_deref(), Some("Systems language"));
+            assert_eq!(rust.tags, ["lang", "rust"]);
+            assert_eq!(db.get_stats().unwrap().total_topics, 2);
         }
 
         #[test]
-        fn stats_due_now_counts_new_topics() {
+        fn import_topic_array_repeated_name_within_file() {
             let db = setup_db();
-            // New topics are immediately due
-            db.add_topic("A", None, &[]).unwrap();
-            db.add_topic("B", None, &[]).unwrap();
-
-            let stats = db.get_stats().unwrap();
-            assert_eq!(stats.due_now, 2);
+            let json = r#"[{"name": "Zig"}, {"name": "Zig", "tags": ["lang"]}]"#;
+            let summary = import_json(&db, json, OnConflict::Update);
+            assert_eq!((summary.added, summary.updated), (1, 1));
+            assert_eq!(db.list_topics(None).unwrap()[0].tags, ["lang"]);
         }
     }
 
     mod session_tests {
         use super::*;
+        use crate::clock::FixedClock;
+        use chrono::Duration;
+
+        #[test]
+        fn query_sessions_filters_active_and_outcome() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            let succeeded = db.start_session(a, SessionType::Feynman).unwrap();
+            db.end_session(succeeded, SessionOutcome::Success, None, None)
+                .unwrap();
+            let abandoned = db.start_session(b, SessionType::Socratic).unwrap();
+            db.end_session(abandoned, SessionOutcome::Abandoned, None, None)
+                .unwrap();
+            let running = db.start_session(b, SessionType::Feynman).unwrap();
+
+            let ids = |query: SessionQuery| -> Vec<i64> {
+                let mut ids: Vec<i64> = db
+                    .query_sessions(&query)
+                    .unwrap()
+                    .iter()
+                    .map(|s| s.id)
+                    .collect();
+                ids.sort();
+                ids
+            };
+            assert_eq!(
+                ids(SessionQuery::default()),
+                [succeeded, abandoned, running]
+            );
+            assert_eq!(
+                ids(SessionQuery {
+                    active: true,
+                    ..SessionQuery::default()
+                }),
+                [running]
+            );
+            assert_eq!(
+                ids(SessionQuery {
+                    outcome: Some(SessionOutcome::Abandoned),
+                    ..SessionQuery::default()
+                }),
+                [abandoned]
+            );
+            assert_eq!(
+                ids(SessionQuery {
+                    topic_id: Some(b),
+                    outcome: Some(SessionOutcome::Success),
+                    ..SessionQuery::default()
+                }),
+                Vec::<i64>::new()
+            );
+            assert_eq!(
+                ids(SessionQuery {
+                    topic_id: Some(b),
+                    ..SessionQuery::default()
+                }),
+                [abandoned, running]
+            );
+        }
+
+        #[test]
+        fn recent_sessions_page_newest_first() {
+            let clock = FixedClock::at("2024-03-10T12:00:00Z");
+            let db = setup_db().with_clock(clock.clone());
+            let a = db.add_topic("A", None, &[]).unwrap();
+            let b = db.add_topic("B", None, &[]).unwrap();
+            let mut ids = Vec::new();
+            for i in 0..5 {
+                let topic = if i % 2 == 0 { a } else { b };
+                ids.push(db.start_session(topic, SessionType::Feynman).unwrap());
+                clock.advance(Duration::minutes(10));
+            }
+            ids.reverse();
+
+            let window = |limit, offset| -> Vec<(i64, String)> {
+                db.get_recent_sessions_with_topics(limit, offset)
+                    .unwrap()
+                    .into_iter()
+                    .map(|(s, name)| (s.id, name))
+                    .collect()
+            };
+            assert_eq!(
+                window(2, 0),
+                [(ids[0], "A".to_string()), (ids[1], "B".to_string())]
+            );
+            assert_eq!(
+                window(2, 2),
+                [(ids[2], "A".to_string()), (ids[3], "B".to_string())]
+            );
+            assert_eq!(window(2, 4), [(ids[4], "A".to_string())]);
+            assert!(window(2, 5).is_empty());
+            assert_eq!(window(10, 0).len(), 5);
+        }
+
+        #[test]
+        fn recent_sessions_order_mixed_timestamp_formats_by_time() {
+            let db = setup_db();
+            let id = db.add_topic("A", None, &[]).unwrap();
+            let older = db.start_session(id, SessionType::Feynman).unwrap();
+            let newer = db.start_session(id, SessionType::Feynman).unwrap();
+            // As text the older RFC 3339 start would sort after the newer one
+            for (session, at) in [
+                (older, "2024-03-01T12:00:00Z"),
+                (newer, "2024-03-01 13:00:00"),
+            ] {
+                db.conn
+                    .execute(
+                        "UPDATE learning_sessions SET started_at = ?1 WHERE id = ?2",
+                        params![at, session],
+                    )
+                    .unwrap();
+            }
+
+            let ids: Vec<i64> = db
+                .get_recent_sessions_with_topics(10, 0)
+                .unwrap()
+                .into_iter()
+                .map(|(s, _)| s.id)
+                .collect();
+            assert_eq!(ids, [newer, older]);
+        }
 
         #[test]
         fn start_session_creates_record() {
@@ -1909,6 +7182,70 @@ mod tests {
             assert!(session.outcome.is_none());
         }
 
+        #[test]
+        fn start_sessions_for_tag_starts_one_per_matching_topic() {
+            let db = setup_db();
+            let a = db.add_topic("A", None, &["rust".to_string()]).unwrap();
+            let b = db.add_topic("B", None, &["rust".to_string()]).unwrap();
+            db.add_topic("C", None, &["go".to_string()]).unwrap();
+            let archived = db.add_topic("D", None, &["rust".to_string()]).unwrap();
+            db.set_topic_archived(archived, true).unwrap();
+
+            let started = db
+                .start_sessions_for_tag("rust", SessionType::Socratic)
+                .unwrap();
+            assert_eq!(
+                started.iter().map(|&(topic, _)| topic).collect::<Vec<_>>(),
+                [a, b]
+            );
+            let active = db
+                .query_sessions(&SessionQuery {
+                    active: true,
+                    ..SessionQuery::default()
+                })
+                .unwrap();
+            assert_eq!(active.len(), 2);
+            for (topic, session) in &started {
+                let s = db.get_session(*session).unwrap().unwrap();
+                assert_eq!(s.topic_id, *topic);
+                assert_eq!(s.session_type, SessionType::Socratic);
+                assert!(s.is_active());
+            }
+            assert!(db
+                .start_sessions_for_tag("nothing", SessionType::Feynman)
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn end_sessions_skips_ended_and_missing() {
+            let clock = FixedClock::at("2024-03-01T09:00:00Z");
+            let db = Database::open(":memory:")
+                .unwrap()
+                .with_clock(clock.clone());
+            db.init().unwrap();
+            let topic = db.add_topic("A", None, &[]).unwrap();
+            let early = db.start_session(topic, SessionType::Feynman).unwrap();
+            clock.advance(Duration::hours(2));
+            let since = db.now();
+            let batch: Vec<i64> = (0..2)
+                .map(|_| db.start_session(topic, SessionType::Feynman).unwrap())
+                .collect();
+            db.end_session(batch[1], SessionOutcome::Fail, None, None)
+                .unwrap();
+
+            assert_eq!(db.active_session_ids_since(since).unwrap(), [batch[0]]);
+            let ended = db
+                .end_sessions(&[early, batch[0], batch[1], 999], SessionOutcome::Success)
+                .unwrap();
+            assert_eq!(ended, [early, batch[0]]);
+            let s = db.get_session(batch[0]).unwrap().unwrap();
+            assert_eq!(s.outcome, Some(SessionOutcome::Success));
+            // Already ended, so its outcome stands
+            let s = db.get_session(batch[1]).unwrap().unwrap();
+            assert_eq!(s.outcome, Some(SessionOutcome::Fail));
+        }
+
         #[test]
         fn start_session_records_skill_level() {
             let db = setup_db();
@@ -1949,6 +7286,70 @@ mod tests {
             assert_eq!(session.notes, Some("User understood well".to_string()));
         }
 
+        #[test]
+        fn transcript_round_trips_multiple_paragraphs() {
+            let db = setup_db();
+            let topic_id = db.add_topic("Test", None, &[]).unwrap();
+            let session_id = db.start_session(topic_id, SessionType::Feynman).unwrap();
+            assert_eq!(db.get_session_transcript(session_id).unwrap(), None);
+
+            let transcript = "Q: What is ownership?\n\nA: Each value has one owner.\n  When the owner goes out of scope, the value is dropped.\n\n\nQ: And borrowing?\n";
+            db.end_session_with_gaps(
+                session_id,
+                SessionOutcome::Success,
+                None,
+                None,
+                Some(transcript),
+                &[],
+            )
+            .unwrap();
+            assert_eq!(
+                db.get_session_transcript(session_id).unwrap().as_deref(),
+                Some(transcript)
+            );
+
+            // Ending again without one keeps it
+            db.end_session(session_id, SessionOutcome::Success, None, None)
+                .unwrap();
+            assert_eq!(
+                db.get_session_transcript(session_id).unwrap().as_deref(),
+                Some(transcript)
+            );
+            assert_eq!(db.get_session_transcript(999).unwrap(), None);
+        }
+
+        #[test]
+        fn end_session_with_gaps_links_each_gap() {
+            let db = setup_db();
+            let topic_id = db.add_topic("Test", None, &[]).unwrap();
+            let session_id = db.start_session(topic_id, SessionType::Feynman).unwrap();
+
+            let gap_ids = db
+                .end_session_with_gaps(
+                    session_id,
+                    SessionOutcome::Partial,
+                    None,
+                    None,
+                    None,
+                    &["a".to_string(), "b".to_string()],
+                )
+                .unwrap();
+            assert_eq!(gap_ids.len(), 2);
+
+            let gaps = db.get_session_gaps(session_id).unwrap();
+            let descriptions: Vec<&str> = gaps.iter().map(|g| g.gap_description.as_str()).collect();
+            assert_eq!(descriptions, ["a", "b"]);
+            assert!(gaps
+                .iter()
+                .all(|g| g.session_id == session_id && !g.addressed));
+            assert!(db
+                .get_session(session_id)
+                .unwrap()
+                .unwrap()
+                .ended_at
+                .is_some());
+        }
+
         #[test]
         fn list_sessions_returns_all_for_topic() {
             let db = setup_db();
@@ -2030,6 +7431,167 @@ mod tests {
             assert_eq!(unaddressed.len(), 1);
             assert_eq!(unaddressed[0].gap_description, "Gap 2");
         }
+
+        #[test]
+        fn get_all_gaps_spans_topics_and_filters_by_addressed() {
+            let db = setup_db();
+            let rust = db.add_topic("Rust", None, &[]).unwrap();
+            let go = db.add_topic("Go", None, &[]).unwrap();
+            let s1 = db.start_session(rust, SessionType::Feynman).unwrap();
+            let s2 = db.start_session(go, SessionType::Feynman).unwrap();
+
+            let gap1 = db.add_session_gap(s1, "Lifetimes").unwrap();
+            db.add_session_gap(s1, "Traits").unwrap();
+            db.add_session_gap(s2, "Channels").unwrap();
+            db.mark_gap_addressed(gap1).unwrap();
+
+            assert_eq!(db.get_all_gaps(None).unwrap().len(), 3);
+
+            let open = db.get_all_gaps(Some(false)).unwrap();
+            assert_eq!(open.len(), 2);
+            assert!(open.iter().all(|g| !g.addressed));
+
+            let addressed = db.get_all_gaps(Some(true)).unwrap();
+            assert_eq!(addressed.len(), 1);
+            assert_eq!(addressed[0].gap_description, "Lifetimes");
+        }
+
+        #[test]
+        fn stats_count_total_and_open_gaps() {
+            let db = setup_db();
+            let stats = db.get_stats().unwrap();
+            assert_eq!((stats.total_gaps, stats.open_gaps), (0, 0));
+
+            let topic_id = db.add_topic("Test", None, &[]).unwrap();
+            let session_id = db.start_session(topic_id, SessionType::Feynman).unwrap();
+            let gap = db.add_session_gap(session_id, "Gap 1").unwrap();
+            db.add_session_gap(session_id, "Gap 2").unwrap();
+            db.mark_gap_addressed(gap).unwrap();
+
+            let stats = db.get_stats().unwrap();
+            assert_eq!(stats.total_gaps, 2);
+            assert_eq!(stats.open_gaps, 1);
+        }
+    }
+
+    mod prompt_tests {
+        use super::*;
+
+        fn prompt_texts(db: &Database, topic_id: i64) -> Vec<String> {
+            db.get_prompts(topic_id)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.prompt)
+                .collect()
+        }
+
+        #[test]
+        fn prompts_come_back_in_the_order_added() {
+            let db = setup_db();
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            let other = db.add_topic("Traits", None, &[]).unwrap();
+            let first = db.add_prompt(id, "Explain it to a five-year-old").unwrap();
+            db.add_prompt(other, "What is a blanket impl?").unwrap();
+            db.add_prompt(id, "Why does Rust need it?").unwrap();
+
+            let prompts = db.get_prompts(id).unwrap();
+            assert_eq!(prompts[0].id, first);
+            assert_eq!(
+                prompts.iter().map(|p| p.ord).collect::<Vec<_>>(),
+                vec![1, 2]
+            );
+            assert_eq!(
+                prompt_texts(&db, id),
+                ["Explain it to a five-year-old", "Why does Rust need it?"]
+            );
+            assert_eq!(prompt_texts(&db, other), ["What is a blanket impl?"]);
+        }
+
+        #[test]
+        fn removing_a_prompt_keeps_the_order_of_the_rest() {
+            let db = setup_db();
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            db.add_prompt(id, "One").unwrap();
+            let two = db.add_prompt(id, "Two").unwrap();
+            db.add_prompt(id, "Three").unwrap();
+
+            assert!(db.delete_prompt(two).unwrap());
+            assert!(!db.delete_prompt(two).unwrap());
+            assert_eq!(prompt_texts(&db, id), ["One", "Three"]);
+
+            // New prompts still go last
+            db.add_prompt(id, "Four").unwrap();
+            assert_eq!(prompt_texts(&db, id), ["One", "Three", "Four"]);
+        }
+
+        #[test]
+        fn each_review_moves_on_to_the_next_prompt() {
+            let db = setup_db();
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            assert!(db.get_review_prompt(id).unwrap().is_none());
+
+            db.add_prompt(id, "One").unwrap();
+            db.add_prompt(id, "Two").unwrap();
+            let mut asked = Vec::new();
+            for _ in 0..3 {
+                asked.push(db.get_review_prompt(id).unwrap().unwrap().prompt);
+                db.record_review(id, ReviewOutcome::Success, None).unwrap();
+            }
+            assert_eq!(asked, ["One", "Two", "One"]);
+        }
+    }
+
+    mod resource_tests {
+        use super::*;
+
+        #[test]
+        fn resources_come_back_in_the_order_added() {
+            let db = setup_db();
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            let other = db.add_topic("Traits", None, &[]).unwrap();
+
+            let book = db
+                .add_resource(
+                    id,
+                    Some("The Book, ch. 4"),
+                    "https://doc.rust-lang.org/book/ch04-00-understanding-ownership.html",
+                )
+                .unwrap();
+            db.add_resource(
+                other,
+                None,
+                "https://doc.rust-lang.org/reference/items/traits.html",
+            )
+            .unwrap();
+            db.add_resource(id, None, "isbn:9781718503106").unwrap();
+
+            let resources = db.get_resources(id).unwrap();
+            assert_eq!(resources.len(), 2);
+            assert_eq!(resources[0].id, book);
+            assert_eq!(resources[0].topic_id, id);
+            assert_eq!(resources[0].label.as_deref(), Some("The Book, ch. 4"));
+            assert_eq!(resources[1].label, None);
+            assert_eq!(resources[1].url, "isbn:9781718503106");
+            assert_eq!(db.get_resources(other).unwrap().len(), 1);
+        }
+
+        #[test]
+        fn removing_a_resource_leaves_the_rest() {
+            let db = setup_db();
+            let id = db.add_topic("Ownership", None, &[]).unwrap();
+            let first = db.add_resource(id, None, "https://a.example").unwrap();
+            db.add_resource(id, None, "https://b.example").unwrap();
+
+            assert!(db.delete_resource(first).unwrap());
+            assert!(!db.delete_resource(first).unwrap());
+            let urls: Vec<String> = db
+                .get_resources(id)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.url)
+                .collect();
+            assert_eq!(urls, ["https://b.example"]);
+        }
     }
 
     mod skill_assessment_tests {
@@ -2141,7 +7703,14 @@ mod tests {
             let p1 = db.create_plan("Plan 1", "Desc 1").unwrap();
             db.create_plan("Plan 2", "Desc 2").unwrap();
 
-            db.update_plan_status(p1, PlanStatus::Complete).unwrap();
+            for status in [
+                PlanStatus::SpecReady,
+                PlanStatus::Approved,
+                PlanStatus::InProgress,
+                PlanStatus::Complete,
+            ] {
+                db.update_plan_status(p1, status).unwrap();
+            }
 
             let interviewing = db.list_plans(Some(PlanStatus::Interviewing)).unwrap();
             assert_eq!(interviewing.len(), 1);
@@ -2162,6 +7731,29 @@ mod tests {
             assert_eq!(plan.status, PlanStatus::SpecReady);
         }
 
+        #[test]
+        fn update_plan_status_rejects_disallowed_moves() {
+            let db = setup_db();
+            let plan_id = db.create_plan("Plan", "Desc").unwrap();
+
+            let e = db
+                .update_plan_status(plan_id, PlanStatus::Complete)
+                .unwrap_err();
+            assert!(matches!(e, FeynmanError::Validation(_)));
+            assert_eq!(
+                e.to_string(),
+                "Can't move a plan from interviewing to complete (allowed: spec_ready, abandoned)"
+            );
+            assert_eq!(
+                db.get_plan(plan_id).unwrap().unwrap().status,
+                PlanStatus::Interviewing
+            );
+            assert!(matches!(
+                db.update_plan_status(999, PlanStatus::SpecReady),
+                Err(FeynmanError::NotFound("Plan"))
+            ));
+        }
+
         #[test]
         fn update_plan_engineer_level_sets_level() {
             let db = setup_db();
@@ -2309,6 +7901,31 @@ mod tests {
             assert_eq!(security[0].content, "Security Q");
         }
 
+        #[test]
+        fn plan_notes_are_returned_in_order() {
+            let db = setup_db();
+            let plan_id = db.create_plan("Plan", "Desc").unwrap();
+
+            db.add_plan_note(plan_id, "First").unwrap();
+            db.add_interview_entry(
+                plan_id,
+                InterviewEntryType::Question,
+                "Not a note",
+                InterviewCategory::Other,
+            )
+            .unwrap();
+            db.add_plan_note(plan_id, "Second").unwrap();
+            db.add_plan_note(plan_id, "Third").unwrap();
+
+            let notes: Vec<String> = db
+                .get_plan_notes(plan_id)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.content)
+                .collect();
+            assert_eq!(notes, ["First", "Second", "Third"]);
+        }
+
         #[test]
         fn delete_plan_cascades_to_entries() {
             let db = setup_db();
@@ -0,0 +1,83 @@
+// The crate's error type, so callers can match on what went wrong (and main
+// can pick an exit code) instead of only printing it.
+
+use thiserror::Error;
+
+use crate::models::{EnumKind, ParseEnumError};
+
+#[derive(Debug, Error)]
+pub enum FeynmanError {
+    #[error(transparent)]
+    Db(#[from] rusqlite::Error),
+
+    // A lookup by id that matched nothing, e.g. NotFound("Topic")
+    #[error("{0} not found")]
+    NotFound(&'static str),
+
+    // Input that was understood but not accepted, e.g. a bad setting value
+    #[error("{0}")]
+    Validation(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid outcome '{0}'. Use: success, partial, or fail")]
+    InvalidOutcome(String),
+
+    // A schema upgrade step that failed; the upgrade runs in one transaction,
+    // so the database is left as it was
+    #[error("Migrating the database to schema version {version} failed at {step}: {source}")]
+    Migration {
+        version: i32,
+        // e.g. "adding progress.difficulty"
+        step: String,
+        source: rusqlite::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, FeynmanError>;
+
+impl From<String> for FeynmanError {
+    fn from(message: String) -> Self {
+        FeynmanError::Validation(message)
+    }
+}
+
+impl From<&str> for FeynmanError {
+    fn from(message: &str) -> Self {
+        FeynmanError::Validation(message.to_string())
+    }
+}
+
+// An outcome name that isn't one becomes InvalidOutcome; any other unknown
+// name is a validation error
+impl From<ParseEnumError> for FeynmanError {
+    fn from(e: ParseEnumError) -> Self {
+        match e.kind {
+            EnumKind::ReviewOutcome => FeynmanError::InvalidOutcome(e.value),
+            _ => FeynmanError::Validation(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ReviewOutcome, SessionType};
+
+    #[test]
+    fn unknown_outcomes_are_their_own_kind() {
+        let e: FeynmanError = "meh".parse::<ReviewOutcome>().unwrap_err().into();
+        assert!(matches!(&e, FeynmanError::InvalidOutcome(v) if v == "meh"));
+        assert_eq!(
+            e.to_string(),
+            "Invalid outcome 'meh'. Use: success, partial, or fail"
+        );
+
+        let e: FeynmanError = "chat".parse::<SessionType>().unwrap_err().into();
+        assert!(matches!(e, FeynmanError::Validation(_)));
+    }
+}
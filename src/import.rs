@@ -0,0 +1,794 @@
+// JSON import format and pre-import validation.
+//
+// IDs inside an import file are file-local: sessions point at topics and gaps
+// point at sessions by those IDs, and the importer remaps them to fresh rows.
+// Validation runs over the parsed structure before anything is written.
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::models::{ReviewOutcome, SessionOutcome, SessionType};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportData {
+    #[serde(default)]
+    pub topics: Vec<ImportTopic>,
+    #[serde(default)]
+    pub reviews: Vec<ImportReview>,
+    #[serde(default)]
+    pub sessions: Vec<ImportSession>,
+    #[serde(default)]
+    pub gaps: Vec<ImportGap>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportTopic {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub progress: Option<ImportProgress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub mastery_level: i32,
+    #[serde(default)]
+    pub times_reviewed: i32,
+    #[serde(default)]
+    pub times_succeeded: i32,
+    #[serde(default)]
+    pub last_reviewed: Option<String>,
+    #[serde(default)]
+    pub next_review: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReview {
+    pub topic_id: i64,
+    pub outcome: String,
+    pub reviewed_at: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSession {
+    pub id: i64,
+    pub topic_id: i64,
+    pub session_type: String,
+    pub started_at: String,
+    #[serde(default)]
+    pub ended_at: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportGap {
+    pub session_id: i64,
+    pub gap_description: String,
+    #[serde(default)]
+    pub addressed: bool,
+}
+
+// A single problem found during validation, pointing at the offending row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportProblem {
+    pub section: &'static str,
+    pub index: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ImportProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.section, self.index, self.message)
+    }
+}
+
+// Result of validation: the problems found plus which rows are safe to write.
+// Rows that depend on a rejected row (e.g. gaps of a rejected session) are
+// rejected too, so `--force` never writes a dangling reference.
+#[derive(Debug, Clone, Default)]
+pub struct ImportValidation {
+    pub problems: Vec<ImportProblem>,
+    pub skip_topics: HashSet<usize>,
+    pub skip_reviews: HashSet<usize>,
+    pub skip_sessions: HashSet<usize>,
+    pub skip_gaps: HashSet<usize>,
+}
+
+impl ImportValidation {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skip_topics.len()
+            + self.skip_reviews.len()
+            + self.skip_sessions.len()
+            + self.skip_gaps.len()
+    }
+
+    fn reject(&mut self, section: &'static str, index: usize, message: String) {
+        self.problems.push(ImportProblem {
+            section,
+            index,
+            message,
+        });
+        match section {
+            "topics" => self.skip_topics.insert(index),
+            "reviews" => self.skip_reviews.insert(index),
+            "sessions" => self.skip_sessions.insert(index),
+            _ => self.skip_gaps.insert(index),
+        };
+    }
+}
+
+impl ImportData {
+    // Check that every internal reference resolves and every enum string
+    // parses. `existing_names` are topic names already in the database.
+    pub fn validate(&self, existing_names: &HashSet<String>) -> ImportValidation {
+        let mut v = ImportValidation::default();
+
+        let mut topic_ids: HashSet<i64> = HashSet::new();
+        let mut names: HashSet<&str> = HashSet::new();
+        for (i, topic) in self.topics.iter().enumerate() {
+            let name = topic.name.trim();
+            if name.is_empty() {
+                v.reject("topics", i, "topic name is empty".to_string());
+            } else if existing_names.contains(name) {
+                v.reject("topics", i, format!("topic '{}' already exists", name));
+            } else if !names.insert(name) {
+                v.reject("topics", i, format!("duplicate topic name '{}'", name));
+            } else if topic_ids.contains(&topic.id) {
+                v.reject("topics", i, format!("duplicate topic id {}", topic.id));
+            } else if let Some(level) = topic.progress.as_ref().map(|p| p.mastery_level) {
+                if !(0..=5).contains(&level) {
+                    v.reject(
+                        "topics",
+                        i,
+                        format!("mastery_level {} is out of range 0-5", level),
+                    );
+                } else {
+                    topic_ids.insert(topic.id);
+                }
+            } else {
+                topic_ids.insert(topic.id);
+            }
+        }
+
+        for (i, review) in self.reviews.iter().enumerate() {
+            if !topic_ids.contains(&review.topic_id) {
+                v.reject("reviews", i, missing_topic(review.topic_id));
+            } else if let Err(e) = review.outcome.parse::<ReviewOutcome>() {
+                v.reject("reviews", i, e.to_string());
+            }
+        }
+
+        let mut session_ids: HashSet<i64> = HashSet::new();
+        for (i, session) in self.sessions.iter().enumerate() {
+            if !topic_ids.contains(&session.topic_id) {
+                v.reject("sessions", i, missing_topic(session.topic_id));
+            } else if let Err(e) = session.session_type.parse::<SessionType>() {
+                v.reject("sessions", i, e.to_string());
+            } else if let Some(Err(e)) =
+                session.outcome.as_deref().map(str::parse::<SessionOutcome>)
+            {
+                v.reject("sessions", i, e.to_string());
+            } else if !session_ids.insert(session.id) {
+                v.reject(
+                    "sessions",
+                    i,
+                    format!("duplicate session id {}", session.id),
+                );
+            }
+        }
+
+        for (i, gap) in self.gaps.iter().enumerate() {
+            if !session_ids.contains(&gap.session_id) {
+                v.reject(
+                    "gaps",
+                    i,
+                    format!(
+                        "session_id {} does not match any session in the file",
+                        gap.session_id
+                    ),
+                );
+            }
+        }
+
+        v
+    }
+}
+
+fn missing_topic(topic_id: i64) -> String {
+    format!("topic_id {} does not match any topic in the file", topic_id)
+}
+
+// One topic read by `topic add --stdin` or `topic import-json`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TopicLine {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+// Parse newline-delimited topics: each line is a name, optionally followed by
+// a tab, a description, another tab and comma-separated tags. Blank lines are
+// skipped; a line with fields but no name is an error naming its line number.
+pub fn parse_topic_lines(input: &str) -> Result<Vec<TopicLine>, String> {
+    let mut topics = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\t').map(str::trim);
+        let name = fields.next().unwrap_or_default();
+        if name.is_empty() {
+            return Err(format!("line {}: missing topic name", i + 1));
+        }
+        let description = fields.next().filter(|d| !d.is_empty());
+        let tags = fields
+            .next()
+            .map(|t| {
+                t.split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if fields.next().is_some() {
+            return Err(format!(
+                "line {}: expected at most 3 tab-separated fields (name, description, tags)",
+                i + 1
+            ));
+        }
+        topics.push(TopicLine {
+            name: name.to_string(),
+            description: description.map(String::from),
+            tags,
+        });
+    }
+    Ok(topics)
+}
+
+// Parse a JSON array of `{"name", "description", "tags"}` objects, trimming
+// each field. Unknown fields are ignored so other tools' output can be fed in.
+pub fn parse_topic_array(input: &str) -> Result<Vec<TopicLine>, String> {
+    let raw: Vec<TopicLine> =
+        serde_json::from_str(input).map_err(|e| format!("invalid topic array: {}", e))?;
+    raw.into_iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let name = t.name.trim();
+            if name.is_empty() {
+                return Err(format!("topics[{}]: missing topic name", i));
+            }
+            Ok(TopicLine {
+                name: name.to_string(),
+                description: t
+                    .description
+                    .map(|d| d.trim().to_string())
+                    .filter(|d| !d.is_empty()),
+                tags: t
+                    .tags
+                    .iter()
+                    .map(|t| t.trim())
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+// Names in `topics` that are already in `existing` or repeat an earlier
+// entry, in file order
+pub fn conflicting_names(topics: &[TopicLine], existing: &HashSet<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    topics
+        .iter()
+        .filter(|t| existing.contains(&t.name) || !seen.insert(t.name.as_str()))
+        .map(|t| t.name.clone())
+        .collect()
+}
+
+// Anki's collection tables exported as JSON (not the binary .apkg). Only the
+// columns feynman uses are read; anything else in the file is ignored.
+//
+//   {"notes":  [{"id": 1, "flds": "Ownership\u001f...", "tags": " rust memory "}],
+//    "cards":  [{"id": 10, "nid": 1}],
+//    "revlog": [{"id": 1704067200000, "cid": 10, "ease": 3}]}
+//
+// Fields and tags may also be given as arrays ("fields": [...], "tags": [...]).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnkiExport {
+    #[serde(default)]
+    pub notes: Vec<AnkiNote>,
+    #[serde(default)]
+    pub cards: Vec<AnkiCard>,
+    #[serde(default)]
+    pub revlog: Vec<AnkiReview>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnkiNote {
+    pub id: i64,
+    #[serde(alias = "flds")]
+    pub fields: AnkiList,
+    #[serde(default)]
+    pub tags: AnkiList,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnkiCard {
+    pub id: i64,
+    pub nid: i64,
+}
+
+// One answer: `id` is when it happened (ms since the epoch), `ease` the
+// button pressed
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnkiReview {
+    pub id: i64,
+    pub cid: i64,
+    pub ease: i32,
+}
+
+// Anki stores fields joined by 0x1f and tags joined by spaces
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AnkiList {
+    Items(Vec<String>),
+    Joined(String),
+}
+
+impl Default for AnkiList {
+    fn default() -> Self {
+        AnkiList::Items(Vec::new())
+    }
+}
+
+impl AnkiList {
+    fn fields(&self) -> Vec<&str> {
+        match self {
+            AnkiList::Items(items) => items.iter().map(String::as_str).collect(),
+            AnkiList::Joined(joined) => joined.split('\u{1f}').collect(),
+        }
+    }
+
+    fn tags(&self) -> Vec<String> {
+        match self {
+            AnkiList::Items(items) => items
+                .iter()
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect(),
+            AnkiList::Joined(joined) => joined.split_whitespace().map(String::from).collect(),
+        }
+    }
+}
+
+// Again fails, Hard is partial, Good and Easy succeed. Ease 0 marks a manual
+// reschedule rather than an answer, so it has no outcome.
+pub fn anki_ease_outcome(ease: i32) -> Option<ReviewOutcome> {
+    match ease {
+        1 => Some(ReviewOutcome::Fail),
+        2 => Some(ReviewOutcome::Partial),
+        3 | 4 => Some(ReviewOutcome::Success),
+        _ => None,
+    }
+}
+
+fn anki_ease_label(ease: i32) -> &'static str {
+    match ease {
+        1 => "Again",
+        2 => "Hard",
+        3 => "Good",
+        _ => "Easy",
+    }
+}
+
+// A field's text without its HTML: tags dropped, common entities decoded and
+// whitespace collapsed
+fn anki_text(field: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in field.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// An Anki export as feynman import rows, with what couldn't be mapped
+#[derive(Debug, Clone, Default)]
+pub struct AnkiMapping {
+    pub data: ImportData,
+    // Notes with an empty first field or a name already taken
+    pub skipped_notes: usize,
+    // Answers for skipped or unknown notes, or without an outcome
+    pub skipped_reviews: usize,
+}
+
+impl AnkiExport {
+    // Each note becomes a topic named after its first field, keeping its
+    // tags; each answer to one of its cards becomes a review. Notes whose
+    // name is in `existing_names` (or an earlier note's) are skipped.
+    pub fn to_import(&self, existing_names: &HashSet<String>) -> AnkiMapping {
+        let mut mapping = AnkiMapping::default();
+        let mut names: HashSet<String> = HashSet::new();
+        for note in &self.notes {
+            let name = note
+                .fields
+                .fields()
+                .first()
+                .map(|f| anki_text(f))
+                .unwrap_or_default();
+            if name.is_empty() || existing_names.contains(&name) || !names.insert(name.clone()) {
+                mapping.skipped_notes += 1;
+                continue;
+            }
+            mapping.data.topics.push(ImportTopic {
+                id: note.id,
+                name,
+                description: None,
+                tags: note.tags.tags(),
+                progress: None,
+            });
+        }
+
+        let topic_ids: HashSet<i64> = mapping.data.topics.iter().map(|t| t.id).collect();
+        let card_notes: HashMap<i64, i64> = self.cards.iter().map(|c| (c.id, c.nid)).collect();
+        for entry in &self.revlog {
+            let topic_id = card_notes
+                .get(&entry.cid)
+                .filter(|nid| topic_ids.contains(nid));
+            let outcome = anki_ease_outcome(entry.ease);
+            let reviewed_at = DateTime::from_timestamp_millis(entry.id);
+            let (Some(&topic_id), Some(outcome), Some(reviewed_at)) =
+                (topic_id, outcome, reviewed_at)
+            else {
+                mapping.skipped_reviews += 1;
+                continue;
+            };
+            mapping.data.reviews.push(ImportReview {
+                topic_id,
+                outcome: outcome.as_str().to_string(),
+                reviewed_at: reviewed_at.to_rfc3339(),
+                notes: Some(format!("Anki: {}", anki_ease_label(entry.ease))),
+            });
+        }
+        mapping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ImportData {
+        serde_json::from_str(json).expect("valid import JSON")
+    }
+
+    #[test]
+    fn valid_data_has_no_problems() {
+        let data = parse(
+            r#"{
+                "topics": [{"id": 1, "name": "Rust", "tags": ["rust"]}],
+                "reviews": [{"topic_id": 1, "outcome": "success", "reviewed_at": "2024-01-01T00:00:00Z"}],
+                "sessions": [{"id": 10, "topic_id": 1, "session_type": "feynman", "started_at": "2024-01-01T00:00:00Z", "outcome": "partial"}],
+                "gaps": [{"session_id": 10, "gap_description": "Lifetimes"}]
+            }"#,
+        );
+        let validation = data.validate(&HashSet::new());
+        assert!(validation.is_ok(), "{:?}", validation.problems);
+    }
+
+    #[test]
+    fn gap_with_unknown_session_is_rejected() {
+        let data = parse(
+            r#"{
+                "topics": [{"id": 1, "name": "Rust"}],
+                "sessions": [{"id": 10, "topic_id": 1, "session_type": "feynman", "started_at": "2024-01-01T00:00:00Z"}],
+                "gaps": [{"session_id": 99, "gap_description": "Orphan"}]
+            }"#,
+        );
+        let validation = data.validate(&HashSet::new());
+
+        assert_eq!(validation.problems.len(), 1);
+        assert_eq!(
+            validation.problems[0].to_string(),
+            "gaps[0]: session_id 99 does not match any session in the file"
+        );
+        assert!(validation.skip_gaps.contains(&0));
+        assert!(validation.skip_sessions.is_empty());
+    }
+
+    #[test]
+    fn session_with_unknown_topic_is_rejected() {
+        let data = parse(
+            r#"{
+                "sessions": [{"id": 10, "topic_id": 5, "session_type": "feynman", "started_at": "2024-01-01T00:00:00Z"}]
+            }"#,
+        );
+        let validation = data.validate(&HashSet::new());
+        assert_eq!(
+            validation.problems[0].to_string(),
+            "sessions[0]: topic_id 5 does not match any topic in the file"
+        );
+    }
+
+    #[test]
+    fn rejected_session_cascades_to_its_gaps() {
+        let data = parse(
+            r#"{
+                "topics": [{"id": 1, "name": "Rust"}],
+                "sessions": [{"id": 10, "topic_id": 1, "session_type": "lecture", "started_at": "2024-01-01T00:00:00Z"}],
+                "gaps": [{"session_id": 10, "gap_description": "Lifetimes"}]
+            }"#,
+        );
+        let validation = data.validate(&HashSet::new());
+        assert_eq!(validation.problems.len(), 2);
+        assert!(validation.skip_sessions.contains(&0));
+        assert!(validation.skip_gaps.contains(&0));
+    }
+
+    #[test]
+    fn unknown_enum_strings_are_rejected() {
+        let data = parse(
+            r#"{
+                "topics": [{"id": 1, "name": "Rust"}],
+                "reviews": [{"topic_id": 1, "outcome": "meh", "reviewed_at": "2024-01-01T00:00:00Z"}],
+                "sessions": [{"id": 10, "topic_id": 1, "session_type": "socratic", "started_at": "2024-01-01T00:00:00Z", "outcome": "great"}]
+            }"#,
+        );
+        let validation = data.validate(&HashSet::new());
+        let messages: Vec<String> = validation.problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "reviews[0]: unknown review outcome 'meh'",
+                "sessions[0]: unknown session outcome 'great'",
+            ]
+        );
+    }
+
+    #[test]
+    fn existing_and_duplicate_names_are_rejected() {
+        let data = parse(
+            r#"{
+                "topics": [
+                    {"id": 1, "name": "Rust"},
+                    {"id": 2, "name": "Go"},
+                    {"id": 3, "name": "Go"}
+                ]
+            }"#,
+        );
+        let existing: HashSet<String> = ["Rust".to_string()].into_iter().collect();
+        let validation = data.validate(&existing);
+        let messages: Vec<String> = validation.problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "topics[0]: topic 'Rust' already exists",
+                "topics[2]: duplicate topic name 'Go'",
+            ]
+        );
+    }
+
+    #[test]
+    fn topic_lines_take_optional_description_and_tags() {
+        let topics = parse_topic_lines(
+            "Ownership\n\nLifetimes\tHow long refs live\trust, memory\nTraits\t\trust\n",
+        )
+        .unwrap();
+        assert_eq!(
+            topics,
+            vec![
+                TopicLine {
+                    name: "Ownership".to_string(),
+                    description: None,
+                    tags: vec![],
+                },
+                TopicLine {
+                    name: "Lifetimes".to_string(),
+                    description: Some("How long refs live".to_string()),
+                    tags: vec!["rust".to_string(), "memory".to_string()],
+                },
+                TopicLine {
+                    name: "Traits".to_string(),
+                    description: None,
+                    tags: vec!["rust".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn topic_lines_report_bad_line_numbers() {
+        assert_eq!(
+            parse_topic_lines("Ownership\n\tno name\n").unwrap_err(),
+            "line 2: missing topic name"
+        );
+        assert!(parse_topic_lines("a\tb\tc\td")
+            .unwrap_err()
+            .starts_with("line 1:"));
+    }
+
+    #[test]
+    fn topic_array_trims_fields_and_ignores_unknown_keys() {
+        let topics = parse_topic_array(
+            r#"[
+                {"name": " Ownership ", "description": "  ", "tags": ["rust", " "]},
+                {"name": "Lifetimes", "description": "How long refs live", "source": "anki"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            topics,
+            vec![
+                TopicLine {
+                    name: "Ownership".to_string(),
+                    description: None,
+                    tags: vec!["rust".to_string()],
+                },
+                TopicLine {
+                    name: "Lifetimes".to_string(),
+                    description: Some("How long refs live".to_string()),
+                    tags: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn topic_array_rejects_missing_names() {
+        assert_eq!(
+            parse_topic_array(r#"[{"name": "A"}, {"name": " "}]"#).unwrap_err(),
+            "topics[1]: missing topic name"
+        );
+        assert!(parse_topic_array(r#"[{"description": "x"}]"#)
+            .unwrap_err()
+            .starts_with("invalid topic array:"));
+        assert!(parse_topic_array(r#"{"topics": []}"#).is_err());
+    }
+
+    // Two notes (one with joined fields and tags, one with arrays), a third
+    // with an empty front, a reverse card and one answer of every kind
+    const ANKI_FIXTURE: &str = r#"{
+        "notes": [
+            {"id": 1, "flds": "<b>Ownership</b>&nbsp;rules\u001fEach value has one owner", "tags": " rust memory "},
+            {"id": 2, "fields": ["Traits", "Shared behaviour"], "tags": ["rust::traits"]},
+            {"id": 3, "flds": "<br>\u001fNo front", "tags": ""}
+        ],
+        "cards": [
+            {"id": 10, "nid": 1},
+            {"id": 11, "nid": 1},
+            {"id": 20, "nid": 2},
+            {"id": 30, "nid": 3}
+        ],
+        "revlog": [
+            {"id": 1704067200000, "cid": 10, "ease": 1, "type": 0},
+            {"id": 1704153600000, "cid": 10, "ease": 2, "type": 1},
+            {"id": 1704240000000, "cid": 11, "ease": 3, "type": 1},
+            {"id": 1704326400000, "cid": 20, "ease": 4, "type": 1},
+            {"id": 1704412800000, "cid": 20, "ease": 0, "type": 4},
+            {"id": 1704499200000, "cid": 30, "ease": 3, "type": 1},
+            {"id": 1704585600000, "cid": 99, "ease": 3, "type": 1}
+        ]
+    }"#;
+
+    #[test]
+    fn ease_maps_to_review_outcomes() {
+        assert_eq!(anki_ease_outcome(1), Some(ReviewOutcome::Fail));
+        assert_eq!(anki_ease_outcome(2), Some(ReviewOutcome::Partial));
+        assert_eq!(anki_ease_outcome(3), Some(ReviewOutcome::Success));
+        assert_eq!(anki_ease_outcome(4), Some(ReviewOutcome::Success));
+        assert_eq!(anki_ease_outcome(0), None);
+    }
+
+    #[test]
+    fn anki_notes_become_topics_with_their_tags() {
+        let export: AnkiExport = serde_json::from_str(ANKI_FIXTURE).unwrap();
+        let mapping = export.to_import(&HashSet::new());
+
+        let topics: Vec<(&str, &[String])> = mapping
+            .data
+            .topics
+            .iter()
+            .map(|t| (t.name.as_str(), t.tags.as_slice()))
+            .collect();
+        assert_eq!(
+            topics,
+            [
+                (
+                    "Ownership rules",
+                    &["rust".to_string(), "memory".to_string()][..]
+                ),
+                ("Traits", &["rust::traits".to_string()][..]),
+            ]
+        );
+        assert_eq!(mapping.skipped_notes, 1);
+        assert!(mapping.data.validate(&HashSet::new()).is_ok());
+    }
+
+    #[test]
+    fn anki_answers_become_reviews() {
+        let export: AnkiExport = serde_json::from_str(ANKI_FIXTURE).unwrap();
+        let mapping = export.to_import(&HashSet::new());
+
+        let reviews: Vec<(i64, &str, &str)> = mapping
+            .data
+            .reviews
+            .iter()
+            .map(|r| (r.topic_id, r.outcome.as_str(), r.reviewed_at.as_str()))
+            .collect();
+        // Both cards of note 1 count towards its topic
+        assert_eq!(
+            reviews,
+            [
+                (1, "fail", "2024-01-01T00:00:00+00:00"),
+                (1, "partial", "2024-01-02T00:00:00+00:00"),
+                (1, "success", "2024-01-03T00:00:00+00:00"),
+                (2, "success", "2024-01-04T00:00:00+00:00"),
+            ]
+        );
+        assert_eq!(
+            mapping.data.reviews[0].notes.as_deref(),
+            Some("Anki: Again")
+        );
+        // The reschedule, the empty note's answer and the unknown card
+        assert_eq!(mapping.skipped_reviews, 3);
+    }
+
+    #[test]
+    fn anki_notes_already_imported_are_skipped() {
+        let export: AnkiExport = serde_json::from_str(ANKI_FIXTURE).unwrap();
+        let existing: HashSet<String> = ["Traits".to_string()].into();
+        let mapping = export.to_import(&existing);
+
+        assert_eq!(mapping.data.topics.len(), 1);
+        assert_eq!(mapping.skipped_notes, 2);
+        assert_eq!(mapping.data.reviews.len(), 3);
+        assert_eq!(mapping.skipped_reviews, 4);
+    }
+
+    #[test]
+    fn conflicting_names_covers_existing_and_repeated() {
+        let topics = parse_topic_array(
+            r#"[{"name": "Rust"}, {"name": "Go"}, {"name": "Zig"}, {"name": "Zig"}]"#,
+        )
+        .unwrap();
+        let existing: HashSet<String> = ["Go".to_string()].into();
+        assert_eq!(conflicting_names(&topics, &existing), ["Go", "Zig"]);
+    }
+}
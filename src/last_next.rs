@@ -0,0 +1,104 @@
+// The topic `next` most recently suggested, so `review last` can record
+// against it without copying the ID. Kept in a small file beside the database,
+// so each database has its own, and only trusted for MAX_AGE_MINUTES. Saving
+// is best-effort: a failed save just means `review last` has nothing to use.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error;
+
+// After this long the suggestion is stale and `review last` refuses it
+pub const MAX_AGE_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastNext {
+    pub topic_id: i64,
+    pub suggested_at: DateTime<Utc>,
+}
+
+// "<db file>.last-next.json"; None for an in-memory database
+pub fn path_for(db_path: &Path) -> Option<PathBuf> {
+    if db_path == Path::new(":memory:") {
+        return None;
+    }
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(".last-next.json");
+    Some(PathBuf::from(name))
+}
+
+impl LastNext {
+    pub fn new(topic_id: i64, suggested_at: DateTime<Utc>) -> Self {
+        Self {
+            topic_id,
+            suggested_at,
+        }
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn save(&self, path: &Path) -> error::Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    // The suggested topic, unless it's older than MAX_AGE_MINUTES
+    pub fn fresh_topic(&self, now: DateTime<Utc>) -> Result<i64, String> {
+        let age = now - self.suggested_at;
+        if age > Duration::minutes(MAX_AGE_MINUTES) {
+            return Err(format!(
+                "`next` last suggested topic {} {} minutes ago, which is too long to \
+                 assume; run `feynman next` again or give the ID",
+                self.topic_id,
+                age.num_minutes()
+            ));
+        }
+        Ok(self.topic_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn path_sits_beside_the_database() {
+        assert_eq!(
+            path_for(Path::new("/data/feynman.db")),
+            Some(PathBuf::from("/data/feynman.db.last-next.json"))
+        );
+        assert_eq!(path_for(Path::new(":memory:")), None);
+    }
+
+    #[test]
+    fn suggestions_expire() {
+        let last = LastNext::new(4, at("2024-03-01T09:00:00Z"));
+        assert_eq!(last.fresh_topic(at("2024-03-01T09:59:00Z")), Ok(4));
+        let e = last.fresh_topic(at("2024-03-01T10:01:00Z")).unwrap_err();
+        assert!(e.contains("61 minutes ago"), "{}", e);
+    }
+
+    #[test]
+    fn missing_or_invalid_file_loads_nothing() {
+        assert_eq!(
+            LastNext::load(Path::new("/nonexistent/last-next.json")),
+            None
+        );
+        let path = std::env::temp_dir().join(format!(
+            "feynman-last-next-invalid-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, "{\"topic_id\": ").unwrap();
+        assert_eq!(LastNext::load(&path), None);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,12 +1,41 @@
+mod clock;
+mod columns;
+mod config;
 mod db;
+mod error;
+mod import;
+mod last_next;
+mod metrics;
 mod models;
+mod prompt;
+mod scheduler;
+mod server;
+mod settings;
 mod tui;
 
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 
+use columns::{Columns, SESSION_COLUMNS, TOPIC_COLUMNS};
+use config::{Config, Timezone};
 use db::Database;
-use models::{JsonOutput, ReviewOutcome};
+use error::FeynmanError;
+use import::{AnkiExport, ImportData};
+use last_next::LastNext;
+use models::{
+    count_bar, format_duration, mastery_bar, mastery_label, mastery_trend, normalize_tag,
+    parse_timestamp, recommend_action, CalibrationReport, DueFilter, JsonOutput, NewTopicOptions,
+    OnConflict, Progress, RecentSession, ReviewOptions, ReviewOutcome, ReviewPage,
+    ScheduleReasoning, SelectionExplanation, SelectionStrategy, SessionOutcome, SessionQuery,
+    SessionRow, SessionType, SkillLevel, StatusCounts, StudySession, Topic, TopicQuery,
+    TopicResource, TopicSort, TopicWithProgress, DEFAULT_STUDY_GAP_MINUTES,
+};
+use tui::{pad_to_width, truncate};
 
 const DEFAULT_DB_NAME: &str = "feynman.db";
 
@@ -19,6 +48,16 @@ struct Cli {
     #[arg(long, global = true)]
     json: bool,
 
+    /// Answer yes to confirmation prompts, e.g. for scripts
+    #[arg(long, short, global = true)]
+    yes: bool,
+
+    /// Only these fields (comma-separated). Supported by `topic list` and
+    /// `sessions` (not `--implicit` or its subcommands); any other command
+    /// rejects it
+    #[arg(long, global = true, value_name = "FIELDS")]
+    columns: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -32,35 +71,469 @@ enum Commands {
     #[command(subcommand)]
     Topic(TopicCommands),
 
+    /// Manage learning sessions
+    #[command(subcommand)]
+    Session(SessionCommands),
+
+    /// Keep a running log of notes on a plan
+    #[command(subcommand)]
+    Plan(PlanCommands),
+
     /// List all tags
-    Tags,
+    Tags {
+        /// List the topics under each tag instead of counting them
+        #[arg(long)]
+        with_topics: bool,
+    },
+
+    /// Bulk tag operations across topics
+    #[command(subcommand)]
+    Tag(TagCommands),
+
+    /// List learning sessions, newest first, with their durations
+    #[command(args_conflicts_with_subcommands = true)]
+    Sessions {
+        /// Only sessions for this topic
+        #[arg(long)]
+        topic: Option<i64>,
+
+        /// Only sessions that haven't been ended
+        #[arg(long)]
+        active: bool,
+
+        /// Only sessions with this outcome
+        #[arg(long, short, ignore_case = true)]
+        outcome: Option<SessionOutcome>,
+
+        /// Group review history into study sessions by the pauses between reviews
+        #[arg(long, conflicts_with_all = ["topic", "active", "outcome"])]
+        implicit: bool,
+
+        /// With --implicit: longest pause in minutes within one study session
+        #[arg(long, requires = "implicit", default_value_t = DEFAULT_STUDY_GAP_MINUTES,
+              value_parser = clap::value_parser!(i64).range(1..))]
+        gap: i64,
+
+        #[command(subcommand)]
+        view: Option<SessionsView>,
+    },
 
     /// Show learning statistics
-    Stats,
+    Stats {
+        /// Compare self-rated confidence (review --confidence) with outcomes
+        #[arg(long)]
+        calibration: bool,
+
+        /// Show how many topics sit at each mastery level
+        #[arg(long, conflicts_with = "calibration")]
+        histogram: bool,
+
+        /// Print Prometheus text-format metrics, with per-tag gauges
+        #[arg(
+            long,
+            visible_alias = "export-prometheus",
+            conflicts_with_all = ["calibration", "histogram"]
+        )]
+        prometheus: bool,
+    },
+
+    /// Show a terse status summary (for shell prompts / status bars)
+    Status {
+        /// Print a single line: due, overdue, streak, avg
+        #[arg(long)]
+        compact: bool,
+    },
 
     /// Get next topic to review (stochastic selection)
     Next {
         /// Filter by tag
         #[arg(long, short)]
         tag: Option<String>,
+
+        /// Also start a learning session for the selected topic
+        #[arg(long, ignore_case = true)]
+        start_session: Option<SessionType>,
+
+        /// Show the selection weights behind the pick
+        #[arg(long)]
+        explain: bool,
+
+        /// How to choose among candidate topics [default: weighted, or the
+        /// config file's strategy]
+        #[arg(long, value_enum, ignore_case = true)]
+        strategy: Option<SelectionStrategy>,
+
+        /// Share of picks drawn from never-reviewed topics, 0.0-1.0; the rest
+        /// come from topics already in review. New topics stop once
+        /// selection.new_per_day have had their first review today
+        #[arg(long, value_parser = parse_ratio)]
+        new_ratio: Option<f64>,
     },
 
     /// Record a review outcome for a topic
     Review {
-        /// Topic ID
-        id: i64,
+        /// Topic ID, or "last" for the topic `next` most recently suggested
+        #[arg(value_parser = parse_review_target)]
+        id: ReviewTarget,
 
-        /// Review outcome: success/partial/fail
-        #[arg(long, short)]
-        outcome: String,
+        /// Review outcome (prompted for when omitted)
+        #[arg(long, short, ignore_case = true)]
+        outcome: Option<ReviewOutcome>,
 
         /// Optional notes about the review
         #[arg(long, short)]
         notes: Option<String>,
+
+        /// On fail/partial, make the topic due again immediately (for cramming)
+        #[arg(long)]
+        retry_now: bool,
+
+        /// Backfill a past review: when it happened (RFC 3339 or YYYY-MM-DD)
+        #[arg(long)]
+        at: Option<String>,
+
+        /// How confident you felt before checking, 1 (guessing) to 5 (certain)
+        #[arg(long, value_parser = parse_rating)]
+        confidence: Option<i32>,
+
+        /// Show how the scheduler picked the next review date
+        #[arg(long)]
+        explain: bool,
+    },
+
+    /// Recompute progress by replaying review history through the scheduler
+    RebuildProgress {
+        /// Only rebuild this topic (default: all topics)
+        topic_id: Option<i64>,
+    },
+
+    /// Recompute next review dates with the current schedule settings
+    Reschedule {
+        /// Only reschedule topics with this tag
+        #[arg(long, short)]
+        tag: Option<String>,
+    },
+
+    /// Delete reviews older than a date; progress is kept, but
+    /// rebuild-progress can no longer replay the deleted reviews
+    PruneHistory {
+        /// Delete reviews before this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        before: String,
+
+        /// Report how many reviews would be deleted, and stop
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List review history, oldest first
+    History {
+        /// Only reviews of this topic
+        topic_id: Option<i64>,
+
+        /// Only reviews with an ID greater than this
+        #[arg(long)]
+        after: Option<i64>,
+
+        /// Maximum number of reviews to return
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+
+    /// Import topics, reviews, sessions and gaps from a JSON file
+    Import {
+        /// Path to the JSON file
+        file: PathBuf,
+
+        /// Import despite validation problems, skipping the offending rows
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Import notes and review history from Anki's JSON collection export
+    /// (notes, cards and revlog; not .apkg). Each note becomes a topic named
+    /// after its first field, and its answers become reviews: Again fails,
+    /// Hard is partial, Good and Easy succeed
+    ImportAnki {
+        /// Path to the JSON export
+        file: PathBuf,
     },
 
     /// Launch interactive terminal UI
-    Tui,
+    Tui {
+        /// Show session outcomes as symbols (✓/~/✗/⊘) to save space
+        #[arg(long)]
+        compact_symbols: bool,
+
+        /// Number of due topics shown on the dashboard
+        #[arg(long, default_value_t = tui::DEFAULT_DASHBOARD_LIMIT)]
+        due_limit: usize,
+
+        /// Number of recent sessions shown on the dashboard
+        #[arg(long, default_value_t = tui::DEFAULT_DASHBOARD_LIMIT)]
+        recent_limit: usize,
+
+        /// Start on the Dashboard with no filter instead of where you left off
+        #[arg(long)]
+        no_restore: bool,
+    },
+
+    /// Render the TUI dashboard once as text, for sharing progress
+    Snapshot {
+        /// Write the snapshot to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Keep the dashboard's colors as ANSI escape codes
+        #[arg(long)]
+        ansi: bool,
+
+        /// Width in columns
+        #[arg(long, default_value_t = tui::DEFAULT_SNAPSHOT_WIDTH)]
+        width: u16,
+
+        /// Number of due topics shown
+        #[arg(long, default_value_t = tui::DEFAULT_DASHBOARD_LIMIT)]
+        due_limit: usize,
+
+        /// Number of recent sessions shown
+        #[arg(long, default_value_t = tui::DEFAULT_DASHBOARD_LIMIT)]
+        recent_limit: usize,
+    },
+
+    /// Show the review interval for each mastery level
+    Schedule,
+
+    /// View or change settings (e.g. grading.fail.delta)
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// Housekeeping: archive long-mastered topics (auto_archive.after_days)
+    /// and checkpoint the database's write-ahead log
+    Maintain,
+
+    /// Print the version; --verbose adds build and database details
+    Version {
+        /// Include the git commit, SQLite version, database path and schema version
+        #[arg(long, short)]
+        verbose: bool,
+    },
+
+    /// Check the database for inconsistencies, such as topics missing progress
+    /// or timestamps stored in mixed formats
+    Doctor {
+        /// Repair what was found
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Serve read-only JSON (/stats, /topics, /next, /due) over HTTP
+    Serve {
+        /// Port to listen on
+        #[arg(long, short, default_value_t = server::DEFAULT_PORT)]
+        port: u16,
+
+        /// Address to bind; the default only accepts local connections
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+
+    /// Print a shell completion script
+    #[command(hide = true)]
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionCommands {
+    /// End a session, recording its outcome and any knowledge gaps found
+    End {
+        /// Session ID
+        id: i64,
+
+        /// Session outcome
+        #[arg(long, short, ignore_case = true)]
+        outcome: SessionOutcome,
+
+        /// Short summary of the session
+        #[arg(long, short)]
+        summary: Option<String>,
+
+        /// Optional notes about the session
+        #[arg(long, short)]
+        notes: Option<String>,
+
+        /// A knowledge gap found during the session (repeatable)
+        #[arg(long = "gap")]
+        gaps: Vec<String>,
+
+        /// File holding the session's full explanation or dialogue
+        #[arg(long)]
+        transcript_file: Option<PathBuf>,
+    },
+
+    /// Start a session for every topic with a tag, printing the session IDs
+    StartBatch {
+        /// Tag whose topics to start sessions for
+        #[arg(long, short)]
+        tag: String,
+
+        /// Session type
+        #[arg(long = "type", ignore_case = true)]
+        session_type: SessionType,
+    },
+
+    /// End several active sessions with the same outcome
+    EndBatch {
+        /// Session outcome
+        #[arg(long, short, ignore_case = true)]
+        outcome: SessionOutcome,
+
+        /// Sessions to end, e.g. the IDs start-batch printed (comma-separated)
+        #[arg(long, value_delimiter = ',', required_unless_present = "since")]
+        ids: Vec<i64>,
+
+        /// Or: every active session started since (YYYY-MM-DD or RFC 3339)
+        #[arg(long, conflicts_with = "ids")]
+        since: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionsView {
+    /// Page through sessions newest first, with their topic names
+    Recent {
+        /// Number of sessions to show
+        #[arg(long, default_value_t = tui::DEFAULT_DASHBOARD_LIMIT)]
+        limit: usize,
+
+        /// Skip this many of the newest sessions first
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum PlanCommands {
+    /// Append a note to a plan
+    Note {
+        /// Plan ID
+        plan_id: i64,
+
+        /// Note text
+        content: String,
+    },
+
+    /// List a plan's notes, oldest first
+    Notes {
+        /// Plan ID
+        plan_id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Show every setting with its effective value
+    List,
+
+    /// Show one setting
+    Get {
+        /// Setting key
+        key: String,
+    },
+
+    /// Change a setting
+    Set {
+        /// Setting key
+        key: String,
+
+        /// New value
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+
+    /// Reset a setting to its default
+    Unset {
+        /// Setting key
+        key: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PromptCommands {
+    /// Add a question to answer when reviewing the topic
+    Add {
+        /// Topic ID
+        topic_id: i64,
+
+        /// The question, e.g. "Explain it to a five-year-old"
+        prompt: String,
+    },
+
+    /// List a topic's prompts in the order they're asked
+    List {
+        /// Topic ID
+        topic_id: i64,
+    },
+
+    /// Remove a prompt
+    Remove {
+        /// Prompt ID
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ResourceCommands {
+    /// Attach a link or reference to a topic
+    Add {
+        /// Topic ID
+        topic_id: i64,
+
+        /// Where it lives, e.g. https://doc.rust-lang.org/book/ or isbn:9781718503106
+        url: String,
+
+        /// What to show instead of the URL, e.g. "The Book, ch. 4"
+        #[arg(long, short)]
+        label: Option<String>,
+    },
+
+    /// List a topic's resources in the order they were added
+    List {
+        /// Topic ID
+        topic_id: i64,
+    },
+
+    /// Remove a resource
+    Remove {
+        /// Resource ID
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Add a tag to every topic that has the filter tag
+    Apply {
+        /// Tag to add
+        tag: String,
+
+        /// Only topics with this tag
+        #[arg(long)]
+        filter_tag: String,
+    },
+
+    /// Remove a tag from every topic that has the filter tag
+    Strip {
+        /// Tag to remove
+        tag: String,
+
+        /// Only topics with this tag
+        #[arg(long)]
+        filter_tag: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -70,12 +543,51 @@ enum TopicCommands {
         /// Filter by tag
         #[arg(long, short)]
         tag: Option<String>,
+
+        /// Include archived topics
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Include suspended topics
+        #[arg(long)]
+        include_suspended: bool,
+
+        /// Only topics due for review now
+        #[arg(long, conflicts_with = "overdue_only")]
+        due_only: bool,
+
+        /// Only topics that were already due before today
+        #[arg(long)]
+        overdue_only: bool,
+
+        /// Output format: table, or tree (topics grouped under each tag)
+        #[arg(long, default_value = "table", value_parser = ["table", "tree"])]
+        format: String,
+
+        /// Only topics with an ID greater than this (pages are ordered by ID)
+        #[arg(long)]
+        after: Option<i64>,
+
+        /// Maximum number of topics to return (pages are ordered by ID)
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Sort order; `health` puts the topics needing attention first and
+        /// adds a HEALTH column (default: name)
+        #[arg(long, value_enum, ignore_case = true, conflicts_with = "after")]
+        sort: Option<TopicSort>,
+
+        /// Also load progress: MASTERY and NEXT columns (slower on large
+        /// decks). JSON output always includes each topic's progress.
+        #[arg(long)]
+        detailed: bool,
     },
 
     /// Add a new topic
     Add {
         /// Topic name
-        name: String,
+        #[arg(required_unless_present = "stdin")]
+        name: Option<String>,
 
         /// Topic description
         #[arg(long, short)]
@@ -84,18 +596,106 @@ enum TopicCommands {
         /// Comma-separated tags
         #[arg(long, short)]
         tags: Option<String>,
+
+        /// Starting mastery (0-5, or new, learning, familiar, comfortable,
+        /// proficient, mastered) for a topic you already know; the first
+        /// review is scheduled by that level's interval
+        #[arg(long, value_parser = parse_mastery)]
+        mastery: Option<i32>,
+
+        /// Starting skill level (unknown, novice, beginner, intermediate,
+        /// advanced, expert or 0-5), recorded as a self-assessment
+        #[arg(long, value_parser = parse_skill)]
+        skill: Option<SkillLevel>,
+
+        /// Read topics from stdin, one per line: name[<TAB>description[<TAB>tags]].
+        /// Prints each new ID on its own line
+        #[arg(long, conflicts_with_all = ["name", "description", "tags", "mastery", "skill"])]
+        stdin: bool,
+    },
+
+    /// Add topics from a JSON array of {"name", "description", "tags"} objects
+    ImportJson {
+        /// JSON file to read
+        file: PathBuf,
+
+        /// What to do when a topic name already exists: fail (import nothing),
+        /// skip it, or update its description and add its tags
+        #[arg(long, value_enum, ignore_case = true, default_value = "fail")]
+        on_conflict: OnConflict,
     },
 
     /// Show topic details
     Show {
         /// Topic ID
         id: i64,
+
+        /// Also list the topic's learning sessions with their durations
+        #[arg(long)]
+        history: bool,
+
+        /// Print only the recommended next action
+        #[arg(long, conflicts_with = "history")]
+        next_action: bool,
     },
 
     /// Delete a topic
     Delete {
+        /// Topic ID
+        #[arg(required_unless_present = "tag")]
+        id: Option<i64>,
+
+        /// Also delete its reviews, sessions, gaps, assessments, prompts,
+        /// resources, tags and progress explicitly, and report how many of each were removed
+        #[arg(long)]
+        purge: bool,
+
+        /// Instead, delete every topic with this tag, along with its history
+        #[arg(long, conflicts_with_all = ["id", "purge"])]
+        tag: Option<String>,
+
+        /// With --tag: list the topics that would be deleted, and stop
+        #[arg(long, conflicts_with = "id")]
+        dry_run: bool,
+    },
+
+    /// Merge a duplicate topic into another, combining their history
+    Merge {
+        /// Topic to merge (deleted afterwards)
+        source_id: i64,
+
+        /// Topic to keep
+        target_id: i64,
+    },
+
+    /// Archive a topic (hidden from lists and never scheduled)
+    Archive {
+        /// Topic ID
+        id: i64,
+
+        /// Restore an archived topic
+        #[arg(long)]
+        undo: bool,
+    },
+
+    /// Suspend a topic (temporarily excluded from reviews)
+    Suspend {
+        /// Topic ID
+        id: i64,
+
+        /// Resume a suspended topic
+        #[arg(long)]
+        undo: bool,
+    },
+
+    /// Set topic difficulty: 1 (easy) to 5 (hard); harder topics are reviewed more often
+    Difficulty {
         /// Topic ID
         id: i64,
+
+        /// Difficulty from 1 to 5 (default 3)
+        #[arg(value_parser = parse_rating)]
+        level: i32,
     },
 
     /// Update topic tags
@@ -107,12 +707,25 @@ enum TopicCommands {
         #[arg(long, short)]
         tags: String,
     },
+
+    /// Manage the questions asked when reviewing a topic; each review moves
+    /// on to the next one
+    #[command(subcommand)]
+    Prompt(PromptCommands),
+
+    /// Manage links and references for a topic (opened with `o` in the TUI)
+    #[command(subcommand)]
+    Resource(ResourceCommands),
 }
 
-fn get_db_path() -> PathBuf {
+// FEYNMAN_DB, then the config file's db_path, then the platform config dir
+fn get_db_path(config: &Config) -> PathBuf {
     if let Ok(path) = std::env::var("FEYNMAN_DB") {
         return PathBuf::from(path);
     }
+    if let Some(path) = &config.db_path {
+        return path.clone();
+    }
 
     let config_dir = dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -122,18 +735,96 @@ fn get_db_path() -> PathBuf {
     config_dir.join(DEFAULT_DB_NAME)
 }
 
+// Exit status when the topic, session or plan asked for doesn't exist, so
+// scripts can tell it apart from other failures (1) and bad arguments (2,
+// clap's usage error)
+const EXIT_NOT_FOUND: i32 = 3;
+
 fn main() {
     let cli = Cli::parse();
+    let json = cli.json;
 
     if let Err(e) = run(cli) {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+        match &e {
+            // The envelope still goes to stdout so scripts can parse it
+            FeynmanError::NotFound(_) if json => println!("{}", not_found_envelope(&e)),
+            FeynmanError::NotFound(_) => eprintln!("{}.", e),
+            _ => eprintln!("Error: {}", e),
+        }
+        std::process::exit(exit_code(&e));
+    }
+}
+
+fn exit_code(e: &FeynmanError) -> i32 {
+    match e {
+        FeynmanError::NotFound(_) => EXIT_NOT_FOUND,
+        _ => 1,
     }
 }
 
-fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let db_path = get_db_path();
+fn not_found_envelope(not_found: &FeynmanError) -> String {
+    serde_json::to_string(&JsonOutput::<()>::not_found(not_found.to_string()))
+        .expect("an error envelope always serializes")
+}
+
+fn run(cli: Cli) -> error::Result<()> {
+    // Completions don't touch the database
+    if let Commands::Completions { shell } = cli.command {
+        write_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    let config = Config::load(&config::config_path())?;
+    let db_path = get_db_path(&config);
+
+    // The server never writes, so it gets a read-only connection
+    if let Commands::Serve { port, host } = &cli.command {
+        let db = Database::open_read_only(&db_path)?;
+        return server::serve(&db, host, *port);
+    }
+
     let db = Database::open(&db_path)?;
+    report_auto_pruned(db.prune_expired_history()?);
+    // `maintain` archives and reports them itself. `config` shouldn't act on
+    // the value it may be about to change, and the TUI's alternate screen
+    // would hide the report.
+    if !matches!(
+        cli.command,
+        Commands::Maintain | Commands::Config(_) | Commands::Tui { .. }
+    ) {
+        report_auto_archived(&db.auto_archive_mastered()?);
+    }
+    run_command(cli, db, &config, &db_path)
+}
+
+// On stderr, like report_auto_archived
+fn report_auto_pruned(deleted: usize) {
+    if deleted > 0 {
+        eprintln!(
+            "Pruned {} review(s) older than history.retention_days",
+            deleted
+        );
+    }
+}
+
+// On stderr, so it never mixes into a command's (possibly JSON) output
+fn report_auto_archived(archived: &[(i64, String)]) {
+    if archived.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = archived.iter().map(|(_, name)| name.as_str()).collect();
+    eprintln!(
+        "Auto-archived {} long-mastered topic(s): {}",
+        archived.len(),
+        names.join(", ")
+    );
+}
+
+// Every command that works on an open database
+fn run_command(cli: Cli, db: Database, config: &Config, db_path: &Path) -> error::Result<()> {
+    if cli.columns.is_some() && !supports_columns(&cli.command) {
+        return Err("--columns only applies to `topic list` and `sessions` (without --implicit or a subcommand)".into());
+    }
 
     match cli.command {
         Commands::Init => {
@@ -146,62 +837,373 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         }
 
         Commands::Topic(topic_cmd) => match topic_cmd {
-            TopicCommands::List { tag } => {
-                let topics = db.list_topics(tag.as_deref())?;
-                if cli.json {
-                    println!("{}", serde_json::to_string(&JsonOutput::ok(&topics))?);
+            TopicCommands::List {
+                tag,
+                include_archived,
+                include_suspended,
+                due_only,
+                overdue_only,
+                format,
+                after,
+                limit,
+                sort,
+                detailed,
+            } => {
+                let paginated = after.is_some() || (limit.is_some() && sort.is_none());
+                let sort = sort.unwrap_or(if paginated {
+                    TopicSort::Id
+                } else {
+                    TopicSort::Name
+                });
+                let due = if overdue_only {
+                    DueFilter::Overdue
+                } else if due_only {
+                    DueFilter::Due
+                } else {
+                    DueFilter::Any
+                };
+                let query = TopicQuery {
+                    tag,
+                    include_archived,
+                    include_suspended,
+                    due,
+                    sort,
+                    after,
+                    limit,
+                };
+                // Names and tags alone skip loading progress unless it's
+                // asked for, needed to sort or listed as JSON
+                let columns = cli
+                    .columns
+                    .as_deref()
+                    .map(|spec| Columns::parse(spec, TOPIC_COLUMNS))
+                    .transpose()?;
+                if columns.is_some() && format == "tree" {
+                    return Err("--columns can't be combined with --format tree".into());
+                }
+                // Due listings also estimate how much is still retained
+                let wants_retention = due != DueFilter::Any && format == "table";
+                let wants_progress = detailed
+                    || wants_retention
+                    || columns.is_some()
+                    || (cli.json && format == "table");
+                let (topics, progress): (Vec<Topic>, Option<Vec<Progress>>) =
+                    if wants_progress || !matches!(sort, TopicSort::Name | TopicSort::Id) {
+                        let (topics, progress) = db
+                            .get_topics_with_progress(&query)?
+                            .into_iter()
+                            .map(|twp| (twp.topic, twp.progress))
+                            .unzip();
+                        (topics, Some(progress))
+                    } else {
+                        (db.get_topics(&query)?, None)
+                    };
+                let health: Option<Vec<f64>> = match &progress {
+                    Some(progress) if sort == TopicSort::Health => {
+                        let (now, weights) = (Utc::now(), db.settings()?.health);
+                        Some(
+                            progress
+                                .iter()
+                                .map(|p| p.health_score_with(now, &weights))
+                                .collect(),
+                        )
+                    }
+                    _ => None,
+                };
+                // None for topics never reviewed, which have nothing to forget
+                let retention: Option<Vec<Option<f64>>> = progress
+                    .as_ref()
+                    .filter(|_| wants_retention)
+                    .map(|progress| {
+                        let now = Utc::now();
+                        progress
+                            .iter()
+                            .map(|p| p.estimated_retention(now))
+                            .collect()
+                    });
+                let progress_cells = progress.as_ref().filter(|_| detailed);
+                let last_id = topics.last().map(|t| t.id);
+                if format == "tree" {
+                    let groups = group_topics_by_tag(&topics);
+                    if cli.json {
+                        let map: serde_json::Map<String, serde_json::Value> = groups
+                            .into_iter()
+                            .map(|(tag, topics)| Ok((tag, serde_json::to_value(topics)?)))
+                            .collect::<serde_json::Result<_>>()?;
+                        println!("{}", serde_json::to_string(&JsonOutput::ok(map))?);
+                    } else if groups.is_empty() {
+                        println!("No topics found.");
+                    } else {
+                        for (tag, topics) in groups {
+                            println!("{} ({})", tag, topics.len());
+                            for topic in topics {
+                                println!("  {:<5} {}", topic.id, topic_list_name(topic));
+                            }
+                        }
+                    }
+                } else if let Some(columns) = &columns {
+                    let rows = topics
+                        .iter()
+                        .zip(progress.as_deref().unwrap_or(&[]))
+                        .map(|(topic, progress)| columns::topic_row(topic, progress))
+                        .collect::<serde_json::Result<Vec<_>>>()?;
+                    if cli.json {
+                        let data = columns_json(columns, &rows, paginated.then_some(last_id));
+                        println!("{}", serde_json::to_string(&JsonOutput::ok(data))?);
+                    } else if rows.is_empty() {
+                        println!("No topics found.");
+                    } else {
+                        columns.table(&rows).iter().for_each(|l| println!("{}", l));
+                        if let (true, Some(id)) = (paginated, last_id) {
+                            println!();
+                            println!("Next page: --after {}", id);
+                        }
+                    }
+                } else if cli.json {
+                    let data = topic_list_json(
+                        &topics,
+                        progress.as_deref().unwrap_or(&[]),
+                        retention.as_deref(),
+                        paginated,
+                    )?;
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(data))?);
                 } else if topics.is_empty() {
                     println!("No topics found.");
                 } else {
-                    println!("{:<5} {:<40} TAGS", "ID", "NAME");
-                    println!("{}", "-".repeat(70));
-                    for topic in topics {
+                    // ID, the tags column, HEALTH, RECALL and MASTERY/NEXT if
+                    // shown and the gaps between them
+                    let health_width = if health.is_some() { 7 } else { 0 };
+                    let retention_width = if retention.is_some() { 7 } else { 0 };
+                    let progress_width = if progress_cells.is_some() { 16 } else { 0 };
+                    let fixed = 5
+                        + 1
+                        + 1
+                        + TAGS_COLUMN_WIDTH
+                        + health_width
+                        + retention_width
+                        + progress_width;
+                    let width = name_column_width(terminal_width(), fixed);
+                    let health_header = if health.is_some() { " HEALTH" } else { "" };
+                    let retention_header = if retention.is_some() { " RECALL" } else { "" };
+                    let progress_header = if progress_cells.is_some() {
+                        " MASTERY NEXT    "
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "{:<5} {:<width$}{}{}{} TAGS",
+                        "ID", "NAME", health_header, retention_header, progress_header
+                    );
+                    println!("{}", "-".repeat(fixed + width));
+                    for (i, topic) in topics.iter().enumerate() {
                         let tags = if topic.tags.is_empty() {
                             String::from("-")
                         } else {
                             topic.tags.join(", ")
                         };
-                        println!("{:<5} {:<40} {}", topic.id, truncate(&topic.name, 38), tags);
+                        let name = topic_list_name(topic);
+                        let health_cell = health
+                            .as_ref()
+                            .map(|h| format!(" {:>6.0}", h[i]))
+                            .unwrap_or_default();
+                        let retention_cell = retention
+                            .as_ref()
+                            .map(|r| {
+                                let cell = r[i].map_or_else(|| "-".to_string(), retention_cell);
+                                format!(" {:>6}", cell)
+                            })
+                            .unwrap_or_default();
+                        let progress_cell = progress_cells
+                            .map(|p| {
+                                let next = p[i].next_review_dt().map_or_else(
+                                    || "-".to_string(),
+                                    |d| d.format("%b %d").to_string(),
+                                );
+                                format!(" {:<7} {:<7}", mastery_bar(p[i].mastery_level), next)
+                            })
+                            .unwrap_or_default();
+                        println!(
+                            "{:<5} {}{}{}{} {}",
+                            topic.id,
+                            name_cell(&name, width),
+                            health_cell,
+                            retention_cell,
+                            progress_cell,
+                            tags
+                        );
+                    }
+                    if let (true, Some(id)) = (paginated, last_id) {
+                        println!();
+                        println!("Next page: --after {}", id);
                     }
                 }
             }
 
-            TopicCommands::Add {
-                name,
-                description,
-                tags,
-            } => {
-                let tag_list: Vec<String> = tags
-                    .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
-                    .unwrap_or_default();
-
-                let id = db.add_topic(&name, description.as_deref(), &tag_list)?;
+            TopicCommands::Add { stdin: true, .. } => {
+                let mut input = String::new();
+                std::io::stdin().read_to_string(&mut input)?;
+                let (ids, warnings) = if cli.json {
+                    add_topic_lines(&db, &input, &mut std::io::sink())?
+                } else {
+                    add_topic_lines(&db, &input, &mut std::io::stdout().lock())?
+                };
 
                 if cli.json {
                     println!(
                         "{}",
                         serde_json::to_string(&JsonOutput::ok(serde_json::json!({
-                            "id": id,
-                            "name": name
+                            "ids": ids,
+                            "warnings": warnings
+                        })))?
+                    );
+                } else {
+                    for warning in &warnings {
+                        eprintln!("Warning: {}", warning);
+                    }
+                    eprintln!("Added {} topic(s).", ids.len());
+                }
+            }
+
+            TopicCommands::ImportJson { file, on_conflict } => {
+                let content = std::fs::read_to_string(&file)?;
+                let topics = import::parse_topic_array(&content)?;
+
+                if on_conflict == OnConflict::Fail {
+                    let existing: HashSet<String> =
+                        db.list_topics(None)?.into_iter().map(|t| t.name).collect();
+                    let conflicts = import::conflicting_names(&topics, &existing);
+                    if !conflicts.is_empty() {
+                        return Err(format!(
+                            "Import aborted: topic name(s) already taken: {} (use --on-conflict skip or update)",
+                            conflicts.join(", ")
+                        )
+                        .into());
+                    }
+                }
+
+                let summary = db.import_topic_lines(&topics, on_conflict)?;
+
+                if cli.json {
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(&summary))?);
+                } else {
+                    println!(
+                        "Added {} topic(s), updated {}, skipped {}.",
+                        summary.added, summary.updated, summary.skipped
+                    );
+                }
+            }
+
+            TopicCommands::Add {
+                name,
+                description,
+                tags,
+                mastery,
+                skill,
+                ..
+            } => {
+                let name = name.ok_or("A topic name is required (or use --stdin)")?;
+                let tag_list: Vec<String> = tags
+                    .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
+                    .unwrap_or_default();
+
+                let warnings = near_duplicate_tag_warnings(&db, &tag_list)?;
+                let options = NewTopicOptions {
+                    mastery: mastery.unwrap_or(0),
+                    skill,
+                };
+                let id = db.add_topic_with(&name, description.as_deref(), &tag_list, &options)?;
+
+                if cli.json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                            "id": id,
+                            "name": name,
+                            "warnings": warnings
                         })))?
                     );
                 } else {
                     println!("Added topic '{}' with ID: {}", name, id);
+                    for warning in &warnings {
+                        println!("Warning: {}", warning);
+                    }
                 }
             }
 
-            TopicCommands::Show { id } => {
+            TopicCommands::Show {
+                id,
+                history,
+                next_action,
+            } => {
                 if let Some(topic) = db.get_topic(id)? {
                     let progress = db.get_progress(id)?;
+                    let gaps = db.get_unaddressed_gaps(id)?;
+                    let recommendation = progress.clone().map(|progress| {
+                        let twp = TopicWithProgress {
+                            topic: topic.clone(),
+                            progress,
+                        };
+                        recommend_action(&twp, &gaps, Utc::now())
+                    });
+                    let recommendation_json = recommendation.as_ref().map(
+                        |r| serde_json::json!({ "action": r.as_str(), "message": r.to_string() }),
+                    );
+                    if next_action {
+                        if cli.json {
+                            println!(
+                                "{}",
+                                serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                                    "topic_id": id,
+                                    "next_action": recommendation_json
+                                })))?
+                            );
+                        } else {
+                            match &recommendation {
+                                Some(r) => println!("{}", r),
+                                None => println!(
+                                    "No progress yet. Run `feynman doctor --fix` to start it."
+                                ),
+                            }
+                        }
+                        return Ok(());
+                    }
+                    let warning = progress.as_ref().and_then(next_review_warning);
+                    let settings = db.settings()?;
+                    let projected_mastery = progress
+                        .as_ref()
+                        .and_then(|p| p.projected_mastery_date(&settings, chrono::Utc::now()));
+                    // Only an estimate once there's a review to decay from
+                    let retention = progress
+                        .as_ref()
+                        .and_then(|p| p.estimated_retention(Utc::now()));
+                    let resources = db.get_resources(id)?;
+                    let (sessions, trajectory): (Vec<SessionRow>, Vec<i32>) = if history {
+                        (
+                            db.list_sessions(Some(id))?
+                                .into_iter()
+                                .map(SessionRow::from)
+                                .collect(),
+                            db.get_mastery_trajectory(id)?,
+                        )
+                    } else {
+                        (Vec::new(), Vec::new())
+                    };
 
                     if cli.json {
-                        println!(
-                            "{}",
-                            serde_json::to_string(&JsonOutput::ok(serde_json::json!({
-                                "topic": topic,
-                                "progress": progress
-                            })))?
-                        );
+                        let mut data = serde_json::json!({
+                            "topic": topic,
+                            "progress": progress,
+                            "projected_mastery": projected_mastery,
+                            "estimated_retention": retention.map(round_retention),
+                            "resources": resources,
+                            "next_action": recommendation_json,
+                            "warnings": warning.iter().collect::<Vec<_>>()
+                        });
+                        if history {
+                            data["sessions"] = serde_json::to_value(&sessions)?;
+                            data["mastery_trajectory"] = serde_json::to_value(&trajectory)?;
+                        }
+                        println!("{}", serde_json::to_string(&JsonOutput::ok(data))?);
                     } else {
                         println!("Topic: {}", topic.name);
                         println!("ID: {}", topic.id);
@@ -217,11 +1219,15 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                             }
                         );
                         println!("Created: {}", topic.created_at);
+                        if let Some(r) = &recommendation {
+                            println!("Next action: {}", r);
+                        }
 
                         if let Some(p) = progress {
                             println!();
                             println!("--- Progress ---");
                             println!("Mastery: {} (level {})", p.mastery_label(), p.mastery_level);
+                            println!("Difficulty: {}/5", p.difficulty);
                             println!(
                                 "Reviews: {} ({:.0}% success rate)",
                                 p.times_reviewed,
@@ -233,467 +1239,4153 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                             if let Some(next) = &p.next_review {
                                 println!("Next review: {}", next);
                             }
+                            if let Some(r) = retention {
+                                println!(
+                                    "Retention: {} (forgetting-curve estimate)",
+                                    retention_cell(r)
+                                );
+                            }
+                            if let Some(date) = &projected_mastery {
+                                println!("Projected mastery: ~{} if every review succeeds", date);
+                            }
+                        }
+                        if !resources.is_empty() {
+                            println!();
+                            println!("--- Resources ---");
+                            for resource in &resources {
+                                println!("{}", resource_line(resource));
+                            }
+                        }
+                        if history {
+                            println!();
+                            println!("--- Mastery Trend ---");
+                            if trajectory.is_empty() {
+                                println!("No reviews yet.");
+                            } else {
+                                println!(
+                                    "{} (after each review, oldest first)",
+                                    mastery_trend(&trajectory, trajectory.len())
+                                );
+                            }
+                            println!();
+                            println!("--- Sessions ---");
+                            if sessions.is_empty() {
+                                println!("No sessions yet.");
+                            } else {
+                                print_sessions(&sessions, None);
+                            }
+                        }
+                        if let Some(warning) = &warning {
+                            println!();
+                            println!("Warning: {}", warning);
                         }
                     }
+                } else {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+            }
+
+            TopicCommands::Delete {
+                tag: Some(tag),
+                dry_run,
+                ..
+            } => {
+                let topics = db.topics_with_tag(&tag)?;
+                let confirmed = if dry_run || topics.is_empty() || cli.yes {
+                    true
                 } else if cli.json {
+                    return Err("--yes or --dry-run is required with --json".into());
+                } else {
+                    println!("Topics tagged '{}':", tag);
+                    for topic in &topics {
+                        println!("  {:>4}  {}", topic.id, topic.name);
+                    }
+                    let question =
+                        format!("Delete these {} topic(s) and their history?", topics.len());
+                    prompt::confirm(&question, false, cli.yes)?
+                };
+
+                let deleted = if dry_run || !confirmed {
+                    0
+                } else {
+                    db.delete_topics_by_tag(&tag)?
+                };
+                if cli.json {
+                    let ids: Vec<i64> = topics.iter().map(|t| t.id).collect();
                     println!(
                         "{}",
-                        serde_json::to_string(&JsonOutput::<()>::err("Topic not found"))?
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                            "tag": tag,
+                            "dry_run": dry_run,
+                            "topic_ids": ids,
+                            "deleted": deleted
+                        })))?
                     );
+                } else if topics.is_empty() {
+                    println!("No topics tagged '{}'.", tag);
+                } else if dry_run {
+                    println!("Would delete {} topic(s) tagged '{}':", topics.len(), tag);
+                    for topic in &topics {
+                        println!("  {:>4}  {}", topic.id, topic.name);
+                    }
+                } else if !confirmed {
+                    println!("Nothing deleted.");
                 } else {
-                    println!("Topic not found.");
+                    println!("Deleted {} topic(s) tagged '{}'.", deleted, tag);
                 }
             }
 
-            TopicCommands::Delete { id } => {
+            TopicCommands::Delete {
+                id: Some(id),
+                purge: true,
+                ..
+            } => {
+                let topic = db.get_topic(id)?.ok_or(FeynmanError::NotFound("Topic"))?;
+                let question = format!(
+                    "Delete topic {} ({}) and everything recorded against it?",
+                    id, topic.name
+                );
+                if !confirm_destructive(&question, cli.yes, cli.json)? {
+                    println!("Nothing deleted.");
+                    return Ok(());
+                }
+                if let Some(summary) = db.purge_topic(id)? {
+                    if cli.json {
+                        println!("{}", serde_json::to_string(&JsonOutput::ok(&summary))?);
+                    } else {
+                        println!("Topic {} deleted. Removed:", id);
+                        for (count, what) in [
+                            (summary.reviews, "review(s)"),
+                            (summary.sessions, "session(s)"),
+                            (summary.gaps, "gap(s)"),
+                            (summary.assessments, "skill assessment(s)"),
+                            (summary.prompts, "prompt(s)"),
+                            (summary.resources, "resource(s)"),
+                            (summary.tags, "tag link(s)"),
+                            (summary.progress, "progress row(s)"),
+                        ] {
+                            println!("  {} {}", count, what);
+                        }
+                    }
+                } else {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+            }
+
+            TopicCommands::Delete { id: Some(id), .. } => {
+                let topic = db.get_topic(id)?.ok_or(FeynmanError::NotFound("Topic"))?;
+                let question = format!("Delete topic {} ({})?", id, topic.name);
+                if !confirm_destructive(&question, cli.yes, cli.json)? {
+                    println!("Nothing deleted.");
+                    return Ok(());
+                }
                 if db.delete_topic(id)? {
                     if cli.json {
                         println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
                     } else {
                         println!("Topic {} deleted.", id);
                     }
-                } else if cli.json {
-                    println!(
-                        "{}",
-                        serde_json::to_string(&JsonOutput::<()>::err("Topic not found"))?
-                    );
                 } else {
-                    println!("Topic not found.");
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+            }
+
+            TopicCommands::Delete { id: None, .. } => {
+                unreachable!("clap requires an id unless --tag is given")
+            }
+
+            TopicCommands::Merge {
+                source_id,
+                target_id,
+            } => {
+                if source_id == target_id {
+                    return Err("Cannot merge a topic into itself".into());
+                }
+                let (Some(source), Some(target)) =
+                    (db.get_topic(source_id)?, db.get_topic(target_id)?)
+                else {
+                    return Err(FeynmanError::NotFound("Topic"));
+                };
+                let question = format!(
+                    "Merge topic {} ({}) into {} ({}) and delete {}?",
+                    source_id, source.name, target_id, target.name, source_id
+                );
+                if !confirm_destructive(&question, cli.yes, cli.json)? {
+                    println!("Nothing merged.");
+                    return Ok(());
+                }
+
+                if let Some(summary) = db.merge_topics(source_id, target_id)? {
+                    if cli.json {
+                        println!("{}", serde_json::to_string(&JsonOutput::ok(&summary))?);
+                    } else {
+                        println!(
+                            "Merged topic {} into {}: moved {} review(s), {} session(s), {} assessment(s), {} prompt(s), {} resource(s) and {} tag(s).",
+                            source_id,
+                            target_id,
+                            summary.reviews,
+                            summary.sessions,
+                            summary.assessments,
+                            summary.prompts,
+                            summary.resources,
+                            summary.tags
+                        );
+                    }
+                } else {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+            }
+
+            TopicCommands::Archive { id, undo } => {
+                if db.set_topic_archived(id, !undo)? {
+                    if cli.json {
+                        println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+                    } else if undo {
+                        println!("Topic {} restored.", id);
+                    } else {
+                        println!("Topic {} archived.", id);
+                    }
+                } else {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+            }
+
+            TopicCommands::Suspend { id, undo } => {
+                if db.set_topic_suspended(id, !undo)? {
+                    if cli.json {
+                        println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+                    } else if undo {
+                        println!("Topic {} resumed.", id);
+                    } else {
+                        println!("Topic {} suspended.", id);
+                    }
+                } else {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+            }
+
+            TopicCommands::Difficulty { id, level } => {
+                if db.set_topic_difficulty(id, level)? {
+                    if cli.json {
+                        println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+                    } else {
+                        println!("Topic {} difficulty set to {}.", id, level);
+                    }
+                } else {
+                    return Err(FeynmanError::NotFound("Topic"));
                 }
             }
 
             TopicCommands::Tag { id, tags } => {
+                if db.get_topic(id)?.is_none() {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
                 let tag_list: Vec<String> = tags.split(',').map(|s| s.trim().to_string()).collect();
+                let warnings = near_duplicate_tag_warnings(&db, &tag_list)?;
                 db.update_topic_tags(id, &tag_list)?;
 
                 if cli.json {
-                    println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+                    println!(
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                            "warnings": warnings
+                        })))?
+                    );
                 } else {
                     println!("Updated tags for topic {}.", id);
+                    for warning in &warnings {
+                        println!("Warning: {}", warning);
+                    }
                 }
             }
-        },
 
-        Commands::Tags => {
-            let tags = db.list_tags()?;
-            if cli.json {
-                println!("{}", serde_json::to_string(&JsonOutput::ok(&tags))?);
-            } else if tags.is_empty() {
-                println!("No tags found.");
-            } else {
-                println!("{:<5} {:<30} TOPICS", "ID", "TAG");
-                println!("{}", "-".repeat(50));
-                for tag in tags {
-                    println!("{:<5} {:<30} {}", tag.id, tag.name, tag.topic_count);
+            TopicCommands::Prompt(PromptCommands::Add { topic_id, prompt }) => {
+                if db.get_topic(topic_id)?.is_none() {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+                let prompt = prompt.trim();
+                if prompt.is_empty() {
+                    return Err("Prompt can't be empty".into());
+                }
+                let id = db.add_prompt(topic_id, prompt)?;
+                if cli.json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({ "id": id })))?
+                    );
+                } else {
+                    println!("Added prompt {} to topic {}.", id, topic_id);
                 }
             }
-        }
 
-        Commands::Stats => {
-            let stats = db.get_stats()?;
-            if cli.json {
-                println!(
-                    "{}",
-                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
-                        "total_topics": stats.total_topics,
-                        "total_reviews": stats.total_reviews,
-                        "mastered": stats.mastered,
-                        "due_now": stats.due_now,
-                        "avg_mastery": stats.avg_mastery
-                    })))?
-                );
-            } else {
-                println!("=== Learning Statistics ===");
-                println!("Total topics: {}", stats.total_topics);
-                println!("Total reviews: {}", stats.total_reviews);
-                println!("Mastered (level 4+): {}", stats.mastered);
-                println!("Due for review: {}", stats.due_now);
-                println!("Average mastery: {:.1}/5", stats.avg_mastery);
+            TopicCommands::Prompt(PromptCommands::List { topic_id }) => {
+                if db.get_topic(topic_id)?.is_none() {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+                let prompts = db.get_prompts(topic_id)?;
+                if cli.json {
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(&prompts))?);
+                } else if prompts.is_empty() {
+                    println!("No prompts for topic {}.", topic_id);
+                } else {
+                    println!("{:<5} PROMPT", "ID");
+                    for prompt in &prompts {
+                        println!("{:<5} {}", prompt.id, prompt.prompt);
+                    }
+                }
             }
-        }
 
-        Commands::Next { tag } => {
-            if let Some(twp) = db.get_next_topic(tag.as_deref())? {
+            TopicCommands::Prompt(PromptCommands::Remove { id }) => {
+                if !db.delete_prompt(id)? {
+                    return Err(FeynmanError::NotFound("Prompt"));
+                }
                 if cli.json {
-                    println!("{}", serde_json::to_string(&JsonOutput::ok(&twp))?);
+                    println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
                 } else {
-                    println!("=== Next Topic to Review ===");
-                    println!();
-                    println!("Topic: {} (ID: {})", twp.topic.name, twp.topic.id);
-                    if let Some(desc) = &twp.topic.description {
-                        println!("Description: {}", desc);
-                    }
-                    println!(
-                        "Tags: {}",
-                        if twp.topic.tags.is_empty() {
-                            "-".to_string()
-                        } else {
-                            twp.topic.tags.join(", ")
-                        }
-                    );
-                    println!();
-                    println!(
-                        "Current mastery: {} (level {})",
-                        twp.progress.mastery_label(),
-                        twp.progress.mastery_level
-                    );
-                    println!(
-                        "Reviews: {} ({:.0}% success)",
-                        twp.progress.times_reviewed,
-                        twp.progress.success_rate()
-                    );
-                    println!();
-                    println!("After review, record outcome with:");
+                    println!("Prompt {} removed.", id);
+                }
+            }
+
+            TopicCommands::Resource(ResourceCommands::Add {
+                topic_id,
+                url,
+                label,
+            }) => {
+                if db.get_topic(topic_id)?.is_none() {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+                let url = url.trim();
+                TopicResource::check_url(url)?;
+                let label = label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+                let id = db.add_resource(topic_id, label, url)?;
+                if cli.json {
                     println!(
-                        "  feynman review {} --outcome <success|partial|fail>",
-                        twp.topic.id
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({ "id": id })))?
                     );
+                } else {
+                    println!("Added resource {} to topic {}.", id, topic_id);
                 }
-            } else if cli.json {
-                println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
-            } else {
-                println!("No topics to review. Add some topics first!");
             }
-        }
 
-        Commands::Review { id, outcome, notes } => {
-            let review_outcome = ReviewOutcome::from_str(&outcome).ok_or_else(|| {
-                format!(
-                    "Invalid outcome '{}'. Use: success, partial, or fail",
-                    outcome
-                )
-            })?;
+            TopicCommands::Resource(ResourceCommands::List { topic_id }) => {
+                if db.get_topic(topic_id)?.is_none() {
+                    return Err(FeynmanError::NotFound("Topic"));
+                }
+                let resources = db.get_resources(topic_id)?;
+                if cli.json {
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(&resources))?);
+                } else if resources.is_empty() {
+                    println!("No resources for topic {}.", topic_id);
+                } else {
+                    println!("{:<5} RESOURCE", "ID");
+                    for resource in &resources {
+                        println!("{:<5} {}", resource.id, resource_line(resource));
+                    }
+                }
+            }
+
+            TopicCommands::Resource(ResourceCommands::Remove { id }) => {
+                if !db.delete_resource(id)? {
+                    return Err(FeynmanError::NotFound("Resource"));
+                }
+                if cli.json {
+                    println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+                } else {
+                    println!("Resource {} removed.", id);
+                }
+            }
+        },
+
+        Commands::Session(SessionCommands::End {
+            id,
+            outcome,
+            summary,
+            notes,
+            gaps,
+            transcript_file,
+        }) => {
+            if db.get_session(id)?.is_none() {
+                return Err(FeynmanError::NotFound("Session"));
+            }
 
-            db.record_review(id, review_outcome, notes.as_deref())?;
+            let gaps: Vec<String> = gaps
+                .iter()
+                .map(|g| g.trim().to_string())
+                .filter(|g| !g.is_empty())
+                .collect();
+            let transcript = transcript_file
+                .map(|path| {
+                    std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Can't read {}: {}", path.display(), e))
+                })
+                .transpose()?;
+            let gap_ids = db.end_session_with_gaps(
+                id,
+                outcome,
+                summary.as_deref(),
+                notes.as_deref(),
+                transcript.as_deref(),
+                &gaps,
+            )?;
 
             if cli.json {
-                println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "session_id": id,
+                        "outcome": outcome.as_str(),
+                        "gap_ids": gap_ids
+                    })))?
+                );
             } else {
-                println!("Review recorded for topic {}.", id);
-                if let Some(progress) = db.get_progress(id)? {
-                    println!(
-                        "New mastery level: {} ({})",
-                        progress.mastery_level,
-                        progress.mastery_label()
-                    );
-                    if let Some(next) = &progress.next_review {
-                        println!("Next review scheduled: {}", next);
-                    }
+                println!("Ended session {} ({}).", id, outcome.as_str());
+                if !gap_ids.is_empty() {
+                    println!("Recorded {} gap(s).", gap_ids.len());
                 }
             }
         }
 
-        Commands::Tui => {
-            tui::run(db)?;
+        Commands::Session(SessionCommands::StartBatch { tag, session_type }) => {
+            let started = db.start_sessions_for_tag(&tag, session_type)?;
+            if cli.json {
+                let sessions: Vec<_> = started
+                    .iter()
+                    .map(|(topic_id, session_id)| {
+                        serde_json::json!({ "topic_id": topic_id, "session_id": session_id })
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "session_type": session_type.as_str(),
+                        "sessions": sessions
+                    })))?
+                );
+            } else if started.is_empty() {
+                println!("No topics tagged '{}'.", tag);
+            } else {
+                for (topic_id, session_id) in &started {
+                    println!("Started session {} for topic {}.", session_id, topic_id);
+                }
+                let ids: Vec<String> = started.iter().map(|(_, id)| id.to_string()).collect();
+                println!(
+                    "Started {} {} session(s): {}",
+                    started.len(),
+                    session_type.as_str(),
+                    ids.join(",")
+                );
+            }
+        }
+
+        Commands::Session(SessionCommands::EndBatch {
+            outcome,
+            ids,
+            since,
+        }) => {
+            let ids = match since {
+                Some(since) => db.active_session_ids_since(parse_time_arg(
+                    "--since",
+                    &since,
+                    config.timezone,
+                )?)?,
+                None => ids,
+            };
+            let ended = db.end_sessions(&ids, outcome)?;
+            let skipped: Vec<i64> = ids.into_iter().filter(|id| !ended.contains(id)).collect();
+
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "outcome": outcome.as_str(),
+                        "ended": ended,
+                        "skipped": skipped
+                    })))?
+                );
+            } else {
+                println!("Ended {} session(s) ({}).", ended.len(), outcome.as_str());
+                if !skipped.is_empty() {
+                    let skipped: Vec<String> = skipped.iter().map(|id| id.to_string()).collect();
+                    println!(
+                        "Skipped {} (not found or already ended).",
+                        skipped.join(", ")
+                    );
+                }
+            }
+        }
+
+        Commands::Plan(command) => {
+            let plan_id = match command {
+                PlanCommands::Note { plan_id, .. } | PlanCommands::Notes { plan_id } => plan_id,
+            };
+            if db.get_plan(plan_id)?.is_none() {
+                return Err(FeynmanError::NotFound("Plan"));
+            }
+
+            match command {
+                PlanCommands::Note { plan_id, content } => {
+                    let content = content.trim();
+                    if content.is_empty() {
+                        return Err("Note is empty".into());
+                    }
+                    let id = db.add_plan_note(plan_id, content)?;
+                    if cli.json {
+                        println!(
+                            "{}",
+                            serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                                "id": id,
+                                "plan_id": plan_id
+                            })))?
+                        );
+                    } else {
+                        println!("Added note {} to plan {}.", id, plan_id);
+                    }
+                }
+
+                PlanCommands::Notes { plan_id } => {
+                    let notes = db.get_plan_notes(plan_id)?;
+                    if cli.json {
+                        println!("{}", serde_json::to_string(&JsonOutput::ok(&notes))?);
+                    } else if notes.is_empty() {
+                        println!("No notes found.");
+                    } else {
+                        for note in &notes {
+                            let created = note.created_at.get(..16).unwrap_or(&note.created_at);
+                            println!("[{}] {}", created.replace('T', " "), note.content);
+                        }
+                    }
+                }
+            }
+        }
+
+        Commands::Tags { with_topics: true } => {
+            let tags = db.list_tags_with_topics()?;
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&tags))?);
+            } else if tags.is_empty() {
+                println!("No tags found.");
+            } else {
+                for tag in tags {
+                    println!("{} ({})", tag.tag, tag.topics.len());
+                    for topic in tag.topics {
+                        println!("  {:<5} {}", topic.id, topic.name);
+                    }
+                }
+            }
+        }
+
+        Commands::Tags { with_topics: false } => {
+            let tags = db.list_tags()?;
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&tags))?);
+            } else if tags.is_empty() {
+                println!("No tags found.");
+            } else {
+                println!("{:<5} {:<30} TOPICS", "ID", "TAG");
+                println!("{}", "-".repeat(50));
+                for tag in tags {
+                    println!(
+                        "{:<5} {} {}",
+                        tag.id,
+                        pad_to_width(&tag.name, 30),
+                        tag.topic_count
+                    );
+                }
+            }
+        }
+
+        Commands::Tag(tag_cmd) => {
+            let (tag, filter_tag, apply) = match tag_cmd {
+                TagCommands::Apply { tag, filter_tag } => (tag, filter_tag, true),
+                TagCommands::Strip { tag, filter_tag } => (tag, filter_tag, false),
+            };
+            let tag = tag.trim();
+            if tag.is_empty() {
+                return Err("Tag name cannot be empty".into());
+            }
+
+            let topic_ids: Vec<i64> = db
+                .list_topics(Some(&filter_tag))?
+                .into_iter()
+                .map(|t| t.id)
+                .collect();
+            let affected = if apply {
+                db.bulk_add_tag(&topic_ids, tag)?
+            } else {
+                db.bulk_remove_tag(&topic_ids, tag)?
+            };
+
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "matched": topic_ids.len(),
+                        "affected": affected
+                    })))?
+                );
+            } else if apply {
+                println!(
+                    "Tagged {} of {} '{}' topic(s) with '{}'.",
+                    affected,
+                    topic_ids.len(),
+                    filter_tag,
+                    tag
+                );
+            } else {
+                println!(
+                    "Removed '{}' from {} of {} '{}' topic(s).",
+                    tag,
+                    affected,
+                    topic_ids.len(),
+                    filter_tag
+                );
+            }
+        }
+
+        Commands::Sessions {
+            view: Some(SessionsView::Recent { limit, offset }),
+            ..
+        } => {
+            let sessions: Vec<RecentSession> = db
+                .get_recent_sessions_with_topics(limit, offset)?
+                .into_iter()
+                .map(RecentSession::from)
+                .collect();
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&sessions))?);
+            } else if sessions.is_empty() {
+                println!("No sessions found.");
+            } else {
+                let names: HashMap<i64, String> = sessions
+                    .iter()
+                    .map(|s| (s.row.session.topic_id, s.topic_name.clone()))
+                    .collect();
+                let rows: Vec<SessionRow> = sessions.into_iter().map(|s| s.row).collect();
+                print_sessions(&rows, Some(&names));
+            }
+        }
+
+        Commands::Sessions {
+            implicit: true,
+            gap,
+            ..
+        } => {
+            let sessions = db.get_study_sessions(gap)?;
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&sessions))?);
+            } else if sessions.is_empty() {
+                println!("No reviews yet.");
+            } else {
+                print_study_sessions(&sessions);
+            }
+        }
+
+        Commands::Sessions {
+            topic,
+            active,
+            outcome,
+            ..
+        } => {
+            let sessions: Vec<SessionRow> = db
+                .query_sessions(&SessionQuery {
+                    topic_id: topic,
+                    active,
+                    outcome,
+                })?
+                .into_iter()
+                .map(SessionRow::from)
+                .collect();
+            let columns = cli
+                .columns
+                .as_deref()
+                .map(|spec| Columns::parse(spec, SESSION_COLUMNS))
+                .transpose()?;
+            if let Some(columns) = columns {
+                let rows = sessions
+                    .iter()
+                    .map(columns::json_row)
+                    .collect::<serde_json::Result<Vec<_>>>()?;
+                if cli.json {
+                    let data = columns_json(&columns, &rows, None);
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(data))?);
+                } else if rows.is_empty() {
+                    println!("No sessions found.");
+                } else {
+                    columns.table(&rows).iter().for_each(|l| println!("{}", l));
+                }
+            } else if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&sessions))?);
+            } else if sessions.is_empty() {
+                println!("No sessions found.");
+            } else {
+                let topic_ids: HashSet<i64> = sessions.iter().map(|r| r.session.topic_id).collect();
+                let mut names = HashMap::new();
+                for id in topic_ids {
+                    if let Some(topic) = db.get_topic(id)? {
+                        names.insert(id, topic.name);
+                    }
+                }
+                print_sessions(&sessions, Some(&names));
+            }
+        }
+
+        Commands::Stats {
+            prometheus: true, ..
+        } => {
+            if cli.json {
+                return Err("--prometheus can't be combined with --json".into());
+            }
+            print!(
+                "{}",
+                metrics::prometheus(&db.get_stats()?, &db.get_tag_stats()?)
+            );
+        }
+
+        Commands::Stats {
+            histogram: true, ..
+        } => {
+            let histogram = db.get_mastery_histogram()?;
+            if cli.json {
+                let map: BTreeMap<i32, i64> = histogram.into_iter().collect();
+                println!("{}", serde_json::to_string(&JsonOutput::ok(map))?);
+            } else {
+                print_histogram(&histogram);
+            }
+        }
+
+        Commands::Stats {
+            calibration: true, ..
+        } => {
+            let report = db.get_calibration()?;
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&report))?);
+            } else {
+                print_calibration(&db, &report)?;
+            }
+        }
+
+        Commands::Stats { .. } => {
+            let stats = db.get_stats()?;
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&stats))?);
+            } else {
+                println!("=== Learning Statistics ===");
+                println!("Total topics: {}", stats.total_topics);
+                println!("Total reviews: {}", stats.total_reviews);
+                println!("Mastered (level 4+): {}", stats.mastered);
+                println!("Due for review: {}", stats.due_now);
+                println!("Average mastery: {:.1}/5", stats.avg_mastery_rounded);
+                println!(
+                    "Knowledge gaps: {} open / {} total",
+                    stats.open_gaps, stats.total_gaps
+                );
+            }
+        }
+
+        Commands::Status { compact } => {
+            let status = db.get_status_counts()?;
+            if compact {
+                println!("{}", format_compact_status(&status));
+            } else if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&status))?);
+            } else {
+                println!("Due: {}", status.due);
+                println!("Overdue: {}", status.overdue);
+                println!("Streak: {} day(s)", status.streak);
+                println!("Average mastery: {:.1}/5", status.avg_mastery_rounded);
+            }
+        }
+
+        Commands::Next {
+            tag,
+            start_session,
+            explain,
+            strategy,
+            new_ratio,
+        } => {
+            let strategy = strategy.or(config.strategy).unwrap_or_default();
+            if let Some((explanation, session_id)) =
+                select_next_topic(&db, tag.as_deref(), strategy, new_ratio, start_session)?
+            {
+                let twp = &explanation.selected;
+                let review_prompt = db.get_review_prompt(twp.topic.id)?;
+                if let Some(path) = last_next::path_for(db_path) {
+                    let _ = LastNext::new(twp.topic.id, Utc::now()).save(&path);
+                }
+                if cli.json {
+                    let mut data = if explain {
+                        serde_json::to_value(&explanation)?
+                    } else {
+                        serde_json::to_value(twp)?
+                    };
+                    if let Some(id) = session_id {
+                        data["session_id"] = serde_json::json!(id);
+                    }
+                    if let Some(pool) = explanation.pool {
+                        data["pool"] = serde_json::json!(pool);
+                    }
+                    if let Some(prompt) = &review_prompt {
+                        data["prompt"] = serde_json::json!(prompt.prompt);
+                    }
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(data))?);
+                } else {
+                    println!("=== Next Topic to Review ===");
+                    println!();
+                    println!("Topic: {} (ID: {})", twp.topic.name, twp.topic.id);
+                    if let Some(desc) = &twp.topic.description {
+                        println!("Description: {}", desc);
+                    }
+                    println!(
+                        "Tags: {}",
+                        if twp.topic.tags.is_empty() {
+                            "-".to_string()
+                        } else {
+                            twp.topic.tags.join(", ")
+                        }
+                    );
+                    println!();
+                    println!(
+                        "Current mastery: {} (level {})",
+                        twp.progress.mastery_label(),
+                        twp.progress.mastery_level
+                    );
+                    println!(
+                        "Reviews: {} ({:.0}% success)",
+                        twp.progress.times_reviewed,
+                        twp.progress.success_rate()
+                    );
+                    if let Some(pool) = explanation.pool {
+                        println!("Drawn from: {} topics", pool.as_str());
+                    }
+                    if let Some(prompt) = &review_prompt {
+                        println!();
+                        println!("Prompt: {}", prompt.prompt);
+                    }
+                    println!();
+                    if explain {
+                        print_selection_explanation(&db, &explanation)?;
+                        println!();
+                    }
+                    if let (Some(id), Some(st)) = (session_id, start_session) {
+                        println!("Started {} session (ID: {})", st.as_str(), id);
+                        println!();
+                    }
+                    println!("After review, record outcome with:");
+                    println!(
+                        "  feynman review {} --outcome <success|partial|fail>",
+                        twp.topic.id
+                    );
+                    println!("  (or `feynman review last` within the hour)");
+                }
+            } else if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::<()>::ok(()))?);
+            } else if new_ratio.is_some() {
+                println!("No topics to review in this mix (see selection.new_per_day).");
+            } else {
+                println!("No topics to review. Add some topics first!");
+            }
+        }
+
+        Commands::Review {
+            id,
+            outcome,
+            notes,
+            retry_now,
+            at,
+            confidence,
+            explain,
+        } => {
+            let at = at
+                .map(|s| parse_review_time(&s, chrono::Utc::now(), config.timezone))
+                .transpose()?;
+            let id = match id {
+                ReviewTarget::Id(id) => id,
+                ReviewTarget::Last => last_next::path_for(db_path)
+                    .and_then(|path| LastNext::load(&path))
+                    .ok_or("`next` hasn't suggested a topic yet; give the topic ID")?
+                    .fresh_topic(Utc::now())?,
+            };
+            if db.get_topic(id)?.is_none() {
+                return Err(FeynmanError::NotFound("Topic"));
+            }
+            let (review_outcome, notes) = match outcome {
+                Some(outcome) => (outcome, notes),
+                None if cli.json => return Err("--outcome is required with --json".into()),
+                // A script that forgot --outcome fails rather than reading EOF
+                // as a cancel and exiting 0
+                None if !std::io::stdin().is_terminal() => {
+                    return Err("--outcome is required when stdin is not a terminal".into())
+                }
+                None => {
+                    if let Some(prompt) = db.get_review_prompt(id)? {
+                        println!("Prompt: {}", prompt.prompt);
+                    }
+                    let mut input = prompt::interactive();
+                    match prompt_review(input.as_mut(), notes)? {
+                        Some(answers) => answers,
+                        None => {
+                            println!("Review cancelled.");
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+
+            let options = ReviewOptions {
+                retry_now,
+                at,
+                confidence,
+            };
+            let reasoning =
+                db.record_review_with(id, review_outcome, notes.as_deref(), &options)?;
+            let progress = db.get_progress(id)?;
+
+            if cli.json {
+                let data = review_json(id, progress.as_ref(), reasoning.as_ref());
+                println!("{}", serde_json::to_string(&JsonOutput::ok(data))?);
+            } else {
+                println!("Review recorded for topic {}.", id);
+                if let Some(progress) = &progress {
+                    println!(
+                        "New mastery level: {} ({})",
+                        progress.mastery_level,
+                        progress.mastery_label()
+                    );
+                    if let Some(next) = &progress.next_review {
+                        println!("Next review scheduled: {}", next);
+                    }
+                }
+                if explain {
+                    println!();
+                    match &reasoning {
+                        Some(reasoning) => print_schedule_reasoning(review_outcome, reasoning),
+                        None => println!(
+                            "Backfilled before the latest review, so progress was rebuilt \
+                             from the whole history rather than by one scheduler step."
+                        ),
+                    }
+                }
+            }
+        }
+
+        Commands::History {
+            topic_id,
+            after,
+            limit,
+        } => {
+            let reviews = db.get_review_history(topic_id, after, limit)?;
+            let last_id = reviews.last().map(|r| r.id);
+            if cli.json {
+                let page = ReviewPage { reviews, last_id };
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&page))?);
+            } else if reviews.is_empty() {
+                println!("No reviews found.");
+            } else {
+                println!(
+                    "{:<6} {:<6} {:<8} {:<20} NOTES",
+                    "ID", "TOPIC", "OUTCOME", "REVIEWED"
+                );
+                println!("{}", "-".repeat(70));
+                for review in &reviews {
+                    let reviewed = review.reviewed_at.get(..16).unwrap_or(&review.reviewed_at);
+                    println!(
+                        "{:<6} {:<6} {:<8} {:<20} {}",
+                        review.id,
+                        review.topic_id,
+                        review.outcome.as_str(),
+                        reviewed.replace('T', " "),
+                        truncate(review.notes.as_deref().unwrap_or("-"), 30)
+                    );
+                }
+                if let (Some(_), Some(id)) = (limit, last_id) {
+                    println!();
+                    println!("Next page: --after {}", id);
+                }
+            }
+        }
+
+        Commands::PruneHistory { before, dry_run } => {
+            let cutoff = parse_time_arg("--before", &before, config.timezone)?;
+            let count = db.count_reviews_before(cutoff)?;
+            let confirmed = if dry_run || count == 0 || cli.yes {
+                true
+            } else if cli.json {
+                return Err("--yes or --dry-run is required with --json".into());
+            } else {
+                println!(
+                    "Warning: deleted reviews are kept only as each topic's totals. \
+                     rebuild-progress, topic merge, `review --at` and import-anki \
+                     replay from those, so `review --at` can't backfill before {} \
+                     and a pruned topic can't be merged into another.",
+                    cutoff.format("%Y-%m-%d %H:%M")
+                );
+                let question = format!("Delete {} review(s)?", count);
+                prompt::confirm(&question, false, cli.yes)?
+            };
+
+            let deleted = if dry_run || !confirmed {
+                0
+            } else {
+                db.prune_review_history(cutoff)?
+            };
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "before": cutoff.to_rfc3339(),
+                        "dry_run": dry_run,
+                        "matched": count,
+                        "deleted": deleted
+                    })))?
+                );
+            } else if count == 0 {
+                println!("No reviews before {}.", cutoff.format("%Y-%m-%d %H:%M"));
+            } else if dry_run {
+                println!("Would delete {} review(s).", count);
+            } else if !confirmed {
+                println!("Nothing deleted.");
+            } else {
+                println!("Deleted {} review(s).", deleted);
+            }
+        }
+
+        Commands::RebuildProgress { topic_id } => {
+            let rebuilt = db.rebuild_progress(topic_id)?;
+            if topic_id.is_some() && rebuilt == 0 {
+                return Err(FeynmanError::NotFound("Topic"));
+            }
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "rebuilt": rebuilt
+                    })))?
+                );
+            } else {
+                println!("Rebuilt progress for {} topic(s).", rebuilt);
+            }
+        }
+
+        Commands::Reschedule { tag } => {
+            let moved = db.reschedule(tag.as_deref())?;
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "rescheduled": moved
+                    })))?
+                );
+            } else {
+                println!("Rescheduled {} topic(s).", moved);
+            }
+        }
+
+        Commands::Import { file, force } => {
+            let content = std::fs::read_to_string(&file)?;
+            let data: ImportData = serde_json::from_str(&content)?;
+
+            let existing: HashSet<String> =
+                db.list_topics(None)?.into_iter().map(|t| t.name).collect();
+            let validation = data.validate(&existing);
+
+            if !validation.is_ok() && !force {
+                if !cli.json {
+                    for problem in &validation.problems {
+                        eprintln!("  {}", problem);
+                    }
+                }
+                return Err(format!(
+                    "Import aborted: {} problem(s) found (use --force to skip invalid rows)",
+                    validation.problems.len()
+                )
+                .into());
+            }
+
+            let summary = db.import_data(&data, &validation)?;
+
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&summary))?);
+            } else {
+                for problem in &validation.problems {
+                    println!("Skipped {}", problem);
+                }
+                println!(
+                    "Imported {} topic(s), {} review(s), {} session(s), {} gap(s).",
+                    summary.topics, summary.reviews, summary.sessions, summary.gaps
+                );
+            }
+        }
+
+        Commands::ImportAnki { file } => {
+            let content = std::fs::read_to_string(&file)?;
+            let export: AnkiExport = serde_json::from_str(&content)?;
+
+            let existing: HashSet<String> =
+                db.list_topics(None)?.into_iter().map(|t| t.name).collect();
+            let mapping = export.to_import(&existing);
+            let validation = mapping.data.validate(&existing);
+            let summary = db.import_and_replay(&mapping.data, &validation)?;
+
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "topics": summary.topics,
+                        "reviews": summary.reviews,
+                        "skipped_notes": mapping.skipped_notes,
+                        "skipped_reviews": mapping.skipped_reviews,
+                    })))?
+                );
+            } else {
+                println!(
+                    "Imported {} topic(s) and {} review(s) from Anki.",
+                    summary.topics, summary.reviews
+                );
+                if mapping.skipped_notes > 0 || mapping.skipped_reviews > 0 {
+                    println!(
+                        "Skipped {} note(s) (empty or existing name) and {} review(s) (no matching note or not an answer).",
+                        mapping.skipped_notes, mapping.skipped_reviews
+                    );
+                }
+            }
+        }
+
+        Commands::Tui {
+            compact_symbols,
+            due_limit,
+            recent_limit,
+            no_restore,
+        } => {
+            // NO_COLOR already turns colors off; the config file can too
+            if config.color == Some(false) {
+                crossterm::style::force_color_output(false);
+            }
+            tui::run(
+                db,
+                tui::TuiOptions {
+                    compact_symbols,
+                    due_limit,
+                    recent_limit,
+                    state_path: Some(config::config_dir().join(tui::STATE_FILE_NAME)),
+                    restore: !no_restore,
+                },
+            )?;
+        }
+
+        Commands::Snapshot {
+            output,
+            ansi,
+            width,
+            due_limit,
+            recent_limit,
+        } => {
+            if cli.json {
+                return Err("snapshot is text only; drop --json".into());
+            }
+            let text = tui::snapshot(
+                db,
+                tui::TuiOptions {
+                    due_limit,
+                    recent_limit,
+                    state_path: None,
+                    ..tui::TuiOptions::default()
+                },
+                width,
+                ansi,
+            )?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, text)?;
+                    println!("Wrote snapshot to {}", path.display());
+                }
+                None => print!("{}", text),
+            }
+        }
+
+        Commands::Schedule => {
+            let ladder = scheduler::interval_ladder(&db.settings()?);
+            if cli.json {
+                let map: BTreeMap<i32, i32> = ladder.into_iter().collect();
+                println!("{}", serde_json::to_string(&JsonOutput::ok(map))?);
+            } else {
+                println!("{:<6} {:<12} {:>8}", "Level", "Label", "Interval");
+                println!("{}", "-".repeat(28));
+                for (level, days) in ladder {
+                    println!("{:<6} {:<12} {:>7}d", level, mastery_label(level), days);
+                }
+            }
+        }
+
+        Commands::Config(config_cmd) => match config_cmd {
+            ConfigCommands::List => {
+                let current = db.settings()?;
+                let stored: HashSet<String> =
+                    db.list_settings()?.into_iter().map(|(k, _)| k).collect();
+                let values: BTreeMap<&str, String> = settings::KEYS
+                    .iter()
+                    .filter_map(|key| current.get(key).map(|value| (*key, value)))
+                    .collect();
+
+                if cli.json {
+                    println!("{}", serde_json::to_string(&JsonOutput::ok(values))?);
+                } else {
+                    for (key, value) in &values {
+                        let marker = if stored.contains(*key) {
+                            ""
+                        } else {
+                            " (default)"
+                        };
+                        println!("{} = {}{}", key, value, marker);
+                    }
+                }
+            }
+            ConfigCommands::Get { key } => {
+                let value = db
+                    .settings()?
+                    .get(&key)
+                    .ok_or_else(|| format!("Unknown setting '{}'", key))?;
+                if cli.json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                            "key": key,
+                            "value": value
+                        })))?
+                    );
+                } else {
+                    println!("{}", value);
+                }
+            }
+            ConfigCommands::Set { key, value } => {
+                let value = value.trim();
+                db.set_setting(&key, value)?;
+                if cli.json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                            "key": key,
+                            "value": value
+                        })))?
+                    );
+                } else {
+                    println!("Set {} = {}", key, value);
+                }
+            }
+            ConfigCommands::Unset { key } => {
+                if !settings::KEYS.contains(&key.as_str()) {
+                    return Err(format!("Unknown setting '{}'", key).into());
+                }
+                let removed = db.unset_setting(&key)?;
+                if cli.json {
+                    println!(
+                        "{}",
+                        serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                            "key": key,
+                            "removed": removed
+                        })))?
+                    );
+                } else if removed {
+                    println!("Reset {} to its default.", key);
+                } else {
+                    println!("{} was not set.", key);
+                }
+            }
+        },
+
+        Commands::Version { verbose } => {
+            let info = version_info(&db, db_path)?;
+            if cli.json {
+                println!("{}", serde_json::to_string(&JsonOutput::ok(&info))?);
+            } else if verbose {
+                println!("feynman {} ({})", info.version, info.git_commit);
+                println!("SQLite:         {}", info.sqlite_version);
+                println!("Database:       {}", info.db_path);
+                println!(
+                    "Schema version: {} (this build: {})",
+                    info.schema_version, info.latest_schema_version
+                );
+            } else {
+                println!("feynman {}", info.version);
+            }
+        }
+
+        Commands::Maintain => {
+            let archived = db.auto_archive_mastered()?;
+            let checkpointed = db.checkpoint()?;
+            if cli.json {
+                let archived: Vec<i64> = archived.iter().map(|(id, _)| *id).collect();
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "checkpointed": checkpointed,
+                        "archived": archived
+                    })))?
+                );
+            } else {
+                report_auto_archived(&archived);
+                if checkpointed {
+                    println!("Checkpointed write-ahead log.");
+                } else if archived.is_empty() {
+                    println!("Nothing to do (database is not in WAL mode).");
+                }
+            }
+        }
+
+        Commands::Doctor { fix } => {
+            let missing = db.topics_missing_progress()?;
+            let formats = db.timestamp_formats()?;
+            let (backfilled, normalized) = if fix {
+                (db.backfill_missing_progress()?, db.normalize_timestamps()?)
+            } else {
+                (0, 0)
+            };
+            let mismatched: Vec<_> = formats.iter().filter(|f| f.mismatched() > 0).collect();
+
+            if cli.json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&JsonOutput::ok(serde_json::json!({
+                        "missing_progress": missing,
+                        "backfilled_progress": backfilled,
+                        "timestamp_formats": formats,
+                        "normalized_timestamps": normalized
+                    })))?
+                );
+            } else if missing.is_empty() && mismatched.is_empty() {
+                println!("No problems found.");
+            } else {
+                if !missing.is_empty() {
+                    let ids: Vec<String> = missing.iter().map(|id| id.to_string()).collect();
+                    println!(
+                        "{} topic(s) missing a progress row: {}",
+                        missing.len(),
+                        ids.join(", ")
+                    );
+                }
+                for f in &mismatched {
+                    println!(
+                        "{}: {} RFC 3339, {} SQLite-style, {} unparseable",
+                        f.column, f.rfc3339, f.sqlite, f.invalid
+                    );
+                }
+                if fix {
+                    if !missing.is_empty() {
+                        println!("Backfilled progress for {} topic(s).", backfilled);
+                    }
+                    if !mismatched.is_empty() {
+                        println!("Rewrote {} timestamp(s) as RFC 3339.", normalized);
+                    }
+                    if mismatched.iter().any(|f| f.invalid > 0) {
+                        println!("Unparseable timestamps were left as they are.");
+                    }
+                } else {
+                    println!("Run `feynman doctor --fix` to repair.");
+                }
+            }
+        }
+
+        Commands::Completions { .. } => unreachable!("handled before opening the database"),
+        Commands::Serve { .. } => unreachable!("handled before opening the database"),
+    }
+
+    Ok(())
+}
+
+// Build and database details for `feynman version`, meant for bug reports
+#[derive(Debug, Serialize, Deserialize)]
+struct VersionInfo {
+    version: String,
+    git_commit: String,
+    sqlite_version: String,
+    db_path: String,
+    // What the database was last initialized with, and what this build expects
+    schema_version: i32,
+    latest_schema_version: i32,
+}
+
+fn version_info(db: &Database, db_path: &Path) -> error::Result<VersionInfo> {
+    Ok(VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("FEYNMAN_GIT_COMMIT").to_string(),
+        sqlite_version: rusqlite::version().to_string(),
+        db_path: db_path.display().to_string(),
+        schema_version: db.schema_version()?,
+        latest_schema_version: db::SCHEMA_VERSION,
+    })
+}
+
+fn write_completions(shell: Shell, out: &mut dyn std::io::Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "feynman", out);
+}
+
+// Pick the next topic and, when a session type is given, start a session
+// for it. Without a session type this is a pure preview.
+fn select_next_topic(
+    db: &Database,
+    tag: Option<&str>,
+    strategy: SelectionStrategy,
+    new_ratio: Option<f64>,
+    session_type: Option<SessionType>,
+) -> error::Result<Option<(SelectionExplanation, Option<i64>)>> {
+    let explanation = match new_ratio {
+        Some(ratio) => {
+            db.explain_next_topic_mixed(tag, strategy, ratio, &mut rand::thread_rng())?
+        }
+        None => db.explain_next_topic(tag, strategy)?,
+    };
+    let Some(explanation) = explanation else {
+        return Ok(None);
+    };
+
+    let session_id = match session_type {
+        Some(st) => Some(db.start_session(explanation.selected.topic.id, st)?),
+        None => None,
+    };
+
+    Ok(Some((explanation, session_id)))
+}
+
+// What `review` records against
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReviewTarget {
+    Id(i64),
+    // The topic `next` most recently suggested (see last_next)
+    Last,
+}
+
+fn parse_review_target(s: &str) -> Result<ReviewTarget, String> {
+    if s.eq_ignore_ascii_case("last") {
+        return Ok(ReviewTarget::Last);
+    }
+    s.parse()
+        .map(ReviewTarget::Id)
+        .map_err(|_| format!("expected a topic ID or 'last', got '{}'", s))
+}
+
+// A level given as a whole number from `min` to `max` or, where the levels have
+// names, by name in any case. Clap prefixes the error with the offending value
+// and flag, so it only says what would have been accepted.
+fn parse_level(
+    s: &str,
+    min: i32,
+    max: i32,
+    name: fn(i32) -> Option<&'static str>,
+) -> Result<i32, String> {
+    let s = s.trim();
+    let level = s.parse::<i32>().ok().or_else(|| {
+        (min..=max).find(|&level| name(level).is_some_and(|n| n.eq_ignore_ascii_case(s)))
+    });
+    match level {
+        Some(level) if (min..=max).contains(&level) => Ok(level),
+        _ => {
+            let names: Vec<String> = (min..=max)
+                .filter_map(|level| name(level).map(|n| n.to_lowercase()))
+                .collect();
+            Err(if names.is_empty() {
+                format!("expected a whole number from {} to {}", min, max)
+            } else {
+                format!("expected {}-{} or one of: {}", min, max, names.join(", "))
+            })
+        }
+    }
+}
+
+fn parse_skill(s: &str) -> Result<SkillLevel, String> {
+    parse_level(s, 0, 5, |level| Some(SkillLevel::from_i32(level).label()))
+        .map(SkillLevel::from_i32)
+}
+
+fn parse_mastery(s: &str) -> Result<i32, String> {
+    parse_level(s, 0, settings::MAX_MASTERY, |level| {
+        Some(mastery_label(level))
+    })
+}
+
+// Difficulty and confidence: 1 to 5, numbers only
+fn parse_rating(s: &str) -> Result<i32, String> {
+    parse_level(s, 1, 5, |_| None)
+}
+
+// "~62%": the tilde marks it as an estimate (Progress::estimated_retention)
+fn retention_cell(retention: f64) -> String {
+    format!("~{:.0}%", retention * 100.0)
+}
+
+// Two decimals are as precise as the estimate is
+fn round_retention(retention: f64) -> f64 {
+    (retention * 100.0).round() / 100.0
+}
+
+// A share between 0 and 1, e.g. for --new-ratio
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    let ratio: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("expected a number between 0 and 1, got '{}'", s))?;
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!("must be between 0 and 1, got {}", s));
+    }
+    Ok(ratio)
+}
+
+// "The Book, ch. 4 <https://...>", or just the URL when there's no label
+fn resource_line(resource: &TopicResource) -> String {
+    match &resource.label {
+        Some(label) => format!("{} <{}>", label, resource.url),
+        None => resource.url.clone(),
+    }
+}
+
+// One line per session; `topic_names` adds a TOPIC column
+fn print_sessions(sessions: &[SessionRow], topic_names: Option<&HashMap<i64, String>>) {
+    let topic_header = if topic_names.is_some() {
+        format!(" {:<24}", "TOPIC")
+    } else {
+        String::new()
+    };
+    println!(
+        "{:<5}{} {:<9} {:<16} {:>8}  OUTCOME",
+        "ID", topic_header, "TYPE", "STARTED", "DURATION"
+    );
+    for row in sessions {
+        let session = &row.session;
+        let topic_cell = topic_names
+            .map(|names| {
+                let name = names.get(&session.topic_id).map(String::as_str);
+                format!(" {}", pad_to_width(&truncate(name.unwrap_or("?"), 24), 24))
+            })
+            .unwrap_or_default();
+        let started = parse_timestamp(&session.started_at)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| session.started_at.clone());
+        let duration = match session.duration() {
+            Some(duration) => format_duration(duration),
+            None if session.is_active() => String::from("active"),
+            None => String::from("-"),
+        };
+        println!(
+            "{:<5}{} {:<9} {:<16} {:>8}  {}",
+            session.id,
+            topic_cell,
+            session.session_type.as_str(),
+            started,
+            duration,
+            session.outcome.map(|o| o.as_str()).unwrap_or("-")
+        );
+    }
+}
+
+fn print_study_sessions(sessions: &[StudySession]) {
+    println!(
+        "{:<16} {:>8} {:>7} {:>7}",
+        "STARTED", "DURATION", "REVIEWS", "SUCCESS"
+    );
+    for session in sessions {
+        let started = parse_timestamp(&session.start)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| session.start.clone());
+        let duration = session
+            .duration()
+            .map(format_duration)
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<16} {:>8} {:>7} {:>6.0}%",
+            started,
+            duration,
+            session.reviews,
+            session.success_rate * 100.0
+        );
+    }
+}
+
+fn print_histogram(histogram: &[(i32, i64)]) {
+    const BAR_WIDTH: usize = 20;
+    let max = histogram.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    println!("=== Mastery Distribution ===");
+    for (level, count) in histogram {
+        println!(
+            "{} {:<12} {} {}",
+            level,
+            mastery_label(*level),
+            count_bar(*count, max, BAR_WIDTH),
+            count
+        );
+    }
+}
+
+fn print_calibration(db: &Database, report: &CalibrationReport) -> error::Result<()> {
+    println!("=== Confidence Calibration ===");
+    if report.rated_reviews() == 0 {
+        println!("No rated reviews yet. Add --confidence <1-5> when recording reviews.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:>7} {:>7} {:>7} {:>7} {:>8}",
+        "CONFIDENCE", "REVIEWS", "SUCCESS", "PARTIAL", "FAIL", "SUCCESS%"
+    );
+    for level in &report.levels {
+        let rate = if level.reviews == 0 {
+            String::from("-")
+        } else {
+            format!("{:.0}%", level.success_rate() * 100.0)
+        };
+        println!(
+            "{:<10} {:>7} {:>7} {:>7} {:>7} {:>8}",
+            level.confidence, level.reviews, level.success, level.partial, level.fail, rate
+        );
+    }
+
+    for (heading, reviews) in [
+        (
+            "Overconfident (failed at confidence 4+)",
+            &report.overconfident,
+        ),
+        (
+            "Underconfident (succeeded at confidence 2 or less)",
+            &report.underconfident,
+        ),
+    ] {
+        if reviews.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}:", heading);
+        for review in reviews {
+            let name = db
+                .get_topic(review.topic_id)?
+                .map(|t| t.name)
+                .unwrap_or_default();
+            let reviewed = review.reviewed_at.get(..10).unwrap_or(&review.reviewed_at);
+            println!(
+                "  {} {} (topic {}, confidence {})",
+                reviewed,
+                name,
+                review.topic_id,
+                review.confidence.unwrap_or_default()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_selection_explanation(
+    db: &Database,
+    explanation: &SelectionExplanation,
+) -> error::Result<()> {
+    // ID, OVERDUE, MASTERY, WEIGHT, CHANCE plus the selection marker and gaps
+    let fixed = 5 + 7 + 7 + 8 + 6 + 2 + 5;
+    let width = name_column_width(terminal_width(), fixed);
+    println!("--- Selection ---");
+    println!(
+        "{:<5} {:<width$} {:>7} {:>7} {:>8} {:>6}",
+        "ID", "TOPIC", "OVERDUE", "MASTERY", "WEIGHT", "CHANCE"
+    );
+    for c in &explanation.candidates {
+        let name = db
+            .get_topic(c.topic_id)?
+            .map(|t| t.name)
+            .unwrap_or_default();
+        let marker = if c.topic_id == explanation.selected.topic.id {
+            " <"
+        } else {
+            ""
+        };
+        println!(
+            "{:<5} {} {:>7} {:>7.0} {:>8.1} {:>5.1}%{}",
+            c.topic_id,
+            name_cell(&name, width),
+            c.overdue_days,
+            c.mastery_factor,
+            c.weight,
+            c.weight / explanation.total_weight * 100.0,
+            marker
+        );
+    }
+    match explanation.draw {
+        Some(draw) => println!(
+            "Draw ({}): {:.2} of {:.2} total weight",
+            explanation.strategy.as_str(),
+            draw,
+            explanation.total_weight
+        ),
+        None => println!(
+            "Picked by the '{}' strategy (no draw)",
+            explanation.strategy.as_str()
+        ),
+    }
+    Ok(())
+}
+
+// Confirm a destructive command that has no dry run. --json can't prompt, so
+// it needs --yes up front.
+fn confirm_destructive(question: &str, yes: bool, json: bool) -> error::Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if json {
+        return Err("--yes is required with --json".into());
+    }
+    Ok(prompt::confirm(question, false, yes)?)
+}
+
+// Ask for a review outcome (until a valid one is entered) and, unless already
+// given, optional notes. None if input ends before an outcome is entered.
+fn prompt_review(
+    input: &mut dyn prompt::Prompt,
+    notes: Option<String>,
+) -> std::io::Result<Option<(ReviewOutcome, Option<String>)>> {
+    let outcome = loop {
+        let Some(line) = input.read_line("Outcome (success/partial/fail): ")? else {
+            return Ok(None);
+        };
+        match ReviewOutcome::from_str(line.trim()) {
+            Some(outcome) => break outcome,
+            None => eprintln!(
+                "Invalid outcome '{}'. Use: success, partial, or fail",
+                line.trim()
+            ),
+        }
+    };
+
+    let notes = match notes {
+        Some(notes) => Some(notes),
+        None => input
+            .read_line("Notes (optional): ")?
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty()),
+    };
+
+    Ok(Some((outcome, notes)))
+}
+
+// `review --json`: the topic's progress after the review and the scheduler
+// step behind it (null when a backfill replayed the whole history)
+fn review_json(
+    topic_id: i64,
+    progress: Option<&Progress>,
+    reasoning: Option<&ScheduleReasoning>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "topic_id": topic_id,
+        "progress": progress,
+        "reasoning": reasoning
+    })
+}
+
+// `review --explain`: the level change and interval behind the new date
+fn print_schedule_reasoning(outcome: ReviewOutcome, reasoning: &ScheduleReasoning) {
+    let capped = if reasoning.level_delta != reasoning.grading_delta {
+        format!(", held at {}", reasoning.new_level)
+    } else {
+        String::new()
+    };
+    println!(
+        "Mastery: {} ({}) -> {} ({}), {} delta {:+}{}",
+        reasoning.previous_level,
+        mastery_label(reasoning.previous_level),
+        reasoning.new_level,
+        mastery_label(reasoning.new_level),
+        outcome.as_str(),
+        reasoning.grading_delta,
+        capped
+    );
+    println!(
+        "Previous interval: {}",
+        reasoning
+            .previous_interval_minutes
+            .map_or_else(|| "none (first review)".to_string(), format_interval)
+    );
+    println!("Difficulty: {}", reasoning.difficulty);
+    if let Some(bonus) = reasoning.on_time_bonus {
+        println!("On time: interval stretched x{}", bonus);
+    }
+    if reasoning.retry_now {
+        println!("Interval: none (--retry-now makes it due again now)");
+    } else {
+        println!("Interval: {}", format_interval(reasoning.interval_minutes));
+    }
+}
+
+// Whole days as "1 day"/"7 days", anything shorter as "10m", "2h 30m"
+fn format_interval(minutes: i64) -> String {
+    const DAY: i64 = 24 * 60;
+    match minutes {
+        DAY => "1 day".to_string(),
+        m if m > 0 && m % DAY == 0 => format!("{} days", m / DAY),
+        m => format_duration(chrono::Duration::minutes(m)),
+    }
+}
+
+// Flag a stored next_review that can't be parsed; such topics never count as
+// overdue and show as "Unknown" in the TUI
+fn next_review_warning(progress: &Progress) -> Option<String> {
+    if !progress.has_unparseable_next_review() {
+        return None;
+    }
+    Some(format!(
+        "next review date {:?} is not a valid timestamp, so this topic is never \
+         treated as overdue. Run `feynman rebuild-progress {}` to recompute it.",
+        progress.next_review.as_deref().unwrap_or_default(),
+        progress.topic_id
+    ))
+}
+
+// `review --at`: RFC 3339, or a bare date meaning the start of that day in
+// the configured timezone. Must not be later than `now`.
+fn parse_review_time(
+    s: &str,
+    now: DateTime<Utc>,
+    timezone: Timezone,
+) -> Result<DateTime<Utc>, String> {
+    let at = parse_time_arg("--at", s, timezone)?;
+    if at > now {
+        return Err(format!("--at {} is in the future", s));
+    }
+    Ok(at)
+}
+
+// RFC 3339, or YYYY-MM-DD for the start of that day in `timezone`
+fn parse_time_arg(flag: &str, s: &str, timezone: Timezone) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| timezone.start_of_day(d)))
+        .map_err(|_| format!("Invalid {} '{}': expected RFC 3339 or YYYY-MM-DD", flag, s))
+}
+
+// For each tag that doesn't exist yet but is one edit away from one that
+// does, a "did you mean" hint. Tags differing only in case or spacing are
+// the same tag, so they never warn. Never blocks the change.
+fn near_duplicate_tag_warnings(db: &Database, tags: &[String]) -> error::Result<Vec<String>> {
+    let existing: Vec<String> = db.list_tags()?.into_iter().map(|t| t.name).collect();
+    Ok(tags
+        .iter()
+        .filter_map(|tag| {
+            let normalized = normalize_tag(tag);
+            if normalized.is_empty() || existing.contains(&normalized) {
+                return None;
+            }
+            let similar = existing
+                .iter()
+                .find(|name| edit_distance(name, &normalized) <= 1)?;
+            Some(format!("tag '{}' is new; did you mean '{}'?", tag, similar))
+        })
+        .collect())
+}
+
+// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+fn format_compact_status(status: &StatusCounts) -> String {
+    format!(
+        "due:{} overdue:{} streak:{} avg:{:.1}",
+        status.due, status.overdue, status.streak, status.avg_mastery_rounded
+    )
+}
+
+const NO_TAG_GROUP: &str = "(no tag)";
+
+// Group topics under each of their tags, sorted by tag, with untagged topics
+// last under "(no tag)". A topic with several tags appears in each group.
+fn group_topics_by_tag(topics: &[Topic]) -> Vec<(String, Vec<&Topic>)> {
+    let mut groups: BTreeMap<&str, Vec<&Topic>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for topic in topics {
+        if topic.tags.is_empty() {
+            untagged.push(topic);
+        }
+        for tag in &topic.tags {
+            groups.entry(tag).or_default().push(topic);
+        }
+    }
+
+    let mut grouped: Vec<(String, Vec<&Topic>)> = groups
+        .into_iter()
+        .map(|(tag, topics)| (tag.to_string(), topics))
+        .collect();
+    if !untagged.is_empty() {
+        grouped.push((NO_TAG_GROUP.to_string(), untagged));
+    }
+    grouped
+}
+
+// Create the topics listed in `input` (see import::parse_topic_lines) in one
+// transaction, writing each new ID to `out` on its own line. Returns the IDs
+// and any near-duplicate tag warnings.
+fn add_topic_lines(
+    db: &Database,
+    input: &str,
+    out: &mut impl Write,
+) -> error::Result<(Vec<i64>, Vec<String>)> {
+    let topics = import::parse_topic_lines(input)?;
+    let mut tags: Vec<String> = topics.iter().flat_map(|t| t.tags.clone()).collect();
+    tags.sort();
+    tags.dedup();
+    let warnings = near_duplicate_tag_warnings(db, &tags)?;
+
+    let ids = db.add_topics(&topics)?;
+    for id in &ids {
+        writeln!(out, "{}", id)?;
+    }
+    Ok((ids, warnings))
+}
+
+// `topic list --json`: each topic with its progress (and estimated_retention
+// for due listings), wrapped with the `last_id` to continue from when paginated
+fn topic_list_json(
+    topics: &[Topic],
+    progress: &[Progress],
+    retention: Option<&[Option<f64>]>,
+    paginated: bool,
+) -> serde_json::Result<serde_json::Value> {
+    let rows: Vec<TopicWithProgress> = topics
+        .iter()
+        .cloned()
+        .zip(progress.iter().cloned())
+        .map(|(topic, progress)| TopicWithProgress { topic, progress })
+        .collect();
+    let mut rows = serde_json::to_value(rows)?;
+    if let (Some(retention), Some(items)) = (retention, rows.as_array_mut()) {
+        for (item, r) in items.iter_mut().zip(retention) {
+            item["estimated_retention"] = serde_json::json!(r.map(round_retention));
+        }
+    }
+    Ok(if paginated {
+        let last_id = topics.last().map(|t| t.id);
+        serde_json::json!({ "topics": rows, "last_id": last_id })
+    } else {
+        rows
+    })
+}
+
+// `--columns` rows as JSON; `page` carries last_id for paginated topic lists
+fn columns_json(
+    columns: &Columns,
+    rows: &[serde_json::Map<String, serde_json::Value>],
+    page: Option<Option<i64>>,
+) -> serde_json::Value {
+    let rows: Vec<serde_json::Value> = rows.iter().map(|row| columns.project(row)).collect();
+    match page {
+        Some(last_id) => serde_json::json!({ "topics": rows, "last_id": last_id }),
+        None => serde_json::Value::Array(rows),
+    }
+}
+
+// Commands whose output --columns can narrow
+fn supports_columns(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Topic(TopicCommands::List { .. })
+            | Commands::Sessions {
+                implicit: false,
+                view: None,
+                ..
+            }
+    )
+}
+
+fn topic_list_name(topic: &Topic) -> String {
+    if topic.archived {
+        format!("{} [archived]", topic.name)
+    } else if topic.suspended {
+        format!("{} [suspended]", topic.name)
+    } else {
+        topic.name.clone()
+    }
+}
+
+// Name column width when the terminal size is unknown (e.g. piped output)
+const DEFAULT_NAME_WIDTH: usize = 40;
+// Narrowest name column, so names stay readable on tiny terminals
+const MIN_NAME_WIDTH: usize = 12;
+// Room kept for the trailing tags column of `topic list`
+const TAGS_COLUMN_WIDTH: usize = 24;
+
+// Columns available to CLI tables: the terminal's width when stdout is one,
+// else $COLUMNS, else unknown
+fn terminal_width() -> Option<usize> {
+    if std::io::stdout().is_terminal() {
+        if let Ok((cols, _)) = crossterm::terminal::size() {
+            return Some(cols as usize);
+        }
+    }
+    std::env::var("COLUMNS")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&cols| cols > 0)
+}
+
+// Width for a table's name column: whatever `total` leaves after the fixed
+// columns, never below MIN_NAME_WIDTH
+fn name_column_width(total: Option<usize>, fixed: usize) -> usize {
+    match total {
+        Some(total) => total.saturating_sub(fixed).max(MIN_NAME_WIDTH),
+        None => DEFAULT_NAME_WIDTH,
+    }
+}
+
+// A name filling a `width`-column name cell, measured in display columns so
+// wide characters don't push the rest of the row out of line
+fn name_cell(name: &str, width: usize) -> String {
+    pad_to_width(&truncate(name, width - 2), width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    mod truncate_tests {
+        use super::*;
+
+        #[test]
+        fn truncate_short_string() {
+            assert_eq!(truncate("hello", 10), "hello");
+        }
+
+        #[test]
+        fn truncate_exact_length() {
+            assert_eq!(truncate("hello", 5), "hello");
+        }
+
+        #[test]
+        fn truncate_long_string() {
+            assert_eq!(truncate("hello world", 8), "hello...");
+        }
+
+        #[test]
+        fn truncate_empty_string() {
+            assert_eq!(truncate("", 10), "");
+        }
+
+        #[test]
+        fn truncate_multibyte_does_not_split_chars() {
+            assert_eq!(truncate("ownership → borrowing", 13), "ownership ...");
+            // Wide characters take two columns each
+            assert_eq!(truncate("所有权与借用规则", 7), "所有...");
+        }
+
+        #[test]
+        fn truncate_minimum_length() {
+            // With max_len = 4, we get 1 char + "..."
+            assert_eq!(truncate("hello", 4), "h...");
+        }
+    }
+
+    mod column_width_tests {
+        use super::*;
+
+        #[test]
+        fn name_column_takes_remaining_width() {
+            assert_eq!(name_column_width(Some(120), 31), 89);
+            assert_eq!(name_column_width(Some(80), 31), 49);
+        }
+
+        #[test]
+        fn name_column_has_a_minimum() {
+            assert_eq!(name_column_width(Some(40), 31), MIN_NAME_WIDTH);
+            assert_eq!(name_column_width(Some(10), 31), MIN_NAME_WIDTH);
+        }
+
+        #[test]
+        fn unknown_width_uses_default() {
+            assert_eq!(name_column_width(None, 31), DEFAULT_NAME_WIDTH);
+        }
+
+        #[test]
+        fn name_cells_fill_the_column_by_display_width() {
+            use unicode_width::UnicodeWidthStr;
+
+            for name in ["Rust", "所有权与借用规则", "🦀 crabs 🦀 everywhere"] {
+                assert_eq!(name_cell(name, 12).width(), 12, "{}", name);
+            }
+            assert_eq!(name_cell("所有权与借用规则", 12), "所有权...   ");
+        }
+    }
+
+    mod compact_status_tests {
+        use super::*;
+
+        #[test]
+        fn format_compact_status_single_line_in_order() {
+            let status = StatusCounts {
+                due: 7,
+                overdue: 3,
+                streak: 5,
+                avg_mastery: 2.44,
+                avg_mastery_rounded: 2.4,
+            };
+            let line = format_compact_status(&status);
+            assert_eq!(line, "due:7 overdue:3 streak:5 avg:2.4");
+            assert!(!line.contains('\n'));
+        }
+    }
+
+    mod topic_tree_tests {
+        use super::*;
+
+        fn setup_db() -> Database {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            db
+        }
+
+        fn names(group: &[&Topic]) -> Vec<String> {
+            group.iter().map(|t| t.name.clone()).collect()
+        }
+
+        #[test]
+        fn topic_with_two_tags_appears_in_both_groups() {
+            let db = setup_db();
+            db.add_topic("Async", None, &["rust".into(), "concurrency".into()])
+                .unwrap();
+            db.add_topic("Ownership", None, &["rust".into()]).unwrap();
+            db.add_topic("Misc", None, &[]).unwrap();
+
+            let topics = db.list_topics(None).unwrap();
+            let groups = group_topics_by_tag(&topics);
+            let tags: Vec<&str> = groups.iter().map(|(tag, _)| tag.as_str()).collect();
+            assert_eq!(tags, vec!["concurrency", "rust", NO_TAG_GROUP]);
+
+            assert_eq!(names(&groups[0].1), vec!["Async"]);
+            assert_eq!(names(&groups[1].1), vec!["Async", "Ownership"]);
+            assert_eq!(names(&groups[2].1), vec!["Misc"]);
+        }
+
+        #[test]
+        fn no_tag_group_omitted_when_everything_is_tagged() {
+            let db = setup_db();
+            db.add_topic("Ownership", None, &["rust".into()]).unwrap();
+
+            let topics = db.list_topics(None).unwrap();
+            let groups = group_topics_by_tag(&topics);
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].0, "rust");
+        }
+    }
+
+    mod prompt_review_tests {
+        use super::*;
+        use crate::prompt::ScriptedPrompt;
+
+        #[test]
+        fn reprompts_until_outcome_is_valid() {
+            let mut input = ScriptedPrompt::new(&["meh", " partial ", "Borrowing rules"]);
+            let answers = prompt_review(&mut input, None).unwrap();
+            assert_eq!(
+                answers,
+                Some((ReviewOutcome::Partial, Some("Borrowing rules".to_string())))
+            );
+            assert_eq!(input.prompts.len(), 3);
+        }
+
+        #[test]
+        fn empty_notes_are_none() {
+            let mut input = ScriptedPrompt::new(&["success", "   "]);
+            let answers = prompt_review(&mut input, None).unwrap();
+            assert_eq!(answers, Some((ReviewOutcome::Success, None)));
+        }
+
+        #[test]
+        fn notes_flag_skips_notes_prompt() {
+            let mut input = ScriptedPrompt::new(&["fail"]);
+            let answers = prompt_review(&mut input, Some("from flag".to_string())).unwrap();
+            assert_eq!(
+                answers,
+                Some((ReviewOutcome::Fail, Some("from flag".to_string())))
+            );
+            assert_eq!(input.prompts.len(), 1);
+        }
+
+        #[test]
+        fn end_of_input_cancels() {
+            let mut input = ScriptedPrompt::new(&[]);
+            assert_eq!(prompt_review(&mut input, None).unwrap(), None);
+        }
+    }
+
+    // Lock the --json contract: each payload must survive a round trip
+    // through its model unchanged and keep its required fields
+    mod json_contract_tests {
+        use super::*;
+        use serde::de::DeserializeOwned;
+        use serde::Serialize;
+
+        fn seeded_db() -> Database {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            let rust = db
+                .add_topic("Rust", Some("Ownership"), &["lang".to_string()])
+                .unwrap();
+            db.add_topic("Go", None, &[]).unwrap();
+            db.record_review(rust, ReviewOutcome::Success, Some("solid"))
+                .unwrap();
+            db.record_review(rust, ReviewOutcome::Partial, None)
+                .unwrap();
+            let session = db.start_session(rust, SessionType::Feynman).unwrap();
+            db.end_session_with_gaps(
+                session,
+                SessionOutcome::Partial,
+                None,
+                None,
+                None,
+                &["Lifetimes".to_string()],
+            )
+            .unwrap();
+            db
+        }
+
+        // Serialize as the CLI does, read it back as T, and check nothing was
+        // lost. Returns the emitted `data` value.
+        fn round_trip<T: Serialize + DeserializeOwned>(payload: &T) -> serde_json::Value {
+            let emitted = serde_json::to_string(&JsonOutput::ok(payload)).unwrap();
+            let parsed: JsonOutput<T> = serde_json::from_str(&emitted).unwrap();
+            assert!(parsed.success);
+            assert!(parsed.error.is_none());
+
+            let original: serde_json::Value = serde_json::from_str(&emitted).unwrap();
+            let reparsed = serde_json::to_value(&parsed).unwrap();
+            assert_eq!(original, reparsed, "JSON changed after a round trip");
+            original["data"].clone()
+        }
+
+        fn assert_fields(value: &serde_json::Value, fields: &[&str]) {
+            let object = value.as_object().expect("expected a JSON object");
+            for field in fields {
+                assert!(object.contains_key(*field), "missing field '{}'", field);
+            }
+        }
+
+        const TOPIC_FIELDS: &[&str] = &[
+            "id",
+            "name",
+            "description",
+            "created_at",
+            "updated_at",
+            "tags",
+            "archived",
+            "suspended",
+        ];
+        const PROGRESS_FIELDS: &[&str] = &[
+            "topic_id",
+            "mastery_level",
+            "times_reviewed",
+            "times_succeeded",
+            "last_reviewed",
+            "next_review",
+            "skill_level",
+            "difficulty",
+        ];
+
+        fn assert_topic_with_progress(value: &serde_json::Value) {
+            assert_fields(value, &["topic", "progress"]);
+            assert_fields(&value["topic"], TOPIC_FIELDS);
+            assert_fields(&value["progress"], PROGRESS_FIELDS);
+        }
+
+        // Exactly these keys, so a renamed or dropped field breaks the test
+        fn assert_only_fields(value: &serde_json::Value, fields: &[&str]) {
+            let mut keys: Vec<&str> = value
+                .as_object()
+                .expect("expected a JSON object")
+                .keys()
+                .map(String::as_str)
+                .collect();
+            let mut expected = fields.to_vec();
+            keys.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(keys, expected);
+        }
+
+        #[test]
+        fn stats() {
+            let db = seeded_db();
+            let data = round_trip(&db.get_stats().unwrap());
+            assert_only_fields(
+                &data,
+                &[
+                    "total_topics",
+                    "total_reviews",
+                    "mastered",
+                    "due_now",
+                    "avg_mastery",
+                    "avg_mastery_rounded",
+                    "total_gaps",
+                    "open_gaps",
+                ],
+            );
+            assert_eq!(data["total_topics"], 2);
+            assert_eq!(data["open_gaps"], 1);
+        }
+
+        #[test]
+        fn status() {
+            let db = seeded_db();
+            let data = round_trip(&db.get_status_counts().unwrap());
+            assert_only_fields(
+                &data,
+                &[
+                    "due",
+                    "overdue",
+                    "streak",
+                    "avg_mastery",
+                    "avg_mastery_rounded",
+                ],
+            );
+            assert_eq!(data["due"], 1);
+        }
+
+        #[test]
+        fn topic_list() {
+            let db = seeded_db();
+            let topics = db.get_topics_with_progress(&TopicQuery::default()).unwrap();
+            let data = round_trip(&topics);
+            let items = data.as_array().unwrap();
+            assert_eq!(items.len(), 2);
+            items.iter().for_each(assert_topic_with_progress);
+        }
+
+        #[test]
+        fn topic_list_json_includes_progress() {
+            let db = seeded_db();
+            let (topics, progress): (Vec<Topic>, Vec<Progress>) = db
+                .get_topics_with_progress(&TopicQuery::default())
+                .unwrap()
+                .into_iter()
+                .map(|twp| (twp.topic, twp.progress))
+                .unzip();
+
+            let data = topic_list_json(&topics, &progress, None, false).unwrap();
+            let items = data.as_array().unwrap();
+            assert_eq!(items.len(), 2);
+            for item in items {
+                assert_topic_with_progress(item);
+                assert!(item["progress"]["mastery_level"].is_number());
+                assert!(item["progress"]["next_review"].is_string());
+            }
+
+            let page = topic_list_json(&topics, &progress, None, true).unwrap();
+            assert_only_fields(&page, &["topics", "last_id"]);
+            page["topics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .for_each(assert_topic_with_progress);
+            assert_eq!(page["last_id"], topics[1].id);
+        }
+
+        #[test]
+        fn due_topic_list_json_estimates_retention() {
+            let db = seeded_db();
+            let (topics, progress): (Vec<Topic>, Vec<Progress>) = db
+                .get_topics_with_progress(&TopicQuery::default())
+                .unwrap()
+                .into_iter()
+                .map(|twp| (twp.topic, twp.progress))
+                .unzip();
+
+            let retention = [Some(0.8612), None];
+            let data = topic_list_json(&topics, &progress, Some(&retention), false).unwrap();
+            let items = data.as_array().unwrap();
+            assert_fields(&items[0], &["topic", "progress", "estimated_retention"]);
+            assert_eq!(items[0]["estimated_retention"], 0.86);
+            assert!(items[1]["estimated_retention"].is_null());
+        }
+
+        #[test]
+        fn columns_only_apply_to_plain_lists() {
+            let command = |args: &[&str]| {
+                Cli::try_parse_from([&["feynman"], args].concat())
+                    .ok()
+                    .unwrap()
+                    .command
+            };
+            assert!(supports_columns(&command(&["topic", "list"])));
+            assert!(supports_columns(&command(&["sessions"])));
+            assert!(!supports_columns(&command(&["sessions", "--implicit"])));
+            assert!(!supports_columns(&command(&["stats"])));
+        }
+
+        #[test]
+        fn columns_project_topic_list_rows() {
+            let db = seeded_db();
+            let rows = db
+                .get_topics_with_progress(&TopicQuery::default())
+                .unwrap()
+                .iter()
+                .map(|twp| columns::topic_row(&twp.topic, &twp.progress).unwrap())
+                .collect::<Vec<_>>();
+            // Every known column is a real field, and vice versa
+            let keys: Vec<&str> = rows[0].keys().map(String::as_str).collect();
+            let mut known = TOPIC_COLUMNS.to_vec();
+            known.sort_unstable();
+            assert_eq!(keys, known);
+
+            let columns = Columns::parse("name,mastery_level", TOPIC_COLUMNS).unwrap();
+            let data = columns_json(&columns, &rows, None);
+            assert_eq!(
+                data,
+                serde_json::json!([
+                    { "name": "Go", "mastery_level": 0 },
+                    { "name": "Rust", "mastery_level": 1 }
+                ])
+            );
+            let page = columns_json(&columns, &rows, Some(Some(2)));
+            assert_only_fields(&page, &["topics", "last_id"]);
+
+            let table = columns.table(&rows);
+            assert_eq!(table[0], "NAME MASTERY_LEVEL");
+            assert_eq!(table[2], "Go   0");
+            assert_eq!(table[3], "Rust 1");
+        }
+
+        #[test]
+        fn columns_cover_session_rows() {
+            let db = seeded_db();
+            let session = SessionRow::from(db.list_sessions(None).unwrap().remove(0));
+            let row = columns::json_row(&session).unwrap();
+            let keys: Vec<&str> = row.keys().map(String::as_str).collect();
+            let mut known = SESSION_COLUMNS.to_vec();
+            known.sort_unstable();
+            assert_eq!(keys, known);
+        }
+
+        #[test]
+        fn recent_sessions_are_session_rows_with_a_topic_name() {
+            let db = seeded_db();
+            let recent: Vec<RecentSession> = db
+                .get_recent_sessions_with_topics(1, 0)
+                .unwrap()
+                .into_iter()
+                .map(RecentSession::from)
+                .collect();
+            let data = round_trip(&recent);
+            let mut fields = SESSION_COLUMNS.to_vec();
+            fields.push("topic_name");
+            assert_only_fields(&data[0], &fields);
+            assert!(data[0]["topic_name"].is_string());
+        }
+
+        #[test]
+        fn review_reports_the_new_progress() {
+            let db = seeded_db();
+            let go = db
+                .list_topics(None)
+                .unwrap()
+                .into_iter()
+                .find(|t| t.name == "Go")
+                .unwrap()
+                .id;
+            let reasoning = db
+                .record_review_with(go, ReviewOutcome::Success, None, &ReviewOptions::default())
+                .unwrap();
+            let progress = db.get_progress(go).unwrap();
+
+            let data = round_trip(&review_json(go, progress.as_ref(), reasoning.as_ref()));
+            assert_only_fields(&data, &["topic_id", "progress", "reasoning"]);
+            assert_fields(&data["progress"], PROGRESS_FIELDS);
+            assert_eq!(data["topic_id"], go);
+            assert_eq!(data["progress"]["mastery_level"], 1);
+            assert_eq!(data["progress"]["times_reviewed"], 1);
+            assert!(data["progress"]["next_review"].is_string());
+            assert_eq!(
+                data["reasoning"]["next_review"],
+                data["progress"]["next_review"]
+            );
+            assert_eq!(data["reasoning"]["new_level"], 1);
+        }
+
+        #[test]
+        fn next_and_next_explain() {
+            let db = seeded_db();
+            let explanation = db
+                .explain_next_topic(None, SelectionStrategy::Weighted)
+                .unwrap()
+                .unwrap();
+
+            assert_topic_with_progress(&round_trip(&explanation.selected));
+
+            let data = round_trip(&explanation);
+            assert_fields(&data, &["selected", "candidates", "total_weight", "draw"]);
+            assert_topic_with_progress(&data["selected"]);
+            for candidate in data["candidates"].as_array().unwrap() {
+                assert_fields(
+                    candidate,
+                    &[
+                        "topic_id",
+                        "weight",
+                        "overdue_days",
+                        "mastery_factor",
+                        "recency_factor",
+                    ],
+                );
+            }
+        }
+
+        #[test]
+        fn review_history() {
+            let db = seeded_db();
+            let reviews = db.get_review_history(None, None, Some(1)).unwrap();
+            let last_id = reviews.last().map(|r| r.id);
+            let data = round_trip(&ReviewPage { reviews, last_id });
+
+            assert_fields(&data, &["reviews", "last_id"]);
+            assert_eq!(data["last_id"], 1);
+            let review = &data["reviews"][0];
+            assert_fields(
+                review,
+                &["id", "topic_id", "outcome", "reviewed_at", "notes"],
+            );
+            assert_eq!(review["outcome"], "Success");
+        }
+
+        #[test]
+        fn review_explain() {
+            let db = seeded_db();
+            let reasoning = db
+                .record_review_with(1, ReviewOutcome::Fail, None, &ReviewOptions::default())
+                .unwrap()
+                .unwrap();
+            let data = round_trip(&reasoning);
+            assert_fields(
+                &data,
+                &[
+                    "previous_level",
+                    "new_level",
+                    "grading_delta",
+                    "level_delta",
+                    "previous_interval_minutes",
+                    "difficulty",
+                    "interval_minutes",
+                    "retry_now",
+                    "next_review",
+                ],
+            );
+            assert_eq!(data["previous_level"], 1);
+            assert_eq!(data["new_level"], 0);
+            assert_eq!(data["interval_minutes"], 24 * 60);
+        }
+
+        #[test]
+        fn version_info() {
+            let db = seeded_db();
+            let info = super::version_info(&db, Path::new("/tmp/feynman.db")).unwrap();
+            let data = round_trip(&info);
+            assert_fields(
+                &data,
+                &[
+                    "version",
+                    "git_commit",
+                    "sqlite_version",
+                    "db_path",
+                    "schema_version",
+                    "latest_schema_version",
+                ],
+            );
+            assert_eq!(data["version"], env!("CARGO_PKG_VERSION"));
+            assert_eq!(data["db_path"], "/tmp/feynman.db");
+            assert_eq!(data["schema_version"], db::SCHEMA_VERSION);
+        }
+
+        #[test]
+        fn import_summary() {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            let data: ImportData = serde_json::from_str(
+                r#"{"topics": [{"id": 1, "name": "Rust"}],
+                    "reviews": [{"topic_id": 1, "outcome": "success", "reviewed_at": "2024-01-01T00:00:00Z"}]}"#,
+            )
+            .unwrap();
+            let validation = data.validate(&HashSet::new());
+            let summary = db.import_data(&data, &validation).unwrap();
+
+            let data = round_trip(&summary);
+            assert_fields(&data, &["topics", "reviews", "sessions", "gaps", "skipped"]);
+            assert_eq!(data["topics"], 1);
+            assert_eq!(data["reviews"], 1);
+        }
+    }
+
+    mod review_time_tests {
+        use super::*;
+
+        #[test]
+        fn intervals_show_whole_days_or_a_duration() {
+            assert_eq!(format_interval(24 * 60), "1 day");
+            assert_eq!(format_interval(7 * 24 * 60), "7 days");
+            assert_eq!(format_interval(10), "10m");
+            assert_eq!(format_interval(36 * 60), "36h 00m");
+            assert_eq!(format_interval(0), "0s");
+        }
+
+        fn now() -> DateTime<Utc> {
+            DateTime::parse_from_rfc3339("2024-03-10T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc)
+        }
+
+        #[test]
+        fn accepts_rfc3339_and_bare_dates() {
+            assert_eq!(
+                parse_review_time("2024-03-01T09:30:00+02:00", now(), Timezone::Utc)
+                    .unwrap()
+                    .to_rfc3339(),
+                "2024-03-01T07:30:00+00:00"
+            );
+            assert_eq!(
+                parse_review_time("2024-03-01", now(), Timezone::Utc)
+                    .unwrap()
+                    .to_rfc3339(),
+                "2024-03-01T00:00:00+00:00"
+            );
+            assert!(parse_review_time("2024-03-10", now(), Timezone::Utc).is_ok());
+        }
+
+        #[test]
+        fn bare_dates_start_in_the_configured_timezone() {
+            let tz = Timezone::try_from(String::from("-05:00")).unwrap();
+            assert_eq!(
+                parse_review_time("2024-03-01", now(), tz)
+                    .unwrap()
+                    .to_rfc3339(),
+                "2024-03-01T05:00:00+00:00"
+            );
+            // Explicit offsets are kept as given
+            assert_eq!(
+                parse_review_time("2024-03-01T09:30:00+02:00", now(), tz)
+                    .unwrap()
+                    .to_rfc3339(),
+                "2024-03-01T07:30:00+00:00"
+            );
+        }
+
+        #[test]
+        fn rejects_future_and_malformed_times() {
+            assert!(parse_review_time("2024-03-11", now(), Timezone::Utc)
+                .unwrap_err()
+                .contains("future"));
+            assert!(parse_review_time("2024-03-10T12:00:01Z", now(), Timezone::Utc).is_err());
+            assert!(parse_review_time("last week", now(), Timezone::Utc).is_err());
+        }
+    }
+
+    mod topic_add_stdin_tests {
+        use super::*;
+
+        #[test]
+        fn adds_each_line_and_prints_ids() {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            db.add_topic("Existing", None, &[]).unwrap();
+
+            let input = "Ownership\nLifetimes\tHow long refs live\trust,memory\n\nTraits\n";
+            let mut out = Vec::new();
+            let (ids, _) = add_topic_lines(&db, input, &mut out).unwrap();
+
+            assert_eq!(ids, vec![2, 3, 4]);
+            assert_eq!(String::from_utf8(out).unwrap(), "2\n3\n4\n");
+            let lifetimes = db.get_topic(3).unwrap().unwrap();
+            assert_eq!(lifetimes.name, "Lifetimes");
+            assert_eq!(lifetimes.description.as_deref(), Some("How long refs live"));
+            assert_eq!(lifetimes.tags, ["memory", "rust"]);
+            assert_eq!(db.get_stats().unwrap().total_topics, 4);
+        }
+
+        #[test]
+        fn bad_line_creates_nothing() {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+
+            let mut out = Vec::new();
+            let err = add_topic_lines(&db, "Ownership\n\tno name\n", &mut out).unwrap_err();
+            assert_eq!(err.to_string(), "line 2: missing topic name");
+            assert!(out.is_empty());
+            assert_eq!(db.get_stats().unwrap().total_topics, 0);
+        }
+    }
+
+    mod tag_warning_tests {
+        use super::*;
+
+        #[test]
+        fn edit_distance_counts_single_edits() {
+            assert_eq!(edit_distance("rust", "rust"), 0);
+            assert_eq!(edit_distance("rust", "rusty"), 1);
+            assert_eq!(edit_distance("rust", "rst"), 1);
+            assert_eq!(edit_distance("rust", "bust"), 1);
+            assert_eq!(edit_distance("rust", "rsut"), 2);
+            assert_eq!(edit_distance("", "go"), 2);
+        }
+
+        #[test]
+        fn warns_about_near_duplicate_tags_only() {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            db.add_topic("A", None, &["rust".to_string(), "database".to_string()])
+                .unwrap();
+
+            let tags: Vec<String> = ["rustt", "Rust", " rust", "python", "Databse"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect();
+            let warnings = near_duplicate_tag_warnings(&db, &tags).unwrap();
+            assert_eq!(
+                warnings,
+                [
+                    "tag 'rustt' is new; did you mean 'rust'?",
+                    "tag 'Databse' is new; did you mean 'database'?",
+                ]
+            );
+        }
+    }
+
+    mod topic_show_tests {
+        use super::*;
+
+        #[test]
+        fn warns_about_malformed_next_review() {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            let id = db.add_topic("Rust", None, &[]).unwrap();
+            let mut progress = db.get_progress(id).unwrap().unwrap();
+            assert!(next_review_warning(&progress).is_none());
+
+            progress.next_review = Some("next tuesday".to_string());
+            let warning = next_review_warning(&progress).unwrap();
+            assert!(warning.contains("\"next tuesday\""));
+            assert!(warning.contains(&format!("rebuild-progress {}", id)));
+        }
+    }
+
+    mod next_tests {
+        use super::*;
+
+        fn setup_db() -> Database {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            db
+        }
+
+        #[test]
+        fn select_next_topic_preview_creates_no_session() {
+            let db = setup_db();
+            db.add_topic("Rust", None, &[]).unwrap();
+
+            let (explanation, session_id) =
+                select_next_topic(&db, None, SelectionStrategy::Weighted, None, None)
+                    .unwrap()
+                    .unwrap();
+            assert!(session_id.is_none());
+            assert!(db
+                .list_sessions(Some(explanation.selected.topic.id))
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn select_next_topic_starts_one_session() {
+            let db = setup_db();
+            db.add_topic("Rust", None, &[]).unwrap();
+            db.add_topic("Go", None, &[]).unwrap();
+
+            let (explanation, session_id) = select_next_topic(
+                &db,
+                None,
+                SelectionStrategy::Weighted,
+                None,
+                Some(SessionType::Feynman),
+            )
+            .unwrap()
+            .unwrap();
+
+            let sessions = db.list_sessions(None).unwrap();
+            assert_eq!(sessions.len(), 1);
+            assert_eq!(sessions[0].id, session_id.unwrap());
+            assert_eq!(sessions[0].topic_id, explanation.selected.topic.id);
+            assert_eq!(sessions[0].session_type, SessionType::Feynman);
+        }
+
+        #[test]
+        fn select_next_topic_empty_db() {
+            let db = setup_db();
+            assert!(select_next_topic(
+                &db,
+                None,
+                SelectionStrategy::Weighted,
+                None,
+                Some(SessionType::Socratic)
+            )
+            .unwrap()
+            .is_none());
+            assert!(db.list_sessions(None).unwrap().is_empty());
+        }
+    }
+
+    mod cli_parsing_tests {
+        use super::*;
+
+        #[test]
+        fn parse_init_command() {
+            let cli = Cli::try_parse_from(["feynman", "init"]).unwrap();
+            assert!(!cli.json);
+            assert!(matches!(cli.command, Commands::Init));
+        }
+
+        #[test]
+        fn parse_init_with_json() {
+            let cli = Cli::try_parse_from(["feynman", "--json", "init"]).unwrap();
+            assert!(cli.json);
+            assert!(matches!(cli.command, Commands::Init));
+        }
+
+        #[test]
+        fn parse_topic_list() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "list"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List { tag, .. }) => {
+                    assert!(tag.is_none());
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_list_detailed() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "list", "--detailed"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::List { detailed: true, .. })
+            ));
+        }
+
+        #[test]
+        fn parse_topic_list_sort() {
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "list", "--sort", "health"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::List {
+                    sort: Some(TopicSort::Health),
+                    ..
+                })
+            ));
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "list", "--sort", "next-review"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::List {
+                    sort: Some(TopicSort::NextReview),
+                    ..
+                })
+            ));
+            assert!(Cli::try_parse_from([
+                "feynman", "topic", "list", "--sort", "health", "--after", "3"
+            ])
+            .is_err());
+        }
+
+        #[test]
+        fn parse_topic_list_with_tag() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "list", "--tag", "rust"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List { tag, .. }) => {
+                    assert_eq!(tag, Some("rust".to_string()));
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_list_due_filters() {
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "list", "--due-only", "--tag", "rust"])
+                    .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List {
+                    due_only,
+                    overdue_only,
+                    tag,
+                    ..
+                }) => {
+                    assert!(due_only && !overdue_only);
+                    assert_eq!(tag, Some("rust".to_string()));
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+            assert!(Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "list",
+                "--due-only",
+                "--overdue-only"
+            ])
+            .is_err());
+        }
+
+        #[test]
+        fn skill_levels_parse_from_numbers_and_names() {
+            assert_eq!(parse_skill("0"), Ok(SkillLevel::Unknown));
+            assert_eq!(parse_skill(" 4 "), Ok(SkillLevel::Advanced));
+            assert_eq!(parse_skill("Novice"), Ok(SkillLevel::Novice));
+            assert_eq!(parse_skill("EXPERT"), Ok(SkillLevel::Expert));
+            let expected = "expected 0-5 or one of: unknown, novice, beginner, \
+                            intermediate, advanced, expert";
+            for bad in ["6", "-1", "wizard", ""] {
+                assert_eq!(parse_skill(bad), Err(expected.to_string()), "{:?}", bad);
+            }
+        }
+
+        #[test]
+        fn level_errors_name_the_valid_inputs() {
+            assert_eq!(parse_mastery("proficient"), Ok(4));
+            assert_eq!(
+                parse_mastery("7").unwrap_err(),
+                "expected 0-5 or one of: new, learning, familiar, comfortable, proficient, mastered"
+            );
+            assert_eq!(parse_rating("5"), Ok(5));
+            assert_eq!(
+                parse_rating("hard").unwrap_err(),
+                "expected a whole number from 1 to 5"
+            );
+            let e = Cli::try_parse_from(["feynman", "topic", "difficulty", "1", "0"])
+                .err()
+                .unwrap()
+                .to_string();
+            assert!(e.contains("expected a whole number from 1 to 5"), "{}", e);
+        }
+
+        #[test]
+        fn parse_topic_add_with_starting_progress() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "add",
+                "Ownership",
+                "--mastery",
+                "3",
+                "--skill",
+                "Advanced",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Add { mastery, skill, .. }) => {
+                    assert_eq!(mastery, Some(3));
+                    assert_eq!(skill, Some(SkillLevel::Advanced));
+                }
+                _ => panic!("Expected Topic Add command"),
+            }
+
+            let skill = |value: &str| match Cli::try_parse_from([
+                "feynman", "topic", "add", "X", "--skill", value,
+            ])
+            .map(|cli| cli.command)
+            {
+                Ok(Commands::Topic(TopicCommands::Add { skill, .. })) => skill,
+                _ => None,
+            };
+            assert_eq!(skill("4"), Some(SkillLevel::Advanced));
+            assert_eq!(skill("wizard"), None);
+            for bad in ["6", "-1", "high"] {
+                assert!(
+                    Cli::try_parse_from(["feynman", "topic", "add", "X", "--mastery", bad])
+                        .is_err()
+                );
+            }
+            assert!(
+                Cli::try_parse_from(["feynman", "topic", "add", "--stdin", "--mastery", "2"])
+                    .is_err()
+            );
+        }
+
+        #[test]
+        fn parse_topic_list_with_tag_short() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "list", "-t", "rust"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List { tag, .. }) => {
+                    assert_eq!(tag, Some("rust".to_string()));
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_list_include_flags() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "list",
+                "--include-archived",
+                "--include-suspended",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List {
+                    include_archived,
+                    include_suspended,
+                    ..
+                }) => {
+                    assert!(include_archived);
+                    assert!(include_suspended);
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_list_format() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "list"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List { format, .. }) => {
+                    assert_eq!(format, "table");
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "list", "--format", "tree"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List { format, .. }) => {
+                    assert_eq!(format, "tree");
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+
+            assert!(Cli::try_parse_from(["feynman", "topic", "list", "--format", "csv"]).is_err());
+        }
+
+        #[test]
+        fn parse_topic_list_pagination() {
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "list", "--after", "20", "--limit", "10"])
+                    .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::List { after, limit, .. }) => {
+                    assert_eq!(after, Some(20));
+                    assert_eq!(limit, Some(10));
+                }
+                _ => panic!("Expected Topic List command"),
+            }
+        }
+
+        #[test]
+        fn parse_history_command() {
+            let cli = Cli::try_parse_from(["feynman", "history"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::History {
+                    topic_id: None,
+                    after: None,
+                    limit: None
+                }
+            ));
+
+            let cli =
+                Cli::try_parse_from(["feynman", "history", "3", "--after", "40", "--limit", "5"])
+                    .unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::History {
+                    topic_id: Some(3),
+                    after: Some(40),
+                    limit: Some(5)
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_topic_difficulty() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "difficulty", "4", "5"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Difficulty { id: 4, level: 5 })
+            ));
+
+            for bad in ["0", "6", "hard"] {
+                assert!(Cli::try_parse_from(["feynman", "topic", "difficulty", "4", bad]).is_err());
+            }
+        }
+
+        #[test]
+        fn parse_topic_archive_undo() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "archive", "4", "--undo"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Archive { id, undo }) => {
+                    assert_eq!(id, 4);
+                    assert!(undo);
+                }
+                _ => panic!("Expected Topic Archive command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_add_basic() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "add", "Rust Basics"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Add {
+                    name,
+                    description,
+                    tags,
+                    ..
+                }) => {
+                    assert_eq!(name.as_deref(), Some("Rust Basics"));
+                    assert!(description.is_none());
+                    assert!(tags.is_none());
+                }
+                _ => panic!("Expected Topic Add command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_add_with_description() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "add",
+                "Rust Basics",
+                "--description",
+                "Learn fundamentals",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Add {
+                    name,
+                    description,
+                    tags,
+                    ..
+                }) => {
+                    assert_eq!(name.as_deref(), Some("Rust Basics"));
+                    assert_eq!(description, Some("Learn fundamentals".to_string()));
+                    assert!(tags.is_none());
+                }
+                _ => panic!("Expected Topic Add command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_add_with_tags() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "add",
+                "Rust Basics",
+                "--tags",
+                "rust,programming",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Add {
+                    name,
+                    description,
+                    tags,
+                    ..
+                }) => {
+                    assert_eq!(name.as_deref(), Some("Rust Basics"));
+                    assert!(description.is_none());
+                    assert_eq!(tags, Some("rust,programming".to_string()));
+                }
+                _ => panic!("Expected Topic Add command"),
+            }
+        }
+
+        #[test]
+        fn parse_topic_add_stdin() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "add", "--stdin"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Add {
+                    name: None,
+                    stdin: true,
+                    ..
+                })
+            ));
+            assert!(Cli::try_parse_from(["feynman", "topic", "add"]).is_err());
+            assert!(Cli::try_parse_from(["feynman", "topic", "add", "X", "--stdin"]).is_err());
+        }
+
+        #[test]
+        fn parse_topic_import_json() {
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "import-json", "deck.json"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::ImportJson { file, on_conflict }) => {
+                    assert_eq!(file, PathBuf::from("deck.json"));
+                    assert_eq!(on_conflict, OnConflict::Fail);
+                }
+                _ => panic!("Expected Topic ImportJson command"),
+            }
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "import-json",
+                "deck.json",
+                "--on-conflict",
+                "update",
+            ])
+            .unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::ImportJson {
+                    on_conflict: OnConflict::Update,
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        fn parse_topic_add_full() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "add",
+                "Rust Basics",
+                "-d",
+                "Description",
+                "-t",
+                "tag1,tag2",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Add {
+                    name,
+                    description,
+                    tags,
+                    ..
+                }) => {
+                    assert_eq!(name.as_deref(), Some("Rust Basics"));
+                    assert_eq!(description, Some("Description".to_string()));
+                    assert_eq!(tags, Some("tag1,tag2".to_string()));
+                }
+                _ => panic!("Expected Topic Add command"),
+            }
         }
-    }
 
-    Ok(())
-}
+        #[test]
+        fn parse_topic_show() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "show", "42"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Show { id, .. }) => {
+                    assert_eq!(id, 42);
+                }
+                _ => panic!("Expected Topic Show command"),
+            }
+        }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len - 3])
-    }
-}
+        #[test]
+        fn parse_topic_merge() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "merge", "4", "2"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Merge {
+                    source_id: 4,
+                    target_id: 2
+                })
+            ));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::Parser;
+        #[test]
+        fn parse_topic_delete() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "delete", "5"]).unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Delete { id, purge, .. }) => {
+                    assert_eq!(id, Some(5));
+                    assert!(!purge);
+                }
+                _ => panic!("Expected Topic Delete command"),
+            }
+        }
 
-    mod truncate_tests {
-        use super::*;
+        #[test]
+        fn parse_topic_delete_purge() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "delete", "5", "--purge"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Delete {
+                    id: Some(5),
+                    purge: true,
+                    ..
+                })
+            ));
+        }
 
         #[test]
-        fn truncate_short_string() {
-            assert_eq!(truncate("hello", 10), "hello");
+        fn parse_topic_delete_by_tag() {
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "delete", "--tag", "rust", "--dry-run"])
+                    .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Delete {
+                    id, tag, dry_run, ..
+                }) => {
+                    assert_eq!(id, None);
+                    assert_eq!(tag.as_deref(), Some("rust"));
+                    assert!(dry_run);
+                }
+                _ => panic!("Expected Topic Delete command"),
+            }
+            assert!(!cli.yes);
+
+            // An id or a tag is required, but not both
+            assert!(Cli::try_parse_from(["feynman", "topic", "delete"]).is_err());
+            assert!(
+                Cli::try_parse_from(["feynman", "topic", "delete", "5", "--tag", "rust"]).is_err()
+            );
+            assert!(Cli::try_parse_from(["feynman", "topic", "delete", "5", "--dry-run"]).is_err());
         }
 
         #[test]
-        fn truncate_exact_length() {
-            assert_eq!(truncate("hello", 5), "hello");
+        fn parse_topic_tag() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "tag", "3", "--tags", "new,tags"])
+                .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Tag { id, tags }) => {
+                    assert_eq!(id, 3);
+                    assert_eq!(tags, "new,tags");
+                }
+                _ => panic!("Expected Topic Tag command"),
+            }
         }
 
         #[test]
-        fn truncate_long_string() {
-            assert_eq!(truncate("hello world", 8), "hello...");
+        fn parse_topic_prompt_commands() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "prompt",
+                "add",
+                "3",
+                "Explain it to a five-year-old",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Prompt(PromptCommands::Add {
+                    topic_id,
+                    prompt,
+                })) => {
+                    assert_eq!(topic_id, 3);
+                    assert_eq!(prompt, "Explain it to a five-year-old");
+                }
+                _ => panic!("Expected Topic Prompt Add command"),
+            }
+
+            let cli = Cli::try_parse_from(["feynman", "topic", "prompt", "remove", "7"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Prompt(PromptCommands::Remove { id: 7 }))
+            ));
+            assert!(Cli::try_parse_from(["feynman", "topic", "prompt", "list"]).is_err());
         }
 
         #[test]
-        fn truncate_empty_string() {
-            assert_eq!(truncate("", 10), "");
+        fn parse_topic_resource_commands() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "topic",
+                "resource",
+                "add",
+                "3",
+                "https://doc.rust-lang.org/book/",
+                "--label",
+                "The Book",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Topic(TopicCommands::Resource(ResourceCommands::Add {
+                    topic_id,
+                    url,
+                    label,
+                })) => {
+                    assert_eq!(topic_id, 3);
+                    assert_eq!(url, "https://doc.rust-lang.org/book/");
+                    assert_eq!(label.as_deref(), Some("The Book"));
+                }
+                _ => panic!("Expected Topic Resource Add command"),
+            }
+
+            let cli = Cli::try_parse_from(["feynman", "topic", "resource", "remove", "7"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Resource(ResourceCommands::Remove { id: 7 }))
+            ));
+            assert!(Cli::try_parse_from(["feynman", "topic", "resource", "add", "3"]).is_err());
         }
 
         #[test]
-        fn truncate_minimum_length() {
-            // With max_len = 4, we get 1 char + "..."
-            assert_eq!(truncate("hello", 4), "h...");
+        fn parse_tags_command() {
+            let cli = Cli::try_parse_from(["feynman", "tags"]).unwrap();
+            assert!(matches!(cli.command, Commands::Tags { with_topics: false }));
+            let cli = Cli::try_parse_from(["feynman", "tags", "--with-topics"]).unwrap();
+            assert!(matches!(cli.command, Commands::Tags { with_topics: true }));
         }
-    }
 
-    mod cli_parsing_tests {
-        use super::*;
+        #[test]
+        fn parse_tag_apply_and_strip() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "tag",
+                "apply",
+                "priority",
+                "--filter-tag",
+                "rust",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Tag(TagCommands::Apply { tag, filter_tag }) => {
+                    assert_eq!(tag, "priority");
+                    assert_eq!(filter_tag, "rust");
+                }
+                _ => panic!("Expected Tag Apply command"),
+            }
+
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "tag",
+                "strip",
+                "priority",
+                "--filter-tag",
+                "rust",
+            ])
+            .unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Tag(TagCommands::Strip { .. })
+            ));
+
+            // The filter is required so a typo can't retag the whole deck
+            assert!(Cli::try_parse_from(["feynman", "tag", "apply", "priority"]).is_err());
+        }
 
         #[test]
-        fn parse_init_command() {
-            let cli = Cli::try_parse_from(["feynman", "init"]).unwrap();
-            assert!(!cli.json);
-            assert!(matches!(cli.command, Commands::Init));
+        fn parse_config_set_accepts_negative_values() {
+            let cli = Cli::try_parse_from(["feynman", "config", "set", "grading.fail.delta", "-2"])
+                .unwrap();
+            match cli.command {
+                Commands::Config(ConfigCommands::Set { key, value }) => {
+                    assert_eq!(key, "grading.fail.delta");
+                    assert_eq!(value, "-2");
+                }
+                _ => panic!("Expected Config Set command"),
+            }
+        }
+
+        #[test]
+        fn parse_session_end_with_repeated_gaps() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "session",
+                "end",
+                "7",
+                "--outcome",
+                "partial",
+                "--gap",
+                "a",
+                "--gap",
+                "b",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Session(SessionCommands::End {
+                    id, outcome, gaps, ..
+                }) => {
+                    assert_eq!(id, 7);
+                    assert_eq!(outcome, SessionOutcome::Partial);
+                    assert_eq!(gaps, ["a", "b"]);
+                }
+                _ => panic!("Expected Session End command"),
+            }
+        }
+
+        #[test]
+        fn parse_session_end_transcript_file() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "session",
+                "end",
+                "7",
+                "--outcome",
+                "success",
+                "--transcript-file",
+                "session.md",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Session(SessionCommands::End {
+                    transcript_file, ..
+                }) => assert_eq!(transcript_file, Some(PathBuf::from("session.md"))),
+                _ => panic!("Expected Session End command"),
+            }
+        }
+
+        #[test]
+        fn parse_session_batch_commands() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "session",
+                "start-batch",
+                "--tag",
+                "rust",
+                "--type",
+                "Socratic",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Session(SessionCommands::StartBatch { tag, session_type }) => {
+                    assert_eq!(tag, "rust");
+                    assert_eq!(session_type, SessionType::Socratic);
+                }
+                _ => panic!("Expected Session StartBatch command"),
+            }
+
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "session",
+                "end-batch",
+                "--outcome",
+                "partial",
+                "--ids",
+                "4,5,6",
+            ])
+            .unwrap();
+            match cli.command {
+                Commands::Session(SessionCommands::EndBatch { ids, since, .. }) => {
+                    assert_eq!(ids, [4, 5, 6]);
+                    assert_eq!(since, None);
+                }
+                _ => panic!("Expected Session EndBatch command"),
+            }
+
+            // One of --ids or --since, not both
+            let base = ["feynman", "session", "end-batch", "--outcome", "success"];
+            assert!(Cli::try_parse_from(base).is_err());
+            assert!(Cli::try_parse_from(base.iter().chain(&["--since", "2024-01-01"])).is_ok());
+            assert!(Cli::try_parse_from(base.iter().chain(&[
+                "--since",
+                "2024-01-01",
+                "--ids",
+                "1"
+            ]))
+            .is_err());
+        }
+
+        #[test]
+        fn parse_plan_note_and_notes() {
+            let cli =
+                Cli::try_parse_from(["feynman", "plan", "note", "3", "Ask about quotas"]).unwrap();
+            match cli.command {
+                Commands::Plan(PlanCommands::Note { plan_id, content }) => {
+                    assert_eq!(plan_id, 3);
+                    assert_eq!(content, "Ask about quotas");
+                }
+                _ => panic!("Expected Plan Note command"),
+            }
+            let cli = Cli::try_parse_from(["feynman", "plan", "notes", "3"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Plan(PlanCommands::Notes { plan_id: 3 })
+            ));
+        }
+
+        #[test]
+        fn parse_next_strategy() {
+            let cli = Cli::try_parse_from(["feynman", "next"]).unwrap();
+            assert!(matches!(cli.command, Commands::Next { strategy: None, .. }));
+
+            let cli = Cli::try_parse_from(["feynman", "next", "--strategy", "LRU"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Next {
+                    strategy: Some(SelectionStrategy::Lru),
+                    ..
+                }
+            ));
+            assert!(Cli::try_parse_from(["feynman", "next", "--strategy", "fifo"]).is_err());
+        }
+
+        #[test]
+        fn parse_next_new_ratio() {
+            let cli = Cli::try_parse_from(["feynman", "next", "--new-ratio", "0.25"]).unwrap();
+            match cli.command {
+                Commands::Next { new_ratio, .. } => assert_eq!(new_ratio, Some(0.25)),
+                _ => panic!("Expected Next command"),
+            }
+            assert!(Cli::try_parse_from(["feynman", "next", "--new-ratio", "1.5"]).is_err());
+            assert!(Cli::try_parse_from(["feynman", "next", "--new-ratio", "half"]).is_err());
+        }
+
+        #[test]
+        fn parse_schedule_command() {
+            let cli = Cli::try_parse_from(["feynman", "schedule"]).unwrap();
+            assert!(matches!(cli.command, Commands::Schedule));
+        }
+
+        #[test]
+        fn parse_reschedule_with_tag() {
+            let cli = Cli::try_parse_from(["feynman", "reschedule", "--tag", "rust"]).unwrap();
+            match cli.command {
+                Commands::Reschedule { tag } => assert_eq!(tag.as_deref(), Some("rust")),
+                _ => panic!("Expected Reschedule command"),
+            }
+        }
+
+        #[test]
+        fn parse_serve_defaults_to_localhost() {
+            let cli = Cli::try_parse_from(["feynman", "serve"]).unwrap();
+            match cli.command {
+                Commands::Serve { port, host } => {
+                    assert_eq!(port, server::DEFAULT_PORT);
+                    assert_eq!(host, "127.0.0.1");
+                }
+                _ => panic!("Expected Serve command"),
+            }
+            let cli = Cli::try_parse_from(["feynman", "serve", "--port", "9000"]).unwrap();
+            assert!(matches!(cli.command, Commands::Serve { port: 9000, .. }));
+        }
+
+        #[test]
+        fn parse_doctor_command() {
+            let cli = Cli::try_parse_from(["feynman", "doctor"]).unwrap();
+            assert!(matches!(cli.command, Commands::Doctor { fix: false }));
+            let cli = Cli::try_parse_from(["feynman", "doctor", "--fix"]).unwrap();
+            assert!(matches!(cli.command, Commands::Doctor { fix: true }));
+        }
+
+        #[test]
+        fn parse_version_command() {
+            let cli = Cli::try_parse_from(["feynman", "version"]).unwrap();
+            assert!(matches!(cli.command, Commands::Version { verbose: false }));
+            let cli = Cli::try_parse_from(["feynman", "version", "--verbose"]).unwrap();
+            assert!(matches!(cli.command, Commands::Version { verbose: true }));
+        }
+
+        #[test]
+        fn parse_maintain_command() {
+            let cli = Cli::try_parse_from(["feynman", "maintain"]).unwrap();
+            assert!(matches!(cli.command, Commands::Maintain));
+        }
+
+        #[test]
+        fn parse_stats_command() {
+            let cli = Cli::try_parse_from(["feynman", "stats"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Stats {
+                    calibration: false,
+                    histogram: false,
+                    prometheus: false
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_stats_calibration() {
+            let cli = Cli::try_parse_from(["feynman", "stats", "--calibration"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Stats {
+                    calibration: true,
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_stats_prometheus() {
+            for flag in ["--prometheus", "--export-prometheus"] {
+                let cli = Cli::try_parse_from(["feynman", "stats", flag]).unwrap();
+                assert!(matches!(
+                    cli.command,
+                    Commands::Stats {
+                        prometheus: true,
+                        ..
+                    }
+                ));
+            }
+            assert!(
+                Cli::try_parse_from(["feynman", "stats", "--prometheus", "--histogram"]).is_err()
+            );
+        }
+
+        #[test]
+        fn parse_sessions_filters() {
+            let cli = Cli::try_parse_from(["feynman", "sessions"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Sessions {
+                    topic: None,
+                    active: false,
+                    outcome: None,
+                    implicit: false,
+                    ..
+                }
+            ));
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "sessions",
+                "--active",
+                "--topic",
+                "3",
+                "--outcome",
+                "Abandoned",
+            ])
+            .unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Sessions {
+                    topic: Some(3),
+                    active: true,
+                    outcome: Some(SessionOutcome::Abandoned),
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_sessions_recent() {
+            let cli = Cli::try_parse_from(["feynman", "sessions", "recent"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Sessions {
+                    view: Some(SessionsView::Recent {
+                        limit: tui::DEFAULT_DASHBOARD_LIMIT,
+                        offset: 0
+                    }),
+                    ..
+                }
+            ));
+            let cli = Cli::try_parse_from([
+                "feynman", "sessions", "recent", "--limit", "3", "--offset", "6",
+            ])
+            .unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Sessions {
+                    view: Some(SessionsView::Recent {
+                        limit: 3,
+                        offset: 6
+                    }),
+                    ..
+                }
+            ));
+            // The list filters don't apply to a page of recent sessions
+            assert!(Cli::try_parse_from(["feynman", "sessions", "--active", "recent"]).is_err());
+        }
+
+        #[test]
+        fn parse_sessions_implicit() {
+            let cli = Cli::try_parse_from(["feynman", "sessions", "--implicit"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Sessions {
+                    implicit: true,
+                    gap: DEFAULT_STUDY_GAP_MINUTES,
+                    ..
+                }
+            ));
+            let cli =
+                Cli::try_parse_from(["feynman", "sessions", "--implicit", "--gap", "10"]).unwrap();
+            assert!(matches!(cli.command, Commands::Sessions { gap: 10, .. }));
+
+            assert!(Cli::try_parse_from(["feynman", "sessions", "--gap", "10"]).is_err());
+            assert!(
+                Cli::try_parse_from(["feynman", "sessions", "--implicit", "--gap", "0"]).is_err()
+            );
+            assert!(
+                Cli::try_parse_from(["feynman", "sessions", "--implicit", "--active"]).is_err()
+            );
+        }
+
+        #[test]
+        fn parse_topic_show_history() {
+            let cli = Cli::try_parse_from(["feynman", "topic", "show", "4", "--history"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Show {
+                    id: 4,
+                    history: true,
+                    next_action: false
+                })
+            ));
+            let cli =
+                Cli::try_parse_from(["feynman", "topic", "show", "4", "--next-action"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Topic(TopicCommands::Show {
+                    next_action: true,
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        fn parse_stats_histogram() {
+            let cli = Cli::try_parse_from(["feynman", "stats", "--histogram"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Stats {
+                    histogram: true,
+                    ..
+                }
+            ));
+            assert!(
+                Cli::try_parse_from(["feynman", "stats", "--histogram", "--calibration"]).is_err()
+            );
+        }
+
+        #[test]
+        fn parse_review_explain() {
+            let cli =
+                Cli::try_parse_from(["feynman", "review", "1", "-o", "s", "--explain"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Review { explain: true, .. }
+            ));
+            let cli = Cli::try_parse_from(["feynman", "review", "1", "-o", "s"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Review { explain: false, .. }
+            ));
+        }
+
+        #[test]
+        fn parse_review_confidence_range() {
+            let cli =
+                Cli::try_parse_from(["feynman", "review", "1", "-o", "fail", "--confidence", "5"])
+                    .unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Review {
+                    confidence: Some(5),
+                    ..
+                }
+            ));
+            for bad in ["0", "6", "high"] {
+                assert!(
+                    Cli::try_parse_from(["feynman", "review", "1", "--confidence", bad]).is_err()
+                );
+            }
         }
 
         #[test]
-        fn parse_init_with_json() {
-            let cli = Cli::try_parse_from(["feynman", "--json", "init"]).unwrap();
-            assert!(cli.json);
-            assert!(matches!(cli.command, Commands::Init));
+        fn parse_status_compact() {
+            let cli = Cli::try_parse_from(["feynman", "status", "--compact"]).unwrap();
+            assert!(matches!(cli.command, Commands::Status { compact: true }));
         }
 
         #[test]
-        fn parse_topic_list() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "list"]).unwrap();
+        fn parse_next_command() {
+            let cli = Cli::try_parse_from(["feynman", "next"]).unwrap();
             match cli.command {
-                Commands::Topic(TopicCommands::List { tag }) => {
+                Commands::Next { tag, .. } => {
                     assert!(tag.is_none());
                 }
-                _ => panic!("Expected Topic List command"),
+                _ => panic!("Expected Next command"),
             }
         }
 
         #[test]
-        fn parse_topic_list_with_tag() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "list", "--tag", "rust"]).unwrap();
-            match cli.command {
-                Commands::Topic(TopicCommands::List { tag }) => {
-                    assert_eq!(tag, Some("rust".to_string()));
-                }
-                _ => panic!("Expected Topic List command"),
-            }
+        fn parse_next_explain() {
+            let cli = Cli::try_parse_from(["feynman", "--json", "next", "--explain"]).unwrap();
+            assert!(cli.json);
+            assert!(matches!(cli.command, Commands::Next { explain: true, .. }));
         }
 
         #[test]
-        fn parse_topic_list_with_tag_short() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "list", "-t", "rust"]).unwrap();
+        fn parse_next_with_tag() {
+            let cli = Cli::try_parse_from(["feynman", "next", "--tag", "rust"]).unwrap();
             match cli.command {
-                Commands::Topic(TopicCommands::List { tag }) => {
+                Commands::Next { tag, .. } => {
                     assert_eq!(tag, Some("rust".to_string()));
                 }
-                _ => panic!("Expected Topic List command"),
+                _ => panic!("Expected Next command"),
             }
         }
 
         #[test]
-        fn parse_topic_add_basic() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "add", "Rust Basics"]).unwrap();
+        fn parse_next_start_session() {
+            let cli =
+                Cli::try_parse_from(["feynman", "next", "--start-session", "feynman"]).unwrap();
             match cli.command {
-                Commands::Topic(TopicCommands::Add {
-                    name,
-                    description,
-                    tags,
-                }) => {
-                    assert_eq!(name, "Rust Basics");
-                    assert!(description.is_none());
-                    assert!(tags.is_none());
+                Commands::Next { start_session, .. } => {
+                    assert_eq!(start_session, Some(SessionType::Feynman));
                 }
-                _ => panic!("Expected Topic Add command"),
+                _ => panic!("Expected Next command"),
             }
         }
 
         #[test]
-        fn parse_topic_add_with_description() {
-            let cli = Cli::try_parse_from([
-                "feynman",
-                "topic",
-                "add",
-                "Rust Basics",
-                "--description",
-                "Learn fundamentals",
-            ])
-            .unwrap();
+        fn parse_review_command() {
+            let cli =
+                Cli::try_parse_from(["feynman", "review", "7", "--outcome", "success"]).unwrap();
             match cli.command {
-                Commands::Topic(TopicCommands::Add {
-                    name,
-                    description,
-                    tags,
-                }) => {
-                    assert_eq!(name, "Rust Basics");
-                    assert_eq!(description, Some("Learn fundamentals".to_string()));
-                    assert!(tags.is_none());
+                Commands::Review {
+                    id, outcome, notes, ..
+                } => {
+                    assert_eq!(id, ReviewTarget::Id(7));
+                    assert_eq!(outcome, Some(ReviewOutcome::Success));
+                    assert!(notes.is_none());
                 }
-                _ => panic!("Expected Topic Add command"),
+                _ => panic!("Expected Review command"),
             }
         }
 
         #[test]
-        fn parse_topic_add_with_tags() {
+        fn parse_review_with_notes() {
             let cli = Cli::try_parse_from([
                 "feynman",
-                "topic",
-                "add",
-                "Rust Basics",
-                "--tags",
-                "rust,programming",
+                "review",
+                "7",
+                "--outcome",
+                "partial",
+                "--notes",
+                "Struggled with X",
             ])
             .unwrap();
             match cli.command {
-                Commands::Topic(TopicCommands::Add {
-                    name,
-                    description,
-                    tags,
-                }) => {
-                    assert_eq!(name, "Rust Basics");
-                    assert!(description.is_none());
-                    assert_eq!(tags, Some("rust,programming".to_string()));
+                Commands::Review {
+                    id, outcome, notes, ..
+                } => {
+                    assert_eq!(id, ReviewTarget::Id(7));
+                    assert_eq!(outcome, Some(ReviewOutcome::Partial));
+                    assert_eq!(notes, Some("Struggled with X".to_string()));
                 }
-                _ => panic!("Expected Topic Add command"),
+                _ => panic!("Expected Review command"),
             }
         }
 
         #[test]
-        fn parse_topic_add_full() {
-            let cli = Cli::try_parse_from([
-                "feynman",
-                "topic",
-                "add",
-                "Rust Basics",
-                "-d",
-                "Description",
-                "-t",
-                "tag1,tag2",
-            ])
-            .unwrap();
+        fn parse_review_short_flags() {
+            let cli = Cli::try_parse_from(["feynman", "review", "1", "-o", "fail", "-n", "notes"])
+                .unwrap();
             match cli.command {
-                Commands::Topic(TopicCommands::Add {
-                    name,
-                    description,
-                    tags,
-                }) => {
-                    assert_eq!(name, "Rust Basics");
-                    assert_eq!(description, Some("Description".to_string()));
-                    assert_eq!(tags, Some("tag1,tag2".to_string()));
+                Commands::Review {
+                    id, outcome, notes, ..
+                } => {
+                    assert_eq!(id, ReviewTarget::Id(1));
+                    assert_eq!(outcome, Some(ReviewOutcome::Fail));
+                    assert_eq!(notes, Some("notes".to_string()));
                 }
-                _ => panic!("Expected Topic Add command"),
+                _ => panic!("Expected Review command"),
             }
         }
 
         #[test]
-        fn parse_topic_show() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "show", "42"]).unwrap();
-            match cli.command {
-                Commands::Topic(TopicCommands::Show { id }) => {
-                    assert_eq!(id, 42);
+        fn parse_review_without_outcome() {
+            let cli = Cli::try_parse_from(["feynman", "review", "3"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Review {
+                    id: ReviewTarget::Id(3),
+                    outcome: None,
+                    ..
                 }
-                _ => panic!("Expected Topic Show command"),
-            }
+            ));
         }
 
         #[test]
-        fn parse_topic_delete() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "delete", "5"]).unwrap();
-            match cli.command {
-                Commands::Topic(TopicCommands::Delete { id }) => {
-                    assert_eq!(id, 5);
+        fn parse_review_outcome_aliases_and_case() {
+            for (input, expected) in [
+                ("SUCCESS", ReviewOutcome::Success),
+                ("y", ReviewOutcome::Success),
+                ("ok", ReviewOutcome::Partial),
+                ("Bad", ReviewOutcome::Fail),
+                ("0", ReviewOutcome::Fail),
+            ] {
+                let cli = Cli::try_parse_from(["feynman", "review", "1", "-o", input]).unwrap();
+                match cli.command {
+                    Commands::Review { outcome, .. } => assert_eq!(outcome, Some(expected)),
+                    _ => panic!("Expected Review command"),
                 }
-                _ => panic!("Expected Topic Delete command"),
             }
         }
 
         #[test]
-        fn parse_topic_tag() {
-            let cli = Cli::try_parse_from(["feynman", "topic", "tag", "3", "--tags", "new,tags"])
+        fn parse_review_invalid_outcome_fails_at_parse_time() {
+            let err = Cli::try_parse_from(["feynman", "review", "1", "-o", "meh"])
+                .err()
                 .unwrap();
-            match cli.command {
-                Commands::Topic(TopicCommands::Tag { id, tags }) => {
-                    assert_eq!(id, 3);
-                    assert_eq!(tags, "new,tags");
+            assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+            let message = err.to_string();
+            assert!(message.contains("success"));
+            assert!(message.contains("partial"));
+            assert!(message.contains("fail"));
+        }
+
+        #[test]
+        fn parse_next_start_session_alias_and_invalid() {
+            let cli = Cli::try_parse_from(["feynman", "next", "--start-session", "S"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Next {
+                    start_session: Some(SessionType::Socratic),
+                    ..
                 }
-                _ => panic!("Expected Topic Tag command"),
-            }
+            ));
+
+            assert!(
+                Cli::try_parse_from(["feynman", "next", "--start-session", "lecture"]).is_err()
+            );
         }
 
         #[test]
-        fn parse_tags_command() {
-            let cli = Cli::try_parse_from(["feynman", "tags"]).unwrap();
-            assert!(matches!(cli.command, Commands::Tags));
+        fn parse_review_retry_now() {
+            let cli = Cli::try_parse_from(["feynman", "review", "1", "-o", "fail", "--retry-now"])
+                .unwrap();
+            match cli.command {
+                Commands::Review { retry_now, .. } => assert!(retry_now),
+                _ => panic!("Expected Review command"),
+            }
         }
 
         #[test]
-        fn parse_stats_command() {
-            let cli = Cli::try_parse_from(["feynman", "stats"]).unwrap();
-            assert!(matches!(cli.command, Commands::Stats));
+        fn parse_rebuild_progress() {
+            let cli = Cli::try_parse_from(["feynman", "rebuild-progress"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::RebuildProgress { topic_id: None }
+            ));
+
+            let cli = Cli::try_parse_from(["feynman", "rebuild-progress", "3"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::RebuildProgress { topic_id: Some(3) }
+            ));
         }
 
         #[test]
-        fn parse_next_command() {
-            let cli = Cli::try_parse_from(["feynman", "next"]).unwrap();
+        fn parse_prune_history() {
+            let cli = Cli::try_parse_from([
+                "feynman",
+                "prune-history",
+                "--before",
+                "2024-01-01",
+                "--dry-run",
+            ])
+            .unwrap();
             match cli.command {
-                Commands::Next { tag } => {
-                    assert!(tag.is_none());
+                Commands::PruneHistory { before, dry_run } => {
+                    assert_eq!(before, "2024-01-01");
+                    assert!(dry_run);
                 }
-                _ => panic!("Expected Next command"),
+                _ => panic!("Expected PruneHistory command"),
+            }
+            assert!(!cli.yes);
+
+            // --yes is global, so it can come before or after the command
+            for args in [
+                ["feynman", "-y", "prune-history", "--before", "2024-01-01"],
+                [
+                    "feynman",
+                    "prune-history",
+                    "--before",
+                    "2024-01-01",
+                    "--yes",
+                ],
+            ] {
+                assert!(Cli::try_parse_from(args).unwrap().yes);
             }
+
+            assert!(Cli::try_parse_from(["feynman", "prune-history"]).is_err());
+            assert!(parse_time_arg("--before", "last year", Timezone::Utc)
+                .unwrap_err()
+                .contains("--before"));
         }
 
         #[test]
-        fn parse_next_with_tag() {
-            let cli = Cli::try_parse_from(["feynman", "next", "--tag", "rust"]).unwrap();
+        fn parse_import_anki_command() {
+            let cli = Cli::try_parse_from(["feynman", "import-anki", "collection.json"]).unwrap();
             match cli.command {
-                Commands::Next { tag } => {
-                    assert_eq!(tag, Some("rust".to_string()));
+                Commands::ImportAnki { file } => {
+                    assert_eq!(file, PathBuf::from("collection.json"));
                 }
-                _ => panic!("Expected Next command"),
+                _ => panic!("Expected ImportAnki command"),
             }
         }
 
         #[test]
-        fn parse_review_command() {
-            let cli =
-                Cli::try_parse_from(["feynman", "review", "7", "--outcome", "success"]).unwrap();
+        fn parse_import_command() {
+            let cli = Cli::try_parse_from(["feynman", "import", "data.json", "--force"]).unwrap();
             match cli.command {
-                Commands::Review { id, outcome, notes } => {
-                    assert_eq!(id, 7);
-                    assert_eq!(outcome, "success");
-                    assert!(notes.is_none());
+                Commands::Import { file, force } => {
+                    assert_eq!(file, PathBuf::from("data.json"));
+                    assert!(force);
                 }
-                _ => panic!("Expected Review command"),
+                _ => panic!("Expected Import command"),
             }
         }
 
         #[test]
-        fn parse_review_with_notes() {
-            let cli = Cli::try_parse_from([
-                "feynman",
-                "review",
-                "7",
-                "--outcome",
-                "partial",
-                "--notes",
-                "Struggled with X",
-            ])
-            .unwrap();
+        fn parse_tui_compact_symbols() {
+            let cli = Cli::try_parse_from(["feynman", "tui", "--compact-symbols"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Tui {
+                    compact_symbols: true,
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_tui_no_restore() {
+            let cli = Cli::try_parse_from(["feynman", "tui", "--no-restore"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Tui {
+                    no_restore: true,
+                    ..
+                }
+            ));
+        }
+
+        #[test]
+        fn parse_tui_dashboard_limits() {
+            let cli = Cli::try_parse_from(["feynman", "tui"]).unwrap();
             match cli.command {
-                Commands::Review { id, outcome, notes } => {
-                    assert_eq!(id, 7);
-                    assert_eq!(outcome, "partial");
-                    assert_eq!(notes, Some("Struggled with X".to_string()));
+                Commands::Tui {
+                    due_limit,
+                    recent_limit,
+                    ..
+                } => {
+                    assert_eq!(due_limit, 5);
+                    assert_eq!(recent_limit, 5);
                 }
-                _ => panic!("Expected Review command"),
+                _ => panic!("Expected Tui command"),
             }
+
+            let cli = Cli::try_parse_from(["feynman", "tui", "--due-limit", "10"]).unwrap();
+            assert!(matches!(cli.command, Commands::Tui { due_limit: 10, .. }));
         }
 
         #[test]
-        fn parse_review_short_flags() {
-            let cli = Cli::try_parse_from(["feynman", "review", "1", "-o", "fail", "-n", "notes"])
+        fn parse_snapshot() {
+            let cli = Cli::try_parse_from(["feynman", "snapshot", "-o", "progress.txt", "--ansi"])
                 .unwrap();
             match cli.command {
-                Commands::Review { id, outcome, notes } => {
-                    assert_eq!(id, 1);
-                    assert_eq!(outcome, "fail");
-                    assert_eq!(notes, Some("notes".to_string()));
+                Commands::Snapshot {
+                    output,
+                    ansi,
+                    width,
+                    ..
+                } => {
+                    assert_eq!(output, Some(PathBuf::from("progress.txt")));
+                    assert!(ansi);
+                    assert_eq!(width, tui::DEFAULT_SNAPSHOT_WIDTH);
                 }
-                _ => panic!("Expected Review command"),
+                _ => panic!("Expected Snapshot command"),
             }
         }
 
@@ -720,12 +5412,251 @@ mod tests {
             let result = Cli::try_parse_from(["feynman", "topic", "add"]);
             assert!(result.is_err());
 
-            // review requires id and outcome
+            // review requires id (outcome is prompted for when omitted)
             let result = Cli::try_parse_from(["feynman", "review"]);
             assert!(result.is_err());
+        }
+    }
 
-            let result = Cli::try_parse_from(["feynman", "review", "1"]);
-            assert!(result.is_err());
+    mod completions_tests {
+        use super::*;
+
+        #[test]
+        fn bash_completions_mention_subcommands() {
+            let mut out = Vec::new();
+            write_completions(Shell::Bash, &mut out);
+            let script = String::from_utf8(out).unwrap();
+
+            assert!(script.contains("feynman"));
+            for subcommand in ["topic", "review", "next", "status", "tui"] {
+                assert!(script.contains(subcommand), "missing {}", subcommand);
+            }
+        }
+
+        #[test]
+        fn parse_completions_shell() {
+            let cli = Cli::try_parse_from(["feynman", "completions", "zsh"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Completions { shell: Shell::Zsh }
+            ));
+
+            assert!(Cli::try_parse_from(["feynman", "completions", "tcsh"]).is_err());
+        }
+    }
+
+    mod confirm_tests {
+        use super::*;
+
+        fn run(db_path: &Path, args: &[&str]) -> error::Result<()> {
+            let db = Database::open(db_path).unwrap();
+            let cli = Cli::try_parse_from(args).unwrap();
+            run_command(cli, db, &Config::default(), db_path)
+        }
+
+        #[test]
+        fn destructive_commands_need_confirmation() {
+            let dir = std::env::temp_dir().join(format!("feynman-confirm-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let db_path = dir.join("feynman.db");
+            let _ = std::fs::remove_file(&db_path);
+            run(&db_path, &["feynman", "init"]).unwrap();
+            let db = Database::open(&db_path).unwrap();
+            let a = db.add_topic("A", None, &[]).unwrap().to_string();
+            let b = db.add_topic("B", None, &[]).unwrap().to_string();
+            drop(db);
+            let count = || {
+                Database::open(&db_path)
+                    .unwrap()
+                    .get_stats()
+                    .unwrap()
+                    .total_topics
+            };
+
+            let commands: [&[&str]; 3] = [
+                &["feynman", "topic", "delete", &a],
+                &["feynman", "topic", "delete", &a, "--purge"],
+                &["feynman", "topic", "merge", &a, &b],
+            ];
+            for args in commands {
+                // --json can't prompt
+                let mut json = args.to_vec();
+                json.insert(1, "--json");
+                let e = run(&db_path, &json).unwrap_err();
+                assert!(e.to_string().contains("--yes"), "{:?}: {}", args, e);
+
+                // Off a terminal the prompt declines without asking
+                if !std::io::stdin().is_terminal() {
+                    run(&db_path, args).unwrap();
+                }
+                assert_eq!(count(), 2, "{:?}", args);
+            }
+
+            run(&db_path, &["feynman", "-y", "topic", "merge", &a, &b]).unwrap();
+            assert_eq!(count(), 1);
+            run(
+                &db_path,
+                &["feynman", "--json", "-y", "topic", "delete", &b],
+            )
+            .unwrap();
+            assert_eq!(count(), 0);
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    mod review_last_tests {
+        use super::*;
+
+        fn run(db_path: &Path, args: &[&str]) -> error::Result<()> {
+            let db = Database::open(db_path).unwrap();
+            let cli = Cli::try_parse_from(args).unwrap();
+            run_command(cli, db, &Config::default(), db_path)
+        }
+
+        #[test]
+        fn review_last_targets_what_next_suggested() {
+            let dir =
+                std::env::temp_dir().join(format!("feynman-review-last-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let db_path = dir.join("feynman.db");
+            let state_path = last_next::path_for(&db_path).unwrap();
+            let _ = std::fs::remove_file(&state_path);
+            run(&db_path, &["feynman", "init"]).unwrap();
+            let topic = Database::open(&db_path)
+                .unwrap()
+                .add_topic("Ownership", None, &[])
+                .unwrap();
+
+            // Nothing suggested yet
+            assert!(run(
+                &db_path,
+                &["feynman", "--json", "review", "last", "-o", "success"]
+            )
+            .is_err());
+
+            run(&db_path, &["feynman", "--json", "next"]).unwrap();
+            let last = LastNext::load(&state_path).unwrap();
+            assert_eq!(last.topic_id, topic);
+
+            run(
+                &db_path,
+                &["feynman", "--json", "review", "last", "-o", "success"],
+            )
+            .unwrap();
+            let progress = Database::open(&db_path)
+                .unwrap()
+                .get_progress(topic)
+                .unwrap()
+                .unwrap();
+            assert_eq!(progress.times_reviewed, 1);
+
+            // A stale suggestion is refused rather than guessed at
+            LastNext::new(topic, Utc::now() - chrono::Duration::hours(2))
+                .save(&state_path)
+                .unwrap();
+            let e = run(
+                &db_path,
+                &["feynman", "--json", "review", "last", "-o", "fail"],
+            )
+            .unwrap_err();
+            assert!(e.to_string().contains("too long"), "{}", e);
+
+            let _ = std::fs::remove_dir_all(dir);
+        }
+
+        #[test]
+        fn parse_review_last() {
+            let cli = Cli::try_parse_from(["feynman", "review", "LAST", "-o", "s"]).unwrap();
+            assert!(matches!(
+                cli.command,
+                Commands::Review {
+                    id: ReviewTarget::Last,
+                    ..
+                }
+            ));
+            assert!(Cli::try_parse_from(["feynman", "review", "latest", "-o", "s"]).is_err());
+        }
+    }
+
+    mod not_found_tests {
+        use super::*;
+
+        fn run_against_empty_db(args: &[&str]) -> error::Result<()> {
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            let cli = Cli::try_parse_from(args).unwrap();
+            run_command(cli, db, &Config::default(), Path::new(":memory:"))
+        }
+
+        #[test]
+        fn review_without_outcome_fails_off_a_terminal() {
+            if std::io::stdin().is_terminal() {
+                return;
+            }
+            let db = Database::open(":memory:").unwrap();
+            db.init().unwrap();
+            db.add_topic("Ownership", None, &[]).unwrap();
+            let cli = Cli::try_parse_from(["feynman", "review", "1"]).unwrap();
+            let e = run_command(cli, db, &Config::default(), Path::new(":memory:")).unwrap_err();
+            assert!(matches!(e, FeynmanError::Validation(_)), "{}", e);
+            assert!(e.to_string().contains("--outcome is required"));
+        }
+
+        #[test]
+        fn missing_ids_exit_with_not_found() {
+            let commands: &[&[&str]] = &[
+                &["feynman", "--json", "topic", "show", "99"],
+                &["feynman", "--json", "topic", "delete", "99"],
+                &["feynman", "--json", "topic", "archive", "99"],
+                &["feynman", "--json", "topic", "tag", "99", "-t", "rust"],
+                &["feynman", "--json", "review", "99", "-o", "success"],
+                &["feynman", "--json", "session", "end", "99", "-o", "success"],
+                &["feynman", "--json", "plan", "notes", "99"],
+                &["feynman", "--json", "rebuild-progress", "99"],
+                &["feynman", "topic", "show", "99"],
+            ];
+            for args in commands {
+                let e = run_against_empty_db(args).unwrap_err();
+                assert!(matches!(e, FeynmanError::NotFound(_)), "{:?}: {}", args, e);
+                assert_eq!(exit_code(&e), EXIT_NOT_FOUND, "{:?}", args);
+            }
+            // Distinct from clap's exit status for bad arguments
+            let usage = Cli::try_parse_from(["feynman", "bogus"]).err().unwrap();
+            assert_ne!(usage.exit_code(), EXIT_NOT_FOUND);
+        }
+
+        #[test]
+        fn not_found_envelope_carries_the_kind() {
+            let e =
+                run_against_empty_db(&["feynman", "--json", "topic", "show", "99"]).unwrap_err();
+            let envelope: serde_json::Value =
+                serde_json::from_str(&not_found_envelope(&e)).unwrap();
+            assert_eq!(envelope["success"], false);
+            assert_eq!(envelope["data"], serde_json::Value::Null);
+            assert_eq!(envelope["error"], "Topic not found");
+            assert_eq!(envelope["error_kind"], "not_found");
+        }
+
+        #[test]
+        fn columns_are_checked_against_the_command() {
+            let e = run_against_empty_db(&["feynman", "topic", "list", "--columns", "name,nme"])
+                .unwrap_err();
+            assert!(e.to_string().contains("Unknown column 'nme'"), "{}", e);
+            assert!(e.to_string().contains("mastery_level"), "{}", e);
+            assert!(run_against_empty_db(&["feynman", "tags", "--columns", "name"]).is_err());
+            assert!(run_against_empty_db(&["feynman", "sessions", "--columns", "outcome"]).is_ok());
+        }
+
+        #[test]
+        fn other_errors_keep_exit_code_one_and_no_kind() {
+            let e = run_against_empty_db(&["feynman", "--json", "topic", "merge", "1", "1"])
+                .unwrap_err();
+            assert_eq!(exit_code(&e), 1);
+
+            let envelope = serde_json::to_value(JsonOutput::<()>::err("boom")).unwrap();
+            assert!(envelope.get("error_kind").is_none());
+            let envelope = serde_json::to_value(JsonOutput::ok(1)).unwrap();
+            assert!(envelope.get("error_kind").is_none());
         }
     }
 
@@ -738,7 +5669,7 @@ mod tests {
             let test_path = "/tmp/test_feynman.db";
             env::set_var("FEYNMAN_DB", test_path);
 
-            let path = get_db_path();
+            let path = get_db_path(&Config::default());
             assert_eq!(path.to_str().unwrap(), test_path);
 
             env::remove_var("FEYNMAN_DB");
@@ -748,7 +5679,7 @@ mod tests {
         fn get_db_path_default_includes_feynman_db() {
             env::remove_var("FEYNMAN_DB");
 
-            let path = get_db_path();
+            let path = get_db_path(&Config::default());
             let path_str = path.to_str().unwrap();
 
             assert!(path_str.ends_with("feynman.db"));
@@ -0,0 +1,179 @@
+// `stats --prometheus` and `serve`'s /metrics: the learning statistics in the
+// Prometheus text exposition format, for node_exporter's textfile collector,
+// a scrape of the server, or anything else that reads it. Every metric is a
+// gauge; even the review count drops when prune-history deletes old reviews.
+
+use std::fmt::Write;
+
+use crate::models::{Stats, TagStats};
+
+// Reads one per-tag figure out of a TagStats
+type TagValue = fn(&TagStats) -> f64;
+
+pub fn prometheus(stats: &Stats, tags: &[TagStats]) -> String {
+    let mut out = String::new();
+    let totals: [(&str, &str, &str, f64); 6] = [
+        (
+            "feynman_topics_total",
+            "gauge",
+            "Topics in the deck",
+            stats.total_topics as f64,
+        ),
+        (
+            "feynman_reviews_total",
+            "gauge",
+            "Reviews in the history (pruning lowers it)",
+            stats.total_reviews as f64,
+        ),
+        (
+            "feynman_mastered_topics",
+            "gauge",
+            "Topics at mastery level 4 or above",
+            stats.mastered as f64,
+        ),
+        (
+            "feynman_due_now",
+            "gauge",
+            "Active topics due for review",
+            stats.due_now as f64,
+        ),
+        (
+            "feynman_avg_mastery",
+            "gauge",
+            "Average mastery level (0-5)",
+            stats.avg_mastery,
+        ),
+        (
+            "feynman_open_gaps",
+            "gauge",
+            "Knowledge gaps not yet addressed",
+            stats.open_gaps as f64,
+        ),
+    ];
+    for (name, kind, help, value) in totals {
+        header(&mut out, name, kind, help);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+
+    let per_tag: [(&str, &str, TagValue); 3] = [
+        ("feynman_tag_topics", "Topics with the tag", |t| {
+            t.topics as f64
+        }),
+        (
+            "feynman_tag_due_now",
+            "Active topics with the tag due for review",
+            |t| t.due_now as f64,
+        ),
+        (
+            "feynman_tag_avg_mastery",
+            "Average mastery level of topics with the tag",
+            |t| t.avg_mastery,
+        ),
+    ];
+    for (name, help, value) in per_tag {
+        header(&mut out, name, "gauge", help);
+        for tag in tags {
+            let _ = writeln!(
+                out,
+                "{}{{tag=\"{}\"}} {}",
+                name,
+                escape_label(&tag.tag),
+                value(tag)
+            );
+        }
+    }
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+// Label values escape backslashes, double quotes and newlines
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> Stats {
+        Stats {
+            total_topics: 3,
+            total_reviews: 7,
+            mastered: 1,
+            due_now: 2,
+            avg_mastery: 2.5,
+            avg_mastery_rounded: 2.5,
+            total_gaps: 4,
+            open_gaps: 1,
+        }
+    }
+
+    // A sample line: a metric name, optional {label="value",...}, a number
+    fn is_sample(line: &str) -> bool {
+        let Some((series, value)) = line.rsplit_once(' ') else {
+            return false;
+        };
+        let (name, labels) = match series.split_once('{') {
+            Some((name, rest)) => (name, rest.strip_suffix('}')),
+            None => (series, Some("")),
+        };
+        let valid_name = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':');
+        let valid_labels = labels.is_some_and(|labels| {
+            labels.is_empty()
+                || labels
+                    .split("\",")
+                    .all(|pair| pair.split_once("=\"").is_some_and(|(k, _)| !k.is_empty()))
+        });
+        valid_name && valid_labels && value.parse::<f64>().is_ok()
+    }
+
+    #[test]
+    fn every_line_is_a_comment_or_a_sample() {
+        let tags = [
+            TagStats {
+                tag: "rust".to_string(),
+                topics: 2,
+                due_now: 1,
+                avg_mastery: 3.0,
+            },
+            TagStats {
+                tag: "say \"hi\"".to_string(),
+                topics: 1,
+                due_now: 0,
+                avg_mastery: 0.5,
+            },
+        ];
+        let text = prometheus(&stats(), &tags);
+        for line in text.lines() {
+            assert!(
+                line.starts_with("# HELP ") || line.starts_with("# TYPE ") || is_sample(line),
+                "{}",
+                line
+            );
+        }
+        assert!(text.contains("\nfeynman_due_now 2\n"));
+        assert!(text.contains("# TYPE feynman_reviews_total gauge\n"));
+        assert!(text.contains("feynman_tag_avg_mastery{tag=\"rust\"} 3\n"));
+        assert!(text.contains("feynman_tag_topics{tag=\"say \\\"hi\\\"\"} 1\n"));
+    }
+
+    #[test]
+    fn no_tags_still_declares_the_per_tag_metrics() {
+        let text = prometheus(&stats(), &[]);
+        assert!(text.contains("# TYPE feynman_tag_topics gauge\n"));
+        assert!(!text.contains("feynman_tag_topics{"));
+    }
+}
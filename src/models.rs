@@ -1,7 +1,87 @@
 // Many types and methods are public API for the Claude skill integration but not used by CLI/TUI yet
 #![allow(dead_code)]
 
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::scheduler;
+use crate::settings::{HealthWeights, Settings, MAX_MASTERY};
+
+// A string that names none of an enum's variants, e.g. "meh" as a review
+// outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEnumError {
+    // What was being parsed
+    pub kind: EnumKind,
+    pub value: String,
+}
+
+// The enums parsed through text_enum!, so errors can be told apart by type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumKind {
+    ReviewOutcome,
+    SessionType,
+    SessionOutcome,
+    SkillLevel,
+    PlanStatus,
+    EntryType,
+    InterviewCategory,
+}
+
+impl EnumKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnumKind::ReviewOutcome => "review outcome",
+            EnumKind::SessionType => "session type",
+            EnumKind::SessionOutcome => "session outcome",
+            EnumKind::SkillLevel => "skill level",
+            EnumKind::PlanStatus => "plan status",
+            EnumKind::EntryType => "entry type",
+            EnumKind::InterviewCategory => "interview category",
+        }
+    }
+}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {} '{}'", self.kind.name(), self.value)
+    }
+}
+
+impl std::error::Error for ParseEnumError {}
+
+// Display and FromStr on top of an enum's own text methods (`$display` and
+// the inherent `from_str`), so it works with format!, .parse() and clap
+macro_rules! text_enum {
+    ($ty:ty, $kind:expr, $display:ident) => {
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.$display())
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = ParseEnumError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                <$ty>::from_str(s).ok_or_else(|| ParseEnumError {
+                    kind: $kind,
+                    value: s.to_string(),
+                })
+            }
+        }
+    };
+}
+
+text_enum!(ReviewOutcome, EnumKind::ReviewOutcome, as_str);
+text_enum!(SessionType, EnumKind::SessionType, as_str);
+text_enum!(SessionOutcome, EnumKind::SessionOutcome, as_str);
+text_enum!(SkillLevel, EnumKind::SkillLevel, label);
+text_enum!(PlanStatus, EnumKind::PlanStatus, as_str);
+text_enum!(InterviewEntryType, EnumKind::EntryType, as_str);
+text_enum!(InterviewCategory, EnumKind::InterviewCategory, as_str);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Topic {
@@ -11,16 +91,26 @@ pub struct Topic {
     pub created_at: String,
     pub updated_at: String,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub suspended: bool,
 }
 
 // Skill levels for user's knowledge assessment
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum SkillLevel {
+    #[value(alias = "0")]
     Unknown = 0,
+    #[value(alias = "1")]
     Novice = 1,
+    #[value(alias = "2")]
     Beginner = 2,
+    #[value(alias = "3")]
     Intermediate = 3,
+    #[value(alias = "4")]
     Advanced = 4,
+    #[value(alias = "5")]
     Expert = 5,
 }
 
@@ -91,9 +181,11 @@ impl AssessmentMethod {
 }
 
 // Learning session types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum SessionType {
+    #[value(alias = "f")]
     Feynman,
+    #[value(alias = "s")]
     Socratic,
 }
 
@@ -105,12 +197,9 @@ impl SessionType {
         }
     }
 
+    // Case-insensitive; accepts the same aliases as the CLI
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "feynman" | "f" => Some(SessionType::Feynman),
-            "socratic" | "s" => Some(SessionType::Socratic),
-            _ => None,
-        }
+        <Self as ValueEnum>::from_str(s, true).ok()
     }
 
     pub fn description(&self) -> &'static str {
@@ -122,11 +211,15 @@ impl SessionType {
 }
 
 // Session outcome (extends ReviewOutcome with abandoned)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum SessionOutcome {
+    #[value(alias = "s")]
     Success,
+    #[value(alias = "p")]
     Partial,
+    #[value(alias = "f")]
     Fail,
+    #[value(aliases = ["a", "quit", "q"])]
     Abandoned,
 }
 
@@ -140,14 +233,9 @@ impl SessionOutcome {
         }
     }
 
+    // Case-insensitive; accepts the same aliases as the CLI
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "success" | "s" => Some(SessionOutcome::Success),
-            "partial" | "p" => Some(SessionOutcome::Partial),
-            "fail" | "f" => Some(SessionOutcome::Fail),
-            "abandoned" | "a" | "quit" | "q" => Some(SessionOutcome::Abandoned),
-            _ => None,
-        }
+        <Self as ValueEnum>::from_str(s, true).ok()
     }
 
     pub fn to_review_outcome(self) -> Option<ReviewOutcome> {
@@ -174,6 +262,74 @@ pub struct LearningSession {
     pub notes: Option<String>,
 }
 
+impl LearningSession {
+    // Still running: started but never ended
+    pub fn is_active(&self) -> bool {
+        self.ended_at.is_none()
+    }
+
+    // Time from start to end; None while active or if a timestamp is invalid
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let started = parse_timestamp(&self.started_at)?;
+        let ended = parse_timestamp(self.ended_at.as_deref()?)?;
+        Some(ended - started)
+    }
+}
+
+// Compact duration: "45s", "12m", "1h 05m"; negative spans show as "0s"
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+// A session as listed by `sessions` and `topic show --history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRow {
+    #[serde(flatten)]
+    pub session: LearningSession,
+    // None while the session is active
+    pub duration_secs: Option<i64>,
+}
+
+impl From<LearningSession> for SessionRow {
+    fn from(session: LearningSession) -> Self {
+        Self {
+            duration_secs: session.duration().map(|d| d.num_seconds()),
+            session,
+        }
+    }
+}
+
+// A session with its topic's name, as listed by `sessions recent`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentSession {
+    #[serde(flatten)]
+    pub row: SessionRow,
+    pub topic_name: String,
+}
+
+impl From<(LearningSession, String)> for RecentSession {
+    fn from((session, topic_name): (LearningSession, String)) -> Self {
+        Self {
+            row: SessionRow::from(session),
+            topic_name,
+        }
+    }
+}
+
+// Filters for listing sessions. The default is every session, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct SessionQuery {
+    pub topic_id: Option<i64>,
+    // Only sessions that haven't been ended
+    pub active: bool,
+    pub outcome: Option<SessionOutcome>,
+}
+
 // A knowledge gap identified during a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionGap {
@@ -183,6 +339,77 @@ pub struct SessionGap {
     pub addressed: bool,
 }
 
+// A question to answer when reviewing a topic, e.g. "Explain it to a
+// five-year-old". A topic's prompts are taken in `ord` order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopicPrompt {
+    pub id: i64,
+    pub topic_id: i64,
+    pub prompt: String,
+    pub ord: i32,
+}
+
+impl TopicPrompt {
+    // The prompt for a topic's next review: each review moves on to the next
+    // one, wrapping around, so every prompt gets its turn
+    pub fn for_review(prompts: &[TopicPrompt], times_reviewed: i32) -> Option<&TopicPrompt> {
+        if prompts.is_empty() {
+            return None;
+        }
+        prompts.get(times_reviewed.max(0) as usize % prompts.len())
+    }
+}
+
+// A link or reference attached to a topic, e.g. a chapter of a book
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopicResource {
+    pub id: i64,
+    pub topic_id: i64,
+    pub label: Option<String>,
+    pub url: String,
+}
+
+impl TopicResource {
+    // What lists show: the label, or the URL when there isn't one
+    pub fn title(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.url)
+    }
+
+    // Just enough to hand the URL to a browser: a scheme ("https:",
+    // "file:", "isbn:"...) followed by something, with no whitespace, and a
+    // host for http(s)
+    pub fn check_url(url: &str) -> Result<(), String> {
+        let invalid = || {
+            format!(
+                "\"{}\" isn't a URL, e.g. https://doc.rust-lang.org/book/",
+                url
+            )
+        };
+        if url.is_empty() || url.chars().any(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let Some((scheme, rest)) = url.split_once(':') else {
+            return Err(invalid());
+        };
+        let scheme_ok = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+        if !scheme_ok || rest.is_empty() {
+            return Err(invalid());
+        }
+        let scheme = scheme.to_ascii_lowercase();
+        if (scheme == "http" || scheme == "https")
+            && rest
+                .strip_prefix("//")
+                .is_none_or(|host| host.is_empty() || host.starts_with('/'))
+        {
+            return Err(invalid());
+        }
+        Ok(())
+    }
+}
+
 // A skill assessment record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillAssessment {
@@ -208,6 +435,15 @@ pub enum PlanStatus {
 }
 
 impl PlanStatus {
+    pub const ALL: [PlanStatus; 6] = [
+        PlanStatus::Interviewing,
+        PlanStatus::SpecReady,
+        PlanStatus::Approved,
+        PlanStatus::InProgress,
+        PlanStatus::Complete,
+        PlanStatus::Abandoned,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             PlanStatus::Interviewing => "interviewing",
@@ -241,6 +477,44 @@ impl PlanStatus {
             PlanStatus::Abandoned => "Abandoned",
         }
     }
+
+    // Plans move forward one step at a time, may go back from SpecReady to
+    // Interviewing, can be abandoned until complete, and reopen from Abandoned
+    pub fn can_transition_to(&self, to: PlanStatus) -> bool {
+        use PlanStatus::*;
+        matches!(
+            (self, to),
+            (Interviewing, SpecReady)
+                | (SpecReady, Approved | Interviewing)
+                | (Approved, InProgress)
+                | (InProgress, Complete)
+                | (Interviewing | SpecReady | Approved | InProgress, Abandoned)
+                | (Abandoned, Interviewing)
+        )
+    }
+
+    // Every status can_transition_to accepts from this one
+    pub fn allowed_transitions(&self) -> Vec<PlanStatus> {
+        Self::ALL
+            .into_iter()
+            .filter(|to| self.can_transition_to(*to))
+            .collect()
+    }
+
+    // Where `s` in the TUI moves a plan: the next lifecycle step, or back to
+    // Interviewing from Abandoned. None for Complete, which is final.
+    pub fn next_in_cycle(&self) -> Option<PlanStatus> {
+        let next = match self {
+            PlanStatus::Interviewing => PlanStatus::SpecReady,
+            PlanStatus::SpecReady => PlanStatus::Approved,
+            PlanStatus::Approved => PlanStatus::InProgress,
+            PlanStatus::InProgress => PlanStatus::Complete,
+            PlanStatus::Abandoned => PlanStatus::Interviewing,
+            PlanStatus::Complete => return None,
+        };
+        debug_assert!(self.can_transition_to(next));
+        Some(next)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -253,6 +527,14 @@ pub enum InterviewEntryType {
 }
 
 impl InterviewEntryType {
+    pub const ALL: [InterviewEntryType; 5] = [
+        InterviewEntryType::Question,
+        InterviewEntryType::Answer,
+        InterviewEntryType::Note,
+        InterviewEntryType::Clarification,
+        InterviewEntryType::Decision,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             InterviewEntryType::Question => "question",
@@ -292,6 +574,21 @@ pub enum InterviewCategory {
 }
 
 impl InterviewCategory {
+    pub const ALL: [InterviewCategory; 12] = [
+        InterviewCategory::Requirements,
+        InterviewCategory::EdgeCases,
+        InterviewCategory::Security,
+        InterviewCategory::Deployment,
+        InterviewCategory::Architecture,
+        InterviewCategory::Performance,
+        InterviewCategory::Testing,
+        InterviewCategory::DoD,
+        InterviewCategory::Scope,
+        InterviewCategory::Dependencies,
+        InterviewCategory::Risks,
+        InterviewCategory::Other,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             InterviewCategory::Requirements => "requirements",
@@ -359,6 +656,29 @@ pub struct Tag {
     pub topic_count: i64,
 }
 
+// A tag with every topic carrying it, for `tags --with-topics`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagTopics {
+    pub tag: String,
+    pub topics: Vec<TopicRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopicRef {
+    pub id: i64,
+    pub name: String,
+}
+
+// The form tags are stored and matched in: lowercase, with runs of
+// whitespace collapsed to one space and none at either end, so "Rust",
+// "rust" and " rust " are the same tag
+pub fn normalize_tag(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Progress {
     pub id: i64,
@@ -373,19 +693,28 @@ pub struct Progress {
     pub skill_level: SkillLevel,
     pub assessment_method: AssessmentMethod,
     pub last_assessed: Option<String>,
+    // 1 (easy) to 5 (hard), scales review intervals
+    #[serde(default = "default_difficulty")]
+    pub difficulty: i32,
+}
+
+pub const DEFAULT_DIFFICULTY: i32 = 3;
+
+// Days overdue at which a topic's health score loses all its recency part
+pub const HEALTH_OVERDUE_HORIZON_DAYS: i64 = 30;
+
+// Estimated recall on the due date, from mastery 0 up to 5 (see
+// Progress::estimated_retention)
+const RETENTION_AT_DUE_MIN: f64 = 0.8;
+const RETENTION_AT_DUE_MAX: f64 = 0.95;
+
+fn default_difficulty() -> i32 {
+    DEFAULT_DIFFICULTY
 }
 
 impl Progress {
     pub fn mastery_label(&self) -> &'static str {
-        match self.mastery_level {
-            0 => "New",
-            1 => "Learning",
-            2 => "Familiar",
-            3 => "Comfortable",
-            4 => "Proficient",
-            5 => "Mastered",
-            _ => "Unknown",
-        }
+        mastery_label(self.mastery_level)
     }
 
     pub fn success_rate(&self) -> f64 {
@@ -395,6 +724,138 @@ impl Progress {
             (self.times_succeeded as f64 / self.times_reviewed as f64) * 100.0
         }
     }
+
+    // Whole days past next_review (0 if not yet due or unparseable)
+    pub fn overdue_days(&self, now: DateTime<Utc>) -> i64 {
+        self.next_review_dt()
+            .map(|next| now.signed_duration_since(next).num_days().max(0))
+            .unwrap_or(0)
+    }
+
+    // 0-100 summary of how well a topic is holding up, using the default
+    // weights (see health_score_with)
+    pub fn health_score(&self, now: DateTime<Utc>) -> f64 {
+        self.health_score_with(now, &HealthWeights::default())
+    }
+
+    // Weighted average of three parts, each 0-1, scaled to 0-100:
+    //   mastery = mastery_level / 5
+    //   recency = 1 - overdue_days / 30 (0 once a month overdue)
+    //   success = times_succeeded / times_reviewed (0 if never reviewed)
+    pub fn health_score_with(&self, now: DateTime<Utc>, weights: &HealthWeights) -> f64 {
+        let total = weights.total();
+        if total <= 0 {
+            return 0.0;
+        }
+        let mastery = self.mastery_level.clamp(0, MAX_MASTERY) as f64 / MAX_MASTERY as f64;
+        let recency = 1.0
+            - self.overdue_days(now).min(HEALTH_OVERDUE_HORIZON_DAYS) as f64
+                / HEALTH_OVERDUE_HORIZON_DAYS as f64;
+        let success = self.success_rate() / 100.0;
+
+        100.0
+            * (weights.mastery as f64 * mastery
+                + weights.recency as f64 * recency
+                + weights.success as f64 * success)
+            / total as f64
+    }
+
+    pub fn next_review_dt(&self) -> Option<DateTime<Utc>> {
+        self.next_review.as_deref().and_then(parse_timestamp)
+    }
+
+    // Days from the last review to the scheduled next one
+    pub fn scheduled_interval_days(&self) -> Option<i64> {
+        let last = self.last_reviewed.as_deref().and_then(parse_timestamp)?;
+        Some((self.next_review_dt()? - last).num_days())
+    }
+
+    // A rough 0-1 estimate of how much of the topic is still recalled at `now`,
+    // on an exponential forgetting curve from the last review:
+    //   retention = at_due ^ (elapsed / scheduled interval)
+    // at_due runs from 80% at mastery 0 to 95% at mastery 5, so a better-known
+    // topic decays more slowly over the same interval. None if never
+    // reviewed, since there's nothing to forget yet.
+    pub fn estimated_retention(&self, now: DateTime<Utc>) -> Option<f64> {
+        let last = self.last_reviewed.as_deref().and_then(parse_timestamp)?;
+        // Without a usable next review, assume a one-day interval
+        let interval = self
+            .next_review_dt()
+            .map(|next| (next - last).num_seconds())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(24 * 60 * 60) as f64;
+        let elapsed = (now - last).num_seconds().max(0) as f64;
+        let mastery = self.mastery_level.clamp(0, MAX_MASTERY) as f64 / MAX_MASTERY as f64;
+        let at_due = RETENTION_AT_DUE_MIN + (RETENTION_AT_DUE_MAX - RETENTION_AT_DUE_MIN) * mastery;
+        Some(at_due.powf(elapsed / interval))
+    }
+
+    // A next_review is stored but can't be parsed, so scheduling treats the
+    // topic as not overdue and the TUI shows "Unknown"
+    pub fn has_unparseable_next_review(&self) -> bool {
+        self.next_review.is_some() && self.next_review_dt().is_none()
+    }
+
+    // The day (YYYY-MM-DD) this topic would reach mastery level 5 if every
+    // review from the next one on succeeds and happens on time. None when
+    // it's already there or success doesn't raise the level.
+    pub fn projected_mastery_date(
+        &self,
+        settings: &Settings,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if self.mastery_level >= MAX_MASTERY {
+            return None;
+        }
+        // An overdue topic's next review is taken to be now. Only a date an
+        // earlier review set is due for the on-time bonus; after that each
+        // projected review lands on the date the one before set.
+        let mut at = self.next_review_dt().map_or(now, |next| next.max(now));
+        let mut due = self.last_reviewed.as_ref().and(self.next_review_dt());
+        let mut level = self.mastery_level;
+        loop {
+            let next_level = settings.grading.apply(level, ReviewOutcome::Success);
+            if next_level <= level {
+                return None;
+            }
+            level = next_level;
+            if level >= MAX_MASTERY {
+                return Some(at.format("%Y-%m-%d").to_string());
+            }
+            let on_time = scheduler::earns_on_time_bonus(settings, ReviewOutcome::Success, due, at);
+            at += scheduler::scheduled_interval(
+                settings,
+                level,
+                ReviewOutcome::Success,
+                self.difficulty,
+                on_time,
+            );
+            due = Some(at);
+        }
+    }
+}
+
+pub fn mastery_label(mastery_level: i32) -> &'static str {
+    match mastery_level {
+        0 => "New",
+        1 => "Learning",
+        2 => "Familiar",
+        3 => "Comfortable",
+        4 => "Proficient",
+        5 => "Mastered",
+        _ => "Unknown",
+    }
+}
+
+// Parse a stored timestamp: RFC 3339 (written by the app) or SQLite's
+// `datetime('now')` format (column defaults)
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|dt| dt.and_utc())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -403,10 +864,196 @@ pub struct TopicWithProgress {
     pub progress: Progress,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+// Self-rated skill this many levels from mastery is worth re-checking
+pub const SKILL_DIVERGENCE: i32 = 2;
+
+// The one thing to do next with a topic, from recommend_action
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recommendation {
+    // Archived or suspended, so never scheduled
+    Paused,
+    CloseGaps { gaps: usize },
+    // Self-assessed skill and mastery (both 0-5) disagree
+    CheckSkill { skill: SkillLevel, mastery: i32 },
+    // Never reviewed
+    Learn,
+    Review,
+    Mastered { next_review: DateTime<Utc> },
+    Wait { next_review: DateTime<Utc> },
+}
+
+impl Recommendation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recommendation::Paused => "paused",
+            Recommendation::CloseGaps { .. } => "close_gaps",
+            Recommendation::CheckSkill { .. } => "check_skill",
+            Recommendation::Learn => "learn",
+            Recommendation::Review => "review",
+            Recommendation::Mastered { .. } => "mastered",
+            Recommendation::Wait { .. } => "wait",
+        }
+    }
+}
+
+impl fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recommendation::Paused => write!(f, "Paused: unarchive or unsuspend it to study again"),
+            Recommendation::CloseGaps { gaps } => write!(
+                f,
+                "Run a Feynman session \u{2014} you have {} unaddressed gap{}",
+                gaps,
+                if *gaps == 1 { "" } else { "s" }
+            ),
+            Recommendation::CheckSkill { skill, mastery } => write!(
+                f,
+                "Run a Socratic session \u{2014} you rated yourself {} but mastery is {}/{}",
+                skill.label(),
+                mastery,
+                MAX_MASTERY
+            ),
+            Recommendation::Learn => write!(f, "New topic \u{2014} explain it from scratch"),
+            Recommendation::Review => write!(f, "Due for review"),
+            Recommendation::Mastered { next_review } => write!(
+                f,
+                "Mastered \u{2014} next check-in {}",
+                next_review.format("%b %d")
+            ),
+            Recommendation::Wait { next_review } => {
+                write!(f, "Nothing to do until {}", next_review.format("%b %d"))
+            }
+        }
+    }
+}
+
+// What to do next with a topic, given its unaddressed gaps. The first that
+// applies wins:
+//   1. archived or suspended: Paused
+//   2. any unaddressed gap: CloseGaps
+//   3. assessed skill SKILL_DIVERGENCE or more levels from mastery: CheckSkill
+//   4. never reviewed: Learn
+//   5. due (or no parseable next review): Review
+//   6. at MAX_MASTERY: Mastered
+//   7. otherwise: Wait for the next review
+pub fn recommend_action(
+    twp: &TopicWithProgress,
+    gaps: &[SessionGap],
+    now: DateTime<Utc>,
+) -> Recommendation {
+    let progress = &twp.progress;
+    let open_gaps = gaps.iter().filter(|g| !g.addressed).count();
+    let skill = progress.skill_level;
+
+    if twp.topic.archived || twp.topic.suspended {
+        Recommendation::Paused
+    } else if open_gaps > 0 {
+        Recommendation::CloseGaps { gaps: open_gaps }
+    } else if skill != SkillLevel::Unknown
+        && (skill.as_i32() - progress.mastery_level).abs() >= SKILL_DIVERGENCE
+    {
+        Recommendation::CheckSkill {
+            skill,
+            mastery: progress.mastery_level,
+        }
+    } else if progress.times_reviewed == 0 {
+        Recommendation::Learn
+    } else {
+        match progress.next_review_dt() {
+            Some(next_review) if next_review > now && progress.mastery_level >= MAX_MASTERY => {
+                Recommendation::Mastered { next_review }
+            }
+            Some(next_review) if next_review > now => Recommendation::Wait { next_review },
+            _ => Recommendation::Review,
+        }
+    }
+}
+
+// Display-ready fields for one topic in the TUI lists. Dates are shown in UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicRow {
+    pub topic_id: i64,
+    pub name: String,
+    pub mastery_level: i32,
+    pub mastery_bar: String,
+    pub mastery_label: &'static str,
+    pub skill_label: &'static str,
+    // "Mar 01", or "Not set" / "Unknown" (unparseable)
+    pub next_review: String,
+    // "Mar 01, 2024", same fallbacks
+    pub next_review_long: String,
+    pub overdue: bool,
+}
+
+impl TopicRow {
+    pub fn new(twp: &TopicWithProgress, now: DateTime<Utc>) -> Self {
+        let progress = &twp.progress;
+        let next = progress.next_review_dt();
+        let format_next = |fmt: &str| match (&progress.next_review, next) {
+            (None, _) => "Not set".to_string(),
+            (Some(_), None) => "Unknown".to_string(),
+            (Some(_), Some(dt)) => dt.format(fmt).to_string(),
+        };
+
+        Self {
+            topic_id: twp.topic.id,
+            name: twp.topic.name.clone(),
+            mastery_level: progress.mastery_level,
+            mastery_bar: mastery_bar(progress.mastery_level),
+            mastery_label: progress.mastery_label(),
+            skill_label: progress.skill_level.label(),
+            next_review: format_next("%b %d"),
+            next_review_long: format_next("%b %d, %Y"),
+            overdue: next.is_some_and(|dt| dt < now),
+        }
+    }
+}
+
+impl From<&TopicWithProgress> for TopicRow {
+    fn from(twp: &TopicWithProgress) -> Self {
+        Self::new(twp, Utc::now())
+    }
+}
+
+// Five-cell bar, one filled cell per mastery level
+pub fn mastery_bar(mastery_level: i32) -> String {
+    let filled = mastery_level.clamp(0, 5) as usize;
+    format!("{}{}", "█".repeat(filled), "░".repeat(5 - filled))
+}
+
+// "1→2→1→2": mastery after each review, the last `max_shown` of them, with a
+// leading "…" when older ones are left out
+pub fn mastery_trend(levels: &[i32], max_shown: usize) -> String {
+    let shown = &levels[levels.len().saturating_sub(max_shown)..];
+    let trend = shown
+        .iter()
+        .map(|level| level.to_string())
+        .collect::<Vec<_>>()
+        .join("→");
+    if shown.len() < levels.len() {
+        format!("…→{}", trend)
+    } else {
+        trend
+    }
+}
+
+// `width`-cell bar filled in proportion to count / max
+pub fn count_bar(count: i64, max: i64, width: usize) -> String {
+    let filled = if max > 0 {
+        ((count.clamp(0, max) as f64 / max as f64) * width as f64).round() as usize
+    } else {
+        0
+    };
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
 pub enum ReviewOutcome {
+    #[value(aliases = ["s", "yes", "y", "good", "1"])]
     Success,
+    #[value(aliases = ["p", "maybe", "ok", "2"])]
     Partial,
+    #[value(aliases = ["f", "no", "n", "bad", "0", "3"])]
     Fail,
 }
 
@@ -419,22 +1066,417 @@ impl ReviewOutcome {
         }
     }
 
+    // Case-insensitive; accepts the same aliases as the CLI
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "success" | "s" | "yes" | "y" | "good" | "1" => Some(ReviewOutcome::Success),
-            "partial" | "p" | "maybe" | "ok" | "2" => Some(ReviewOutcome::Partial),
-            "fail" | "f" | "no" | "n" | "bad" | "0" | "3" => Some(ReviewOutcome::Fail),
-            _ => None,
+        <Self as ValueEnum>::from_str(s, true).ok()
+    }
+}
+
+// Ordering for topic listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TopicSort {
+    #[default]
+    Name,
+    NextReview,
+    Mastery,
+    // Lowest health score first (computed after the query, see
+    // Progress::health_score_with)
+    Health,
+    // Stable order for keyset pagination with `after`
+    Id,
+}
+
+// What to do with an imported topic whose name is already taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    // Abort the whole import
+    #[default]
+    Fail,
+    // Leave the existing topic untouched
+    Skip,
+    // Replace the existing description (if one is given) and add the tags
+    Update,
+}
+
+// Which topics to keep by when they're next due
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DueFilter {
+    #[default]
+    Any,
+    // Next review is now or earlier
+    Due,
+    // Was already due before the start of today (as `status` counts it)
+    Overdue,
+}
+
+// Options for querying topics with their progress. The default is every
+// active (non-archived, non-suspended) topic sorted by name.
+#[derive(Debug, Clone, Default)]
+pub struct TopicQuery {
+    pub tag: Option<String>,
+    pub include_archived: bool,
+    pub include_suspended: bool,
+    pub due: DueFilter,
+    pub sort: TopicSort,
+    // Keyset pagination: only topics with id > after (use with TopicSort::Id)
+    pub after: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+// A single row of review history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRecord {
+    pub id: i64,
+    pub topic_id: i64,
+    pub outcome: ReviewOutcome,
+    pub reviewed_at: String,
+    pub notes: Option<String>,
+    // Self-rated confidence 1-5, if given with --confidence
+    #[serde(default)]
+    pub confidence: Option<i32>,
+}
+
+pub const MIN_CONFIDENCE: i32 = 1;
+pub const MAX_CONFIDENCE: i32 = 5;
+
+// Confidence at or above this on a failed review counts as overconfident,
+// at or below it on a successful one as underconfident
+pub const OVERCONFIDENT_AT: i32 = 4;
+pub const UNDERCONFIDENT_AT: i32 = 2;
+
+// Outcomes of the reviews rated at one confidence level
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationLevel {
+    pub confidence: i32,
+    pub reviews: i64,
+    pub success: i64,
+    pub partial: i64,
+    pub fail: i64,
+}
+
+impl CalibrationLevel {
+    pub fn success_rate(&self) -> f64 {
+        if self.reviews == 0 {
+            0.0
+        } else {
+            self.success as f64 / self.reviews as f64
         }
     }
 }
 
+// How well self-rated confidence predicted review outcomes (`stats
+// --calibration`). Reviews without a confidence rating are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    // One entry per confidence level, 1 through 5
+    pub levels: Vec<CalibrationLevel>,
+    // Failed reviews rated OVERCONFIDENT_AT or higher
+    pub overconfident: Vec<ReviewRecord>,
+    // Successful reviews rated UNDERCONFIDENT_AT or lower
+    pub underconfident: Vec<ReviewRecord>,
+}
+
+impl CalibrationReport {
+    pub fn from_reviews(reviews: &[ReviewRecord]) -> Self {
+        let mut levels: Vec<CalibrationLevel> = (MIN_CONFIDENCE..=MAX_CONFIDENCE)
+            .map(|confidence| CalibrationLevel {
+                confidence,
+                ..CalibrationLevel::default()
+            })
+            .collect();
+        let mut overconfident = Vec::new();
+        let mut underconfident = Vec::new();
+
+        for review in reviews {
+            let Some(confidence) = review
+                .confidence
+                .filter(|c| (MIN_CONFIDENCE..=MAX_CONFIDENCE).contains(c))
+            else {
+                continue;
+            };
+            let level = &mut levels[(confidence - MIN_CONFIDENCE) as usize];
+            level.reviews += 1;
+            match review.outcome {
+                ReviewOutcome::Success => level.success += 1,
+                ReviewOutcome::Partial => level.partial += 1,
+                ReviewOutcome::Fail => level.fail += 1,
+            }
+
+            match review.outcome {
+                ReviewOutcome::Fail if confidence >= OVERCONFIDENT_AT => {
+                    overconfident.push(review.clone())
+                }
+                ReviewOutcome::Success if confidence <= UNDERCONFIDENT_AT => {
+                    underconfident.push(review.clone())
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            levels,
+            overconfident,
+            underconfident,
+        }
+    }
+
+    pub fn rated_reviews(&self) -> i64 {
+        self.levels.iter().map(|l| l.reviews).sum()
+    }
+}
+
+// A page of `history` output; pass last_id as --after for the next page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPage {
+    pub reviews: Vec<ReviewRecord>,
+    pub last_id: Option<i64>,
+}
+
+// Default largest pause between reviews in one implicit study session
+pub const DEFAULT_STUDY_GAP_MINUTES: i64 = 30;
+
+// A run of reviews done close together (`sessions --implicit`): inferred from
+// review history, unlike the learning sessions Claude records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StudySession {
+    pub start: String,
+    pub end: String,
+    pub reviews: i64,
+    pub successes: i64,
+    pub success_rate: f64,
+}
+
+impl StudySession {
+    // Group reviews into sessions, newest first. A review more than `gap`
+    // after the one before it starts a new session. Reviews are taken in
+    // time order whatever order they come in; unparseable timestamps are
+    // skipped.
+    pub fn cluster(reviews: &[ReviewRecord], gap: chrono::Duration) -> Vec<Self> {
+        let mut timed: Vec<(DateTime<Utc>, ReviewOutcome)> = reviews
+            .iter()
+            .filter_map(|r| Some((parse_timestamp(&r.reviewed_at)?, r.outcome)))
+            .collect();
+        timed.sort_by_key(|(at, _)| *at);
+
+        // (start, end, reviews, successes)
+        let mut runs: Vec<(DateTime<Utc>, DateTime<Utc>, i64, i64)> = Vec::new();
+        for (at, outcome) in timed {
+            let success = (outcome == ReviewOutcome::Success) as i64;
+            match runs.last_mut() {
+                Some(run) if at - run.1 <= gap => {
+                    run.1 = at;
+                    run.2 += 1;
+                    run.3 += success;
+                }
+                _ => runs.push((at, at, 1, success)),
+            }
+        }
+
+        runs.into_iter()
+            .rev()
+            .map(|(start, end, reviews, successes)| Self {
+                start: start.to_rfc3339(),
+                end: end.to_rfc3339(),
+                reviews,
+                successes,
+                success_rate: successes as f64 / reviews as f64,
+            })
+            .collect()
+    }
+
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        Some(parse_timestamp(&self.end)? - parse_timestamp(&self.start)?)
+    }
+}
+
+// One due topic's share of the stochastic draw in `next`.
+// weight = (overdue_days + 1)^overdue_weight * mastery_factor * recency_factor,
+// where overdue_days is whole days past next_review (0 if not yet due),
+// mastery_factor = (baseline - mastery)^mastery_weight; by default that is
+// (overdue_days + 1) * (6 - mastery). See settings::SelectionWeights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectionCandidate {
+    pub topic_id: i64,
+    pub weight: f64,
+    pub overdue_days: i64,
+    pub mastery_factor: f64,
+    // RECENT_PENALTY if reviewed within selection.recent_hours, otherwise 1
+    pub recency_factor: f64,
+}
+
+// How `next` chooses among the candidate topics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionStrategy {
+    // Random, weighted by overdue-ness and low mastery
+    #[default]
+    Weighted,
+    // Earliest next_review first
+    Overdue,
+    // Least recently reviewed first (never-reviewed topics before all others)
+    Lru,
+    // Uniformly random
+    Random,
+}
+
+impl SelectionStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelectionStrategy::Weighted => "weighted",
+            SelectionStrategy::Overdue => "overdue",
+            SelectionStrategy::Lru => "lru",
+            SelectionStrategy::Random => "random",
+        }
+    }
+}
+
+// How `next` picked its topic. For the random strategies `draw` is uniform in
+// [0, total_weight) and selects the first candidate whose cumulative weight
+// reaches it; the deterministic ones have no draw.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectionExplanation {
+    pub selected: TopicWithProgress,
+    #[serde(default)]
+    pub strategy: SelectionStrategy,
+    pub candidates: Vec<SelectionCandidate>,
+    pub total_weight: f64,
+    pub draw: Option<f64>,
+    // Which side of a `--new-ratio` mix the topic came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool: Option<TopicPool>,
+}
+
+// With `next --new-ratio`, each pick draws from topics never reviewed or from
+// those already in review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TopicPool {
+    New,
+    Review,
+}
+
+impl TopicPool {
+    // New with probability `new_ratio` when both pools have topics,
+    // otherwise whichever one does; None when both are empty
+    pub fn choose(
+        new_available: bool,
+        review_available: bool,
+        new_ratio: f64,
+        rng: &mut impl rand::Rng,
+    ) -> Option<Self> {
+        match (new_available, review_available) {
+            (true, true) if rng.gen::<f64>() < new_ratio => Some(TopicPool::New),
+            (_, true) => Some(TopicPool::Review),
+            (true, false) => Some(TopicPool::New),
+            (false, false) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TopicPool::New => "new",
+            TopicPool::Review => "review",
+        }
+    }
+}
+
+// Where a new topic starts, for topics the learner already knows. The
+// default is a fresh topic: mastery 0, skill Unknown and due straight away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NewTopicOptions {
+    pub mastery: i32,
+    // Recorded as a self-assessment
+    pub skill: Option<SkillLevel>,
+}
+
+// Optional knobs for recording a review
+#[derive(Debug, Clone, Default)]
+pub struct ReviewOptions {
+    // On fail/partial, make the topic due immediately instead of waiting a day
+    pub retry_now: bool,
+    // Backfill: when the review happened, instead of now
+    pub at: Option<DateTime<Utc>>,
+    // Self-rated confidence 1-5, stored with the review for calibration
+    pub confidence: Option<i32>,
+}
+
+// The scheduling step a review applied, for `review --explain`. Intervals are
+// in minutes since schedule.fail_minutes/partial_minutes can make them short.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleReasoning {
+    pub previous_level: i32,
+    pub new_level: i32,
+    // The grading.<outcome>.delta setting; the level change is smaller when
+    // it would leave 0-5
+    pub grading_delta: i32,
+    pub level_delta: i32,
+    // From the last review to the date it set; None on a first review
+    pub previous_interval_minutes: Option<i64>,
+    pub difficulty: i32,
+    pub interval_minutes: i64,
+    // The schedule.on_time_bonus multiplier, when the review was on time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_time_bonus: Option<f64>,
+    // --retry-now made a missed topic due straight away instead
+    pub retry_now: bool,
+    pub next_review: String,
+}
+
+// Averages are shown to one decimal. Rounding once, here, keeps the CLI, the
+// TUI and JSON consumers agreeing on the figure.
+pub fn round_to_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+// Deck-wide totals for `stats` and the TUI dashboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub total_topics: i64,
+    pub total_reviews: i64,
+    pub mastered: i64,
+    pub due_now: i64,
+    pub avg_mastery: f64,
+    // avg_mastery to one decimal (see round_to_tenth)
+    pub avg_mastery_rounded: f64,
+    pub total_gaps: i64,
+    pub open_gaps: i64,
+}
+
+// Stats narrowed to the topics carrying one tag, for the per-tag metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagStats {
+    pub tag: String,
+    pub topics: i64,
+    pub due_now: i64,
+    pub avg_mastery: f64,
+}
+
+// The few numbers `status` shows, cheap enough for a shell prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusCounts {
+    pub due: i64,
+    pub overdue: i64,
+    pub streak: i64,
+    pub avg_mastery: f64,
+    pub avg_mastery_rounded: f64,
+}
+
+// Class of a failed command, so scripts needn't match on the error message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    NotFound,
+}
+
 // JSON output wrapper for CLI
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JsonOutput<T: Serialize> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    // Only present on errors that have a kind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<ErrorKind>,
 }
 
 impl<T: Serialize> JsonOutput<T> {
@@ -443,6 +1485,7 @@ impl<T: Serialize> JsonOutput<T> {
             success: true,
             data: Some(data),
             error: None,
+            error_kind: None,
         }
     }
 
@@ -451,6 +1494,14 @@ impl<T: Serialize> JsonOutput<T> {
             success: false,
             data: None,
             error: Some(msg.into()),
+            error_kind: None,
+        }
+    }
+
+    pub fn not_found(msg: impl Into<String>) -> Self {
+        Self {
+            error_kind: Some(ErrorKind::NotFound),
+            ..Self::err(msg)
         }
     }
 }
@@ -479,9 +1530,188 @@ mod tests {
                 skill_level: SkillLevel::Unknown,
                 assessment_method: AssessmentMethod::None,
                 last_assessed: None,
+                difficulty: DEFAULT_DIFFICULTY,
             }
         }
 
+        fn now() -> DateTime<Utc> {
+            parse_timestamp("2024-03-31T12:00:00Z").unwrap()
+        }
+
+        #[test]
+        fn retention_starts_near_one_and_decays_over_the_interval() {
+            let mut p = make_progress(2, 3, 2);
+            assert_eq!(p.estimated_retention(now()), None);
+
+            // Reviewed at now(), due 8 days later
+            p.last_reviewed = Some(now().to_rfc3339());
+            p.next_review = Some((now() + chrono::Duration::days(8)).to_rfc3339());
+            let at = |days: i64| {
+                p.estimated_retention(now() + chrono::Duration::days(days))
+                    .unwrap()
+            };
+            assert!((at(0) - 1.0).abs() < 1e-9);
+            assert!(at(1) < 1.0 && at(1) > at(4));
+
+            // Mastery 2 → 86% on the due date, its square root at the midpoint
+            assert!((at(8) - 0.86).abs() < 1e-9);
+            assert!((at(4) - 0.86f64.sqrt()).abs() < 1e-9);
+            assert!(at(16) < at(8));
+
+            // Same interval, higher mastery: slower decay
+            let mut known = p.clone();
+            known.mastery_level = 5;
+            let four_days = now() + chrono::Duration::days(4);
+            assert!(known.estimated_retention(four_days) > p.estimated_retention(four_days));
+        }
+
+        #[test]
+        fn projected_mastery_from_level_0_sums_the_success_ladder() {
+            let mut p = make_progress(0, 0, 0);
+            p.next_review = Some("2024-04-01T09:00:00Z".to_string());
+            // Reviews on Apr 1 (0→1), then after 2, 4, 7 and 14 days
+            assert_eq!(
+                p.projected_mastery_date(&Settings::default(), now()),
+                Some("2024-04-28".to_string())
+            );
+        }
+
+        #[test]
+        fn projected_mastery_from_level_3_is_one_interval_away() {
+            let mut p = make_progress(3, 3, 3);
+            p.next_review = Some("2024-04-01T09:00:00Z".to_string());
+            assert_eq!(
+                p.projected_mastery_date(&Settings::default(), now()),
+                Some("2024-04-15".to_string())
+            );
+
+            // Overdue: the next review counts as today
+            p.next_review = Some("2024-03-20T09:00:00Z".to_string());
+            assert_eq!(
+                p.projected_mastery_date(&Settings::default(), now()),
+                Some("2024-04-14".to_string())
+            );
+
+            // Hard topics get shorter intervals (14 days * 0.6)
+            p.difficulty = 5;
+            assert_eq!(
+                p.projected_mastery_date(&Settings::default(), now()),
+                Some("2024-04-08".to_string())
+            );
+        }
+
+        #[test]
+        fn projected_mastery_stretches_on_time_reviews() {
+            let bonus = Settings::from_pairs([(crate::settings::SCHEDULE_ON_TIME_BONUS, "1.5")]);
+            let mut p = make_progress(2, 2, 2);
+            p.next_review = Some("2024-04-01T09:00:00Z".to_string());
+            // Never reviewed, so Apr 1 isn't a due date: 7 days, then 21 (14 * 1.5)
+            assert_eq!(
+                p.projected_mastery_date(&bonus, now()),
+                Some("2024-04-29".to_string())
+            );
+
+            // Reviewed before: 11 days (7 * 1.5, rounded), then 21
+            p.last_reviewed = Some("2024-03-28T09:00:00Z".to_string());
+            assert_eq!(
+                p.projected_mastery_date(&bonus, now()),
+                Some("2024-05-03".to_string())
+            );
+            assert_eq!(
+                p.projected_mastery_date(&Settings::default(), now()),
+                Some("2024-04-22".to_string())
+            );
+        }
+
+        #[test]
+        fn no_projection_when_mastered_or_success_does_not_advance() {
+            let p = make_progress(5, 5, 5);
+            assert_eq!(p.projected_mastery_date(&Settings::default(), now()), None);
+
+            let p = make_progress(2, 0, 0);
+            let stuck = Settings::from_pairs([(crate::settings::GRADING_SUCCESS_DELTA, "0")]);
+            assert_eq!(p.projected_mastery_date(&stuck, now()), None);
+
+            // Level 4 masters on its next review
+            let p = make_progress(4, 4, 4);
+            assert_eq!(
+                p.projected_mastery_date(&Settings::default(), now()),
+                Some("2024-03-31".to_string())
+            );
+        }
+
+        #[test]
+        fn health_of_new_topic_is_recency_only() {
+            let mut p = make_progress(0, 0, 0);
+            p.next_review = Some("2024-03-31T12:00:00Z".to_string());
+            assert_eq!(p.health_score(now()), 30.0);
+        }
+
+        #[test]
+        fn health_of_mastered_topic_on_schedule_is_full() {
+            let mut p = make_progress(5, 10, 10);
+            p.next_review = Some("2024-04-20T12:00:00Z".to_string());
+            assert_eq!(p.health_score(now()), 100.0);
+        }
+
+        #[test]
+        fn health_drops_as_topic_goes_overdue() {
+            // Mastery 3/5, 6 of 8 successful
+            let mut p = make_progress(3, 8, 6);
+            p.next_review = Some("2024-03-31T12:00:00Z".to_string());
+            assert!((p.health_score(now()) - 76.5).abs() < 1e-9);
+
+            // 15 days overdue: half the recency part is gone
+            p.next_review = Some("2024-03-16T12:00:00Z".to_string());
+            assert!((p.health_score(now()) - 61.5).abs() < 1e-9);
+
+            // Past the 30-day horizon recency bottoms out
+            p.next_review = Some("2024-01-01T12:00:00Z".to_string());
+            assert!((p.health_score(now()) - 46.5).abs() < 1e-9);
+        }
+
+        #[test]
+        fn health_weights_change_the_blend() {
+            let mut p = make_progress(5, 4, 0);
+            p.next_review = Some("2024-01-01T12:00:00Z".to_string());
+            let mastery_only = HealthWeights {
+                mastery: 1,
+                recency: 0,
+                success: 0,
+            };
+            assert_eq!(p.health_score_with(now(), &mastery_only), 100.0);
+            let success_only = HealthWeights {
+                mastery: 0,
+                recency: 0,
+                success: 2,
+            };
+            assert_eq!(p.health_score_with(now(), &success_only), 0.0);
+        }
+
+        #[test]
+        fn next_review_dt_accepts_rfc3339_and_sqlite_formats() {
+            let mut p = make_progress(0, 0, 0);
+            assert!(p.next_review_dt().is_none());
+            assert!(!p.has_unparseable_next_review());
+
+            p.next_review = Some("2024-03-01T12:00:00+00:00".to_string());
+            assert!(p.next_review_dt().is_some());
+            p.next_review = Some("2024-03-01 12:00:00".to_string());
+            assert_eq!(
+                p.next_review_dt().unwrap().to_rfc3339(),
+                "2024-03-01T12:00:00+00:00"
+            );
+            assert!(!p.has_unparseable_next_review());
+        }
+
+        #[test]
+        fn malformed_next_review_is_flagged() {
+            let mut p = make_progress(0, 0, 0);
+            p.next_review = Some("01/03/2024 noon".to_string());
+            assert!(p.next_review_dt().is_none());
+            assert!(p.has_unparseable_next_review());
+        }
+
         #[test]
         fn mastery_label_level_0() {
             let p = make_progress(0, 0, 0);
@@ -561,6 +1791,240 @@ mod tests {
         }
     }
 
+    mod tag_tests {
+        use super::*;
+
+        #[test]
+        fn normalize_tag_folds_case_and_whitespace() {
+            assert_eq!(normalize_tag("Rust"), "rust");
+            assert_eq!(normalize_tag("  rust "), "rust");
+            assert_eq!(normalize_tag("Data\t  Structures"), "data structures");
+            assert_eq!(normalize_tag("   "), "");
+        }
+    }
+
+    mod topic_row_tests {
+        use super::*;
+
+        fn now() -> DateTime<Utc> {
+            parse_timestamp("2024-03-10T12:00:00Z").unwrap()
+        }
+
+        fn twp(mastery_level: i32, next_review: Option<&str>) -> TopicWithProgress {
+            TopicWithProgress {
+                topic: Topic {
+                    id: 7,
+                    name: "Ownership".to_string(),
+                    description: None,
+                    created_at: "2024-01-01 00:00:00".to_string(),
+                    updated_at: "2024-01-01 00:00:00".to_string(),
+                    tags: Vec::new(),
+                    archived: false,
+                    suspended: false,
+                },
+                progress: Progress {
+                    id: 1,
+                    topic_id: 7,
+                    mastery_level,
+                    times_reviewed: 0,
+                    times_succeeded: 0,
+                    last_reviewed: None,
+                    next_review: next_review.map(str::to_string),
+                    notes: None,
+                    skill_level: SkillLevel::Beginner,
+                    assessment_method: AssessmentMethod::None,
+                    last_assessed: None,
+                    difficulty: DEFAULT_DIFFICULTY,
+                },
+            }
+        }
+
+        #[test]
+        fn overdue_topic() {
+            let row = TopicRow::new(&twp(2, Some("2024-03-01T09:00:00+00:00")), now());
+            assert!(row.overdue);
+            assert_eq!(row.next_review, "Mar 01");
+            assert_eq!(row.next_review_long, "Mar 01, 2024");
+            assert_eq!(row.mastery_bar, "██░░░");
+            assert_eq!(row.mastery_label, "Familiar");
+            assert_eq!(row.skill_label, "Beginner");
+            assert_eq!((row.topic_id, row.name.as_str()), (7, "Ownership"));
+        }
+
+        #[test]
+        fn upcoming_topic_in_sqlite_format() {
+            let row = TopicRow::new(&twp(0, Some("2024-03-12 08:00:00")), now());
+            assert!(!row.overdue);
+            assert_eq!(row.next_review, "Mar 12");
+        }
+
+        #[test]
+        fn unparseable_and_missing_dates() {
+            let row = TopicRow::new(&twp(1, Some("soon")), now());
+            assert!(!row.overdue);
+            assert_eq!(row.next_review, "Unknown");
+            assert_eq!(row.next_review_long, "Unknown");
+
+            let row = TopicRow::new(&twp(1, None), now());
+            assert!(!row.overdue);
+            assert_eq!(row.next_review, "Not set");
+        }
+
+        #[test]
+        fn count_bar_scales_to_the_largest_count() {
+            assert_eq!(count_bar(4, 4, 4), "████");
+            assert_eq!(count_bar(1, 4, 4), "█░░░");
+            assert_eq!(count_bar(0, 4, 4), "░░░░");
+            assert_eq!(count_bar(0, 0, 4), "░░░░");
+        }
+
+        #[test]
+        fn mastery_trend_keeps_the_latest_levels() {
+            assert_eq!(mastery_trend(&[1, 2, 1, 2], 10), "1→2→1→2");
+            assert_eq!(mastery_trend(&[1, 2, 1, 2], 2), "…→1→2");
+            assert_eq!(mastery_trend(&[], 5), "");
+        }
+
+        #[test]
+        fn mastery_bar_clamps_out_of_range_levels() {
+            assert_eq!(mastery_bar(0), "░░░░░");
+            assert_eq!(mastery_bar(5), "█████");
+            assert_eq!(mastery_bar(9), "█████");
+            assert_eq!(mastery_bar(-1), "░░░░░");
+        }
+    }
+
+    mod recommendation_tests {
+        use super::*;
+
+        fn now() -> DateTime<Utc> {
+            parse_timestamp("2024-03-10T12:00:00Z").unwrap()
+        }
+
+        // Reviewed once, level 2, self-rated Beginner (level 2) and due Mar 14
+        fn twp() -> TopicWithProgress {
+            TopicWithProgress {
+                topic: Topic {
+                    id: 7,
+                    name: "Ownership".to_string(),
+                    description: None,
+                    created_at: "2024-01-01 00:00:00".to_string(),
+                    updated_at: "2024-01-01 00:00:00".to_string(),
+                    tags: Vec::new(),
+                    archived: false,
+                    suspended: false,
+                },
+                progress: Progress {
+                    id: 1,
+                    topic_id: 7,
+                    mastery_level: 2,
+                    times_reviewed: 1,
+                    times_succeeded: 1,
+                    last_reviewed: Some("2024-03-08T12:00:00+00:00".to_string()),
+                    next_review: Some("2024-03-14T12:00:00+00:00".to_string()),
+                    notes: None,
+                    skill_level: SkillLevel::Beginner,
+                    assessment_method: AssessmentMethod::SelfAssessed,
+                    last_assessed: None,
+                    difficulty: DEFAULT_DIFFICULTY,
+                },
+            }
+        }
+
+        fn gap(id: i64, addressed: bool) -> SessionGap {
+            SessionGap {
+                id,
+                session_id: 1,
+                gap_description: "Lifetimes".to_string(),
+                addressed,
+            }
+        }
+
+        #[test]
+        fn paused_topics_come_first() {
+            let mut t = twp();
+            t.topic.suspended = true;
+            assert_eq!(
+                recommend_action(&t, &[gap(1, false)], now()),
+                Recommendation::Paused
+            );
+            t.topic.suspended = false;
+            t.topic.archived = true;
+            assert_eq!(recommend_action(&t, &[], now()), Recommendation::Paused);
+        }
+
+        #[test]
+        fn open_gaps_beat_everything_else() {
+            let mut t = twp();
+            t.progress.skill_level = SkillLevel::Expert;
+            let gaps = [gap(1, false), gap(2, true), gap(3, false)];
+            let r = recommend_action(&t, &gaps, now());
+            assert_eq!(r, Recommendation::CloseGaps { gaps: 2 });
+            assert_eq!(
+                r.to_string(),
+                "Run a Feynman session \u{2014} you have 2 unaddressed gaps"
+            );
+            assert_eq!(r.as_str(), "close_gaps");
+        }
+
+        #[test]
+        fn skill_far_from_mastery_is_checked() {
+            let mut t = twp();
+            t.progress.skill_level = SkillLevel::Advanced;
+            assert_eq!(
+                recommend_action(&t, &[gap(1, true)], now()),
+                Recommendation::CheckSkill {
+                    skill: SkillLevel::Advanced,
+                    mastery: 2
+                }
+            );
+            // One level apart, or never assessed, is fine
+            t.progress.skill_level = SkillLevel::Intermediate;
+            assert!(matches!(
+                recommend_action(&t, &[], now()),
+                Recommendation::Wait { .. }
+            ));
+            t.progress.mastery_level = 4;
+            t.progress.skill_level = SkillLevel::Unknown;
+            assert!(matches!(
+                recommend_action(&t, &[], now()),
+                Recommendation::Wait { .. }
+            ));
+        }
+
+        #[test]
+        fn never_reviewed_topics_are_learned() {
+            let mut t = twp();
+            t.progress.times_reviewed = 0;
+            t.progress.next_review = None;
+            assert_eq!(recommend_action(&t, &[], now()), Recommendation::Learn);
+        }
+
+        #[test]
+        fn due_or_unscheduled_topics_are_reviewed() {
+            let mut t = twp();
+            t.progress.next_review = Some("2024-03-10T12:00:00+00:00".to_string());
+            assert_eq!(recommend_action(&t, &[], now()), Recommendation::Review);
+            t.progress.next_review = Some("not a date".to_string());
+            assert_eq!(recommend_action(&t, &[], now()), Recommendation::Review);
+            assert_eq!(Recommendation::Review.to_string(), "Due for review");
+        }
+
+        #[test]
+        fn mastered_and_waiting_topics_show_the_next_review() {
+            let mut t = twp();
+            let r = recommend_action(&t, &[], now());
+            assert!(matches!(r, Recommendation::Wait { .. }));
+            assert_eq!(r.to_string(), "Nothing to do until Mar 14");
+
+            t.progress.mastery_level = MAX_MASTERY;
+            t.progress.skill_level = SkillLevel::Expert;
+            let r = recommend_action(&t, &[], now());
+            assert!(matches!(r, Recommendation::Mastered { .. }));
+            assert_eq!(r.to_string(), "Mastered \u{2014} next check-in Mar 14");
+        }
+    }
+
     mod review_outcome_tests {
         use super::*;
 
@@ -624,6 +2088,291 @@ mod tests {
         }
     }
 
+    mod calibration_tests {
+        use super::*;
+
+        fn review(id: i64, outcome: ReviewOutcome, confidence: Option<i32>) -> ReviewRecord {
+            ReviewRecord {
+                id,
+                topic_id: 1,
+                outcome,
+                reviewed_at: "2024-01-01T00:00:00+00:00".to_string(),
+                notes: None,
+                confidence,
+            }
+        }
+
+        #[test]
+        fn counts_outcomes_per_confidence_level() {
+            let report = CalibrationReport::from_reviews(&[
+                review(1, ReviewOutcome::Success, Some(3)),
+                review(2, ReviewOutcome::Partial, Some(3)),
+                review(3, ReviewOutcome::Fail, Some(1)),
+                review(4, ReviewOutcome::Success, None),
+            ]);
+
+            assert_eq!(report.levels.len(), 5);
+            assert_eq!(report.rated_reviews(), 3);
+            let three = &report.levels[2];
+            assert_eq!((three.confidence, three.reviews), (3, 2));
+            assert_eq!((three.success, three.partial, three.fail), (1, 1, 0));
+            assert_eq!(three.success_rate(), 0.5);
+            assert_eq!(report.levels[4].success_rate(), 0.0);
+        }
+
+        #[test]
+        fn high_confidence_fails_are_overconfident() {
+            let report = CalibrationReport::from_reviews(&[
+                review(1, ReviewOutcome::Fail, Some(5)),
+                review(2, ReviewOutcome::Fail, Some(4)),
+                review(3, ReviewOutcome::Fail, Some(3)),
+                review(4, ReviewOutcome::Partial, Some(5)),
+                review(5, ReviewOutcome::Success, Some(5)),
+            ]);
+
+            let ids: Vec<i64> = report.overconfident.iter().map(|r| r.id).collect();
+            assert_eq!(ids, vec![1, 2]);
+            assert!(report.underconfident.is_empty());
+        }
+
+        #[test]
+        fn low_confidence_successes_are_underconfident() {
+            let report = CalibrationReport::from_reviews(&[
+                review(1, ReviewOutcome::Success, Some(1)),
+                review(2, ReviewOutcome::Success, Some(2)),
+                review(3, ReviewOutcome::Success, Some(3)),
+                review(4, ReviewOutcome::Fail, Some(1)),
+            ]);
+
+            let ids: Vec<i64> = report.underconfident.iter().map(|r| r.id).collect();
+            assert_eq!(ids, vec![1, 2]);
+            assert!(report.overconfident.is_empty());
+        }
+    }
+
+    mod topic_prompt_tests {
+        use super::*;
+
+        fn prompts(texts: &[&str]) -> Vec<TopicPrompt> {
+            texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| TopicPrompt {
+                    id: i as i64 + 1,
+                    topic_id: 1,
+                    prompt: text.to_string(),
+                    ord: i as i32 + 1,
+                })
+                .collect()
+        }
+
+        #[test]
+        fn review_prompt_cycles_through_in_order() {
+            let prompts = prompts(&["A", "B", "C"]);
+            let asked: Vec<&str> = (0..5)
+                .map(|n| {
+                    TopicPrompt::for_review(&prompts, n)
+                        .unwrap()
+                        .prompt
+                        .as_str()
+                })
+                .collect();
+            assert_eq!(asked, ["A", "B", "C", "A", "B"]);
+        }
+
+        #[test]
+        fn no_prompts_means_no_review_prompt() {
+            assert!(TopicPrompt::for_review(&[], 3).is_none());
+            assert_eq!(
+                TopicPrompt::for_review(&prompts(&["A"]), -1)
+                    .unwrap()
+                    .prompt,
+                "A"
+            );
+        }
+    }
+
+    mod topic_resource_tests {
+        use super::*;
+
+        #[test]
+        fn urls_need_a_scheme_and_no_spaces() {
+            for url in [
+                "https://doc.rust-lang.org/book/ch04-00-understanding-ownership.html",
+                "HTTP://example.com",
+                "file:///home/me/notes/ownership.pdf",
+                "isbn:9781718503106",
+            ] {
+                assert!(TopicResource::check_url(url).is_ok(), "{}", url);
+            }
+            for url in [
+                "",
+                "doc.rust-lang.org/book",
+                "https://",
+                "https:///path",
+                "https:example.com",
+                "https://example.com/a b",
+                "-x:y",
+                "mailto:",
+            ] {
+                assert!(TopicResource::check_url(url).is_err(), "{}", url);
+            }
+        }
+
+        #[test]
+        fn title_falls_back_to_the_url() {
+            let mut resource = TopicResource {
+                id: 1,
+                topic_id: 1,
+                label: None,
+                url: "https://example.com".to_string(),
+            };
+            assert_eq!(resource.title(), "https://example.com");
+            resource.label = Some("The Book, ch. 4".to_string());
+            assert_eq!(resource.title(), "The Book, ch. 4");
+        }
+    }
+
+    mod text_enum_tests {
+        use super::*;
+
+        fn assert_round_trips<T>(variants: &[T])
+        where
+            T: fmt::Display + std::str::FromStr<Err = ParseEnumError> + PartialEq + fmt::Debug,
+        {
+            for variant in variants {
+                assert_eq!(variant.to_string().parse::<T>().as_ref(), Ok(variant));
+            }
+        }
+
+        #[test]
+        fn display_round_trips_through_from_str() {
+            assert_round_trips(ReviewOutcome::value_variants());
+            assert_round_trips(SessionType::value_variants());
+            assert_round_trips(SessionOutcome::value_variants());
+            assert_round_trips(SkillLevel::value_variants());
+            assert_round_trips(&[
+                PlanStatus::Interviewing,
+                PlanStatus::SpecReady,
+                PlanStatus::Approved,
+                PlanStatus::InProgress,
+                PlanStatus::Complete,
+                PlanStatus::Abandoned,
+            ]);
+            assert_round_trips(&[
+                InterviewEntryType::Question,
+                InterviewEntryType::Answer,
+                InterviewEntryType::Note,
+                InterviewEntryType::Clarification,
+                InterviewEntryType::Decision,
+            ]);
+            assert_round_trips(&[
+                InterviewCategory::Requirements,
+                InterviewCategory::EdgeCases,
+                InterviewCategory::Security,
+                InterviewCategory::Deployment,
+                InterviewCategory::Architecture,
+                InterviewCategory::Performance,
+                InterviewCategory::Testing,
+                InterviewCategory::DoD,
+                InterviewCategory::Scope,
+                InterviewCategory::Dependencies,
+                InterviewCategory::Risks,
+                InterviewCategory::Other,
+            ]);
+        }
+
+        #[test]
+        fn parse_accepts_aliases_and_names_the_bad_value() {
+            assert_eq!("S".parse::<ReviewOutcome>(), Ok(ReviewOutcome::Success));
+            assert_eq!("3".parse::<SkillLevel>(), Ok(SkillLevel::Intermediate));
+            assert_eq!(SkillLevel::Intermediate.to_string(), "Intermediate");
+            assert_eq!(PlanStatus::SpecReady.to_string(), "spec_ready");
+
+            let err = "meh".parse::<ReviewOutcome>().unwrap_err();
+            assert_eq!(err.kind, EnumKind::ReviewOutcome);
+            assert_eq!(err.to_string(), "unknown review outcome 'meh'");
+        }
+    }
+
+    mod study_session_tests {
+        use super::*;
+
+        fn review(id: i64, outcome: ReviewOutcome, reviewed_at: &str) -> ReviewRecord {
+            ReviewRecord {
+                id,
+                topic_id: 1,
+                outcome,
+                reviewed_at: format!("{}:00+00:00", reviewed_at),
+                notes: None,
+                confidence: None,
+            }
+        }
+
+        fn spans(sessions: &[StudySession]) -> Vec<(String, String, i64)> {
+            sessions
+                .iter()
+                .map(|s| {
+                    (
+                        s.start[..16].to_string(),
+                        s.end[..16].to_string(),
+                        s.reviews,
+                    )
+                })
+                .collect()
+        }
+
+        #[test]
+        fn splits_on_gaps_longer_than_the_limit() {
+            let reviews = [
+                review(1, ReviewOutcome::Success, "2024-03-01T09:00"),
+                review(2, ReviewOutcome::Fail, "2024-03-01T09:10"),
+                // Exactly the gap still continues the session
+                review(3, ReviewOutcome::Success, "2024-03-01T09:40"),
+                review(4, ReviewOutcome::Partial, "2024-03-01T11:00"),
+                review(5, ReviewOutcome::Success, "2024-03-01T11:05"),
+                review(6, ReviewOutcome::Success, "2024-03-02T08:00"),
+            ];
+            let sessions = StudySession::cluster(&reviews, chrono::Duration::minutes(30));
+
+            assert_eq!(
+                spans(&sessions),
+                [
+                    ("2024-03-02T08:00".into(), "2024-03-02T08:00".into(), 1),
+                    ("2024-03-01T11:00".into(), "2024-03-01T11:05".into(), 2),
+                    ("2024-03-01T09:00".into(), "2024-03-01T09:40".into(), 3),
+                ]
+            );
+            assert_eq!(sessions[2].successes, 2);
+            assert!((sessions[2].success_rate - 2.0 / 3.0).abs() < 1e-9);
+            assert_eq!(sessions[1].success_rate, 0.5);
+            assert_eq!(sessions[2].duration(), Some(chrono::Duration::minutes(40)));
+            assert_eq!(sessions[0].duration(), Some(chrono::Duration::zero()));
+        }
+
+        #[test]
+        fn orders_by_time_not_input_order() {
+            let reviews = [
+                review(1, ReviewOutcome::Success, "2024-03-01T10:00"),
+                review(2, ReviewOutcome::Success, "2024-03-01T09:00"),
+                review(3, ReviewOutcome::Success, "2024-03-01T09:50"),
+            ];
+            let sessions = StudySession::cluster(&reviews, chrono::Duration::minutes(15));
+            assert_eq!(
+                spans(&sessions),
+                [
+                    ("2024-03-01T09:50".into(), "2024-03-01T10:00".into(), 2),
+                    ("2024-03-01T09:00".into(), "2024-03-01T09:00".into(), 1),
+                ]
+            );
+        }
+
+        #[test]
+        fn no_reviews_means_no_sessions() {
+            assert!(StudySession::cluster(&[], chrono::Duration::minutes(30)).is_empty());
+        }
+    }
+
     mod json_output_tests {
         use super::*;
 
@@ -846,6 +2595,60 @@ mod tests {
         }
     }
 
+    mod learning_session_tests {
+        use super::*;
+
+        fn session(started_at: &str, ended_at: Option<&str>) -> LearningSession {
+            LearningSession {
+                id: 1,
+                topic_id: 1,
+                session_type: SessionType::Feynman,
+                started_at: started_at.to_string(),
+                ended_at: ended_at.map(str::to_string),
+                skill_level_at_start: None,
+                outcome: None,
+                summary: None,
+                notes: None,
+            }
+        }
+
+        #[test]
+        fn duration_spans_start_to_end() {
+            let s = session("2024-03-01T12:00:00Z", Some("2024-03-01T12:25:30Z"));
+            assert!(!s.is_active());
+            assert_eq!(s.duration(), Some(chrono::Duration::seconds(25 * 60 + 30)));
+            // SQLite's datetime('now') format parses too
+            let s = session("2024-03-01 12:00:00", Some("2024-03-01T13:00:00+00:00"));
+            assert_eq!(s.duration(), Some(chrono::Duration::hours(1)));
+        }
+
+        #[test]
+        fn duration_is_none_without_ended_at() {
+            let s = session("2024-03-01T12:00:00Z", None);
+            assert!(s.is_active());
+            assert_eq!(s.duration(), None);
+            assert_eq!(SessionRow::from(s).duration_secs, None);
+        }
+
+        #[test]
+        fn duration_is_none_for_unparseable_timestamps() {
+            let s = session("garbage", Some("2024-03-01T12:00:00Z"));
+            assert!(!s.is_active());
+            assert_eq!(s.duration(), None);
+        }
+
+        #[test]
+        fn format_duration_picks_a_unit() {
+            assert_eq!(format_duration(chrono::Duration::seconds(45)), "45s");
+            assert_eq!(
+                format_duration(chrono::Duration::seconds(12 * 60 + 59)),
+                "12m"
+            );
+            assert_eq!(format_duration(chrono::Duration::minutes(65)), "1h 05m");
+            assert_eq!(format_duration(chrono::Duration::seconds(-5)), "0s");
+        }
+    }
+
     mod assessment_method_tests {
         use super::*;
 
@@ -915,6 +2718,50 @@ mod tests {
             assert_eq!(PlanStatus::SpecReady.label(), "Spec Ready");
             assert_eq!(PlanStatus::InProgress.label(), "In Progress");
         }
+
+        #[test]
+        fn cycle_steps_through_the_lifecycle() {
+            let mut status = PlanStatus::Interviewing;
+            let mut seen = vec![status];
+            while let Some(next) = status.next_in_cycle() {
+                assert!(status.can_transition_to(next));
+                status = next;
+                seen.push(status);
+            }
+            assert_eq!(
+                seen,
+                [
+                    PlanStatus::Interviewing,
+                    PlanStatus::SpecReady,
+                    PlanStatus::Approved,
+                    PlanStatus::InProgress,
+                    PlanStatus::Complete,
+                ]
+            );
+            assert_eq!(
+                PlanStatus::Abandoned.next_in_cycle(),
+                Some(PlanStatus::Interviewing)
+            );
+        }
+
+        #[test]
+        fn transitions_reject_skips_and_reopening_complete_plans() {
+            assert!(!PlanStatus::Interviewing.can_transition_to(PlanStatus::Approved));
+            assert!(!PlanStatus::Approved.can_transition_to(PlanStatus::Interviewing));
+            assert!(!PlanStatus::Complete.can_transition_to(PlanStatus::Abandoned));
+            assert!(!PlanStatus::Complete.can_transition_to(PlanStatus::Interviewing));
+            assert!(PlanStatus::InProgress.can_transition_to(PlanStatus::Abandoned));
+            assert!(PlanStatus::SpecReady.can_transition_to(PlanStatus::Interviewing));
+            assert_eq!(
+                PlanStatus::SpecReady.allowed_transitions(),
+                [
+                    PlanStatus::Interviewing,
+                    PlanStatus::Approved,
+                    PlanStatus::Abandoned
+                ]
+            );
+            assert!(PlanStatus::Complete.allowed_transitions().is_empty());
+        }
     }
 
     mod interview_entry_type_tests {
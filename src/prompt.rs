@@ -0,0 +1,183 @@
+// Line input for interactive prompts.
+//
+// On a terminal, prompts go through rustyline (line editing, arrow keys and
+// in-memory history for the current process). Otherwise, plain stdin is read
+// so piped input keeps working. Only interactive commands use this.
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+pub trait Prompt {
+    // Read one line without its trailing newline. Ok(None) on EOF or Ctrl-C.
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>>;
+}
+
+// Pick rustyline for a terminal, plain stdin otherwise
+pub fn interactive() -> Box<dyn Prompt> {
+    if io::stdin().is_terminal() {
+        if let Ok(editor) = EditorPrompt::new() {
+            return Box::new(editor);
+        }
+    }
+    Box::new(StdinPrompt)
+}
+
+pub struct EditorPrompt {
+    editor: DefaultEditor,
+}
+
+impl EditorPrompt {
+    pub fn new() -> rustyline::Result<Self> {
+        Ok(Self {
+            editor: DefaultEditor::new()?,
+        })
+    }
+}
+
+impl Prompt for EditorPrompt {
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        match self.editor.readline(prompt) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    let _ = self.editor.add_history_entry(line.as_str());
+                }
+                Ok(Some(line))
+            }
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => Ok(None),
+            Err(ReadlineError::Io(e)) => Err(e),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+pub struct StdinPrompt;
+
+impl Prompt for StdinPrompt {
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+    }
+}
+
+// Confirm a destructive command. `assume_yes` (the global --yes) answers for
+// the user; without it, a stdin that isn't a terminal declines, so a script
+// never blocks on the question or deletes anything by accident.
+pub fn confirm(question: &str, default: bool, assume_yes: bool) -> io::Result<bool> {
+    if assume_yes {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        eprintln!("Not confirmed: stdin isn't a terminal (pass --yes to confirm).");
+        return Ok(false);
+    }
+    ask_yes_no(interactive().as_mut(), question, default)
+}
+
+// Ask until the answer is yes, no or empty (the default). No answer at all
+// (EOF, Ctrl-C) is a no whatever the default.
+pub fn ask_yes_no(input: &mut dyn Prompt, question: &str, default: bool) -> io::Result<bool> {
+    let question = format!("{} {}: ", question, if default { "[Y/n]" } else { "[y/N]" });
+    loop {
+        let Some(answer) = input.read_line(&question)? else {
+            return Ok(false);
+        };
+        match parse_yes_no(&answer, default) {
+            Some(yes) => return Ok(yes),
+            None => println!("Please answer y or n."),
+        }
+    }
+}
+
+// y/yes or n/no in any case; an empty answer is the default
+pub fn parse_yes_no(answer: &str, default: bool) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "" => Some(default),
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+// Canned answers for tests; records the prompts it was shown
+#[cfg(test)]
+pub struct ScriptedPrompt {
+    lines: std::collections::VecDeque<String>,
+    pub prompts: Vec<String>,
+}
+
+#[cfg(test)]
+impl ScriptedPrompt {
+    pub fn new(lines: &[&str]) -> Self {
+        Self {
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+            prompts: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Prompt for ScriptedPrompt {
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        self.prompts.push(prompt.to_string());
+        Ok(self.lines.pop_front())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yes_no_answers_against_both_defaults() {
+        for default in [false, true] {
+            for (answer, expected) in [
+                ("y", Some(true)),
+                ("YES", Some(true)),
+                (" yes ", Some(true)),
+                ("n", Some(false)),
+                ("No", Some(false)),
+                ("", Some(default)),
+                ("  ", Some(default)),
+                ("maybe", None),
+                ("yess", None),
+            ] {
+                assert_eq!(
+                    parse_yes_no(answer, default),
+                    expected,
+                    "{:?} with default {}",
+                    answer,
+                    default
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unclear_answers_ask_again() {
+        let mut input = ScriptedPrompt::new(&["sure", "y"]);
+        assert!(ask_yes_no(&mut input, "Delete?", false).unwrap());
+        assert_eq!(input.prompts, ["Delete? [y/N]: ", "Delete? [y/N]: "]);
+
+        let mut input = ScriptedPrompt::new(&[""]);
+        assert!(ask_yes_no(&mut input, "Keep?", true).unwrap());
+        assert_eq!(input.prompts, ["Keep? [Y/n]: "]);
+    }
+
+    #[test]
+    fn no_answer_declines_even_when_the_default_is_yes() {
+        let mut input = ScriptedPrompt::new(&["what"]);
+        assert!(!ask_yes_no(&mut input, "Delete?", true).unwrap());
+    }
+
+    #[test]
+    fn assume_yes_confirms_without_asking() {
+        assert!(confirm("Delete?", false, true).unwrap());
+    }
+}
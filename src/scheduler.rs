@@ -0,0 +1,368 @@
+// The spaced-repetition scheduler: how a review moves a topic's mastery
+// level and how long until it's due again. Pure functions of the settings, so
+// live reviews, history replay and projections (Progress) all share them.
+
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::models::{ReviewOutcome, DEFAULT_DIFFICULTY};
+use crate::settings::{Settings, MAX_MASTERY};
+
+// Scheduler step shared by live reviews and history replay:
+// returns the new mastery level and the time until the next review.
+// The level change comes from the grading scale (+1/same/-1 by default)
+// and the interval is clamped to schedule.min_days/max_days, except that
+// schedule.fail_minutes/partial_minutes bring a missed topic back within
+// the same session instead. An `on_time` review stretches a day-based
+// interval by schedule.on_time_bonus. With schedule.fuzz_percent set,
+// `rng` then jitters the interval.
+pub fn schedule_review(
+    settings: &Settings,
+    mastery_level: i32,
+    outcome: ReviewOutcome,
+    difficulty: i32,
+    on_time: bool,
+    rng: &mut impl Rng,
+) -> (i32, chrono::Duration) {
+    let new_level = settings.grading.apply(mastery_level, outcome);
+    let interval = scheduled_interval(settings, new_level, outcome, difficulty, on_time);
+    (new_level, fuzz_interval(settings, interval, rng))
+}
+
+// The interval a review that left the topic at `new_level` schedules, before
+// fuzzing: review_interval, stretched by schedule.on_time_bonus when
+// `on_time`
+pub fn scheduled_interval(
+    settings: &Settings,
+    new_level: i32,
+    outcome: ReviewOutcome,
+    difficulty: i32,
+    on_time: bool,
+) -> chrono::Duration {
+    let interval = review_interval(settings, new_level, outcome, difficulty);
+    if on_time {
+        stretch_interval(settings, interval, settings.on_time_bonus)
+    } else {
+        interval
+    }
+}
+
+// A success or partial from the due date an earlier review set (`due`) to
+// schedule.on_time_grace_days after it, with the bonus turned on. Very late
+// reviews don't earn it (the memory has likely already faded) and neither do
+// early ones, which haven't waited out the interval being stretched.
+pub fn earns_on_time_bonus(
+    settings: &Settings,
+    outcome: ReviewOutcome,
+    due: Option<DateTime<Utc>>,
+    reviewed_at: DateTime<Utc>,
+) -> bool {
+    settings.on_time_bonus > 1.0
+        && outcome != ReviewOutcome::Fail
+        && due.is_some_and(|due| {
+            reviewed_at >= due - ON_TIME_TOLERANCE
+                && reviewed_at - due <= chrono::Duration::days(settings.on_time_grace_days as i64)
+        })
+}
+
+// Slack before the due date that still counts as on time, for clock skew
+// between whoever set the date and whoever records the review
+const ON_TIME_TOLERANCE: chrono::Duration = chrono::Duration::minutes(1);
+
+// Multiply a day-based interval, rounding to whole days within
+// schedule.min_days/max_days. Short retries stay exact.
+fn stretch_interval(
+    settings: &Settings,
+    interval: chrono::Duration,
+    factor: f64,
+) -> chrono::Duration {
+    let days = interval.num_days();
+    if interval != chrono::Duration::days(days) {
+        return interval;
+    }
+    let stretched = (days as f64 * factor).round() as i32;
+    chrono::Duration::days(settings.clamp_days(stretched) as i64)
+}
+
+// Move a day-based interval by up to ±schedule.fuzz_percent whole days,
+// staying within schedule.min_days/max_days. Short retries stay exact.
+pub fn fuzz_interval(
+    settings: &Settings,
+    interval: chrono::Duration,
+    rng: &mut impl Rng,
+) -> chrono::Duration {
+    let days = interval.num_days();
+    if interval != chrono::Duration::days(days) {
+        return interval;
+    }
+    let spread = (days as f64 * settings.fuzz_percent as f64 / 100.0).round() as i64;
+    if spread == 0 {
+        return interval;
+    }
+    let fuzzed = days + rng.gen_range(-spread..=spread);
+    chrono::Duration::days(settings.clamp_days(fuzzed as i32) as i64)
+}
+
+// Time until the next review for a topic now at new_level after outcome
+pub fn review_interval(
+    settings: &Settings,
+    new_level: i32,
+    outcome: ReviewOutcome,
+    difficulty: i32,
+) -> chrono::Duration {
+    let retry_minutes = match outcome {
+        ReviewOutcome::Success => None,
+        ReviewOutcome::Partial => settings.partial_minutes,
+        ReviewOutcome::Fail => settings.fail_minutes,
+    };
+    if let Some(minutes) = retry_minutes {
+        return chrono::Duration::minutes(minutes as i64);
+    }
+
+    let days = match outcome {
+        ReviewOutcome::Success => scale_for_difficulty(settings.interval(new_level), difficulty),
+        ReviewOutcome::Partial => {
+            scale_for_difficulty(settings.partial_interval(new_level), difficulty)
+        }
+        ReviewOutcome::Fail => 1, // Review again tomorrow
+    };
+    chrono::Duration::days(settings.clamp_days(days) as i64)
+}
+
+// Days until the next review after a success at each mastery level, with
+// interval overrides, clamps and the default difficulty applied
+pub fn interval_ladder(settings: &Settings) -> Vec<(i32, i32)> {
+    (0..=MAX_MASTERY)
+        .map(|level| {
+            let days = scale_for_difficulty(settings.interval(level), DEFAULT_DIFFICULTY);
+            (level, settings.clamp_days(days))
+        })
+        .collect()
+}
+
+// Stretch or shrink an interval by topic difficulty: 3 is neutral,
+// 1 (easy) grows intervals by half, 5 (hard) cuts them by 40%
+fn scale_for_difficulty(days: i32, difficulty: i32) -> i32 {
+    let factor = match difficulty {
+        ..=1 => 1.5,
+        2 => 1.25,
+        3 => 1.0,
+        4 => 0.8,
+        _ => 0.6,
+    };
+    ((days as f64 * factor).round() as i32).max(1)
+}
+
+// Fuzz for a review is seeded by its topic and time, so replaying the history
+// (rebuild-progress) or rescheduling lands on the dates the review chose
+pub fn review_rng(topic_id: i64, reviewed_at: DateTime<Utc>) -> StdRng {
+    StdRng::seed_from_u64(
+        (topic_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ reviewed_at.timestamp() as u64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings;
+    use chrono::Duration;
+
+    // The scheduler step with a fixed fuzz seed
+    fn schedule(
+        settings: &Settings,
+        mastery_level: i32,
+        outcome: ReviewOutcome,
+        difficulty: i32,
+    ) -> (i32, Duration) {
+        let mut rng = StdRng::seed_from_u64(7);
+        schedule_review(
+            settings,
+            mastery_level,
+            outcome,
+            difficulty,
+            false,
+            &mut rng,
+        )
+    }
+
+    #[test]
+    fn only_reviews_from_the_due_date_to_the_grace_limit_are_on_time() {
+        let mut settings = Settings::default();
+        settings
+            .set(settings::SCHEDULE_ON_TIME_BONUS, "1.5")
+            .unwrap();
+        let due: DateTime<Utc> = "2024-03-10T12:00:00Z".parse().unwrap();
+        let on_time = |at: DateTime<Utc>| {
+            earns_on_time_bonus(&settings, ReviewOutcome::Success, Some(due), at)
+        };
+
+        assert!(on_time(due));
+        assert!(on_time(due - Duration::seconds(30)));
+        assert!(on_time(due + Duration::days(1)));
+        assert!(!on_time(due + Duration::days(1) + Duration::minutes(1)));
+        // Early, even within the grace window
+        assert!(!on_time(due - Duration::hours(12)));
+        assert!(!on_time(due - Duration::days(1)));
+    }
+
+    #[test]
+    fn neutral_difficulty_keeps_base_interval() {
+        assert_eq!(
+            schedule(
+                &Settings::default(),
+                2,
+                ReviewOutcome::Success,
+                DEFAULT_DIFFICULTY
+            ),
+            (3, Duration::days(7))
+        );
+    }
+
+    #[test]
+    fn harder_topics_get_shorter_intervals_at_same_mastery() {
+        for outcome in [ReviewOutcome::Success, ReviewOutcome::Partial] {
+            let (_, easy) = schedule(&Settings::default(), 3, outcome, 1);
+            let (_, normal) = schedule(&Settings::default(), 3, outcome, 3);
+            let (_, hard) = schedule(&Settings::default(), 3, outcome, 5);
+            assert!(easy > normal, "{:?}: {} vs {}", outcome, easy, normal);
+            assert!(normal > hard, "{:?}: {} vs {}", outcome, normal, hard);
+        }
+    }
+
+    #[test]
+    fn difficulty_never_drops_interval_below_one_day() {
+        assert_eq!(
+            schedule(&Settings::default(), 1, ReviewOutcome::Fail, 5),
+            (0, Duration::days(1))
+        );
+        assert_eq!(
+            schedule(&Settings::default(), 0, ReviewOutcome::Partial, 5),
+            (0, Duration::days(1))
+        );
+    }
+
+    #[test]
+    fn max_days_caps_long_intervals() {
+        let mut settings = Settings::default();
+        settings.set("schedule.interval.5", "90").unwrap();
+        assert_eq!(
+            schedule(&settings, 4, ReviewOutcome::Success, DEFAULT_DIFFICULTY),
+            (5, Duration::days(90))
+        );
+
+        settings.set(settings::SCHEDULE_MAX_DAYS, "60").unwrap();
+        assert_eq!(
+            schedule(&settings, 4, ReviewOutcome::Success, DEFAULT_DIFFICULTY),
+            (5, Duration::days(60))
+        );
+        assert_eq!(interval_ladder(&settings)[5], (5, 60));
+    }
+
+    #[test]
+    fn default_partial_factor_halves_the_interval() {
+        let settings = Settings::default();
+        for (level, days) in [(0, 1), (2, 2), (3, 3), (5, 15)] {
+            assert_eq!(
+                schedule(&settings, level, ReviewOutcome::Partial, DEFAULT_DIFFICULTY),
+                (level, Duration::days(days))
+            );
+        }
+    }
+
+    #[test]
+    fn full_partial_factor_matches_success_without_level_up() {
+        let mut settings = Settings::default();
+        settings
+            .set(settings::SCHEDULE_PARTIAL_FACTOR, "1")
+            .unwrap();
+        for level in 0..settings::MAX_MASTERY {
+            for difficulty in 1..=5 {
+                let (partial_level, partial) =
+                    schedule(&settings, level, ReviewOutcome::Partial, difficulty);
+                assert_eq!(partial_level, level);
+                assert_eq!(
+                    partial,
+                    review_interval(&settings, level, ReviewOutcome::Success, difficulty)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_stays_within_the_configured_band() {
+        let mut settings = Settings::default();
+        settings.set(settings::SCHEDULE_FUZZ_PERCENT, "20").unwrap();
+        let mut rng = StdRng::seed_from_u64(42);
+
+        // Level 5 waits 30 days, so 20% is ±6
+        let days: std::collections::HashSet<i64> = (0..200)
+            .map(|_| {
+                let (_, interval) = schedule_review(
+                    &settings,
+                    4,
+                    ReviewOutcome::Success,
+                    DEFAULT_DIFFICULTY,
+                    false,
+                    &mut rng,
+                );
+                interval.num_days()
+            })
+            .collect();
+        assert!(days.iter().all(|d| (24..=36).contains(d)), "{:?}", days);
+        assert!(days.len() > 5, "{:?}", days);
+
+        // Short retries are never fuzzed
+        settings.set(settings::SCHEDULE_FAIL_MINUTES, "10").unwrap();
+        assert_eq!(
+            schedule(&settings, 4, ReviewOutcome::Fail, DEFAULT_DIFFICULTY).1,
+            Duration::minutes(10)
+        );
+    }
+
+    #[test]
+    fn zero_fuzz_keeps_exact_intervals() {
+        let settings = Settings::default();
+        let mut rng = StdRng::seed_from_u64(42);
+        for level in 0..=settings::MAX_MASTERY {
+            for outcome in [
+                ReviewOutcome::Success,
+                ReviewOutcome::Partial,
+                ReviewOutcome::Fail,
+            ] {
+                let (new_level, interval) = schedule_review(
+                    &settings,
+                    level,
+                    outcome,
+                    DEFAULT_DIFFICULTY,
+                    false,
+                    &mut rng,
+                );
+                assert_eq!(
+                    interval,
+                    review_interval(&settings, new_level, outcome, DEFAULT_DIFFICULTY)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn min_days_floors_short_intervals() {
+        // Partial at level 0 halves a 1-day interval to 0 before flooring
+        let mut settings = Settings::default();
+        assert_eq!(
+            schedule(&settings, 0, ReviewOutcome::Partial, 5),
+            (0, Duration::days(1))
+        );
+
+        settings.set(settings::SCHEDULE_MIN_DAYS, "3").unwrap();
+        assert_eq!(
+            schedule(&settings, 0, ReviewOutcome::Partial, 5),
+            (0, Duration::days(3))
+        );
+        assert_eq!(
+            schedule(&settings, 2, ReviewOutcome::Fail, 3),
+            (1, Duration::days(3))
+        );
+    }
+}
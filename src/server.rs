@@ -0,0 +1,220 @@
+// Read-only JSON API over HTTP (`feynman serve`), for dashboards and other
+// local tools. Responses use the same JsonOutput envelope as `--json`.
+//
+//   GET /stats          learning statistics
+//   GET /topics         active topics with their progress
+//   GET /next           a weighted pick, with every candidate's weight
+//   GET /due?limit=N    topics due now, most overdue first (default 50)
+//   GET /metrics        Prometheus text format (as `stats --prometheus`)
+//
+// The database is opened read-only and anything but GET/HEAD gets a 405.
+
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::metrics;
+use crate::models::{JsonOutput, SelectionStrategy, TopicQuery};
+
+pub const DEFAULT_PORT: u16 = 7878;
+const DEFAULT_DUE_LIMIT: usize = 50;
+const METRICS_PATH: &str = "/metrics";
+
+// Serve until the process is stopped
+pub fn serve(db: &Database, host: &str, port: u16) -> Result<()> {
+    let server = Server::http((host, port)).map_err(std::io::Error::other)?;
+    eprintln!("Serving read-only JSON on http://{}", server.server_addr());
+    for request in server.incoming_requests() {
+        // One client hanging up mid-response shouldn't stop the server
+        if let Err(e) = respond(db, request) {
+            eprintln!("Error responding to request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn respond(db: &Database, request: Request) -> std::io::Result<()> {
+    let (status, body) = route(db, request.method(), request.url());
+    let content_type = Header::from_bytes("Content-Type", content_type(status, request.url()))
+        .expect("static header is valid");
+    request.respond(
+        Response::from_string(body)
+            .with_status_code(status)
+            .with_header(content_type),
+    )
+}
+
+// Metrics are plain text; everything else, errors included, is JSON
+fn content_type(status: u16, url: &str) -> &'static str {
+    let path = url.split('?').next().unwrap_or(url).trim_end_matches('/');
+    if status == 200 && path == METRICS_PATH {
+        "text/plain; version=0.0.4"
+    } else {
+        "application/json"
+    }
+}
+
+// Status code and body for one request
+pub fn route(db: &Database, method: &Method, url: &str) -> (u16, String) {
+    if !matches!(method, Method::Get | Method::Head) {
+        return error(405, "Read-only server: only GET is supported");
+    }
+
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let result = match path.trim_end_matches('/') {
+        "/stats" => db.get_stats().map(|stats| ok(&stats)),
+        "/topics" => db
+            .get_topics_with_progress(&TopicQuery::default())
+            .map(|topics| ok(&topics)),
+        "/next" => db
+            .explain_next_topic(None, SelectionStrategy::Weighted)
+            .map(|explanation| ok(&explanation)),
+        "/due" => {
+            let limit = match query_param(query, "limit").map(str::parse::<usize>) {
+                None => DEFAULT_DUE_LIMIT,
+                Some(Ok(limit)) => limit,
+                Some(Err(_)) => return error(400, "limit must be a whole number"),
+            };
+            db.get_due_topics_limited(limit).map(|topics| ok(&topics))
+        }
+        METRICS_PATH => db
+            .get_stats()
+            .and_then(|stats| Ok(metrics::prometheus(&stats, &db.get_tag_stats()?)))
+            .map(|text| (200, text)),
+        _ => return error(404, "Not found"),
+    };
+
+    result.unwrap_or_else(|e| error(500, &e.to_string()))
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn ok<T: Serialize>(data: &T) -> (u16, String) {
+    match serde_json::to_string(&JsonOutput::ok(data)) {
+        Ok(body) => (200, body),
+        Err(e) => error(500, &e.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    let body = serde_json::to_string(&JsonOutput::<()>::err(message))
+        .expect("an error envelope always serializes");
+    (status, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ReviewOutcome;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn setup_db() -> Database {
+        let db = Database::open(":memory:").unwrap();
+        db.init().unwrap();
+        db
+    }
+
+    fn json(body: &str) -> serde_json::Value {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn stats_over_http_is_valid_json() {
+        let db = setup_db();
+        let id = db.add_topic("Ownership", None, &[]).unwrap();
+        db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"GET /stats HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        respond(&db, server.recv().unwrap()).unwrap();
+
+        let response = client.join().unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200"), "{}", head);
+        assert!(head.contains("application/json"));
+        let body = json(body);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["total_topics"], 1);
+        assert_eq!(body["data"]["total_reviews"], 1);
+    }
+
+    #[test]
+    fn routes_return_their_data() {
+        let db = setup_db();
+        db.add_topic("Ownership", None, &[]).unwrap();
+
+        for path in ["/topics", "/due", "/due?limit=1", "/next", "/stats/"] {
+            let (status, body) = route(&db, &Method::Get, path);
+            assert_eq!(status, 200, "{}", path);
+            assert_eq!(json(&body)["success"], true, "{}", path);
+        }
+        let (_, body) = route(&db, &Method::Get, "/next");
+        assert_eq!(
+            json(&body)["data"]["selected"]["topic"]["name"],
+            "Ownership"
+        );
+    }
+
+    #[test]
+    fn refuses_writes_and_unknown_paths() {
+        let db = setup_db();
+        for method in [Method::Post, Method::Put, Method::Delete, Method::Patch] {
+            let (status, body) = route(&db, &method, "/topics");
+            assert_eq!(status, 405);
+            assert_eq!(json(&body)["success"], false);
+        }
+        assert_eq!(route(&db, &Method::Get, "/missing").0, 404);
+        assert_eq!(route(&db, &Method::Get, "/due?limit=lots").0, 400);
+    }
+
+    #[test]
+    fn metrics_are_prometheus_text() {
+        let db = setup_db();
+        let id = db
+            .add_topic("Ownership", None, &["rust".to_string()])
+            .unwrap();
+        db.record_review(id, ReviewOutcome::Success, None).unwrap();
+
+        let (status, body) = route(&db, &Method::Get, "/metrics");
+        assert_eq!(status, 200);
+        assert!(
+            body.contains("# TYPE feynman_reviews_total gauge\n"),
+            "{}",
+            body
+        );
+        assert!(body.contains("feynman_topics_total 1\n"), "{}", body);
+        assert!(
+            body.contains("feynman_tag_topics{tag=\"rust\"} 1\n"),
+            "{}",
+            body
+        );
+        assert!(content_type(status, "/metrics").starts_with("text/plain"));
+        assert_eq!(content_type(status, "/stats"), "application/json");
+        assert_eq!(content_type(405, "/metrics"), "application/json");
+    }
+
+    #[test]
+    fn next_with_no_topics_is_null() {
+        let db = setup_db();
+        let (status, body) = route(&db, &Method::Get, "/next");
+        assert_eq!(status, 200);
+        assert!(json(&body)["data"].is_null());
+    }
+}
@@ -0,0 +1,818 @@
+// User-tunable settings. Values are stored as strings in the settings table
+// (see `feynman config`) and parsed here; anything unset keeps its default.
+
+use crate::models::ReviewOutcome;
+
+pub const MAX_MASTERY: i32 = 5;
+
+pub const GRADING_SUCCESS_DELTA: &str = "grading.success.delta";
+pub const GRADING_PARTIAL_DELTA: &str = "grading.partial.delta";
+pub const GRADING_FAIL_DELTA: &str = "grading.fail.delta";
+
+// Bounds on any computed interval, in days
+pub const SCHEDULE_MIN_DAYS: &str = "schedule.min_days";
+pub const SCHEDULE_MAX_DAYS: &str = "schedule.max_days";
+
+// Jitter day-based intervals by up to ±N% so topics reviewed together
+// don't all come due together (0 = exact intervals)
+pub const SCHEDULE_FUZZ_PERCENT: &str = "schedule.fuzz_percent";
+
+// Short retries: when set, a failed or partial review comes back after this
+// many minutes instead of the day-based interval (none = off)
+pub const SCHEDULE_FAIL_MINUTES: &str = "schedule.fail_minutes";
+pub const SCHEDULE_PARTIAL_MINUTES: &str = "schedule.partial_minutes";
+
+// Share of the level's interval a partial review waits (0.5 = half)
+pub const SCHEDULE_PARTIAL_FACTOR: &str = "schedule.partial_factor";
+
+// Reviews from their due date to on_time_grace_days after it have their
+// next interval multiplied by on_time_bonus (1 = off)
+pub const SCHEDULE_ON_TIME_BONUS: &str = "schedule.on_time_bonus";
+pub const SCHEDULE_ON_TIME_GRACE_DAYS: &str = "schedule.on_time_grace_days";
+
+// Largest on-time multiplier; more would let punctuality outweigh mastery
+pub const MAX_ON_TIME_BONUS: f64 = 2.0;
+
+// Shape of the weighted `next` draw (see SelectionWeights)
+pub const SELECTION_OVERDUE_WEIGHT: &str = "selection.overdue_weight";
+pub const SELECTION_MASTERY_WEIGHT: &str = "selection.mastery_weight";
+pub const SELECTION_MASTERY_BASELINE: &str = "selection.mastery_baseline";
+
+// Hours after a review during which `next` plays that topic down (0 = off)
+pub const SELECTION_RECENT_HOURS: &str = "selection.recent_hours";
+
+// Most never-reviewed topics `next --new-ratio` introduces per day (none = no cap)
+pub const SELECTION_NEW_PER_DAY: &str = "selection.new_per_day";
+
+// Whether `next` skips the random draw and takes the heaviest topic
+pub const SELECTION_DETERMINISTIC: &str = "selection.deterministic";
+
+// Relative weights of the three parts of Progress::health_score
+pub const HEALTH_MASTERY_WEIGHT: &str = "health.mastery_weight";
+pub const HEALTH_RECENCY_WEIGHT: &str = "health.recency_weight";
+pub const HEALTH_SUCCESS_WEIGHT: &str = "health.success_weight";
+
+// Reviews older than this many days are pruned at startup (none = keep all)
+pub const HISTORY_RETENTION_DAYS: &str = "history.retention_days";
+
+// Topics at full mastery not reviewed for this many days, with no fail among
+// their latest reviews, are archived at startup (none = never)
+pub const AUTO_ARCHIVE_AFTER_DAYS: &str = "auto_archive.after_days";
+
+// Whether TUI list navigation wraps from the last item to the first
+pub const TUI_WRAP_LISTS: &str = "tui.wrap_lists";
+
+// Per-level interval overrides: schedule.interval.0 ... schedule.interval.5
+pub const SCHEDULE_INTERVAL_PREFIX: &str = "schedule.interval.";
+
+// Days until the next review at each mastery level
+pub const DEFAULT_INTERVALS: [i32; 6] = [1, 2, 4, 7, 14, 30];
+
+// Every recognised key, in display order
+pub const KEYS: &[&str] = &[
+    GRADING_SUCCESS_DELTA,
+    GRADING_PARTIAL_DELTA,
+    GRADING_FAIL_DELTA,
+    "schedule.interval.0",
+    "schedule.interval.1",
+    "schedule.interval.2",
+    "schedule.interval.3",
+    "schedule.interval.4",
+    "schedule.interval.5",
+    SCHEDULE_MIN_DAYS,
+    SCHEDULE_MAX_DAYS,
+    SCHEDULE_FUZZ_PERCENT,
+    SCHEDULE_FAIL_MINUTES,
+    SCHEDULE_PARTIAL_MINUTES,
+    SCHEDULE_PARTIAL_FACTOR,
+    SCHEDULE_ON_TIME_BONUS,
+    SCHEDULE_ON_TIME_GRACE_DAYS,
+    SELECTION_OVERDUE_WEIGHT,
+    SELECTION_MASTERY_WEIGHT,
+    SELECTION_MASTERY_BASELINE,
+    SELECTION_RECENT_HOURS,
+    SELECTION_NEW_PER_DAY,
+    SELECTION_DETERMINISTIC,
+    HEALTH_MASTERY_WEIGHT,
+    HEALTH_RECENCY_WEIGHT,
+    HEALTH_SUCCESS_WEIGHT,
+    HISTORY_RETENTION_DAYS,
+    AUTO_ARCHIVE_AFTER_DAYS,
+    TUI_WRAP_LISTS,
+];
+
+// Largest accepted fuzz; past this a review could come back at half its interval
+const MAX_FUZZ_PERCENT: i32 = 50;
+
+// Longest short retry; anything longer belongs in the day-based intervals
+const MAX_RETRY_MINUTES: i32 = 24 * 60;
+
+// Largest accepted selection exponent; past this one factor swamps the other
+const MAX_SELECTION_WEIGHT: f64 = 5.0;
+// Largest accepted mastery baseline
+const MAX_MASTERY_BASELINE: i32 = 100;
+
+// Longest recency window; past a couple of days it's the schedule's job
+const MAX_RECENT_HOURS: i32 = 48;
+// Weight multiplier for a topic reviewed within selection.recent_hours
+pub const RECENT_PENALTY: f64 = 0.1;
+
+// Largest accepted health weight; only the ratios between weights matter
+const MAX_HEALTH_WEIGHT: i32 = 100;
+
+// How much each review outcome moves the mastery level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GradingScale {
+    pub success: i32,
+    pub partial: i32,
+    pub fail: i32,
+}
+
+impl Default for GradingScale {
+    fn default() -> Self {
+        Self {
+            success: 1,
+            partial: 0,
+            fail: -1,
+        }
+    }
+}
+
+impl GradingScale {
+    pub fn delta(&self, outcome: ReviewOutcome) -> i32 {
+        match outcome {
+            ReviewOutcome::Success => self.success,
+            ReviewOutcome::Partial => self.partial,
+            ReviewOutcome::Fail => self.fail,
+        }
+    }
+
+    // New mastery level after a review, kept within 0..=MAX_MASTERY
+    pub fn apply(&self, mastery_level: i32, outcome: ReviewOutcome) -> i32 {
+        (mastery_level + self.delta(outcome)).clamp(0, MAX_MASTERY)
+    }
+}
+
+// How much mastery, recency and success rate each count towards a topic's
+// health score. At least one weight is always positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthWeights {
+    pub mastery: i32,
+    pub recency: i32,
+    pub success: i32,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            mastery: 4,
+            recency: 3,
+            success: 3,
+        }
+    }
+}
+
+impl HealthWeights {
+    pub fn total(&self) -> i32 {
+        self.mastery + self.recency + self.success
+    }
+}
+
+// How the weighted `next` draw scores a topic:
+//   weight = (overdue_days + 1)^overdue * (baseline - mastery_level)^mastery
+// The defaults give (overdue_days + 1) * (6 - mastery_level); a weight of 0
+// takes its factor out entirely. baseline is above MAX_MASTERY so the mastery
+// factor is never below 1.
+//
+// A topic reviewed within the last recent_hours also has its weight scaled by
+// RECENT_PENALTY, so one session doesn't get the same topic back-to-back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionWeights {
+    pub overdue: f64,
+    pub mastery: f64,
+    pub baseline: i32,
+    pub recent_hours: i32,
+}
+
+impl Default for SelectionWeights {
+    fn default() -> Self {
+        Self {
+            overdue: 1.0,
+            mastery: 1.0,
+            baseline: MAX_MASTERY + 1,
+            recent_hours: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub grading: GradingScale,
+    pub intervals: [i32; 6],
+    pub min_days: i32,
+    // None means no upper bound
+    pub max_days: Option<i32>,
+    pub fuzz_percent: i32,
+    // None means the outcome uses the day-based interval
+    pub fail_minutes: Option<i32>,
+    pub partial_minutes: Option<i32>,
+    pub partial_factor: f64,
+    pub on_time_bonus: f64,
+    pub on_time_grace_days: i32,
+    pub selection: SelectionWeights,
+    // None means no daily cap on new topics
+    pub new_per_day: Option<i32>,
+    pub deterministic: bool,
+    pub health: HealthWeights,
+    // None keeps review history forever
+    pub retention_days: Option<i32>,
+    // None never archives topics automatically
+    pub auto_archive_days: Option<i32>,
+    pub wrap_lists: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            grading: GradingScale::default(),
+            intervals: DEFAULT_INTERVALS,
+            min_days: 1,
+            max_days: None,
+            fuzz_percent: 0,
+            fail_minutes: None,
+            partial_minutes: None,
+            partial_factor: 0.5,
+            on_time_bonus: 1.0,
+            on_time_grace_days: 1,
+            selection: SelectionWeights::default(),
+            new_per_day: None,
+            deterministic: false,
+            health: HealthWeights::default(),
+            retention_days: None,
+            auto_archive_days: None,
+            wrap_lists: true,
+        }
+    }
+}
+
+impl Settings {
+    // Build from stored key/value pairs. Unknown keys and unparseable values
+    // are skipped so a bad row can't stop reviews from being recorded.
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut settings = Self::default();
+        for (key, value) in pairs {
+            let _ = settings.set(key, value);
+        }
+        settings
+    }
+
+    // Base interval in days for a mastery level (levels past 5 use level 5's)
+    pub fn interval(&self, mastery_level: i32) -> i32 {
+        self.intervals[mastery_level.clamp(0, MAX_MASTERY) as usize]
+    }
+
+    // Days a partial review waits at a mastery level, before difficulty
+    // scaling; rounds down, so the default halves 7 days to 3
+    pub fn partial_interval(&self, mastery_level: i32) -> i32 {
+        (self.interval(mastery_level) as f64 * self.partial_factor).floor() as i32
+    }
+
+    // Keep a computed interval within schedule.min_days..=schedule.max_days
+    pub fn clamp_days(&self, days: i32) -> i32 {
+        let days = days.max(self.min_days);
+        match self.max_days {
+            Some(max) => days.min(max),
+            None => days,
+        }
+    }
+
+    // Parse and apply one setting
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            GRADING_SUCCESS_DELTA => self.grading.success = parse_delta(key, value)?,
+            GRADING_PARTIAL_DELTA => self.grading.partial = parse_delta(key, value)?,
+            GRADING_FAIL_DELTA => self.grading.fail = parse_delta(key, value)?,
+            SCHEDULE_MIN_DAYS => {
+                let min = parse_days(key, value)?;
+                if self.max_days.is_some_and(|max| min > max) {
+                    return Err(format!("{} can't exceed {}", key, SCHEDULE_MAX_DAYS));
+                }
+                self.min_days = min;
+            }
+            SCHEDULE_MAX_DAYS => {
+                self.max_days = if value.trim().eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    let max = parse_days(key, value)?;
+                    if max < self.min_days {
+                        return Err(format!("{} can't be below {}", key, SCHEDULE_MIN_DAYS));
+                    }
+                    Some(max)
+                };
+            }
+            SCHEDULE_FUZZ_PERCENT => {
+                let percent: i32 = value
+                    .trim()
+                    .trim_end_matches('%')
+                    .parse()
+                    .map_err(|_| format!("{} must be a whole number, got '{}'", key, value))?;
+                if !(0..=MAX_FUZZ_PERCENT).contains(&percent) {
+                    return Err(format!(
+                        "{} must be between 0 and {}, got {}",
+                        key, MAX_FUZZ_PERCENT, percent
+                    ));
+                }
+                self.fuzz_percent = percent;
+            }
+            SCHEDULE_FAIL_MINUTES => self.fail_minutes = parse_retry_minutes(key, value)?,
+            SCHEDULE_PARTIAL_MINUTES => self.partial_minutes = parse_retry_minutes(key, value)?,
+            SCHEDULE_PARTIAL_FACTOR => self.partial_factor = parse_partial_factor(key, value)?,
+            SCHEDULE_ON_TIME_BONUS => self.on_time_bonus = parse_on_time_bonus(key, value)?,
+            SCHEDULE_ON_TIME_GRACE_DAYS => self.on_time_grace_days = parse_days(key, value)?,
+            SELECTION_OVERDUE_WEIGHT => self.selection.overdue = parse_exponent(key, value)?,
+            SELECTION_MASTERY_WEIGHT => self.selection.mastery = parse_exponent(key, value)?,
+            SELECTION_MASTERY_BASELINE => {
+                let baseline: i32 = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("{} must be a whole number, got '{}'", key, value))?;
+                if !(MAX_MASTERY + 1..=MAX_MASTERY_BASELINE).contains(&baseline) {
+                    return Err(format!(
+                        "{} must be between {} and {}, got {}",
+                        key,
+                        MAX_MASTERY + 1,
+                        MAX_MASTERY_BASELINE,
+                        baseline
+                    ));
+                }
+                self.selection.baseline = baseline;
+            }
+            SELECTION_RECENT_HOURS => {
+                let hours: i32 = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("{} must be a whole number, got '{}'", key, value))?;
+                if !(0..=MAX_RECENT_HOURS).contains(&hours) {
+                    return Err(format!(
+                        "{} must be between 0 and {}, got {}",
+                        key, MAX_RECENT_HOURS, hours
+                    ));
+                }
+                self.selection.recent_hours = hours;
+            }
+            SELECTION_NEW_PER_DAY => {
+                self.new_per_day = if value.trim().eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    let limit: i32 = value.trim().parse().map_err(|_| {
+                        format!("{} must be a whole number or 'none', got '{}'", key, value)
+                    })?;
+                    if limit < 0 {
+                        return Err(format!("{} can't be negative, got {}", key, limit));
+                    }
+                    Some(limit)
+                };
+            }
+            SELECTION_DETERMINISTIC => self.deterministic = parse_bool(key, value)?,
+            HISTORY_RETENTION_DAYS => {
+                self.retention_days = if value.trim().eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    Some(parse_days(key, value)?)
+                };
+            }
+            AUTO_ARCHIVE_AFTER_DAYS => {
+                self.auto_archive_days = if value.trim().eq_ignore_ascii_case("none") {
+                    None
+                } else {
+                    Some(parse_days(key, value)?)
+                };
+            }
+            TUI_WRAP_LISTS => self.wrap_lists = parse_bool(key, value)?,
+            HEALTH_MASTERY_WEIGHT | HEALTH_RECENCY_WEIGHT | HEALTH_SUCCESS_WEIGHT => {
+                let mut health = self.health;
+                let weight = parse_weight(key, value)?;
+                match key {
+                    HEALTH_MASTERY_WEIGHT => health.mastery = weight,
+                    HEALTH_RECENCY_WEIGHT => health.recency = weight,
+                    _ => health.success = weight,
+                }
+                if health.total() == 0 {
+                    return Err("At least one health.*_weight must be above 0".to_string());
+                }
+                self.health = health;
+            }
+            _ => match interval_level(key) {
+                Some(level) => self.intervals[level] = parse_days(key, value)?,
+                None => return Err(format!("Unknown setting '{}'", key)),
+            },
+        }
+        Ok(())
+    }
+
+    // Effective value of a setting, formatted the way `set` accepts it
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match key {
+            GRADING_SUCCESS_DELTA => format_delta(self.grading.success),
+            GRADING_PARTIAL_DELTA => format_delta(self.grading.partial),
+            GRADING_FAIL_DELTA => format_delta(self.grading.fail),
+            SCHEDULE_MIN_DAYS => self.min_days.to_string(),
+            SCHEDULE_MAX_DAYS => self
+                .max_days
+                .map_or_else(|| "none".to_string(), |max| max.to_string()),
+            SCHEDULE_FUZZ_PERCENT => self.fuzz_percent.to_string(),
+            SCHEDULE_FAIL_MINUTES => format_retry_minutes(self.fail_minutes),
+            SCHEDULE_PARTIAL_MINUTES => format_retry_minutes(self.partial_minutes),
+            SCHEDULE_PARTIAL_FACTOR => self.partial_factor.to_string(),
+            SCHEDULE_ON_TIME_BONUS => self.on_time_bonus.to_string(),
+            SCHEDULE_ON_TIME_GRACE_DAYS => self.on_time_grace_days.to_string(),
+            SELECTION_OVERDUE_WEIGHT => self.selection.overdue.to_string(),
+            SELECTION_MASTERY_WEIGHT => self.selection.mastery.to_string(),
+            SELECTION_MASTERY_BASELINE => self.selection.baseline.to_string(),
+            SELECTION_RECENT_HOURS => self.selection.recent_hours.to_string(),
+            SELECTION_NEW_PER_DAY => self
+                .new_per_day
+                .map_or_else(|| "none".to_string(), |limit| limit.to_string()),
+            SELECTION_DETERMINISTIC => self.deterministic.to_string(),
+            HISTORY_RETENTION_DAYS => self
+                .retention_days
+                .map_or_else(|| "none".to_string(), |days| days.to_string()),
+            AUTO_ARCHIVE_AFTER_DAYS => self
+                .auto_archive_days
+                .map_or_else(|| "none".to_string(), |days| days.to_string()),
+            TUI_WRAP_LISTS => self.wrap_lists.to_string(),
+            HEALTH_MASTERY_WEIGHT => self.health.mastery.to_string(),
+            HEALTH_RECENCY_WEIGHT => self.health.recency.to_string(),
+            HEALTH_SUCCESS_WEIGHT => self.health.success.to_string(),
+            _ => self.intervals[interval_level(key)?].to_string(),
+        };
+        Some(value)
+    }
+}
+
+// Mastery level named by a schedule.interval.N key
+fn interval_level(key: &str) -> Option<usize> {
+    let level: usize = key.strip_prefix(SCHEDULE_INTERVAL_PREFIX)?.parse().ok()?;
+    (level <= MAX_MASTERY as usize).then_some(level)
+}
+
+// Check a value would be accepted on top of the current settings without
+// applying it
+pub fn validate(current: &Settings, key: &str, value: &str) -> Result<(), String> {
+    current.clone().set(key, value)
+}
+
+// A delta beyond the mastery range would always pin the level to 0 or 5
+fn parse_delta(key: &str, value: &str) -> Result<i32, String> {
+    let delta: i32 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} must be a whole number, got '{}'", key, value))?;
+    if delta.abs() > MAX_MASTERY {
+        return Err(format!(
+            "{} must be between -{} and +{}, got {}",
+            key, MAX_MASTERY, MAX_MASTERY, delta
+        ));
+    }
+    Ok(delta)
+}
+
+fn parse_days(key: &str, value: &str) -> Result<i32, String> {
+    let days: i32 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} must be a whole number of days, got '{}'", key, value))?;
+    if days < 1 {
+        return Err(format!("{} must be at least 1 day, got {}", key, days));
+    }
+    Ok(days)
+}
+
+fn parse_retry_minutes(key: &str, value: &str) -> Result<Option<i32>, String> {
+    if value.trim().eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    let minutes: i32 = value.trim().parse().map_err(|_| {
+        format!(
+            "{} must be a whole number of minutes or 'none', got '{}'",
+            key, value
+        )
+    })?;
+    if !(1..=MAX_RETRY_MINUTES).contains(&minutes) {
+        return Err(format!(
+            "{} must be between 1 and {} minutes, got {}",
+            key, MAX_RETRY_MINUTES, minutes
+        ));
+    }
+    Ok(Some(minutes))
+}
+
+fn format_retry_minutes(minutes: Option<i32>) -> String {
+    minutes.map_or_else(|| "none".to_string(), |m| m.to_string())
+}
+
+// Above 1 a partial review would wait longer than a success at the same level
+fn parse_partial_factor(key: &str, value: &str) -> Result<f64, String> {
+    let factor: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} must be a number, got '{}'", key, value))?;
+    if !(factor > 0.0 && factor <= 1.0) {
+        return Err(format!(
+            "{} must be above 0 and at most 1, got {}",
+            key, value
+        ));
+    }
+    Ok(factor)
+}
+
+fn parse_on_time_bonus(key: &str, value: &str) -> Result<f64, String> {
+    let bonus: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} must be a number, got '{}'", key, value))?;
+    if !(1.0..=MAX_ON_TIME_BONUS).contains(&bonus) {
+        return Err(format!(
+            "{} must be between 1 and {}, got {}",
+            key, MAX_ON_TIME_BONUS, value
+        ));
+    }
+    Ok(bonus)
+}
+
+fn parse_exponent(key: &str, value: &str) -> Result<f64, String> {
+    let weight: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} must be a number, got '{}'", key, value))?;
+    if !(0.0..=MAX_SELECTION_WEIGHT).contains(&weight) {
+        return Err(format!(
+            "{} must be between 0 and {}, got {}",
+            key, MAX_SELECTION_WEIGHT, value
+        ));
+    }
+    Ok(weight)
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("{} must be true or false, got '{}'", key, value)),
+    }
+}
+
+fn parse_weight(key: &str, value: &str) -> Result<i32, String> {
+    let weight: i32 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} must be a whole number, got '{}'", key, value))?;
+    if !(0..=MAX_HEALTH_WEIGHT).contains(&weight) {
+        return Err(format!(
+            "{} must be between 0 and {}, got {}",
+            key, MAX_HEALTH_WEIGHT, weight
+        ));
+    }
+    Ok(weight)
+}
+
+fn format_delta(delta: i32) -> String {
+    if delta > 0 {
+        format!("+{}", delta)
+    } else {
+        delta.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_grading_matches_fixed_scale() {
+        let grading = GradingScale::default();
+        assert_eq!(grading.apply(2, ReviewOutcome::Success), 3);
+        assert_eq!(grading.apply(2, ReviewOutcome::Partial), 2);
+        assert_eq!(grading.apply(2, ReviewOutcome::Fail), 1);
+        assert_eq!(grading.apply(5, ReviewOutcome::Success), 5);
+        assert_eq!(grading.apply(0, ReviewOutcome::Fail), 0);
+    }
+
+    #[test]
+    fn custom_deltas_clamp_to_mastery_range() {
+        let settings =
+            Settings::from_pairs([(GRADING_SUCCESS_DELTA, "+2"), (GRADING_FAIL_DELTA, "-2")]);
+        assert_eq!(settings.grading.apply(4, ReviewOutcome::Success), 5);
+        assert_eq!(settings.grading.apply(3, ReviewOutcome::Fail), 1);
+        assert_eq!(settings.grading.apply(1, ReviewOutcome::Fail), 0);
+        assert_eq!(settings.grading.partial, 0);
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_malformed_deltas() {
+        assert!(validate(&Settings::default(), GRADING_FAIL_DELTA, "-5").is_ok());
+        assert!(validate(&Settings::default(), GRADING_FAIL_DELTA, "-6").is_err());
+        assert!(validate(&Settings::default(), GRADING_SUCCESS_DELTA, "one").is_err());
+        assert!(validate(&Settings::default(), "grading.unknown.delta", "1").is_err());
+    }
+
+    #[test]
+    fn bad_stored_values_fall_back_to_defaults() {
+        let settings = Settings::from_pairs([(GRADING_SUCCESS_DELTA, "lots"), ("nope", "1")]);
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn interval_overrides_replace_single_levels() {
+        let settings = Settings::from_pairs([("schedule.interval.5", "60")]);
+        assert_eq!(settings.interval(5), 60);
+        assert_eq!(settings.interval(9), 60);
+        assert_eq!(settings.interval(3), DEFAULT_INTERVALS[3]);
+
+        assert!(validate(&Settings::default(), "schedule.interval.0", "0").is_err());
+        assert!(validate(&Settings::default(), "schedule.interval.6", "10").is_err());
+        assert!(validate(&Settings::default(), "schedule.interval.x", "10").is_err());
+    }
+
+    #[test]
+    fn clamp_days_applies_min_and_max() {
+        let settings = Settings::from_pairs([(SCHEDULE_MIN_DAYS, "2"), (SCHEDULE_MAX_DAYS, "60")]);
+        assert_eq!(settings.clamp_days(90), 60);
+        assert_eq!(settings.clamp_days(0), 2);
+        assert_eq!(settings.clamp_days(14), 14);
+        assert_eq!(Settings::default().clamp_days(0), 1);
+        assert_eq!(Settings::default().clamp_days(400), 400);
+    }
+
+    #[test]
+    fn min_and_max_days_must_not_cross() {
+        let settings = Settings::from_pairs([(SCHEDULE_MAX_DAYS, "10")]);
+        assert!(validate(&settings, SCHEDULE_MIN_DAYS, "11").is_err());
+        assert!(validate(&settings, SCHEDULE_MIN_DAYS, "10").is_ok());
+        assert!(validate(&settings, SCHEDULE_MAX_DAYS, "none").is_ok());
+        assert!(validate(&settings, SCHEDULE_MAX_DAYS, "0").is_err());
+    }
+
+    #[test]
+    fn retry_minutes_accept_none_and_reject_out_of_range() {
+        let mut settings = Settings::default();
+        settings.set(SCHEDULE_FAIL_MINUTES, "10").unwrap();
+        assert_eq!(settings.fail_minutes, Some(10));
+        settings.set(SCHEDULE_FAIL_MINUTES, "None").unwrap();
+        assert_eq!(settings.fail_minutes, None);
+        assert!(validate(&settings, SCHEDULE_PARTIAL_MINUTES, "0").is_err());
+        assert!(validate(&settings, SCHEDULE_PARTIAL_MINUTES, "1441").is_err());
+        assert!(validate(&settings, SCHEDULE_PARTIAL_MINUTES, "soon").is_err());
+    }
+
+    #[test]
+    fn selection_weights_accept_fractions_and_check_ranges() {
+        let mut settings = Settings::default();
+        settings.set(SELECTION_OVERDUE_WEIGHT, "1.5").unwrap();
+        assert_eq!(settings.selection.overdue, 1.5);
+        assert_eq!(
+            settings.get(SELECTION_OVERDUE_WEIGHT).as_deref(),
+            Some("1.5")
+        );
+        assert!(validate(&settings, SELECTION_MASTERY_WEIGHT, "0").is_ok());
+        assert!(validate(&settings, SELECTION_MASTERY_WEIGHT, "-1").is_err());
+        assert!(validate(&settings, SELECTION_MASTERY_WEIGHT, "NaN").is_err());
+        assert!(validate(&settings, SELECTION_MASTERY_BASELINE, "5").is_err());
+        assert!(validate(&settings, SELECTION_MASTERY_BASELINE, "10").is_ok());
+    }
+
+    #[test]
+    fn recent_hours_can_be_turned_off_but_not_stretched() {
+        assert_eq!(Settings::default().selection.recent_hours, 0);
+        let settings = Settings::from_pairs([(SELECTION_RECENT_HOURS, "4")]);
+        assert_eq!(settings.selection.recent_hours, 4);
+        assert!(validate(&settings, SELECTION_RECENT_HOURS, "48").is_ok());
+        assert!(validate(&settings, SELECTION_RECENT_HOURS, "49").is_err());
+        assert!(validate(&settings, SELECTION_RECENT_HOURS, "-1").is_err());
+        assert!(validate(&settings, SELECTION_RECENT_HOURS, "1.5").is_err());
+    }
+
+    #[test]
+    fn wrap_lists_parses_booleans() {
+        let mut settings = Settings::default();
+        assert!(settings.wrap_lists);
+        settings.set(TUI_WRAP_LISTS, "False").unwrap();
+        assert!(!settings.wrap_lists);
+        assert!(validate(&settings, TUI_WRAP_LISTS, "off").is_err());
+    }
+
+    #[test]
+    fn deterministic_selection_is_off_by_default() {
+        assert!(!Settings::default().deterministic);
+        let settings = Settings::from_pairs([(SELECTION_DETERMINISTIC, "true")]);
+        assert!(settings.deterministic);
+        assert!(validate(&settings, SELECTION_DETERMINISTIC, "yes").is_err());
+    }
+
+    #[test]
+    fn health_weights_reject_negatives_and_all_zero() {
+        let settings =
+            Settings::from_pairs([(HEALTH_MASTERY_WEIGHT, "0"), (HEALTH_RECENCY_WEIGHT, "0")]);
+        assert_eq!(settings.health.total(), 3);
+        assert!(validate(&settings, HEALTH_SUCCESS_WEIGHT, "0").is_err());
+        assert!(validate(&settings, HEALTH_SUCCESS_WEIGHT, "-1").is_err());
+        assert!(validate(&settings, HEALTH_SUCCESS_WEIGHT, "101").is_err());
+        assert!(validate(&settings, HEALTH_MASTERY_WEIGHT, "1").is_ok());
+    }
+
+    #[test]
+    fn partial_factor_scales_and_rounds_down() {
+        let settings = Settings::default();
+        assert_eq!(settings.partial_interval(3), 3);
+        assert_eq!(settings.partial_interval(0), 0);
+
+        let settings = Settings::from_pairs([(SCHEDULE_PARTIAL_FACTOR, "0.75")]);
+        assert_eq!(settings.partial_interval(4), 10);
+
+        assert!(validate(&Settings::default(), SCHEDULE_PARTIAL_FACTOR, "1").is_ok());
+        assert!(validate(&Settings::default(), SCHEDULE_PARTIAL_FACTOR, "0").is_err());
+        assert!(validate(&Settings::default(), SCHEDULE_PARTIAL_FACTOR, "1.5").is_err());
+        assert!(validate(&Settings::default(), SCHEDULE_PARTIAL_FACTOR, "NaN").is_err());
+    }
+
+    #[test]
+    fn on_time_bonus_is_off_by_default_and_bounded() {
+        let settings = Settings::default();
+        assert_eq!(settings.on_time_bonus, 1.0);
+        assert_eq!(settings.on_time_grace_days, 1);
+
+        let settings = Settings::from_pairs([
+            (SCHEDULE_ON_TIME_BONUS, "1.25"),
+            (SCHEDULE_ON_TIME_GRACE_DAYS, "2"),
+        ]);
+        assert_eq!(settings.on_time_bonus, 1.25);
+        assert_eq!(settings.on_time_grace_days, 2);
+
+        assert!(validate(&settings, SCHEDULE_ON_TIME_BONUS, "2").is_ok());
+        assert!(validate(&settings, SCHEDULE_ON_TIME_BONUS, "0.9").is_err());
+        assert!(validate(&settings, SCHEDULE_ON_TIME_BONUS, "2.5").is_err());
+        assert!(validate(&settings, SCHEDULE_ON_TIME_BONUS, "NaN").is_err());
+        assert!(validate(&settings, SCHEDULE_ON_TIME_GRACE_DAYS, "0").is_err());
+    }
+
+    #[test]
+    fn fuzz_percent_is_bounded() {
+        assert_eq!(Settings::default().fuzz_percent, 0);
+        let settings = Settings::from_pairs([(SCHEDULE_FUZZ_PERCENT, "15%")]);
+        assert_eq!(settings.fuzz_percent, 15);
+
+        assert!(validate(&settings, SCHEDULE_FUZZ_PERCENT, "50").is_ok());
+        assert!(validate(&settings, SCHEDULE_FUZZ_PERCENT, "51").is_err());
+        assert!(validate(&settings, SCHEDULE_FUZZ_PERCENT, "-5").is_err());
+        assert!(validate(&settings, SCHEDULE_FUZZ_PERCENT, "0.5").is_err());
+    }
+
+    #[test]
+    fn auto_archive_is_off_by_default() {
+        assert_eq!(Settings::default().auto_archive_days, None);
+        let settings = Settings::from_pairs([(AUTO_ARCHIVE_AFTER_DAYS, "180")]);
+        assert_eq!(settings.auto_archive_days, Some(180));
+        assert_eq!(
+            settings.get(AUTO_ARCHIVE_AFTER_DAYS).as_deref(),
+            Some("180")
+        );
+        assert!(validate(&settings, AUTO_ARCHIVE_AFTER_DAYS, "None").is_ok());
+        assert!(validate(&settings, AUTO_ARCHIVE_AFTER_DAYS, "0").is_err());
+    }
+
+    #[test]
+    fn retention_days_accepts_days_or_none() {
+        let settings = Settings::from_pairs([(HISTORY_RETENTION_DAYS, "365")]);
+        assert_eq!(settings.retention_days, Some(365));
+        assert_eq!(Settings::default().retention_days, None);
+
+        assert!(validate(&settings, HISTORY_RETENTION_DAYS, "none").is_ok());
+        assert!(validate(&settings, HISTORY_RETENTION_DAYS, "0").is_err());
+        assert!(validate(&settings, HISTORY_RETENTION_DAYS, "a year").is_err());
+    }
+
+    #[test]
+    fn new_per_day_accepts_a_count_or_none() {
+        assert_eq!(Settings::default().new_per_day, None);
+        let settings = Settings::from_pairs([(SELECTION_NEW_PER_DAY, "10")]);
+        assert_eq!(settings.new_per_day, Some(10));
+
+        assert!(validate(&settings, SELECTION_NEW_PER_DAY, "0").is_ok());
+        assert!(validate(&settings, SELECTION_NEW_PER_DAY, "none").is_ok());
+        assert!(validate(&settings, SELECTION_NEW_PER_DAY, "-1").is_err());
+        assert!(validate(&settings, SELECTION_NEW_PER_DAY, "lots").is_err());
+    }
+
+    #[test]
+    fn get_round_trips_through_set() {
+        let mut settings = Settings::default();
+        settings.set(GRADING_PARTIAL_DELTA, "-1").unwrap();
+        for key in KEYS {
+            let value = settings.get(key).unwrap();
+            let mut copy = Settings::default();
+            copy.set(key, &value).unwrap();
+            assert_eq!(copy.get(key), Some(value));
+        }
+        assert_eq!(settings.get(GRADING_SUCCESS_DELTA).as_deref(), Some("+1"));
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Local, TimeZone, Timelike};
+
+// What changed since the last tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tick {
+    None,
+    // A new wall-clock minute started: redraw so the clock and
+    // due/overdue highlighting stay current
+    Minute,
+    // The date rolled over: due counts change, so reload data too
+    Day,
+}
+
+// Fires once per wall-clock minute. Driven by the event loop so an idle TUI
+// only redraws when something visible can have changed.
+pub struct Clock<Tz: TimeZone = Local> {
+    last: DateTime<Tz>,
+}
+
+impl<Tz: TimeZone> Clock<Tz> {
+    pub fn new(now: DateTime<Tz>) -> Self {
+        Self { last: now }
+    }
+
+    pub fn tick(&mut self, now: DateTime<Tz>) -> Tick {
+        let result = if now.date_naive() != self.last.date_naive() {
+            Tick::Day
+        } else if (now.hour(), now.minute()) != (self.last.hour(), self.last.minute()) {
+            Tick::Minute
+        } else {
+            Tick::None
+        };
+        self.last = now;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, h, m, s).unwrap()
+    }
+
+    #[test]
+    fn same_minute_does_not_tick() {
+        let mut clock = Clock::new(at(9, 30, 0));
+        assert_eq!(clock.tick(at(9, 30, 59)), Tick::None);
+    }
+
+    #[test]
+    fn new_minute_ticks_once() {
+        let mut clock = Clock::new(at(9, 30, 59));
+        assert_eq!(clock.tick(at(9, 31, 0)), Tick::Minute);
+        assert_eq!(clock.tick(at(9, 31, 30)), Tick::None);
+    }
+
+    #[test]
+    fn same_minute_next_hour_ticks() {
+        let mut clock = Clock::new(at(9, 30, 0));
+        assert_eq!(clock.tick(at(10, 30, 0)), Tick::Minute);
+    }
+
+    #[test]
+    fn midnight_rollover_is_day_tick() {
+        let mut clock = Clock::new(at(23, 59, 59));
+        let next_day = Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap();
+        assert_eq!(clock.tick(next_day), Tick::Day);
+        assert_eq!(clock.tick(next_day), Tick::None);
+    }
+}
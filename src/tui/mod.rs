@@ -1,8 +1,18 @@
+mod clock;
+mod open;
+mod plan_entry;
+mod review;
+mod snapshot;
+mod state;
 mod ui;
+mod watch;
 mod widgets;
 
 use std::io;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Utc};
 
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
@@ -10,17 +20,38 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
+use serde::{Deserialize, Serialize};
+
+use clock::{Clock, Tick};
+use plan_entry::{FormAction, PlanEntryForm};
+use review::ReviewSession;
+pub use snapshot::{snapshot, DEFAULT_SNAPSHOT_WIDTH};
+use state::TuiState;
+pub use state::STATE_FILE_NAME;
+// Display-width text helpers, shared with the CLI's tables
+use watch::DataWatcher;
+pub use widgets::text::{pad_to_width, truncate};
+use widgets::text_input::{InputAction, TextInput};
 
-use crate::db::{Database, Stats};
-use crate::models::{InterviewEntry, LearningSession, Plan, SessionGap, TopicWithProgress};
+use crate::db::Database;
+use crate::error::Result;
+use crate::models::{
+    InterviewEntry, LearningSession, Plan, ReviewOutcome, SessionGap, Stats, StudySession,
+    TopicQuery, TopicResource, TopicWithProgress, DEFAULT_STUDY_GAP_MINUTES,
+};
+use crate::settings::HealthWeights;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum View {
+    #[default]
     Dashboard,
     Topics,
     TopicDetail,
     Plans,
     PlanDetail,
+    // Focus-mode review of everything due, entered with r from the dashboard
+    Review,
 }
 
 impl View {
@@ -31,6 +62,7 @@ impl View {
             View::TopicDetail => View::Topics,
             View::Plans => View::Dashboard,
             View::PlanDetail => View::Plans,
+            View::Review => View::Dashboard,
         }
     }
 
@@ -41,6 +73,7 @@ impl View {
             View::TopicDetail => View::Topics,
             View::Plans => View::Topics,
             View::PlanDetail => View::Plans,
+            View::Review => View::Dashboard,
         }
     }
 }
@@ -48,12 +81,18 @@ impl View {
 pub struct StatefulList<T> {
     pub items: Vec<T>,
     pub selected: Option<usize>,
+    // Moving past either end jumps to the other; when false it stops there
+    pub wrap: bool,
 }
 
 impl<T> StatefulList<T> {
     fn with_items(items: Vec<T>) -> Self {
         let selected = if items.is_empty() { None } else { Some(0) };
-        Self { items, selected }
+        Self {
+            items,
+            selected,
+            wrap: true,
+        }
     }
 
     fn next(&mut self) {
@@ -63,7 +102,11 @@ impl<T> StatefulList<T> {
         let i = match self.selected {
             Some(i) => {
                 if i >= self.items.len() - 1 {
-                    0
+                    if self.wrap {
+                        0
+                    } else {
+                        i
+                    }
                 } else {
                     i + 1
                 }
@@ -80,7 +123,11 @@ impl<T> StatefulList<T> {
         let i = match self.selected {
             Some(i) => {
                 if i == 0 {
-                    self.items.len() - 1
+                    if self.wrap {
+                        self.items.len() - 1
+                    } else {
+                        0
+                    }
                 } else {
                     i - 1
                 }
@@ -93,6 +140,68 @@ impl<T> StatefulList<T> {
     fn selected_item(&self) -> Option<&T> {
         self.selected.and_then(|i| self.items.get(i))
     }
+
+    // Swap in reloaded items, keeping the same item selected (matched by
+    // `key`) if it's still there, else the same position (the first item if
+    // nothing was selected)
+    fn replace_items<K: PartialEq>(&mut self, items: Vec<T>, key: impl Fn(&T) -> K) {
+        let selected_key = self.selected_item().map(&key);
+        let position = selected_key
+            .and_then(|k| items.iter().position(|item| key(item) == k))
+            .or_else(|| self.selected.map(|i| i.min(items.len().saturating_sub(1))))
+            .unwrap_or(0);
+        self.selected = if items.is_empty() {
+            None
+        } else {
+            Some(position)
+        };
+        self.items = items;
+    }
+}
+
+pub const DEFAULT_DASHBOARD_LIMIT: usize = 5;
+
+// A session's full transcript, shown over the topic detail view
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptView {
+    pub session_id: i64,
+    // None when the session has no transcript recorded
+    pub text: Option<String>,
+    // Lines scrolled past the top
+    pub scroll: u16,
+}
+
+impl TranscriptView {
+    fn max_scroll(&self) -> u16 {
+        let lines = self.text.as_deref().map_or(0, |t| t.lines().count());
+        lines.saturating_sub(1).min(u16::MAX as usize) as u16
+    }
+}
+
+// Display options for the TUI
+#[derive(Debug, Clone)]
+pub struct TuiOptions {
+    // Render session outcomes as ✓/~/✗/⊘ instead of words
+    pub compact_symbols: bool,
+    // Number of due topics / recent sessions shown on the dashboard
+    pub due_limit: usize,
+    pub recent_limit: usize,
+    // Where the view and tag filter are kept between runs; None keeps nothing
+    pub state_path: Option<PathBuf>,
+    // Reopen the saved view and filter (off with --no-restore; still saved)
+    pub restore: bool,
+}
+
+impl Default for TuiOptions {
+    fn default() -> Self {
+        Self {
+            compact_symbols: false,
+            due_limit: DEFAULT_DASHBOARD_LIMIT,
+            recent_limit: DEFAULT_DASHBOARD_LIMIT,
+            state_path: None,
+            restore: true,
+        }
+    }
 }
 
 pub struct App {
@@ -101,114 +210,480 @@ pub struct App {
     pub topics: StatefulList<TopicWithProgress>,
     pub plans: StatefulList<Plan>,
     pub selected_topic: Option<TopicWithProgress>,
-    pub selected_topic_sessions: Vec<LearningSession>,
+    // j/k pick a session in the topic detail view; t opens its transcript
+    pub selected_topic_sessions: StatefulList<LearningSession>,
+    pub transcript: Option<TranscriptView>,
+    // [/] pick one of the topic's links; o opens it
+    pub selected_topic_resources: StatefulList<TopicResource>,
+    // Why the last o didn't open anything, shown on the resources panel
+    pub resource_error: Option<String>,
+    // The due batch being graded in the review view
+    pub review: Option<ReviewSession>,
     pub selected_topic_gaps: Vec<SessionGap>,
+    // Days between the selected topic's past reviews, oldest first
+    pub selected_topic_intervals: Vec<i64>,
+    // Mastery after each of the selected topic's reviews, oldest first
+    pub selected_topic_trajectory: Vec<i32>,
+    // When the selected topic reaches mastery if every review succeeds
+    pub selected_topic_mastery_date: Option<String>,
     pub selected_plan: Option<Plan>,
     pub selected_plan_entries: Vec<InterviewEntry>,
+    // a on the plan detail view adds an interview entry through this form
+    pub plan_entry_form: Option<PlanEntryForm>,
+    // Why the last s didn't change the plan's status, shown in its header
+    pub plan_status_error: Option<String>,
     pub stats: Stats,
-    pub due_topics: Vec<TopicWithProgress>,
+    // Topic count per mastery level 0-5
+    pub mastery_histogram: Vec<(i32, i64)>,
+    // Navigable with j/k on the dashboard; Enter opens the topic
+    pub due_topics: StatefulList<TopicWithProgress>,
+    // Where h/Esc go from the topic detail: the list it was opened from
+    pub topic_detail_parent: View,
     pub recent_sessions: Vec<(LearningSession, String)>, // session + topic name
+    // Latest study sessions inferred from review history, newest first
+    pub study_sessions: Vec<StudySession>,
     pub filter_tag: Option<String>,
-    pub filter_input: String,
+    pub filter_input: TextInput,
     pub filter_mode: bool,
+    // Existing tags matching what's typed in the filter; Tab takes the first
+    pub tag_suggestions: Vec<String>,
+    // Plans view narrowed to titles (or a status) matching this
+    pub plan_filter: Option<String>,
+    pub options: TuiOptions,
+    // Weights for the Health column (health.* settings)
+    pub health_weights: HealthWeights,
     pub should_quit: bool,
+    watcher: DataWatcher,
+}
+
+// Case-insensitive title substring, or the plan's status name (e.g. "approved")
+fn plan_matches(plan: &Plan, query: &str) -> bool {
+    let query = query.trim().to_lowercase();
+    plan.title.to_lowercase().contains(&query) || plan.status.as_str() == query
+}
+
+fn recent_study_sessions(db: &Database, limit: usize) -> Result<Vec<StudySession>> {
+    db.get_recent_study_sessions(DEFAULT_STUDY_GAP_MINUTES, limit)
 }
 
 impl App {
-    pub fn new(db: Database) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(db: Database, options: TuiOptions) -> Result<Self> {
         let stats = db.get_stats()?;
-        let topics_data = db.get_topics_with_progress(None)?;
+        let mastery_histogram = db.get_mastery_histogram()?;
+        let topics_data = db.get_topics_with_progress(&TopicQuery::default())?;
         let plans_data = db.list_plans(None)?;
-        let due_topics = db.get_due_topics_limited(5)?;
-        let recent_sessions = db.get_recent_sessions_with_topics(5)?;
+        let due_topics = db.get_due_topics_limited(options.due_limit)?;
+        let recent_sessions = db.get_recent_sessions_with_topics(options.recent_limit, 0)?;
+        let study_sessions = recent_study_sessions(&db, options.recent_limit)?;
+        let settings = db.settings()?;
+        let watcher = DataWatcher::new(db.data_version()?);
+        let saved = match &options.state_path {
+            Some(path) if options.restore => TuiState::load(path),
+            _ => TuiState::default(),
+        };
 
-        Ok(Self {
+        let mut app = Self {
             db,
             view: View::Dashboard,
-            topics: StatefulList::with_items(topics_data),
-            plans: StatefulList::with_items(plans_data),
+            topics: StatefulList {
+                wrap: settings.wrap_lists,
+                ..StatefulList::with_items(topics_data)
+            },
+            plans: StatefulList {
+                wrap: settings.wrap_lists,
+                ..StatefulList::with_items(plans_data)
+            },
             selected_topic: None,
-            selected_topic_sessions: Vec::new(),
+            selected_topic_sessions: StatefulList {
+                wrap: settings.wrap_lists,
+                ..StatefulList::with_items(Vec::new())
+            },
+            transcript: None,
+            selected_topic_resources: StatefulList {
+                wrap: settings.wrap_lists,
+                ..StatefulList::with_items(Vec::new())
+            },
+            resource_error: None,
+            review: None,
             selected_topic_gaps: Vec::new(),
+            selected_topic_intervals: Vec::new(),
+            selected_topic_trajectory: Vec::new(),
+            selected_topic_mastery_date: None,
             selected_plan: None,
             selected_plan_entries: Vec::new(),
+            plan_entry_form: None,
+            plan_status_error: None,
             stats,
-            due_topics,
+            mastery_histogram,
+            due_topics: StatefulList {
+                wrap: settings.wrap_lists,
+                ..StatefulList::with_items(due_topics)
+            },
+            topic_detail_parent: View::Topics,
             recent_sessions,
+            study_sessions,
             filter_tag: None,
-            filter_input: String::new(),
+            filter_input: TextInput::new(),
             filter_mode: false,
+            tag_suggestions: Vec::new(),
+            plan_filter: None,
+            options,
+            health_weights: settings.health,
             should_quit: false,
-        })
+            watcher,
+        };
+        app.view = saved.view;
+        if let Some(tag) = saved.filter_tag {
+            app.filter_input.set_value(&tag);
+            app.apply_filter()?;
+        }
+        Ok(app)
     }
 
-    pub fn refresh_data(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    // Remember the view and filter for the next launch, if there's a state file
+    fn save_state(&self) {
+        if let Some(path) = &self.options.state_path {
+            // Best-effort: failing to save just means a fresh start next time
+            let _ = TuiState::new(self.view, self.filter_tag.clone()).save(path);
+        }
+    }
+
+    // Reload everything, keeping list selections and any open detail view
+    pub fn refresh_data(&mut self) -> Result<()> {
         self.stats = self.db.get_stats()?;
-        self.topics = StatefulList::with_items(
-            self.db
-                .get_topics_with_progress(self.filter_tag.as_deref())?,
-        );
-        self.plans = StatefulList::with_items(self.db.list_plans(None)?);
-        self.due_topics = self.db.get_due_topics_limited(5)?;
-        self.recent_sessions = self.db.get_recent_sessions_with_topics(5)?;
+        self.mastery_histogram = self.db.get_mastery_histogram()?;
+        let settings = self.db.settings()?;
+        self.health_weights = settings.health;
+        self.topics.wrap = settings.wrap_lists;
+        self.plans.wrap = settings.wrap_lists;
+        self.selected_topic_sessions.wrap = settings.wrap_lists;
+        self.due_topics.wrap = settings.wrap_lists;
+        let topics = self.db.get_topics_with_progress(&self.topic_query())?;
+        self.topics.replace_items(topics, |t| t.topic.id);
+        let plans = self.filtered_plans()?;
+        self.plans.replace_items(plans, |p| p.id);
+        let due_topics = self.db.get_due_topics_limited(self.options.due_limit)?;
+        self.due_topics.replace_items(due_topics, |t| t.topic.id);
+        self.recent_sessions = self
+            .db
+            .get_recent_sessions_with_topics(self.options.recent_limit, 0)?;
+        self.study_sessions = recent_study_sessions(&self.db, self.options.recent_limit)?;
+
+        if let Some(id) = self.selected_topic.as_ref().map(|t| t.topic.id) {
+            if let Some(twp) = self.topics.items.iter().find(|t| t.topic.id == id) {
+                self.selected_topic = Some(twp.clone());
+            }
+            self.load_topic_detail(id)?;
+        }
+        if let Some(id) = self.selected_plan.as_ref().map(|p| p.id) {
+            if let Some(plan) = self.plans.items.iter().find(|p| p.id == id) {
+                self.selected_plan = Some(plan.clone());
+            }
+            self.selected_plan_entries = self.db.get_interview_entries(id)?;
+        }
         Ok(())
     }
 
-    fn apply_filter(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    // Refresh if another process wrote to the database since the last check
+    pub fn refresh_if_changed(&mut self) -> Result<bool> {
+        if !self.watcher.changed(self.db.data_version()?) {
+            return Ok(false);
+        }
+        self.refresh_data()?;
+        Ok(true)
+    }
+
+    // Topics view shows active topics only, narrowed by the tag filter
+    fn topic_query(&self) -> TopicQuery {
+        TopicQuery {
+            tag: self.filter_tag.clone(),
+            include_archived: false,
+            include_suspended: false,
+            ..TopicQuery::default()
+        }
+    }
+
+    fn apply_filter(&mut self) -> Result<()> {
         if self.filter_input.is_empty() {
             self.filter_tag = None;
         } else {
-            self.filter_tag = Some(self.filter_input.clone());
+            self.filter_tag = Some(self.filter_input.value().to_string());
         }
-        self.topics = StatefulList::with_items(
-            self.db
-                .get_topics_with_progress(self.filter_tag.as_deref())?,
-        );
+        self.topics = StatefulList {
+            wrap: self.topics.wrap,
+            ..StatefulList::with_items(self.db.get_topics_with_progress(&self.topic_query())?)
+        };
         Ok(())
     }
 
-    fn select_topic(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(twp) = self.topics.selected_item() {
-            self.selected_topic = Some(twp.clone());
-            self.selected_topic_sessions = self.db.list_sessions(Some(twp.topic.id))?;
-            self.selected_topic_gaps = self.db.get_unaddressed_gaps(twp.topic.id)?;
-            self.view = View::TopicDetail;
+    // Plans view shows every plan the plan filter matches
+    fn filtered_plans(&self) -> Result<Vec<Plan>> {
+        let mut plans = self.db.list_plans(None)?;
+        if let Some(query) = &self.plan_filter {
+            plans.retain(|plan| plan_matches(plan, query));
+        }
+        Ok(plans)
+    }
+
+    fn apply_plan_filter(&mut self) -> Result<()> {
+        if self.filter_input.is_empty() {
+            self.plan_filter = None;
+        } else {
+            self.plan_filter = Some(self.filter_input.value().to_string());
+        }
+        self.plans = StatefulList {
+            wrap: self.plans.wrap,
+            ..StatefulList::with_items(self.filtered_plans()?)
+        };
+        Ok(())
+    }
+
+    fn update_tag_suggestions(&mut self) -> Result<()> {
+        // Tags only narrow topics; the plan filter has nothing to suggest
+        self.tag_suggestions = if self.filter_input.is_empty() || self.view == View::Plans {
+            Vec::new()
+        } else {
+            self.db.tags_matching_prefix(self.filter_input.value())?
+        };
+        Ok(())
+    }
+
+    fn select_topic(&mut self) -> Result<()> {
+        if let Some(twp) = self.topics.selected_item().cloned() {
+            self.open_topic(twp, View::Topics)?;
         }
         Ok(())
     }
 
-    fn select_plan(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    // The highlighted row of the dashboard's due list
+    fn select_due_topic(&mut self) -> Result<()> {
+        if let Some(twp) = self.due_topics.selected_item().cloned() {
+            self.open_topic(twp, View::Dashboard)?;
+        }
+        Ok(())
+    }
+
+    fn open_topic(&mut self, twp: TopicWithProgress, parent: View) -> Result<()> {
+        let id = twp.topic.id;
+        self.selected_topic = Some(twp);
+        // Start at the newest session rather than the last topic's position
+        self.selected_topic_sessions.selected = None;
+        self.selected_topic_resources.selected = None;
+        self.resource_error = None;
+        self.load_topic_detail(id)?;
+        self.topic_detail_parent = parent;
+        self.view = View::TopicDetail;
+        Ok(())
+    }
+
+    fn load_topic_detail(&mut self, topic_id: i64) -> Result<()> {
+        let sessions = self.db.list_sessions(Some(topic_id))?;
+        self.selected_topic_sessions
+            .replace_items(sessions, |s| s.id);
+        self.selected_topic_gaps = self.db.get_unaddressed_gaps(topic_id)?;
+        let resources = self.db.get_resources(topic_id)?;
+        self.selected_topic_resources
+            .replace_items(resources, |r| r.id);
+        self.selected_topic_intervals = self.db.get_topic_intervals(topic_id)?;
+        self.selected_topic_trajectory = self.db.get_mastery_trajectory(topic_id)?;
+        let settings = self.db.settings()?;
+        self.selected_topic_mastery_date = self
+            .selected_topic
+            .as_ref()
+            .and_then(|t| t.progress.projected_mastery_date(&settings, Utc::now()));
+        Ok(())
+    }
+
+    fn select_plan(&mut self) -> Result<()> {
         if let Some(plan) = self.plans.selected_item() {
             self.selected_plan = Some(plan.clone());
             self.selected_plan_entries = self.db.get_interview_entries(plan.id)?;
+            self.plan_status_error = None;
             self.view = View::PlanDetail;
         }
         Ok(())
     }
 
-    fn handle_key(
-        &mut self,
-        key: KeyCode,
-        modifiers: KeyModifiers,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    // Keys while the entry form is open; a submitted entry is saved at once
+    fn handle_plan_entry_key(&mut self, key: KeyCode) -> Result<()> {
+        let Some(form) = self.plan_entry_form.as_mut() else {
+            return Ok(());
+        };
+        match form.handle_key(key) {
+            FormAction::Continue => {}
+            FormAction::Cancel => self.plan_entry_form = None,
+            FormAction::Submit(entry_type, category, content) => {
+                self.plan_entry_form = None;
+                if let Some(plan_id) = self.selected_plan.as_ref().map(|p| p.id) {
+                    self.db
+                        .add_interview_entry(plan_id, entry_type, &content, category)?;
+                    self.refresh_data()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Move the selected plan to the next status it's allowed to reach
+    fn cycle_plan_status(&mut self) -> Result<()> {
+        let Some(plan) = &self.selected_plan else {
+            return Ok(());
+        };
+        match plan.status.next_in_cycle() {
+            Some(next) => {
+                self.db.update_plan_status(plan.id, next)?;
+                self.plan_status_error = None;
+                self.refresh_data()?;
+            }
+            None => {
+                self.plan_status_error =
+                    Some(format!("{} plans can't change status", plan.status.label()));
+            }
+        }
+        Ok(())
+    }
+
+    fn open_transcript(&mut self) -> Result<()> {
+        if let Some(session) = self.selected_topic_sessions.selected_item() {
+            self.transcript = Some(TranscriptView {
+                session_id: session.id,
+                text: self.db.get_session_transcript(session.id)?,
+                scroll: 0,
+            });
+        }
+        Ok(())
+    }
+
+    fn open_resource(&mut self) {
+        if let Some(resource) = self.selected_topic_resources.selected_item() {
+            self.resource_error = open::open_url(&resource.url)
+                .err()
+                .map(|e| format!("Couldn't open {}: {}", resource.url, e));
+        }
+    }
+
+    // Keys while the transcript overlay is open: scroll it or close it
+    fn handle_transcript_key(&mut self, key: KeyCode) {
+        let Some(view) = self.transcript.as_mut() else {
+            return;
+        };
+        match key {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc | KeyCode::Char('t') | KeyCode::Char('h') | KeyCode::Left => {
+                self.transcript = None;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                view.scroll = (view.scroll + 1).min(view.max_scroll());
+            }
+            KeyCode::Char('k') | KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Char('g') => view.scroll = 0,
+            KeyCode::Char('G') => view.scroll = view.max_scroll(),
+            _ => {}
+        }
+    }
+
+    fn start_review(&mut self) -> Result<()> {
+        let due = self.db.get_stats()?.due_now.max(0) as usize;
+        let queue = self.db.get_due_topics_limited(due)?;
+        let mut session = ReviewSession::new(queue);
+        for twp in &session.queue {
+            if let Some(prompt) = self.db.get_review_prompt(twp.topic.id)? {
+                session.prompts.insert(twp.topic.id, prompt.prompt);
+            }
+        }
+        self.review = Some(session);
+        self.view = View::Review;
+        Ok(())
+    }
+
+    // Save the grade for the topic on screen right away, then show the next
+    fn grade_review(&mut self, outcome: ReviewOutcome) -> Result<()> {
+        let Some(session) = self.review.as_mut() else {
+            return Ok(());
+        };
+        let Some(twp) = session.current() else {
+            return Ok(());
+        };
+        let (id, before) = (twp.topic.id, twp.progress.mastery_level);
+        self.db.record_review(id, outcome, None)?;
+        let after = self
+            .db
+            .get_progress(id)?
+            .map_or(before, |p| p.mastery_level);
+        session.grade(outcome, after);
+        Ok(())
+    }
+
+    fn leave_review(&mut self) -> Result<()> {
+        self.review = None;
+        self.view = View::Dashboard;
+        self.refresh_data()
+    }
+
+    // Keys in the review view: grade, skip or stop; any of Esc/h/Enter closes
+    // the summary once the session is over
+    fn handle_review_key(&mut self, key: KeyCode) -> Result<()> {
+        let finished = self.review.as_ref().is_none_or(ReviewSession::is_finished);
+        match key {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('s') if !finished => self.grade_review(ReviewOutcome::Success)?,
+            KeyCode::Char('p') if !finished => self.grade_review(ReviewOutcome::Partial)?,
+            KeyCode::Char('f') if !finished => self.grade_review(ReviewOutcome::Fail)?,
+            KeyCode::Char('n') if !finished => {
+                if let Some(session) = self.review.as_mut() {
+                    session.skip();
+                }
+            }
+            KeyCode::Esc if !finished => {
+                if let Some(session) = self.review.as_mut() {
+                    session.stop();
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('h') | KeyCode::Left | KeyCode::Enter if finished => {
+                self.leave_review()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyCode, modifiers: KeyModifiers) -> Result<()> {
+        if self.transcript.is_some() {
+            self.handle_transcript_key(key);
+            return Ok(());
+        }
+
+        if self.plan_entry_form.is_some() {
+            return self.handle_plan_entry_key(key);
+        }
+
+        if self.view == View::Review {
+            return self.handle_review_key(key);
+        }
+
         // Handle filter mode input (vim-like / search)
         if self.filter_mode {
-            match key {
-                KeyCode::Esc => {
+            if key == KeyCode::Tab {
+                if let Some(tag) = self.tag_suggestions.first() {
+                    self.filter_input.set_value(tag);
+                }
+            }
+            match self.filter_input.handle_key(key) {
+                InputAction::Cancel => {
                     self.filter_mode = false;
                     self.filter_input.clear();
+                    self.tag_suggestions.clear();
                 }
-                KeyCode::Enter => {
+                InputAction::Submit => {
                     self.filter_mode = false;
-                    self.apply_filter()?;
-                }
-                KeyCode::Backspace => {
-                    self.filter_input.pop();
-                }
-                KeyCode::Char(c) => {
-                    self.filter_input.push(c);
+                    self.tag_suggestions.clear();
+                    if self.view == View::Plans {
+                        self.apply_plan_filter()?;
+                    } else {
+                        self.apply_filter()?;
+                    }
                 }
-                _ => {}
+                InputAction::Continue => self.update_tag_suggestions()?,
             }
             return Ok(());
         }
@@ -222,16 +697,26 @@ impl App {
                 self.refresh_data()?;
             }
 
+            // Review everything due
+            KeyCode::Char('r') if self.view == View::Dashboard => self.start_review()?,
+
             // Search/filter: / (vim search)
             KeyCode::Char('/') if self.view == View::Topics => {
                 self.filter_mode = true;
-                self.filter_input.clear();
+                self.filter_input
+                    .set_value(self.filter_tag.as_deref().unwrap_or_default());
+                self.update_tag_suggestions()?;
+            }
+            KeyCode::Char('/') if self.view == View::Plans => {
+                self.filter_mode = true;
+                self.filter_input
+                    .set_value(self.plan_filter.as_deref().unwrap_or_default());
             }
 
             // Clear filter: Esc or n (next would clear in this context)
             KeyCode::Esc => match self.view {
                 View::TopicDetail => {
-                    self.view = View::Topics;
+                    self.view = self.topic_detail_parent;
                     self.selected_topic = None;
                 }
                 View::PlanDetail => {
@@ -243,15 +728,21 @@ impl App {
                     self.filter_input.clear();
                     self.apply_filter()?;
                 }
+                View::Plans if self.plan_filter.is_some() => {
+                    self.plan_filter = None;
+                    self.filter_input.clear();
+                    self.apply_plan_filter()?;
+                }
                 View::Plans => {}
                 View::Dashboard => {}
                 View::Topics => {}
+                View::Review => {}
             },
 
             // Navigation between views: h/l (left/right like vim)
             KeyCode::Char('h') | KeyCode::Left => match self.view {
                 View::TopicDetail => {
-                    self.view = View::Topics;
+                    self.view = self.topic_detail_parent;
                     self.selected_topic = None;
                 }
                 View::PlanDetail => {
@@ -280,16 +771,38 @@ impl App {
 
             // List navigation: j/k (vim up/down)
             KeyCode::Char('j') | KeyCode::Down => match self.view {
+                View::Dashboard => self.due_topics.next(),
                 View::Topics => self.topics.next(),
+                View::TopicDetail => self.selected_topic_sessions.next(),
                 View::Plans => self.plans.next(),
                 _ => {}
             },
             KeyCode::Char('k') | KeyCode::Up => match self.view {
+                View::Dashboard => self.due_topics.previous(),
                 View::Topics => self.topics.previous(),
+                View::TopicDetail => self.selected_topic_sessions.previous(),
                 View::Plans => self.plans.previous(),
                 _ => {}
             },
 
+            // Transcript of the selected session
+            KeyCode::Char('t') if self.view == View::TopicDetail => self.open_transcript()?,
+
+            // Pick and open one of the topic's resources
+            KeyCode::Char(']') if self.view == View::TopicDetail => {
+                self.selected_topic_resources.next();
+            }
+            KeyCode::Char('[') if self.view == View::TopicDetail => {
+                self.selected_topic_resources.previous();
+            }
+            KeyCode::Char('o') if self.view == View::TopicDetail => self.open_resource(),
+
+            // Add an interview entry, or step the plan's status forward
+            KeyCode::Char('a') if self.view == View::PlanDetail => {
+                self.plan_entry_form = Some(PlanEntryForm::new());
+            }
+            KeyCode::Char('s') if self.view == View::PlanDetail => self.cycle_plan_status()?,
+
             // Jump to top/bottom: gg/G (we use g for top, G for bottom)
             KeyCode::Char('g') => match self.view {
                 View::Topics if !self.topics.items.is_empty() => {
@@ -312,6 +825,7 @@ impl App {
 
             // Enter to select (like vim Enter in quickfix)
             KeyCode::Enter => match self.view {
+                View::Dashboard => self.select_due_topic()?,
                 View::Topics => self.select_topic()?,
                 View::Plans => self.select_plan()?,
                 _ => {}
@@ -323,7 +837,7 @@ impl App {
     }
 }
 
-pub fn run(db: Database) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run(db: Database, options: TuiOptions) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -332,10 +846,11 @@ pub fn run(db: Database) -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = App::new(db)?;
+    let mut app = App::new(db, options)?;
 
     // Main loop
     let result = run_app(&mut terminal, &mut app);
+    app.save_state();
 
     // Restore terminal
     disable_raw_mode()?;
@@ -349,16 +864,43 @@ pub fn run(db: Database) -> Result<(), Box<dyn std::error::Error>> {
     result
 }
 
-fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    app: &mut App,
-) -> Result<(), Box<dyn std::error::Error>> {
+fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<()> {
+    let mut clock = Clock::new(Local::now());
+    let mut last_poll = Instant::now();
+    let mut needs_redraw = true;
+
     loop {
-        terminal.draw(|f| ui::draw(f, app))?;
+        if needs_redraw {
+            terminal.draw(|f| ui::draw(f, app))?;
+            needs_redraw = false;
+        }
 
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                app.handle_key(key.code, key.modifiers)?;
+        if event::poll(Duration::from_millis(250))? {
+            match event::read()? {
+                Event::Key(key) => {
+                    app.handle_key(key.code, key.modifiers)?;
+                    needs_redraw = true;
+                }
+                Event::Resize(_, _) => needs_redraw = true,
+                _ => {}
+            }
+        }
+
+        // Keep the clock and due/overdue highlighting current while idle
+        match clock.tick(Local::now()) {
+            Tick::Day => {
+                app.refresh_data()?;
+                needs_redraw = true;
+            }
+            Tick::Minute => needs_redraw = true,
+            Tick::None => {}
+        }
+
+        // Pick up reviews etc. recorded from another terminal
+        if last_poll.elapsed() >= watch::POLL_INTERVAL {
+            last_poll = Instant::now();
+            if app.refresh_if_changed()? {
+                needs_redraw = true;
             }
         }
 
@@ -367,3 +909,478 @@ fn run_app(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InterviewCategory, InterviewEntryType, PlanStatus, SessionType};
+
+    fn setup_db() -> Database {
+        let db = Database::open(":memory:").unwrap();
+        db.init().unwrap();
+        for i in 0..8 {
+            let id = db.add_topic(&format!("Topic {}", i), None, &[]).unwrap();
+            db.start_session(id, SessionType::Feynman).unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn list_wraps_at_both_ends_by_default() {
+        let mut list = StatefulList::with_items(vec!['a', 'b', 'c']);
+        list.previous();
+        assert_eq!(list.selected, Some(2));
+        list.next();
+        assert_eq!(list.selected, Some(0));
+    }
+
+    #[test]
+    fn list_without_wrap_stops_at_both_ends() {
+        let mut list = StatefulList {
+            wrap: false,
+            ..StatefulList::with_items(vec!['a', 'b', 'c'])
+        };
+        list.previous();
+        assert_eq!(list.selected, Some(0));
+        list.next();
+        list.next();
+        list.next();
+        assert_eq!(list.selected, Some(2));
+        list.previous();
+        assert_eq!(list.selected, Some(1));
+    }
+
+    #[test]
+    fn wrap_setting_applies_to_app_lists() {
+        let db = setup_db();
+        db.set_setting(crate::settings::TUI_WRAP_LISTS, "false")
+            .unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        assert!(!app.topics.wrap && !app.plans.wrap);
+
+        app.topics.previous();
+        assert_eq!(app.topics.selected, Some(0));
+
+        app.db
+            .set_setting(crate::settings::TUI_WRAP_LISTS, "true")
+            .unwrap();
+        app.refresh_data().unwrap();
+        app.topics.previous();
+        assert_eq!(app.topics.selected, Some(app.topics.items.len() - 1));
+    }
+
+    #[test]
+    fn filter_suggests_tags_and_tab_completes() {
+        let db = setup_db();
+        db.add_topic("Tagged", None, &["rust".to_string(), "ruby".to_string()])
+            .unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        app.view = View::Topics;
+
+        app.handle_key(KeyCode::Char('/'), KeyModifiers::NONE)
+            .unwrap();
+        assert!(app.tag_suggestions.is_empty());
+        app.handle_key(KeyCode::Char('r'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.tag_suggestions, ["ruby", "rust"]);
+        app.handle_key(KeyCode::Char('u'), KeyModifiers::NONE)
+            .unwrap();
+        app.handle_key(KeyCode::Char('s'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.tag_suggestions, ["rust"]);
+
+        app.handle_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.filter_input.value(), "rust");
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.filter_tag.as_deref(), Some("rust"));
+        assert!(app.tag_suggestions.is_empty());
+    }
+
+    #[test]
+    fn plan_matches_title_substring_or_status() {
+        let db = setup_db();
+        let id = db.create_plan("Auth Service", "Add login").unwrap();
+        let plan = db.get_plan(id).unwrap().unwrap();
+        assert!(plan_matches(&plan, "auth"));
+        assert!(plan_matches(&plan, "SERVICE"));
+        assert!(plan_matches(&plan, "interviewing"));
+        assert!(!plan_matches(&plan, "billing"));
+        assert!(!plan_matches(&plan, "interview"));
+    }
+
+    #[test]
+    fn slash_filters_plans_and_esc_clears() {
+        let db = setup_db();
+        db.create_plan("Auth", "Add login").unwrap();
+        db.create_plan("Billing", "Invoices").unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        app.view = View::Plans;
+        let press = |app: &mut App, key| app.handle_key(key, KeyModifiers::NONE).unwrap();
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "bill".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert!(app.tag_suggestions.is_empty());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.plan_filter.as_deref(), Some("bill"));
+        assert_eq!(app.plans.items.len(), 1);
+        assert_eq!(app.plans.items[0].title, "Billing");
+        assert!(app.filter_tag.is_none());
+
+        app.refresh_data().unwrap();
+        assert_eq!(app.plans.items.len(), 1);
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.plan_filter.is_none());
+        assert_eq!(app.plans.items.len(), 2);
+    }
+
+    #[test]
+    fn brackets_pick_the_resource_to_open() {
+        let db = setup_db();
+        db.add_resource(1, Some("The Book"), "https://doc.rust-lang.org/book/")
+            .unwrap();
+        let second = db.add_resource(1, None, "isbn:9781718503106").unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        app.view = View::Topics;
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+
+        let resources = &app.selected_topic_resources;
+        assert_eq!(resources.items.len(), 2);
+        assert_eq!(resources.selected, Some(0));
+        app.handle_key(KeyCode::Char(']'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(
+            app.selected_topic_resources.selected_item().map(|r| r.id),
+            Some(second)
+        );
+        app.handle_key(KeyCode::Char('['), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.selected_topic_resources.selected, Some(0));
+
+        // Another topic starts on its own (empty) list
+        app.handle_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+        app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE)
+            .unwrap();
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert!(app.selected_topic_resources.items.is_empty());
+        assert_eq!(app.selected_topic_resources.selected, None);
+    }
+
+    #[test]
+    fn t_opens_the_selected_sessions_transcript() {
+        use crate::models::SessionOutcome;
+
+        let db = setup_db();
+        let session = db.start_session(1, SessionType::Socratic).unwrap();
+        db.end_session_with_gaps(
+            session,
+            SessionOutcome::Success,
+            None,
+            None,
+            Some("First paragraph.\n\nSecond paragraph.\n\nThird."),
+            &[],
+        )
+        .unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        app.view = View::Topics;
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.selected_topic_sessions.items.len(), 2);
+
+        // Newest session first: the one with the transcript
+        app.handle_key(KeyCode::Char('t'), KeyModifiers::NONE)
+            .unwrap();
+        let view = app.transcript.as_ref().unwrap();
+        assert_eq!(view.session_id, session);
+        assert!(view.text.as_deref().unwrap().contains("Second paragraph."));
+
+        app.handle_key(KeyCode::Char('G'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.transcript.as_ref().unwrap().scroll, 4);
+        app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.transcript.as_ref().unwrap().scroll, 4);
+        app.handle_key(KeyCode::Char('k'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.transcript.as_ref().unwrap().scroll, 3);
+
+        app.handle_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+        assert!(app.transcript.is_none());
+        assert_eq!(app.view, View::TopicDetail);
+
+        // The older session has none
+        app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE)
+            .unwrap();
+        app.handle_key(KeyCode::Char('t'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.transcript.as_ref().unwrap().text, None);
+    }
+
+    #[test]
+    fn refresh_keeps_the_selected_topic() {
+        let mut app = App::new(setup_db(), TuiOptions::default()).unwrap();
+        app.view = View::Topics;
+        app.topics.selected = Some(3);
+        let id = app.topics.selected_item().unwrap().topic.id;
+
+        app.db.delete_topic(app.topics.items[0].topic.id).unwrap();
+        app.refresh_data().unwrap();
+        assert_eq!(app.topics.selected, Some(2));
+        assert_eq!(app.topics.selected_item().unwrap().topic.id, id);
+
+        // A vanished selection falls back to the same position
+        app.db.delete_topic(id).unwrap();
+        app.refresh_data().unwrap();
+        assert_eq!(app.topics.selected, Some(2));
+    }
+
+    #[test]
+    fn external_writes_trigger_a_refresh() {
+        let path = std::env::temp_dir().join(format!(
+            "feynman-tui-watch-{}-{}.db",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let db = Database::open(&path).unwrap();
+        db.init().unwrap();
+        let id = db.add_topic("Ownership", None, &[]).unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        app.view = View::Topics;
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert!(!app.refresh_if_changed().unwrap());
+
+        let other = Database::open(&path).unwrap();
+        other
+            .record_review(id, crate::models::ReviewOutcome::Success, None)
+            .unwrap();
+
+        assert!(app.refresh_if_changed().unwrap());
+        assert!(!app.refresh_if_changed().unwrap());
+        let topic = app.selected_topic.as_ref().unwrap();
+        assert_eq!(topic.progress.times_reviewed, 1);
+        assert_eq!(app.stats.total_reviews, 1);
+        assert_eq!(app.mastery_histogram[..2], [(0, 0), (1, 1)]);
+
+        drop(other);
+        drop(app);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn enter_on_the_dashboard_opens_the_highlighted_due_topic() {
+        let mut app = App::new(setup_db(), TuiOptions::default()).unwrap();
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        let first = app.due_topics.items[0].topic.id;
+        assert_eq!(app.view, View::TopicDetail);
+        assert_eq!(app.selected_topic.as_ref().unwrap().topic.id, first);
+
+        // Back returns to the dashboard, not the topic list
+        app.handle_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.view, View::Dashboard);
+
+        app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE)
+            .unwrap();
+        app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.due_topics.selected, Some(2));
+        let third = app.due_topics.items[2].topic.id;
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.selected_topic.as_ref().unwrap().topic.id, third);
+        assert_ne!(first, third);
+
+        app.handle_key(KeyCode::Char('h'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.view, View::Dashboard);
+        // The due list keeps its place across a refresh
+        app.refresh_data().unwrap();
+        assert_eq!(app.due_topics.selected_item().unwrap().topic.id, third);
+    }
+
+    #[test]
+    fn dashboard_limits_default_to_five() {
+        let app = App::new(setup_db(), TuiOptions::default()).unwrap();
+        assert_eq!(app.due_topics.items.len(), DEFAULT_DASHBOARD_LIMIT);
+        assert_eq!(app.recent_sessions.len(), DEFAULT_DASHBOARD_LIMIT);
+    }
+
+    #[test]
+    fn dashboard_limits_are_threaded_into_queries() {
+        let options = TuiOptions {
+            due_limit: 3,
+            recent_limit: 7,
+            ..TuiOptions::default()
+        };
+        let db = setup_db();
+        for topic in db.get_due_topics_limited(8).unwrap() {
+            let options = crate::models::ReviewOptions {
+                at: Some(chrono::Utc::now() - chrono::Duration::days(30 + topic.topic.id)),
+                ..crate::models::ReviewOptions::default()
+            };
+            db.record_review_with(topic.topic.id, ReviewOutcome::Success, None, &options)
+                .unwrap();
+        }
+        let mut app = App::new(db, options).unwrap();
+        assert_eq!(app.due_topics.items.len(), 3);
+        assert_eq!(app.recent_sessions.len(), 7);
+        assert_eq!(app.study_sessions.len(), 7);
+
+        app.refresh_data().unwrap();
+        assert_eq!(app.due_topics.items.len(), 3);
+        assert_eq!(app.recent_sessions.len(), 7);
+        assert_eq!(app.study_sessions.len(), 7);
+    }
+
+    #[test]
+    fn view_and_filter_are_restored_unless_disabled() {
+        let path =
+            std::env::temp_dir().join(format!("feynman-app-state-{}.json", std::process::id()));
+        let options = TuiOptions {
+            state_path: Some(path.clone()),
+            ..TuiOptions::default()
+        };
+        let db = setup_db();
+        db.add_topic("Tagged", None, &["rust".to_string()]).unwrap();
+        let mut app = App::new(db, options.clone()).unwrap();
+        assert_eq!(app.view, View::Dashboard);
+        app.view = View::Topics;
+        app.filter_tag = Some("rust".to_string());
+        app.save_state();
+
+        let app = App::new(app.db, options.clone()).unwrap();
+        assert_eq!(app.view, View::Topics);
+        assert_eq!(app.filter_tag.as_deref(), Some("rust"));
+        assert_eq!(app.topics.items.len(), 1);
+
+        let fresh = TuiOptions {
+            restore: false,
+            ..options
+        };
+        let app = App::new(app.db, fresh).unwrap();
+        assert_eq!(app.view, View::Dashboard);
+        assert!(app.filter_tag.is_none());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn review_grades_the_whole_due_batch() {
+        let mut app = App::new(setup_db(), TuiOptions::default()).unwrap();
+        app.handle_key(KeyCode::Char('r'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.view, View::Review);
+        // The whole batch, not just the dashboard's first five
+        assert_eq!(app.review.as_ref().unwrap().queue.len(), 8);
+
+        for key in ['s', 'p', 'f', 'n'] {
+            app.handle_key(KeyCode::Char(key), KeyModifiers::NONE)
+                .unwrap();
+        }
+        app.handle_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+
+        let session = app.review.as_ref().unwrap();
+        assert!(session.is_finished() && session.stopped);
+        assert_eq!(session.tally.reviewed(), 3);
+        assert_eq!(session.tally.count(ReviewOutcome::Success), 1);
+        assert_eq!(session.tally.graded[0].mastery_after, 1);
+        assert_eq!(app.db.get_stats().unwrap().total_reviews, 3);
+
+        // Grades are ignored once the session is over
+        app.handle_key(KeyCode::Char('s'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.db.get_stats().unwrap().total_reviews, 3);
+
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.view, View::Dashboard);
+        assert!(app.review.is_none());
+        assert_eq!(app.stats.total_reviews, 3);
+    }
+
+    #[test]
+    fn review_shows_the_topic_prompt() {
+        let mut app = App::new(setup_db(), TuiOptions::default()).unwrap();
+        let first = app.db.get_due_topics_limited(1).unwrap()[0].topic.id;
+        app.db
+            .add_prompt(first, "Explain it to a five-year-old")
+            .unwrap();
+
+        app.handle_key(KeyCode::Char('r'), KeyModifiers::NONE)
+            .unwrap();
+        let session = app.review.as_ref().unwrap();
+        assert_eq!(session.current().unwrap().topic.id, first);
+        assert_eq!(
+            session.current_prompt(),
+            Some("Explain it to a five-year-old")
+        );
+
+        app.handle_key(KeyCode::Char('s'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(app.review.as_ref().unwrap().current_prompt(), None);
+    }
+
+    // Open the only plan's detail view
+    fn app_with_open_plan() -> App {
+        let db = setup_db();
+        db.create_plan("Auth", "Add login").unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+        app.view = View::Plans;
+        app.handle_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+        assert_eq!(app.view, View::PlanDetail);
+        app
+    }
+
+    #[test]
+    fn a_adds_an_entry_to_the_chosen_category() {
+        let mut app = app_with_open_plan();
+        let press = |app: &mut App, key| app.handle_key(key, KeyModifiers::NONE).unwrap();
+        press(&mut app, KeyCode::Char('a'));
+        assert!(app.plan_entry_form.is_some());
+
+        // Decision, then Security; the form's keys don't leak to the view
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Char('j'));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        for c in "hash with argon2".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(app.view, View::PlanDetail);
+        assert!(app.selected_plan_entries.is_empty());
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.plan_entry_form.is_none());
+        let entries = &app.selected_plan_entries;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].entry_type, InterviewEntryType::Decision);
+        assert_eq!(entries[0].category, InterviewCategory::Security);
+        assert_eq!(entries[0].content, "hash with argon2");
+    }
+
+    #[test]
+    fn s_cycles_the_plan_status_until_complete() {
+        let mut app = app_with_open_plan();
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            app.handle_key(KeyCode::Char('s'), KeyModifiers::NONE)
+                .unwrap();
+            seen.push(app.selected_plan.as_ref().unwrap().status);
+        }
+        assert_eq!(
+            seen,
+            [
+                PlanStatus::SpecReady,
+                PlanStatus::Approved,
+                PlanStatus::InProgress,
+                PlanStatus::Complete,
+                PlanStatus::Complete,
+            ]
+        );
+        assert_eq!(
+            app.plan_status_error.as_deref(),
+            Some("Complete plans can't change status")
+        );
+        assert_eq!(app.plans.items[0].status, PlanStatus::Complete);
+    }
+}
@@ -0,0 +1,46 @@
+// Hands a topic resource's URL to the platform's opener (browser, PDF
+// viewer...) without waiting for it or letting it write over the TUI.
+
+use std::io;
+use std::process::{Command, Stdio};
+
+// The program and arguments that open `url` on this platform. Windows goes
+// through url.dll rather than `cmd /C start`, which would split on `&`.
+pub fn opener_command(url: &str) -> (&'static str, Vec<String>) {
+    if cfg!(target_os = "macos") {
+        ("open", vec![url.to_string()])
+    } else if cfg!(windows) {
+        (
+            "rundll32",
+            vec!["url.dll,FileProtocolHandler".to_string(), url.to_string()],
+        )
+    } else {
+        ("xdg-open", vec![url.to_string()])
+    }
+}
+
+pub fn open_url(url: &str) -> io::Result<()> {
+    let (program, args) = opener_command(url);
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reap it in the background so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_is_passed_as_a_single_argument() {
+        let url = "https://example.com/search?q=a&b=c";
+        let (program, args) = opener_command(url);
+        assert!(!program.is_empty());
+        assert_eq!(args.last().map(String::as_str), Some(url));
+    }
+}
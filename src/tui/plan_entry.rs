@@ -0,0 +1,142 @@
+// Adding an interview entry from the plan detail view: pick the entry type,
+// then the category, then type the content. Esc drops the entry at any step;
+// nothing is written until the content is submitted.
+
+use crossterm::event::KeyCode;
+
+use super::widgets::text_input::{InputAction, TextInput};
+use crate::models::{InterviewCategory, InterviewEntryType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStep {
+    Type,
+    Category,
+    Content,
+}
+
+// What the app should do after the form has handled a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormAction {
+    Continue,
+    Cancel,
+    Submit(InterviewEntryType, InterviewCategory, String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PlanEntryForm {
+    pub step: EntryStep,
+    // Indexes into InterviewEntryType::ALL and InterviewCategory::ALL
+    pub entry_type: usize,
+    pub category: usize,
+    pub content: TextInput,
+}
+
+impl PlanEntryForm {
+    pub fn new() -> Self {
+        Self {
+            step: EntryStep::Type,
+            entry_type: 0,
+            category: 0,
+            content: TextInput::new(),
+        }
+    }
+
+    pub fn selected_type(&self) -> InterviewEntryType {
+        InterviewEntryType::ALL[self.entry_type]
+    }
+
+    pub fn selected_category(&self) -> InterviewCategory {
+        InterviewCategory::ALL[self.category]
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> FormAction {
+        match self.step {
+            EntryStep::Type => {
+                let action = pick(&mut self.entry_type, InterviewEntryType::ALL.len(), key);
+                advance(action, &mut self.step, EntryStep::Category)
+            }
+            EntryStep::Category => {
+                let action = pick(&mut self.category, InterviewCategory::ALL.len(), key);
+                advance(action, &mut self.step, EntryStep::Content)
+            }
+            EntryStep::Content => match self.content.handle_key(key) {
+                InputAction::Cancel => FormAction::Cancel,
+                // An entry needs some text; Enter on an empty line does nothing
+                InputAction::Submit if self.content.value().trim().is_empty() => {
+                    FormAction::Continue
+                }
+                InputAction::Submit => FormAction::Submit(
+                    self.selected_type(),
+                    self.selected_category(),
+                    self.content.value().trim().to_string(),
+                ),
+                InputAction::Continue => FormAction::Continue,
+            },
+        }
+    }
+}
+
+// j/k move through `len` options, wrapping; Enter takes the highlighted one
+fn pick(index: &mut usize, len: usize, key: KeyCode) -> InputAction {
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => *index = (*index + 1) % len,
+        KeyCode::Char('k') | KeyCode::Up => *index = (*index + len - 1) % len,
+        KeyCode::Enter => return InputAction::Submit,
+        KeyCode::Esc => return InputAction::Cancel,
+        _ => {}
+    }
+    InputAction::Continue
+}
+
+// A submitted choice moves the form on to `next`
+fn advance(action: InputAction, step: &mut EntryStep, next: EntryStep) -> FormAction {
+    match action {
+        InputAction::Submit => {
+            *step = next;
+            FormAction::Continue
+        }
+        InputAction::Cancel => FormAction::Cancel,
+        InputAction::Continue => FormAction::Continue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_through_type_category_and_content() {
+        let mut form = PlanEntryForm::new();
+        assert_eq!(form.handle_key(KeyCode::Char('j')), FormAction::Continue);
+        assert_eq!(form.handle_key(KeyCode::Enter), FormAction::Continue);
+        assert_eq!(form.step, EntryStep::Category);
+        // k from the first category wraps to the last
+        form.handle_key(KeyCode::Char('k'));
+        form.handle_key(KeyCode::Enter);
+        assert_eq!(form.step, EntryStep::Content);
+
+        assert_eq!(form.handle_key(KeyCode::Enter), FormAction::Continue);
+        for c in "Use JWTs".chars() {
+            form.handle_key(KeyCode::Char(c));
+        }
+        assert_eq!(
+            form.handle_key(KeyCode::Enter),
+            FormAction::Submit(
+                InterviewEntryType::Answer,
+                InterviewCategory::Other,
+                "Use JWTs".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn esc_cancels_at_any_step() {
+        let mut form = PlanEntryForm::new();
+        assert_eq!(form.handle_key(KeyCode::Esc), FormAction::Cancel);
+        let mut form = PlanEntryForm::new();
+        form.handle_key(KeyCode::Enter);
+        form.handle_key(KeyCode::Enter);
+        form.handle_key(KeyCode::Char('x'));
+        assert_eq!(form.handle_key(KeyCode::Esc), FormAction::Cancel);
+    }
+}
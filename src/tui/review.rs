@@ -0,0 +1,198 @@
+// Focus-mode review: work through everything due, grading each topic s/p/f.
+// Each grade is recorded as soon as it's given, so quitting part way loses
+// nothing; the tally feeds the running counts and the end-of-session summary.
+
+use std::collections::HashMap;
+
+use crate::models::{ReviewOutcome, TopicWithProgress};
+
+// One graded topic and what the review did to its mastery
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradedTopic {
+    pub topic_id: i64,
+    pub name: String,
+    pub outcome: ReviewOutcome,
+    pub mastery_before: i32,
+    pub mastery_after: i32,
+}
+
+impl GradedTopic {
+    pub fn mastery_delta(&self) -> i32 {
+        self.mastery_after - self.mastery_before
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewTally {
+    pub graded: Vec<GradedTopic>,
+}
+
+impl ReviewTally {
+    pub fn record(&mut self, graded: GradedTopic) {
+        self.graded.push(graded);
+    }
+
+    pub fn reviewed(&self) -> usize {
+        self.graded.len()
+    }
+
+    pub fn count(&self, outcome: ReviewOutcome) -> usize {
+        self.graded.iter().filter(|g| g.outcome == outcome).count()
+    }
+
+    // Sum of every mastery change, e.g. +2 for two level-ups and no drops
+    pub fn net_mastery_change(&self) -> i32 {
+        self.graded.iter().map(GradedTopic::mastery_delta).sum()
+    }
+
+    // Topics whose mastery moved, in the order they were graded
+    pub fn mastery_changes(&self) -> impl Iterator<Item = &GradedTopic> {
+        self.graded.iter().filter(|g| g.mastery_delta() != 0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReviewSession {
+    // Due topics, most overdue first (as on the dashboard)
+    pub queue: Vec<TopicWithProgress>,
+    pub position: usize,
+    pub tally: ReviewTally,
+    // Ended with Esc before the queue ran out
+    pub stopped: bool,
+    // The review prompt for each queued topic that has any, by topic id
+    pub prompts: HashMap<i64, String>,
+}
+
+impl ReviewSession {
+    pub fn new(queue: Vec<TopicWithProgress>) -> Self {
+        Self {
+            queue,
+            position: 0,
+            tally: ReviewTally::default(),
+            stopped: false,
+            prompts: HashMap::new(),
+        }
+    }
+
+    // The topic waiting for a grade; None once the session is over
+    pub fn current(&self) -> Option<&TopicWithProgress> {
+        if self.stopped {
+            return None;
+        }
+        self.queue.get(self.position)
+    }
+
+    pub fn current_prompt(&self) -> Option<&str> {
+        let twp = self.current()?;
+        self.prompts.get(&twp.topic.id).map(String::as_str)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.queue.len().saturating_sub(self.position)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    // Record the grade for the current topic and move on. `mastery_after` is
+    // its level once the review has been saved.
+    pub fn grade(&mut self, outcome: ReviewOutcome, mastery_after: i32) {
+        let Some(twp) = self.current() else {
+            return;
+        };
+        let graded = GradedTopic {
+            topic_id: twp.topic.id,
+            name: twp.topic.name.clone(),
+            outcome,
+            mastery_before: twp.progress.mastery_level,
+            mastery_after,
+        };
+        self.tally.record(graded);
+        self.position += 1;
+    }
+
+    pub fn skip(&mut self) {
+        if self.current().is_some() {
+            self.position += 1;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::models::TopicQuery;
+
+    fn queue(levels: &[i32]) -> Vec<TopicWithProgress> {
+        let db = Database::open(":memory:").unwrap();
+        db.init().unwrap();
+        for i in 0..levels.len() {
+            db.add_topic(&format!("Topic {}", i), None, &[]).unwrap();
+        }
+        let mut topics = db.get_topics_with_progress(&TopicQuery::default()).unwrap();
+        for (twp, level) in topics.iter_mut().zip(levels) {
+            twp.progress.mastery_level = *level;
+        }
+        topics
+    }
+
+    #[test]
+    fn tally_counts_outcomes_and_mastery_deltas() {
+        let mut session = ReviewSession::new(queue(&[1, 3, 2]));
+        assert_eq!(session.remaining(), 3);
+
+        session.grade(ReviewOutcome::Success, 2);
+        session.grade(ReviewOutcome::Fail, 1);
+        assert_eq!(session.remaining(), 1);
+        session.grade(ReviewOutcome::Partial, 2);
+
+        assert!(session.is_finished());
+        let tally = &session.tally;
+        assert_eq!(tally.reviewed(), 3);
+        assert_eq!(tally.count(ReviewOutcome::Success), 1);
+        assert_eq!(tally.count(ReviewOutcome::Partial), 1);
+        assert_eq!(tally.count(ReviewOutcome::Fail), 1);
+        assert_eq!(tally.net_mastery_change(), -1);
+
+        let changes: Vec<(&str, i32)> = tally
+            .mastery_changes()
+            .map(|g| (g.name.as_str(), g.mastery_delta()))
+            .collect();
+        assert_eq!(changes, [("Topic 0", 1), ("Topic 1", -2)]);
+    }
+
+    #[test]
+    fn skipped_topics_are_not_tallied() {
+        let mut session = ReviewSession::new(queue(&[0, 0]));
+        session.skip();
+        session.grade(ReviewOutcome::Success, 1);
+
+        assert!(session.is_finished());
+        assert_eq!(session.tally.reviewed(), 1);
+        assert_eq!(session.tally.graded[0].name, "Topic 1");
+    }
+
+    #[test]
+    fn stopping_early_ends_the_session() {
+        let mut session = ReviewSession::new(queue(&[0, 0, 0]));
+        session.grade(ReviewOutcome::Success, 1);
+        session.stop();
+
+        assert!(session.is_finished());
+        session.grade(ReviewOutcome::Fail, 0);
+        assert_eq!(session.tally.reviewed(), 1);
+    }
+
+    #[test]
+    fn empty_queue_is_finished_at_once() {
+        let session = ReviewSession::new(Vec::new());
+        assert!(session.is_finished());
+        assert_eq!(session.tally, ReviewTally::default());
+    }
+}
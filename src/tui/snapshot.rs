@@ -0,0 +1,179 @@
+// `feynman snapshot`: the dashboard drawn once into an off-screen buffer and
+// returned as text, for sharing progress without opening the TUI. The widgets
+// are the dashboard's own, so the snapshot always looks like the real thing.
+
+use ratatui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    style::{Color, Modifier, Style},
+    Terminal,
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::widgets::dashboard;
+use super::{App, TuiOptions};
+use crate::db::Database;
+use crate::error::Result;
+
+// Columns used when the caller doesn't ask for a width
+pub const DEFAULT_SNAPSHOT_WIDTH: u16 = 100;
+
+// The dashboard at `width` columns, as plain text or with ANSI colors
+pub fn snapshot(db: Database, options: TuiOptions, width: u16, ansi: bool) -> Result<String> {
+    let mut app = App::new(db, options)?;
+    // Nothing is focused in a snapshot, so no row is highlighted
+    app.due_topics.selected = None;
+    render(&app, width, ansi)
+}
+
+fn render(app: &App, width: u16, ansi: bool) -> Result<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height(app)))?;
+    terminal.draw(|f| dashboard::draw(f, app, f.area()))?;
+    let buffer = terminal.backend().buffer();
+    Ok(if ansi {
+        ansi_text(buffer)
+    } else {
+        plain_text(buffer)
+    })
+}
+
+// Tall enough for both rows of panels with nothing cut off
+fn height(app: &App) -> u16 {
+    dashboard::top_row_height(app) + dashboard::bottom_row_height(app)
+}
+
+// Each buffer row's symbols, skipping the cells a wide character covers
+fn rows(buffer: &Buffer) -> Vec<Vec<(&str, Style)>> {
+    let width = buffer.area.width as usize;
+    buffer
+        .content()
+        .chunks(width.max(1))
+        .map(|row| {
+            let mut cells = Vec::new();
+            let mut covered = 0;
+            for cell in row {
+                if covered > 0 {
+                    covered -= 1;
+                    continue;
+                }
+                covered = cell.symbol().width().saturating_sub(1);
+                cells.push((cell.symbol(), cell.style()));
+            }
+            cells
+        })
+        .collect()
+}
+
+fn plain_text(buffer: &Buffer) -> String {
+    rows(buffer)
+        .iter()
+        .map(|row| {
+            let line: String = row.iter().map(|(symbol, _)| *symbol).collect();
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+// Escape codes only where the style changes, reset at the end of each line
+fn ansi_text(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for row in rows(buffer) {
+        let mut current = sgr(Style::default());
+        for (symbol, style) in row {
+            let code = sgr(style);
+            if code != current {
+                out.push_str(&code);
+                current = code;
+            }
+            out.push_str(symbol);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+// A full reset followed by the style's foreground, background and modifiers
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    if let Some(fg) = style.fg.and_then(|c| color_code(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| color_code(c, true)) {
+        codes.push(bg);
+    }
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{}", base + 8, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    };
+    Some(code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ReviewOutcome;
+
+    fn seeded_db() -> Database {
+        let db = Database::open(":memory:").unwrap();
+        db.init().unwrap();
+        let ownership = db.add_topic("Ownership", None, &[]).unwrap();
+        db.add_topic("Lifetimes", None, &[]).unwrap();
+        db.add_topic("Traits", None, &[]).unwrap();
+        db.record_review(ownership, ReviewOutcome::Success, None)
+            .unwrap();
+        db
+    }
+
+    #[test]
+    fn plain_snapshot_shows_the_dashboard_stats() {
+        let text = snapshot(seeded_db(), TuiOptions::default(), 100, false).unwrap();
+        assert!(text.contains("Stats"), "{}", text);
+        assert!(text.contains("Topics: 3"), "{}", text);
+        assert!(text.contains("Reviews: 1"), "{}", text);
+        assert!(text.contains("Due: 2"), "{}", text);
+        assert!(text.contains("Lifetimes"), "{}", text);
+        assert!(text.contains("Recent Sessions"), "{}", text);
+        assert!(!text.contains('\x1b'));
+        assert!(text.lines().all(|line| line.width() <= 100));
+    }
+
+    #[test]
+    fn ansi_snapshot_colors_and_resets_each_line() {
+        let text = snapshot(seeded_db(), TuiOptions::default(), 80, true).unwrap();
+        assert!(text.contains("\x1b[0;36m"), "cyan titles");
+        assert!(text.lines().all(|line| line.ends_with("\x1b[0m")));
+    }
+}
@@ -0,0 +1,90 @@
+// Where the TUI was when it last exited, so the next launch reopens there.
+// Best-effort both ways: a missing or unreadable file means a fresh start and
+// a failed save is ignored.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::View;
+use crate::error::Result;
+
+pub const STATE_FILE_NAME: &str = "tui-state.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuiState {
+    pub view: View,
+    pub filter_tag: Option<String>,
+}
+
+impl TuiState {
+    // Detail and review views need their selection, so they reopen on the
+    // list they came from
+    pub fn new(view: View, filter_tag: Option<String>) -> Self {
+        let view = match view {
+            View::TopicDetail => View::Topics,
+            View::PlanDetail => View::Plans,
+            View::Review => View::Dashboard,
+            view => view,
+        };
+        Self { view, filter_tag }
+    }
+
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("feynman-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn state_round_trips_through_a_file() {
+        let path = temp_path("tui-state");
+        let state = TuiState::new(View::Topics, Some("rust".to_string()));
+        state.save(&path).unwrap();
+
+        assert_eq!(TuiState::load(&path), state);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["view"], "topics");
+        assert_eq!(json["filter_tag"], "rust");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn detail_views_are_saved_as_their_list() {
+        assert_eq!(TuiState::new(View::TopicDetail, None).view, View::Topics);
+        assert_eq!(TuiState::new(View::PlanDetail, None).view, View::Plans);
+        assert_eq!(TuiState::new(View::Review, None).view, View::Dashboard);
+    }
+
+    #[test]
+    fn missing_or_invalid_state_starts_fresh() {
+        assert_eq!(
+            TuiState::load(Path::new("/nonexistent/feynman/tui-state.json")),
+            TuiState::default()
+        );
+
+        let path = temp_path("tui-state-invalid");
+        std::fs::write(&path, "{\"view\": \"nowhere\"").unwrap();
+        assert_eq!(TuiState::load(&path), TuiState::default());
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,15 +1,31 @@
+use chrono::Local;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Tabs},
+    widgets::{Block, Borders, Paragraph, Tabs, Wrap},
     Frame,
 };
 
-use super::widgets::{dashboard, plan_detail, plans, topic_detail, topics};
+use super::plan_entry::EntryStep;
+use super::widgets::{dashboard, plan_detail, plans, review, topic_detail, topics};
 use super::{App, View};
 
+// Smallest terminal the fixed-height panels fit in; below it only a notice is
+// drawn, and the full layout comes back once the terminal grows again
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 10;
+
+pub fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
 pub fn draw(f: &mut Frame, app: &App) {
+    if is_too_small(f.area()) {
+        draw_too_small(f, f.area());
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -22,18 +38,57 @@ pub fn draw(f: &mut Frame, app: &App) {
     draw_tabs(f, app, chunks[0]);
     draw_content(f, app, chunks[1]);
     draw_help_bar(f, app, chunks[2]);
+
+    if let Some(transcript) = &app.transcript {
+        topic_detail::draw_transcript(f, transcript, chunks[1]);
+    }
+    if let Some(form) = &app.plan_entry_form {
+        plan_detail::draw_entry_form(f, form, chunks[1]);
+    }
+}
+
+fn draw_too_small(f: &mut Frame, area: Rect) {
+    let mut lines: Vec<Line> = (0..area.height.saturating_sub(2) / 2)
+        .map(|_| Line::from(""))
+        .collect();
+    lines.push(Line::from(Span::styled(
+        "Terminal too small",
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(format!(
+        "{}x{}, need {}x{}",
+        area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+    )));
+
+    let notice = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(notice, area);
 }
 
 fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
     let tab_titles = vec!["Dashboard", "Topics", "Plans"];
     let selected = match app.view {
-        View::Dashboard => 0,
+        View::Dashboard | View::Review => 0,
         View::Topics | View::TopicDetail => 1,
         View::Plans | View::PlanDetail => 2,
     };
 
+    let clock = Line::from(Span::styled(
+        format!(" {} ", Local::now().format("%H:%M")),
+        Style::default().fg(Color::DarkGray),
+    ))
+    .right_aligned();
+
     let tabs = Tabs::new(tab_titles)
-        .block(Block::default().borders(Borders::ALL).title(" Feynman "))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Feynman ")
+                .title_top(clock),
+        )
         .select(selected)
         .style(Style::default().fg(Color::White))
         .highlight_style(
@@ -52,21 +107,88 @@ fn draw_content(f: &mut Frame, app: &App, area: Rect) {
         View::TopicDetail => topic_detail::draw(f, app, area),
         View::Plans => plans::draw(f, app, area),
         View::PlanDetail => plan_detail::draw(f, app, area),
+        View::Review => review::draw(f, app, area),
     }
 }
 
 fn draw_help_bar(f: &mut Frame, app: &App, area: Rect) {
     let help_text = if app.filter_mode {
-        vec![
-            Span::styled("/", Style::default().fg(Color::Yellow)),
-            Span::raw(&app.filter_input),
-            Span::styled("█", Style::default().fg(Color::Yellow)),
+        let mut spans = vec![Span::styled("/", Style::default().fg(Color::Yellow))];
+        spans.extend(app.filter_input.render());
+        if !app.tag_suggestions.is_empty() {
+            let shown: Vec<&str> = app
+                .tag_suggestions
+                .iter()
+                .take(5)
+                .map(String::as_str)
+                .collect();
+            spans.extend(vec![
+                Span::raw("  "),
+                Span::styled(shown.join(" "), Style::default().fg(Color::DarkGray)),
+                Span::raw(" | "),
+                Span::styled("<Tab>", Style::default().fg(Color::Cyan)),
+                Span::raw(" Complete"),
+            ]);
+        }
+        spans.extend(vec![
             Span::raw(" | "),
             Span::styled("<CR>", Style::default().fg(Color::Cyan)),
             Span::raw(" Apply  "),
             Span::styled("<Esc>", Style::default().fg(Color::Cyan)),
             Span::raw(" Cancel"),
+        ]);
+        spans
+    } else if let Some(form) = &app.plan_entry_form {
+        let mut spans = if form.step == EntryStep::Content {
+            vec![
+                Span::styled("<CR>", Style::default().fg(Color::Cyan)),
+                Span::raw(" Add  "),
+            ]
+        } else {
+            vec![
+                Span::styled("j/k", Style::default().fg(Color::Cyan)),
+                Span::raw(" Pick  "),
+                Span::styled("<CR>", Style::default().fg(Color::Cyan)),
+                Span::raw(" Next  "),
+            ]
+        };
+        spans.extend(vec![
+            Span::styled("<Esc>", Style::default().fg(Color::Cyan)),
+            Span::raw(" Cancel"),
+        ]);
+        spans
+    } else if app.transcript.is_some() {
+        vec![
+            Span::styled("j/k", Style::default().fg(Color::Cyan)),
+            Span::raw(" Scroll  "),
+            Span::styled("g/G", Style::default().fg(Color::Cyan)),
+            Span::raw(" Top/Bot  "),
+            Span::styled("t/<Esc>", Style::default().fg(Color::Cyan)),
+            Span::raw(" Close  "),
+            Span::styled("q", Style::default().fg(Color::Cyan)),
+            Span::raw(" Quit"),
         ]
+    } else if app.view == View::Review {
+        let mut spans = if app.review.as_ref().is_some_and(|r| !r.is_finished()) {
+            vec![
+                Span::styled("s/p/f", Style::default().fg(Color::Cyan)),
+                Span::raw(" Success/Partial/Fail  "),
+                Span::styled("n", Style::default().fg(Color::Cyan)),
+                Span::raw(" Skip  "),
+                Span::styled("<Esc>", Style::default().fg(Color::Cyan)),
+                Span::raw(" Stop  "),
+            ]
+        } else {
+            vec![
+                Span::styled("<CR>/<Esc>", Style::default().fg(Color::Cyan)),
+                Span::raw(" Close  "),
+            ]
+        };
+        spans.extend(vec![
+            Span::styled("q", Style::default().fg(Color::Cyan)),
+            Span::raw(" Quit"),
+        ]);
+        spans
     } else {
         let mut spans = vec![
             Span::styled("h/l", Style::default().fg(Color::Cyan)),
@@ -76,6 +198,12 @@ fn draw_help_bar(f: &mut Frame, app: &App, area: Rect) {
         match app.view {
             View::Dashboard => {
                 spans.extend(vec![
+                    Span::styled("j/k", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Due  "),
+                    Span::styled("<CR>", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Open  "),
+                    Span::styled("r", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Review due  "),
                     Span::styled("^r", Style::default().fg(Color::Cyan)),
                     Span::raw(" Refresh  "),
                 ]);
@@ -100,6 +228,14 @@ fn draw_help_bar(f: &mut Frame, app: &App, area: Rect) {
             }
             View::TopicDetail => {
                 spans.extend(vec![
+                    Span::styled("j/k", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Session  "),
+                    Span::styled("t", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Transcript  "),
+                    Span::styled("[/]", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Link  "),
+                    Span::styled("o", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Open  "),
                     Span::styled("h/<Esc>", Style::default().fg(Color::Cyan)),
                     Span::raw(" Back  "),
                     Span::styled("^r", Style::default().fg(Color::Cyan)),
@@ -114,16 +250,29 @@ fn draw_help_bar(f: &mut Frame, app: &App, area: Rect) {
                     Span::raw(" Top/Bot  "),
                     Span::styled("l/<CR>", Style::default().fg(Color::Cyan)),
                     Span::raw(" Open  "),
+                    Span::styled("/", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Filter  "),
                 ]);
+                if app.plan_filter.is_some() {
+                    spans.extend(vec![
+                        Span::styled("<Esc>", Style::default().fg(Color::Cyan)),
+                        Span::raw(" Clear  "),
+                    ]);
+                }
             }
             View::PlanDetail => {
                 spans.extend(vec![
+                    Span::styled("a", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Add entry  "),
+                    Span::styled("s", Style::default().fg(Color::Cyan)),
+                    Span::raw(" Status  "),
                     Span::styled("h/<Esc>", Style::default().fg(Color::Cyan)),
                     Span::raw(" Back  "),
                     Span::styled("^r", Style::default().fg(Color::Cyan)),
                     Span::raw(" Refresh  "),
                 ]);
             }
+            View::Review => {}
         }
 
         spans.extend(vec![
@@ -138,3 +287,97 @@ fn draw_help_bar(f: &mut Frame, app: &App, area: Rect) {
 
     f.render_widget(help, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::tui::TuiOptions;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn screen_text(width: u16, height: u16) -> String {
+        let db = Database::open(":memory:").unwrap();
+        db.init().unwrap();
+        db.add_topic("Ownership", None, &[]).unwrap();
+        let app = App::new(db, TuiOptions::default()).unwrap();
+        render(&app, width, height)
+    }
+
+    // The screen's rows, joined with newlines
+    fn render(app: &App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(width.max(1) as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn empty_database_shows_hints_in_every_view() {
+        let db = Database::open(":memory:").unwrap();
+        db.init().unwrap();
+        let mut app = App::new(db, TuiOptions::default()).unwrap();
+
+        let dashboard = render(&app, 160, 40);
+        for hint in [
+            "No topics yet — add one with `feynman topic add`",
+            "No learning sessions yet",
+            "No reviews yet",
+        ] {
+            assert!(dashboard.contains(hint), "{}\n{}", hint, dashboard);
+        }
+
+        app.view = View::Topics;
+        assert!(render(&app, 160, 40).contains("No topics yet"));
+        app.view = View::Plans;
+        assert!(render(&app, 160, 40).contains("No plans yet"));
+        for view in [View::TopicDetail, View::PlanDetail, View::Review] {
+            app.view = view;
+            render(&app, 160, 40);
+        }
+    }
+
+    #[test]
+    fn minimum_size_needs_both_dimensions() {
+        for (width, height, too_small) in [
+            (80, 24, false),
+            (MIN_WIDTH, MIN_HEIGHT, false),
+            (MIN_WIDTH - 1, 24, true),
+            (80, MIN_HEIGHT - 1, true),
+            (0, 0, true),
+            (1, 200, true),
+        ] {
+            assert_eq!(
+                is_too_small(Rect::new(0, 0, width, height)),
+                too_small,
+                "{}x{}",
+                width,
+                height
+            );
+        }
+    }
+
+    #[test]
+    fn small_terminals_get_a_notice_instead_of_the_layout() {
+        for (width, height) in [(MIN_WIDTH - 1, 30), (100, MIN_HEIGHT - 1), (20, 3)] {
+            let text = screen_text(width, height);
+            assert!(text.contains("Terminal too small"), "{}x{}", width, height);
+            assert!(!text.contains("Dashboard"), "{}x{}", width, height);
+        }
+
+        let text = screen_text(MIN_WIDTH, MIN_HEIGHT);
+        assert!(!text.contains("Terminal too small"));
+        assert!(text.contains("Dashboard"));
+    }
+
+    #[test]
+    fn degenerate_sizes_do_not_panic() {
+        for (width, height) in [(0, 0), (1, 1), (0, 20), (20, 0)] {
+            screen_text(width, height);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+// How often the TUI checks whether another process has written to the
+// database (e.g. `feynman review` run in a second terminal)
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Compares SQLite's data_version between polls. It only moves when another
+// connection commits, so the TUI's own reads never trigger a refresh.
+pub struct DataWatcher {
+    last: i64,
+}
+
+impl DataWatcher {
+    pub fn new(version: i64) -> Self {
+        Self { last: version }
+    }
+
+    // True when `version` differs from the one seen at the previous poll
+    pub fn changed(&mut self, version: i64) -> bool {
+        let changed = version != self.last;
+        self.last = version;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_version_is_unchanged() {
+        let mut watcher = DataWatcher::new(3);
+        assert!(!watcher.changed(3));
+        assert!(!watcher.changed(3));
+    }
+
+    #[test]
+    fn new_version_reports_change_once() {
+        let mut watcher = DataWatcher::new(3);
+        assert!(watcher.changed(4));
+        assert!(!watcher.changed(4));
+        assert!(watcher.changed(7));
+    }
+}
@@ -3,18 +3,39 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
+use super::text::{pad_to_width, truncate};
+use super::{empty, outcome};
+use crate::models::{count_bar, format_duration, TopicRow};
 use crate::tui::App;
 
+// Stats + Due topics row: at least the 6 stat lines, more if more due topics
+// are configured
+pub fn top_row_height(app: &App) -> u16 {
+    app.options.due_limit.max(7) as u16 + 2
+}
+
+// Recent + study sessions row with every listed session visible; the row
+// takes whatever is left when drawn, but a snapshot sizes itself to this
+pub fn bottom_row_height(app: &App) -> u16 {
+    let rows = app
+        .recent_sessions
+        .len()
+        .max(app.study_sessions.len())
+        // Room for an empty panel's hint to wrap
+        .max(3);
+    rows as u16 + 2
+}
+
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(9), // Stats + Due topics row
-            Constraint::Min(0),    // Recent sessions
+            Constraint::Length(top_row_height(app)),
+            Constraint::Min(0), // Recent + study sessions
         ])
         .split(area);
 
@@ -24,9 +45,23 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(chunks[0]);
 
-    draw_stats(f, app, top_chunks[0]);
+    // Stats with a compact mastery histogram beside them
+    let histogram_width = histogram_column_width(&app.mastery_histogram);
+    let stats_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(histogram_width)])
+        .split(top_chunks[0]);
+
+    draw_stats(f, app, stats_chunks[0]);
+    draw_histogram(f, app, stats_chunks[1]);
     draw_due_topics(f, app, top_chunks[1]);
-    draw_recent_sessions(f, app, chunks[1]);
+
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(chunks[1]);
+    draw_recent_sessions(f, app, bottom_chunks[0]);
+    draw_study_sessions(f, app, bottom_chunks[1]);
 }
 
 fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
@@ -70,10 +105,21 @@ fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
         Line::from(vec![
             Span::styled("Avg Mastery: ", Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("{:.1}", stats.avg_mastery),
+                format!("{:.1}", stats.avg_mastery_rounded),
                 Style::default().fg(Color::Cyan),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("Open Gaps: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{}", stats.open_gaps),
+                Style::default().fg(if stats.open_gaps > 0 {
+                    Color::Magenta
+                } else {
+                    Color::White
+                }),
+            ),
+        ]),
     ];
 
     let block = Block::default()
@@ -85,14 +131,49 @@ fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
+const HISTOGRAM_BAR_WIDTH: usize = 8;
+
+// Borders + "L " + bar + " " + widest count
+fn histogram_column_width(histogram: &[(i32, i64)]) -> u16 {
+    let max = histogram.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    (2 + 2 + HISTOGRAM_BAR_WIDTH + 1 + max.to_string().len()) as u16
+}
+
+fn draw_histogram(f: &mut Frame, app: &App, area: Rect) {
+    let histogram = &app.mastery_histogram;
+    let max = histogram.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+    let text: Vec<Line> = histogram
+        .iter()
+        .map(|(level, count)| {
+            Line::from(vec![
+                Span::styled(format!("{} ", level), Style::default().fg(Color::Gray)),
+                Span::styled(
+                    count_bar(*count, max, HISTOGRAM_BAR_WIDTH),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled(format!(" {}", count), Style::default().fg(Color::White)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Mastery ")
+        .title_style(Style::default().fg(Color::Cyan));
+
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
 fn draw_due_topics(f: &mut Frame, app: &App, area: Rect) {
     let items: Vec<ListItem> = app
         .due_topics
+        .items
         .iter()
         .enumerate()
         .map(|(i, twp)| {
-            let mastery_bar = create_mastery_bar(twp.progress.mastery_level);
-            let style = if twp.progress.mastery_level == 0 {
+            let row = TopicRow::from(twp);
+            let style = if row.mastery_level == 0 {
                 Style::default().fg(Color::Red)
             } else {
                 Style::default().fg(Color::Yellow)
@@ -100,9 +181,9 @@ fn draw_due_topics(f: &mut Frame, app: &App, area: Rect) {
 
             ListItem::new(Line::from(vec![
                 Span::styled(format!("{}. ", i + 1), Style::default().fg(Color::DarkGray)),
-                Span::styled(truncate(&twp.topic.name, 20), style),
+                Span::styled(truncate(&row.name, 20), style),
                 Span::raw(" "),
-                Span::styled(mastery_bar, Style::default().fg(Color::Green)),
+                Span::styled(row.mastery_bar, Style::default().fg(Color::Green)),
             ]))
         })
         .collect();
@@ -112,8 +193,22 @@ fn draw_due_topics(f: &mut Frame, app: &App, area: Rect) {
         .title(" Due Topics ")
         .title_style(Style::default().fg(Color::Yellow));
 
-    let list = List::new(items).block(block);
-    f.render_widget(list, area);
+    if items.is_empty() {
+        let hint = if app.stats.total_topics == 0 {
+            "No topics yet — add one with `feynman topic add`"
+        } else {
+            "Nothing due — you're all caught up!"
+        };
+        empty::draw_hint(f, block, hint, area);
+        return;
+    }
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select(app.due_topics.selected);
+    f.render_stateful_widget(list, area, &mut state);
 }
 
 fn draw_recent_sessions(f: &mut Frame, app: &App, area: Rect) {
@@ -126,13 +221,8 @@ fn draw_recent_sessions(f: &mut Frame, app: &App, area: Rect) {
                 crate::models::SessionType::Feynman => "Feynman",
                 crate::models::SessionType::Socratic => "Socratic",
             };
-            let (outcome_text, outcome_color) = match &session.outcome {
-                Some(crate::models::SessionOutcome::Success) => ("Success", Color::Green),
-                Some(crate::models::SessionOutcome::Partial) => ("Partial", Color::Yellow),
-                Some(crate::models::SessionOutcome::Fail) => ("Fail", Color::Red),
-                Some(crate::models::SessionOutcome::Abandoned) => ("Abandoned", Color::DarkGray),
-                None => ("In Progress", Color::Cyan),
-            };
+            let outcome_text = outcome::cell(session.outcome, app.options.compact_symbols);
+            let outcome_color = outcome::color(session.outcome);
 
             ListItem::new(Line::from(vec![
                 Span::styled(
@@ -140,13 +230,17 @@ fn draw_recent_sessions(f: &mut Frame, app: &App, area: Rect) {
                     Style::default().fg(Color::DarkGray),
                 ),
                 Span::styled(
-                    format!("{:<22}", truncate(topic_name, 20)),
+                    pad_to_width(&truncate(topic_name, 20), 22),
                     Style::default().fg(Color::White),
                 ),
                 Span::styled(
                     format!("{:<10}", session_type),
                     Style::default().fg(Color::Cyan),
                 ),
+                Span::styled(
+                    outcome::duration_cell(session),
+                    Style::default().fg(Color::Gray),
+                ),
                 Span::styled(outcome_text, Style::default().fg(outcome_color)),
             ]))
         })
@@ -157,22 +251,69 @@ fn draw_recent_sessions(f: &mut Frame, app: &App, area: Rect) {
         .title(" Recent Sessions ")
         .title_style(Style::default().fg(Color::Magenta));
 
+    if items.is_empty() {
+        empty::draw_hint(
+            f,
+            block,
+            "No learning sessions yet — start one with /feynman in Claude",
+            area,
+        );
+        return;
+    }
+
     let list = List::new(items).block(block);
     f.render_widget(list, area);
 }
 
-fn create_mastery_bar(level: i32) -> String {
-    let filled = level as usize;
-    let empty = 5 - filled;
-    format!("{}{}", "█".repeat(filled), "░".repeat(empty))
-}
+// Reviews grouped by the pauses between them, e.g. "Mar 01 09:00   45m   12  75%"
+fn draw_study_sessions(f: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .study_sessions
+        .iter()
+        .map(|session| {
+            let started = DateTime::parse_from_rfc3339(&session.start)
+                .map(|dt| dt.format("%b %d %H:%M").to_string())
+                .unwrap_or_else(|_| session.start.chars().take(16).collect());
+            let duration = session.duration().map(format_duration).unwrap_or_default();
+            let rate_color = match session.success_rate {
+                r if r >= 0.8 => Color::Green,
+                r if r >= 0.5 => Color::Yellow,
+                _ => Color::Red,
+            };
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<13}", started),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:>7} ", duration),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::styled(
+                    format!("{:>3} ", session.reviews),
+                    Style::default().fg(Color::White),
+                ),
+                Span::styled(
+                    format!("{:>3.0}%", session.success_rate * 100.0),
+                    Style::default().fg(rate_color),
+                ),
+            ]))
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Study Sessions ")
+        .title_style(Style::default().fg(Color::Magenta));
+
+    if items.is_empty() {
+        empty::draw_hint(f, block, "No reviews yet", area);
+        return;
     }
+
+    let list = List::new(items).block(block);
+    f.render_widget(list, area);
 }
 
 fn format_date(date_str: &str) -> String {
@@ -0,0 +1,23 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Wrap},
+    Frame,
+};
+
+// Render a centered hint inside `block` for views with nothing to show
+pub fn draw_hint(f: &mut Frame, block: Block, hint: &str, area: Rect) {
+    let inner_height = area.height.saturating_sub(2);
+    let top_padding = inner_height.saturating_sub(1) / 2;
+
+    let mut lines: Vec<Line> = (0..top_padding).map(|_| Line::from("")).collect();
+    lines.push(Line::from(hint.to_string()));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
@@ -0,0 +1,209 @@
+// Just enough Markdown for topic notes: **bold**, *italic* or _italic_,
+// `code` and "- " / "* " bullet lines. Nothing else is interpreted, and a
+// marker without a partner is shown as typed, so malformed input never loses
+// text.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+const BULLET: &str = "• ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Inline {
+    Bold,
+    Italic,
+    Code,
+}
+
+// One Line per line of `text`, bullets drawn as "•"
+pub fn lines(text: &str, style: Style) -> Vec<Line<'static>> {
+    text.lines().map(|line| render_line(line, style)).collect()
+}
+
+fn render_line(line: &str, style: Style) -> Line<'static> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let Some(item) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+    else {
+        return Line::from(spans(line, style));
+    };
+
+    let mut bullet = vec![Span::styled(
+        format!("{}{}", indent.replace('\t', "  "), BULLET),
+        Style::default().fg(Color::Gray),
+    )];
+    bullet.extend(spans(item, style));
+    Line::from(bullet)
+}
+
+// Inline emphasis and code within a single line
+pub fn spans(text: &str, style: Style) -> Vec<Span<'static>> {
+    let mut out = Vec::new();
+    push_inline(text, style, &mut out);
+    out
+}
+
+fn push_inline(text: &str, style: Style, out: &mut Vec<Span<'static>>) {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        match marked_at(rest, plain.chars().last()) {
+            Some((kind, inner, after)) => {
+                if !plain.is_empty() {
+                    out.push(Span::styled(std::mem::take(&mut plain), style));
+                }
+                match kind {
+                    Inline::Code => out.push(Span::styled(inner.to_string(), code_style(style))),
+                    Inline::Bold => push_inline(inner, style.add_modifier(Modifier::BOLD), out),
+                    Inline::Italic => push_inline(inner, style.add_modifier(Modifier::ITALIC), out),
+                }
+                rest = after;
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        out.push(Span::styled(plain, style));
+    }
+}
+
+// The closed span opening at the start of `s`, as (kind, contents, rest).
+// Emphasis can't start or end on a space (so "2 * 3 * 4" is left alone) and
+// underscores only count at word edges (so snake_case is too).
+fn marked_at(s: &str, before: Option<char>) -> Option<(Inline, &str, &str)> {
+    let (marker, kind) = if s.starts_with('`') {
+        ("`", Inline::Code)
+    } else if s.starts_with("**") {
+        ("**", Inline::Bold)
+    } else if s.starts_with('*') {
+        ("*", Inline::Italic)
+    } else if s.starts_with('_') && !before.is_some_and(char::is_alphanumeric) {
+        ("_", Inline::Italic)
+    } else {
+        return None;
+    };
+
+    let body = &s[marker.len()..];
+    let end = body.find(marker)?;
+    let inner = &body[..end];
+    let after = &body[end + marker.len()..];
+    if inner.is_empty() {
+        return None;
+    }
+    if kind != Inline::Code && (inner.starts_with(' ') || inner.ends_with(' ')) {
+        return None;
+    }
+    if marker == "_" && after.chars().next().is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    Some((kind, inner, after))
+}
+
+fn code_style(style: Style) -> Style {
+    style.fg(Color::Yellow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Style {
+        Style::default().fg(Color::White)
+    }
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn bold_and_code_become_styled_spans() {
+        assert_eq!(
+            spans("**bold**", base()),
+            vec![Span::styled("bold", base().add_modifier(Modifier::BOLD))]
+        );
+        assert_eq!(
+            spans("`code`", base()),
+            vec![Span::styled("code", base().fg(Color::Yellow))]
+        );
+    }
+
+    #[test]
+    fn emphasis_sits_between_plain_text() {
+        assert_eq!(
+            spans("use *any* `&T` **or** _one_ `&mut T`", base()),
+            vec![
+                Span::styled("use ", base()),
+                Span::styled("any", base().add_modifier(Modifier::ITALIC)),
+                Span::styled(" ", base()),
+                Span::styled("&T", base().fg(Color::Yellow)),
+                Span::styled(" ", base()),
+                Span::styled("or", base().add_modifier(Modifier::BOLD)),
+                Span::styled(" ", base()),
+                Span::styled("one", base().add_modifier(Modifier::ITALIC)),
+                Span::styled(" ", base()),
+                Span::styled("&mut T", base().fg(Color::Yellow)),
+            ]
+        );
+    }
+
+    #[test]
+    fn italic_nests_inside_bold() {
+        assert_eq!(
+            spans("**very *much* so**", base()),
+            vec![
+                Span::styled("very ", base().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    "much",
+                    base().add_modifier(Modifier::BOLD | Modifier::ITALIC)
+                ),
+                Span::styled(" so", base().add_modifier(Modifier::BOLD)),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_contents_are_not_interpreted() {
+        assert_eq!(
+            spans("`**ptr`", base()),
+            vec![Span::styled("**ptr", base().fg(Color::Yellow))]
+        );
+    }
+
+    #[test]
+    fn malformed_markers_are_kept_as_typed() {
+        for input in [
+            "**unclosed",
+            "`unclosed",
+            "2 * 3 * 4",
+            "snake_case_name",
+            "****",
+            "a ` b",
+            "*",
+            "",
+        ] {
+            let rendered = spans(input, base());
+            assert_eq!(text(&rendered), input);
+            assert!(rendered.iter().all(|s| s.style == base()), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn bullet_lines_get_a_bullet() {
+        let rendered = lines("Rules:\n- one owner\n  * **one** `&mut`", base());
+        assert_eq!(rendered.len(), 3);
+        assert_eq!(text(&rendered[0].spans), "Rules:");
+        assert_eq!(text(&rendered[1].spans), "• one owner");
+        assert_eq!(text(&rendered[2].spans), "  • one &mut");
+        assert_eq!(
+            rendered[2].spans[1],
+            Span::styled("one", base().add_modifier(Modifier::BOLD))
+        );
+    }
+}
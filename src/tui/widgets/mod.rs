@@ -1,5 +1,25 @@
 pub mod dashboard;
+pub mod empty;
+pub mod markdown;
+pub mod outcome;
 pub mod plan_detail;
 pub mod plans;
+pub mod review;
+pub mod text;
+pub mod text_input;
 pub mod topic_detail;
 pub mod topics;
+
+use ratatui::layout::Rect;
+
+// A rectangle `percent_x` by `percent_y` of `area`, centered in it
+pub fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
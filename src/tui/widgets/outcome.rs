@@ -0,0 +1,99 @@
+use ratatui::style::Color;
+
+use super::text::pad_to_width;
+use crate::models::{format_duration, LearningSession, SessionOutcome};
+
+// Width of the outcome column in each rendering mode
+pub const LABEL_WIDTH: usize = 12;
+pub const SYMBOL_WIDTH: usize = 2;
+// Width of the duration column that sits before it
+pub const DURATION_WIDTH: usize = 8;
+
+pub fn label(outcome: Option<SessionOutcome>) -> &'static str {
+    match outcome {
+        Some(SessionOutcome::Success) => "Success",
+        Some(SessionOutcome::Partial) => "Partial",
+        Some(SessionOutcome::Fail) => "Fail",
+        Some(SessionOutcome::Abandoned) => "Abandoned",
+        None => "In Progress",
+    }
+}
+
+pub fn symbol(outcome: Option<SessionOutcome>) -> &'static str {
+    match outcome {
+        Some(SessionOutcome::Success) => "✓",
+        Some(SessionOutcome::Partial) => "~",
+        Some(SessionOutcome::Fail) => "✗",
+        Some(SessionOutcome::Abandoned) => "⊘",
+        None => "…",
+    }
+}
+
+pub fn color(outcome: Option<SessionOutcome>) -> Color {
+    match outcome {
+        Some(SessionOutcome::Success) => Color::Green,
+        Some(SessionOutcome::Partial) => Color::Yellow,
+        Some(SessionOutcome::Fail) => Color::Red,
+        Some(SessionOutcome::Abandoned) => Color::DarkGray,
+        None => Color::Cyan,
+    }
+}
+
+// Session length padded to its column; "-" while the session is running
+pub fn duration_cell(session: &LearningSession) -> String {
+    let text = session
+        .duration()
+        .map(format_duration)
+        .unwrap_or_else(|| String::from("-"));
+    pad_to_width(&text, DURATION_WIDTH)
+}
+
+// Outcome text padded to a fixed column, measured by display width so
+// symbols and words line up the same way
+pub fn cell(outcome: Option<SessionOutcome>, compact: bool) -> String {
+    let (text, width) = if compact {
+        (symbol(outcome), SYMBOL_WIDTH)
+    } else {
+        (label(outcome), LABEL_WIDTH)
+    };
+    pad_to_width(text, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Span;
+
+    #[test]
+    fn symbol_maps_each_outcome() {
+        assert_eq!(symbol(Some(SessionOutcome::Success)), "✓");
+        assert_eq!(symbol(Some(SessionOutcome::Partial)), "~");
+        assert_eq!(symbol(Some(SessionOutcome::Fail)), "✗");
+        assert_eq!(symbol(Some(SessionOutcome::Abandoned)), "⊘");
+        assert_eq!(symbol(None), "…");
+    }
+
+    #[test]
+    fn label_maps_each_outcome() {
+        assert_eq!(label(Some(SessionOutcome::Success)), "Success");
+        assert_eq!(label(Some(SessionOutcome::Abandoned)), "Abandoned");
+        assert_eq!(label(None), "In Progress");
+    }
+
+    #[test]
+    fn cells_have_equal_display_width() {
+        let outcomes = [
+            Some(SessionOutcome::Success),
+            Some(SessionOutcome::Partial),
+            Some(SessionOutcome::Fail),
+            Some(SessionOutcome::Abandoned),
+            None,
+        ];
+        for compact in [true, false] {
+            let expected = if compact { SYMBOL_WIDTH } else { LABEL_WIDTH };
+            for outcome in outcomes {
+                assert_eq!(Span::raw(cell(outcome, compact)).width(), expected);
+            }
+        }
+    }
+}
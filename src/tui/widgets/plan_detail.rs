@@ -2,11 +2,14 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
+use super::text::{single_line, truncate};
+use super::{centered, empty};
 use crate::models::{InterviewCategory, InterviewEntryType, PlanStatus};
+use crate::tui::plan_entry::{EntryStep, PlanEntryForm};
 use crate::tui::App;
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
@@ -14,8 +17,7 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
             .borders(Borders::ALL)
             .title(" Plan Detail ");
-        let paragraph = Paragraph::new("No plan selected").block(block);
-        f.render_widget(paragraph, area);
+        empty::draw_hint(f, block, "No plan selected", area);
         return;
     };
 
@@ -27,11 +29,11 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         ])
         .split(area);
 
-    draw_header(f, plan, chunks[0]);
+    draw_header(f, plan, app.plan_status_error.as_deref(), chunks[0]);
     draw_entries(f, app, chunks[1]);
 }
 
-fn draw_header(f: &mut Frame, plan: &crate::models::Plan, area: Rect) {
+fn draw_header(f: &mut Frame, plan: &crate::models::Plan, error: Option<&str>, area: Rect) {
     let (status_text, status_color) = match plan.status {
         PlanStatus::Interviewing => ("Interviewing", Color::Yellow),
         PlanStatus::SpecReady => ("Spec Ready", Color::Cyan),
@@ -43,10 +45,13 @@ fn draw_header(f: &mut Frame, plan: &crate::models::Plan, area: Rect) {
 
     let engineer_level = plan.engineer_level.as_deref().unwrap_or("Not specified");
 
-    let text = vec![
+    let mut text = vec![
         Line::from(vec![
             Span::styled("Description: ", Style::default().fg(Color::Gray)),
-            Span::styled(&plan.initial_description, Style::default().fg(Color::White)),
+            Span::styled(
+                single_line(&plan.initial_description),
+                Style::default().fg(Color::White),
+            ),
         ]),
         Line::from(""),
         Line::from(vec![
@@ -57,6 +62,12 @@ fn draw_header(f: &mut Frame, plan: &crate::models::Plan, area: Rect) {
             Span::styled(engineer_level, Style::default().fg(Color::Cyan)),
         ]),
     ];
+    if let Some(error) = error {
+        text.push(Line::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(Color::Red),
+        )));
+    }
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -166,10 +177,79 @@ fn draw_entries(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(list, area);
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
-    }
+// The a form over the plan detail: a picker for the type and category steps,
+// then a line to type the entry into
+pub fn draw_entry_form(f: &mut Frame, form: &PlanEntryForm, area: Rect) {
+    let popup = centered(area, 60, 60);
+    let (title, options, selected): (&str, Vec<&str>, usize) = match form.step {
+        EntryStep::Type => (
+            " New Entry: Type ",
+            InterviewEntryType::ALL.iter().map(|t| t.as_str()).collect(),
+            form.entry_type,
+        ),
+        EntryStep::Category => (
+            " New Entry: Category ",
+            InterviewCategory::ALL.iter().map(|c| c.as_str()).collect(),
+            form.category,
+        ),
+        EntryStep::Content => {
+            draw_content_step(f, form, popup);
+            return;
+        }
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    let items: Vec<ListItem> = options.into_iter().map(ListItem::new).collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .highlight_symbol("> ");
+    let mut state = ListState::default();
+    state.select(Some(selected));
+
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut state);
+}
+
+fn draw_content_step(f: &mut Frame, form: &PlanEntryForm, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" New Entry: Content ")
+        .title_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut input = vec![Span::styled("> ", Style::default().fg(Color::Yellow))];
+    input.extend(form.content.render());
+    let text = vec![
+        Line::from(vec![
+            Span::styled("Type: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                form.selected_type().as_str(),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw("  "),
+            Span::styled("Category: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                form.selected_category().as_str(),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(input),
+    ];
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+        area,
+    );
 }
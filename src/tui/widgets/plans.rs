@@ -7,10 +7,18 @@ use ratatui::{
     Frame,
 };
 
+use super::empty;
+use super::text::{pad_to_width, truncate};
 use crate::models::PlanStatus;
 use crate::tui::App;
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
+    let title = if let Some(query) = &app.plan_filter {
+        format!(" Plans (filter: {}) ", query)
+    } else {
+        " Plans ".to_string()
+    };
+
     let items: Vec<ListItem> = app
         .plans
         .items
@@ -29,7 +37,7 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
 
             ListItem::new(Line::from(vec![
                 Span::styled(
-                    format!("{:<40}", truncate(&plan.title, 38)),
+                    pad_to_width(&truncate(&plan.title, 38), 40),
                     Style::default().fg(Color::White),
                 ),
                 Span::styled(
@@ -43,9 +51,21 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(" Plans ")
+        .title(title)
         .title_style(Style::default().fg(Color::Cyan));
 
+    if items.is_empty() {
+        let hint = match &app.plan_filter {
+            Some(query) => format!(
+                "No plans match '{}' — press <Esc> to clear the filter",
+                query
+            ),
+            None => "No plans yet — ask Claude to \"plan X\" to start an interview".to_string(),
+        };
+        empty::draw_hint(f, block, &hint, area);
+        return;
+    }
+
     // Header
     let header = Line::from(vec![
         Span::styled(
@@ -100,14 +120,6 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     f.render_stateful_widget(list, list_area, &mut state);
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
-    }
-}
-
 fn format_date(date_str: &str) -> String {
     if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
         dt.format("%b %d").to_string()
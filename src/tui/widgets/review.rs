@@ -0,0 +1,188 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use super::{empty, outcome};
+use crate::models::{mastery_bar, ReviewOutcome, SessionOutcome, TopicWithProgress};
+use crate::tui::review::{ReviewSession, ReviewTally};
+use crate::tui::App;
+
+pub fn draw(f: &mut Frame, app: &App, area: Rect) {
+    let Some(session) = &app.review else {
+        let block = Block::default().borders(Borders::ALL).title(" Review ");
+        empty::draw_hint(f, block, "No review in progress", area);
+        return;
+    };
+
+    match session.current() {
+        Some(twp) => draw_card(f, session, twp, area),
+        None => draw_summary(f, session, area),
+    }
+}
+
+fn draw_card(f: &mut Frame, session: &ReviewSession, twp: &TopicWithProgress, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Queue position + running tally
+            Constraint::Min(0),    // Topic
+        ])
+        .split(area);
+
+    let mut status = vec![
+        Span::styled(
+            format!("Topic {} of {}", session.position + 1, session.queue.len()),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("  ({} left)   ", session.remaining()),
+            Style::default().fg(Color::Gray),
+        ),
+    ];
+    status.extend(tally_spans(&session.tally));
+    let header = Paragraph::new(Line::from(status))
+        .block(Block::default().borders(Borders::ALL).title(" Review "));
+    f.render_widget(header, chunks[0]);
+
+    let progress = &twp.progress;
+    let mut lines = vec![
+        Line::from(Span::styled(
+            twp.topic.name.clone(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Mastery: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                mastery_bar(progress.mastery_level),
+                Style::default().fg(Color::Green),
+            ),
+            Span::raw(format!(" {}", progress.mastery_label())),
+        ]),
+    ];
+    if !twp.topic.tags.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Tags: ", Style::default().fg(Color::Gray)),
+            Span::raw(twp.topic.tags.join(", ")),
+        ]));
+    }
+    if let Some(description) = &twp.topic.description {
+        lines.push(Line::from(""));
+        lines.push(Line::from(description.clone()));
+    }
+    lines.push(Line::from(""));
+    match session.current_prompt() {
+        Some(prompt) => lines.push(Line::from(vec![
+            Span::styled("Prompt: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                prompt.to_string(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ])),
+        None => lines.push(Line::from(Span::styled(
+            "Explain it to yourself, then grade how it went.",
+            Style::default().fg(Color::DarkGray),
+        ))),
+    }
+
+    let card = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(card, chunks[1]);
+}
+
+fn draw_summary(f: &mut Frame, session: &ReviewSession, area: Rect) {
+    let tally = &session.tally;
+    let title = if session.stopped {
+        " Review Stopped "
+    } else {
+        " Review Complete "
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Reviewed: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{} of {}", tally.reviewed(), session.queue.len()),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("Successes: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{}", tally.count(ReviewOutcome::Success)),
+                Style::default().fg(Color::Green),
+            ),
+        ]),
+        Line::from(tally_spans(tally)),
+        Line::from(vec![
+            Span::styled("Mastery change: ", Style::default().fg(Color::Gray)),
+            delta_span(tally.net_mastery_change()),
+        ]),
+        Line::from(""),
+    ];
+
+    let mut changed = tally.mastery_changes().peekable();
+    if changed.peek().is_none() {
+        lines.push(Line::from(Span::styled(
+            "No mastery levels changed",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for graded in changed {
+        lines.push(Line::from(vec![
+            Span::raw(format!(
+                "{}  {} → {} ",
+                graded.name, graded.mastery_before, graded.mastery_after
+            )),
+            delta_span(graded.mastery_delta()),
+        ]));
+    }
+
+    let summary = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
+    f.render_widget(summary, area);
+}
+
+// "✓ 3  ~ 1  ✗ 0", colored like session outcomes
+fn tally_spans(tally: &ReviewTally) -> Vec<Span<'static>> {
+    [
+        (ReviewOutcome::Success, SessionOutcome::Success),
+        (ReviewOutcome::Partial, SessionOutcome::Partial),
+        (ReviewOutcome::Fail, SessionOutcome::Fail),
+    ]
+    .into_iter()
+    .map(|(review, session)| {
+        Span::styled(
+            format!(
+                "{} {}  ",
+                outcome::symbol(Some(session)),
+                tally.count(review)
+            ),
+            Style::default().fg(outcome::color(Some(session))),
+        )
+    })
+    .collect()
+}
+
+fn delta_span(delta: i32) -> Span<'static> {
+    let color = match delta {
+        d if d > 0 => Color::Green,
+        d if d < 0 => Color::Red,
+        _ => Color::Gray,
+    };
+    Span::styled(format!("{:+}", delta), Style::default().fg(color))
+}
@@ -0,0 +1,91 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Shown in place of line breaks so multi-line notes stay on one row
+pub const NEWLINE_GLYPH: char = '⏎';
+
+const ELLIPSIS: &str = "...";
+
+// Collapse line breaks to NEWLINE_GLYPH and tabs to a space
+pub fn single_line(s: &str) -> String {
+    s.replace("\r\n", "\n")
+        .chars()
+        .map(|c| match c {
+            '\n' | '\r' => NEWLINE_GLYPH,
+            '\t' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+// Single-line text cut to at most `max_width` columns, ending in "..." when
+// shortened. Widths are display widths, so wide characters count as two.
+pub fn truncate(s: &str, max_width: usize) -> String {
+    let line = single_line(s);
+    if line.width() <= max_width {
+        return line;
+    }
+
+    let budget = max_width.saturating_sub(ELLIPSIS.len());
+    let mut width = 0;
+    let mut out = String::new();
+    for c in line.chars() {
+        let w = c.width().unwrap_or(0);
+        if width + w > budget {
+            break;
+        }
+        width += w;
+        out.push(c);
+    }
+    out.push_str(&ELLIPSIS[..max_width.min(ELLIPSIS.len())]);
+    out
+}
+
+// Pad with spaces to `width` display columns
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Span;
+
+    #[test]
+    fn short_text_is_unchanged() {
+        assert_eq!(truncate("Ownership", 20), "Ownership");
+    }
+
+    #[test]
+    fn newlines_become_glyphs() {
+        assert_eq!(single_line("one\ntwo\r\nthree\rfour"), "one⏎two⏎three⏎four");
+        assert_eq!(truncate("a\nb", 10), "a⏎b");
+    }
+
+    #[test]
+    fn note_with_newlines_and_wide_chars_stays_within_width() {
+        let note = "学习笔记：所有权\n借用规则\r\n生命周期 and more text here";
+        for width in [3, 10, 15, 20, 40] {
+            let rendered = truncate(note, width);
+            assert!(!rendered.contains('\n') && !rendered.contains('\r'));
+            assert!(
+                Span::raw(rendered.as_str()).width() <= width,
+                "{:?} is wider than {}",
+                rendered,
+                width
+            );
+        }
+        assert!(truncate(note, 20).ends_with("..."));
+    }
+
+    #[test]
+    fn tiny_widths_do_not_panic() {
+        assert_eq!(truncate("abcdef", 0), "");
+        assert_eq!(truncate("abcdef", 2), "..");
+    }
+
+    #[test]
+    fn pad_counts_display_width() {
+        assert_eq!(pad_to_width("中", 4), "中  ");
+        assert_eq!(pad_to_width("toolong", 3), "toolong");
+    }
+}
@@ -0,0 +1,235 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+};
+
+// What the owner should do after a key has been handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    Submit,
+    Cancel,
+    Continue,
+}
+
+// Single-line text buffer with a cursor, shared by every TUI prompt
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    buffer: String,
+    // Cursor position in chars (not bytes), 0..=len
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0;
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.buffer = value.to_string();
+        self.cursor = self.len();
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> InputAction {
+        match key {
+            KeyCode::Enter => return InputAction::Submit,
+            KeyCode::Esc => return InputAction::Cancel,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            _ => {}
+        }
+        InputAction::Continue
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let idx = self.byte_index(self.cursor);
+        self.buffer.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    // Delete the char before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let idx = self.byte_index(self.cursor);
+        self.buffer.remove(idx);
+    }
+
+    // Delete the char under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor >= self.len() {
+            return;
+        }
+        let idx = self.byte_index(self.cursor);
+        self.buffer.remove(idx);
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    // Spans for the buffer with the cursor drawn as a highlighted cell
+    pub fn render(&self) -> Vec<Span<'_>> {
+        let cursor_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+
+        let idx = self.byte_index(self.cursor);
+        let (before, rest) = self.buffer.split_at(idx);
+        let mut chars = rest.chars();
+
+        let mut spans = vec![Span::raw(before)];
+        match chars.next() {
+            Some(c) => {
+                spans.push(Span::styled(c.to_string(), cursor_style));
+                spans.push(Span::raw(chars.as_str()));
+            }
+            None => spans.push(Span::styled("█", Style::default().fg(Color::Yellow))),
+        }
+        spans
+    }
+
+    fn len(&self) -> usize {
+        self.buffer.chars().count()
+    }
+
+    fn byte_index(&self, char_idx: usize) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(char_idx)
+            .map(|(i, _)| i)
+            .unwrap_or(self.buffer.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_with(value: &str) -> TextInput {
+        let mut input = TextInput::new();
+        input.set_value(value);
+        input
+    }
+
+    #[test]
+    fn typing_appends_at_end() {
+        let mut input = TextInput::new();
+        for c in "rust".chars() {
+            input.handle_key(KeyCode::Char(c));
+        }
+        assert_eq!(input.value(), "rust");
+        assert_eq!(input.cursor, 4);
+    }
+
+    #[test]
+    fn insert_in_middle() {
+        let mut input = input_with("rst");
+        input.move_left();
+        input.move_left();
+        input.handle_key(KeyCode::Char('u'));
+        assert_eq!(input.value(), "rust");
+        assert_eq!(input.cursor, 2);
+    }
+
+    #[test]
+    fn backspace_in_middle() {
+        let mut input = input_with("ruust");
+        input.move_left();
+        input.move_left();
+        input.handle_key(KeyCode::Backspace);
+        assert_eq!(input.value(), "rust");
+        assert_eq!(input.cursor, 2);
+    }
+
+    #[test]
+    fn delete_under_cursor() {
+        let mut input = input_with("ruxst");
+        input.handle_key(KeyCode::Home);
+        input.move_right();
+        input.move_right();
+        input.handle_key(KeyCode::Delete);
+        assert_eq!(input.value(), "rust");
+        assert_eq!(input.cursor, 2);
+    }
+
+    #[test]
+    fn backspace_at_start_is_noop() {
+        let mut input = input_with("rust");
+        input.handle_key(KeyCode::Home);
+        input.handle_key(KeyCode::Backspace);
+        assert_eq!(input.value(), "rust");
+        assert_eq!(input.cursor, 0);
+    }
+
+    #[test]
+    fn delete_at_end_is_noop() {
+        let mut input = input_with("rust");
+        input.handle_key(KeyCode::Delete);
+        assert_eq!(input.value(), "rust");
+    }
+
+    #[test]
+    fn cursor_movement_is_clamped() {
+        let mut input = input_with("ab");
+        input.move_right();
+        assert_eq!(input.cursor, 2);
+        input.move_left();
+        input.move_left();
+        input.move_left();
+        assert_eq!(input.cursor, 0);
+    }
+
+    #[test]
+    fn multibyte_chars_edit_by_char() {
+        let mut input = input_with("héllo");
+        input.handle_key(KeyCode::Home);
+        input.move_right();
+        input.move_right();
+        input.handle_key(KeyCode::Backspace);
+        assert_eq!(input.value(), "hllo");
+        input.handle_key(KeyCode::Char('é'));
+        assert_eq!(input.value(), "héllo");
+    }
+
+    #[test]
+    fn enter_and_esc_map_to_actions() {
+        let mut input = input_with("rust");
+        assert_eq!(input.handle_key(KeyCode::Enter), InputAction::Submit);
+        assert_eq!(input.handle_key(KeyCode::Esc), InputAction::Cancel);
+        assert_eq!(input.handle_key(KeyCode::Char('x')), InputAction::Continue);
+    }
+
+    #[test]
+    fn clear_resets_cursor() {
+        let mut input = input_with("rust");
+        input.clear();
+        assert!(input.is_empty());
+        assert_eq!(input.cursor, 0);
+    }
+}
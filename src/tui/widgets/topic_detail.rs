@@ -1,61 +1,98 @@
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate, Utc};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
-use crate::models::SessionOutcome;
-use crate::tui::App;
+use super::text::{single_line, truncate};
+use super::{centered, empty, markdown, outcome};
+use crate::models::{mastery_trend, recommend_action, TopicRow};
+use crate::settings::MAX_MASTERY;
+use crate::tui::{App, TranscriptView};
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     let Some(twp) = &app.selected_topic else {
         let block = Block::default()
             .borders(Borders::ALL)
             .title(" Topic Detail ");
-        let paragraph = Paragraph::new("No topic selected").block(block);
-        f.render_widget(paragraph, area);
+        empty::draw_hint(f, block, "No topic selected", area);
         return;
     };
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(6), // Header info
-            Constraint::Length(5), // Progress
-            Constraint::Length(6), // Unaddressed gaps
-            Constraint::Min(0),    // Sessions
+            Constraint::Length(header_height(twp)),    // Header info
+            Constraint::Length(6),                     // Progress
+            Constraint::Length(3),                     // Schedule
+            Constraint::Length(6),                     // Unaddressed gaps
+            Constraint::Length(resources_height(app)), // Resources
+            Constraint::Min(0),                        // Sessions
         ])
         .split(area);
 
-    draw_header(f, twp, chunks[0]);
-    draw_progress(f, twp, chunks[1]);
-    draw_gaps(f, app, chunks[2]);
-    draw_sessions(f, app, chunks[3]);
+    draw_header(f, app, twp, chunks[0]);
+    draw_progress(
+        f,
+        twp,
+        app.selected_topic_mastery_date.as_deref(),
+        &app.selected_topic_trajectory,
+        chunks[1],
+    );
+    draw_schedule(f, app, twp, chunks[2]);
+    draw_gaps(f, app, chunks[3]);
+    draw_resources(f, app, chunks[4]);
+    draw_sessions(f, app, chunks[5]);
 }
 
-fn draw_header(f: &mut Frame, twp: &crate::models::TopicWithProgress, area: Rect) {
-    let description = twp.topic.description.as_deref().unwrap_or("No description");
+// Multi-line descriptions grow the header up to this many lines
+const MAX_DESCRIPTION_LINES: usize = 6;
+
+fn description_lines(twp: &crate::models::TopicWithProgress) -> Vec<Line<'static>> {
+    let description = twp
+        .topic
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or("No description");
+    let mut lines = markdown::lines(description, Style::default().fg(Color::White));
+    lines.truncate(MAX_DESCRIPTION_LINES);
+    lines
+}
 
+// Borders, the description, a blank line, the tags and the next action
+fn header_height(twp: &crate::models::TopicWithProgress) -> u16 {
+    description_lines(twp).len().max(1) as u16 + 5
+}
+
+fn draw_header(f: &mut Frame, app: &App, twp: &crate::models::TopicWithProgress, area: Rect) {
     let tags = if twp.topic.tags.is_empty() {
         "None".to_string()
     } else {
         twp.topic.tags.join(", ")
     };
 
-    let text = vec![
-        Line::from(vec![
-            Span::styled("Description: ", Style::default().fg(Color::Gray)),
-            Span::styled(description, Style::default().fg(Color::White)),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("Tags: ", Style::default().fg(Color::Gray)),
-            Span::styled(tags, Style::default().fg(Color::Cyan)),
-        ]),
-    ];
+    let mut text = description_lines(twp);
+    let label = Span::styled("Description: ", Style::default().fg(Color::Gray));
+    match text.first_mut() {
+        Some(first) => first.spans.insert(0, label),
+        None => text.push(Line::from(label)),
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(vec![
+        Span::styled("Tags: ", Style::default().fg(Color::Gray)),
+        Span::styled(tags, Style::default().fg(Color::Cyan)),
+    ]));
+    text.push(Line::from(vec![
+        Span::styled("Next: ", Style::default().fg(Color::Gray)),
+        Span::styled(
+            recommend_action(twp, &app.selected_topic_gaps, Utc::now()).to_string(),
+            Style::default().fg(Color::Green),
+        ),
+    ]));
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -66,44 +103,32 @@ fn draw_header(f: &mut Frame, twp: &crate::models::TopicWithProgress, area: Rect
                 .add_modifier(Modifier::BOLD),
         );
 
-    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
     f.render_widget(paragraph, area);
 }
 
-fn draw_progress(f: &mut Frame, twp: &crate::models::TopicWithProgress, area: Rect) {
+fn draw_progress(
+    f: &mut Frame,
+    twp: &crate::models::TopicWithProgress,
+    mastery_date: Option<&str>,
+    trajectory: &[i32],
+    area: Rect,
+) {
     let progress = &twp.progress;
-    let mastery_bar = create_mastery_bar(progress.mastery_level);
+    let row = TopicRow::from(twp);
     let success_rate = progress.success_rate();
 
-    let next_review = match &progress.next_review {
-        Some(date_str) => {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-                dt.format("%b %d, %Y").to_string()
-            } else {
-                "Unknown".to_string()
-            }
-        }
-        None => "Not set".to_string(),
-    };
-
     let text = vec![
         Line::from(vec![
             Span::styled("Mastery: ", Style::default().fg(Color::Gray)),
-            Span::styled(mastery_bar, Style::default().fg(Color::Green)),
+            Span::styled(row.mastery_bar, Style::default().fg(Color::Green)),
             Span::styled(
-                format!(
-                    " {}/5 ({})",
-                    progress.mastery_level,
-                    progress.mastery_label()
-                ),
+                format!(" {}/5 ({})", row.mastery_level, row.mastery_label),
                 Style::default().fg(Color::Yellow),
             ),
             Span::raw("  "),
             Span::styled("Skill: ", Style::default().fg(Color::Gray)),
-            Span::styled(
-                progress.skill_level.label(),
-                Style::default().fg(Color::Cyan),
-            ),
+            Span::styled(row.skill_label, Style::default().fg(Color::Cyan)),
         ]),
         Line::from(vec![
             Span::styled("Reviews: ", Style::default().fg(Color::Gray)),
@@ -125,8 +150,10 @@ fn draw_progress(f: &mut Frame, twp: &crate::models::TopicWithProgress, area: Re
             ),
             Span::raw("  "),
             Span::styled("Next: ", Style::default().fg(Color::Gray)),
-            Span::styled(next_review, Style::default().fg(Color::White)),
+            Span::styled(row.next_review_long, Style::default().fg(Color::White)),
         ]),
+        mastery_projection_line(progress.mastery_level, mastery_date),
+        trend_line(trajectory),
     ];
 
     let block = Block::default()
@@ -138,6 +165,105 @@ fn draw_progress(f: &mut Frame, twp: &crate::models::TopicWithProgress, area: Re
     f.render_widget(paragraph, area);
 }
 
+// Most recent levels shown in the mastery trend
+const TREND_LEVELS_SHOWN: usize = 15;
+
+// "Trend: 1→2→1→2", the mastery after each review
+fn trend_line(trajectory: &[i32]) -> Line<'static> {
+    let trend = if trajectory.is_empty() {
+        Span::styled("Not reviewed yet", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::styled(
+            mastery_trend(trajectory, TREND_LEVELS_SHOWN),
+            Style::default().fg(Color::White),
+        )
+    };
+    Line::from(vec![
+        Span::styled("Trend: ", Style::default().fg(Color::Gray)),
+        trend,
+    ])
+}
+
+// "Mastery by: ~Aug 12, 2024 if you keep succeeding"
+fn mastery_projection_line(mastery_level: i32, mastery_date: Option<&str>) -> Line<'static> {
+    let projection = match mastery_date {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|d| format!("~{} if you keep succeeding", d.format("%b %d, %Y")))
+            .unwrap_or_else(|_| date.to_string()),
+        None if mastery_level >= MAX_MASTERY => "Mastered".to_string(),
+        None => "Not projected".to_string(),
+    };
+    Line::from(vec![
+        Span::styled("Mastery by: ", Style::default().fg(Color::Gray)),
+        Span::styled(projection, Style::default().fg(Color::Green)),
+    ])
+}
+
+// Most recent past intervals shown in the schedule panel
+const SCHEDULE_INTERVALS_SHOWN: usize = 8;
+
+// Past review intervals as bars scaled to their length, then the projected
+// next interval, so the schedule can be seen stretching out
+fn draw_schedule(f: &mut Frame, app: &App, twp: &crate::models::TopicWithProgress, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Schedule ")
+        .title_style(Style::default().fg(Color::Green));
+
+    let intervals = &app.selected_topic_intervals;
+    let past = &intervals[intervals.len().saturating_sub(SCHEDULE_INTERVALS_SHOWN)..];
+    let next = twp.progress.scheduled_interval_days();
+    if past.is_empty() && next.is_none() {
+        let paragraph = Paragraph::new("Not reviewed yet")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let labels: Vec<String> = past
+        .iter()
+        .map(|days| format!(" {}d ", days))
+        .chain(next.map(|days| format!(" {}d next", days)))
+        .collect();
+    let days: Vec<i64> = past.iter().copied().chain(next).collect();
+    let label_width: usize = labels.iter().map(|l| l.len()).sum();
+    let budget = (area.width as usize).saturating_sub(2 + label_width);
+
+    let mut spans = Vec::new();
+    for (i, (len, label)) in bar_lengths(&days, budget)
+        .into_iter()
+        .zip(labels)
+        .enumerate()
+    {
+        let (bar, color) = if i < past.len() {
+            ("━", Color::Green)
+        } else {
+            ("┄", Color::Yellow)
+        };
+        spans.push(Span::styled(bar.repeat(len), Style::default().fg(color)));
+        spans.push(Span::styled(label, Style::default().fg(Color::Gray)));
+    }
+
+    f.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+// Split `budget` columns between the values in proportion to their size,
+// giving every value at least one column
+fn bar_lengths(values: &[i64], budget: usize) -> Vec<usize> {
+    let total: i64 = values.iter().map(|v| v.max(&0)).sum();
+    values
+        .iter()
+        .map(|&v| {
+            if total == 0 {
+                1
+            } else {
+                ((v.max(0) as usize * budget) / total as usize).max(1)
+            }
+        })
+        .collect()
+}
+
 fn draw_gaps(f: &mut Frame, app: &App, area: Rect) {
     let items: Vec<ListItem> = app
         .selected_topic_gaps
@@ -146,7 +272,10 @@ fn draw_gaps(f: &mut Frame, app: &App, area: Rect) {
         .map(|gap| {
             ListItem::new(Line::from(vec![
                 Span::styled("• ", Style::default().fg(Color::Red)),
-                Span::styled(&gap.gap_description, Style::default().fg(Color::White)),
+                Span::styled(
+                    single_line(&gap.gap_description),
+                    Style::default().fg(Color::White),
+                ),
             ]))
         })
         .collect();
@@ -168,16 +297,106 @@ fn draw_gaps(f: &mut Frame, app: &App, area: Rect) {
             .block(block);
         f.render_widget(paragraph, area);
     } else {
-        let list = List::new(items).block(block);
-        f.render_widget(list, area);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol("> ");
+        let mut state = ListState::default();
+        state.select(app.selected_topic_sessions.selected);
+        f.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+// Resources listed before the panel stops growing and scrolls
+const MAX_RESOURCES_SHOWN: usize = 4;
+
+fn resources_height(app: &App) -> u16 {
+    app.selected_topic_resources
+        .items
+        .len()
+        .clamp(1, MAX_RESOURCES_SHOWN) as u16
+        + 2
+}
+
+fn draw_resources(f: &mut Frame, app: &App, area: Rect) {
+    let resources = &app.selected_topic_resources;
+    let title = match &app.resource_error {
+        Some(error) => format!(" Resources - {} ", error),
+        None => format!(" Resources ({}) ", resources.items.len()),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(Style::default().fg(if app.resource_error.is_some() {
+            Color::Red
+        } else {
+            Color::Blue
+        }));
+
+    if resources.items.is_empty() {
+        let paragraph = Paragraph::new("No links yet (feynman topic resource add)")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(paragraph, area);
+        return;
     }
+
+    let items: Vec<ListItem> = resources
+        .items
+        .iter()
+        .map(|resource| {
+            let mut spans = vec![Span::styled(
+                resource.title().to_string(),
+                Style::default().fg(Color::White),
+            )];
+            if resource.label.is_some() {
+                spans.push(Span::styled(
+                    format!("  {}", resource.url),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .highlight_symbol("> ");
+    let mut state = ListState::default();
+    state.select(resources.selected);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+// The selected session's transcript in a scrollable box over the detail view
+pub fn draw_transcript(f: &mut Frame, view: &TranscriptView, area: Rect) {
+    let popup = centered(area, 80, 80);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Session {} Transcript ", view.session_id))
+        .title_style(
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let paragraph = match &view.text {
+        Some(text) => Paragraph::new(text.as_str())
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: false })
+            .scroll((view.scroll, 0)),
+        None => Paragraph::new("No transcript recorded for this session.")
+            .style(Style::default().fg(Color::DarkGray)),
+    };
+
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph.block(block), popup);
 }
 
 fn draw_sessions(f: &mut Frame, app: &App, area: Rect) {
     let items: Vec<ListItem> = app
         .selected_topic_sessions
+        .items
         .iter()
-        .take(10)
         .map(|session| {
             let date = format_date(&session.started_at);
             let session_type = match session.session_type {
@@ -185,13 +404,8 @@ fn draw_sessions(f: &mut Frame, app: &App, area: Rect) {
                 crate::models::SessionType::Socratic => "Socratic",
             };
 
-            let (outcome_text, outcome_color) = match &session.outcome {
-                Some(SessionOutcome::Success) => ("Success ", Color::Green),
-                Some(SessionOutcome::Partial) => ("Partial ", Color::Yellow),
-                Some(SessionOutcome::Fail) => ("Fail    ", Color::Red),
-                Some(SessionOutcome::Abandoned) => ("Abandoned", Color::DarkGray),
-                None => ("Active  ", Color::Cyan),
-            };
+            let outcome_text = outcome::cell(session.outcome, app.options.compact_symbols);
+            let outcome_color = outcome::color(session.outcome);
 
             let summary = session
                 .summary
@@ -209,18 +423,22 @@ fn draw_sessions(f: &mut Frame, app: &App, area: Rect) {
                     Style::default().fg(Color::Cyan),
                 ),
                 Span::styled(
-                    format!("{:<10}", outcome_text),
-                    Style::default().fg(outcome_color),
+                    outcome::duration_cell(session),
+                    Style::default().fg(Color::Gray),
                 ),
+                Span::styled(outcome_text, Style::default().fg(outcome_color)),
                 Span::styled(summary, Style::default().fg(Color::Gray)),
             ]))
         })
         .collect();
 
-    let title = if app.selected_topic_sessions.is_empty() {
+    let title = if app.selected_topic_sessions.items.is_empty() {
         " Recent Sessions (none) ".to_string()
     } else {
-        format!(" Recent Sessions ({}) ", app.selected_topic_sessions.len())
+        format!(
+            " Recent Sessions ({}) ",
+            app.selected_topic_sessions.items.len()
+        )
     };
 
     let block = Block::default()
@@ -239,12 +457,6 @@ fn draw_sessions(f: &mut Frame, app: &App, area: Rect) {
     }
 }
 
-fn create_mastery_bar(level: i32) -> String {
-    let filled = level as usize;
-    let empty = 5 - filled;
-    format!("{}{}", "█".repeat(filled), "░".repeat(empty))
-}
-
 fn format_date(date_str: &str) -> String {
     if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
         dt.format("%b %d").to_string()
@@ -253,10 +465,36 @@ fn format_date(date_str: &str) -> String {
     }
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_scale_with_interval_length() {
+        assert_eq!(bar_lengths(&[1, 2, 4, 8], 30), vec![2, 4, 8, 16]);
+    }
+
+    #[test]
+    fn every_bar_gets_a_column() {
+        assert_eq!(bar_lengths(&[0, 1, 100], 10), vec![1, 1, 9]);
+        assert_eq!(bar_lengths(&[0, 0], 10), vec![1, 1]);
+        assert_eq!(bar_lengths(&[3, 4], 0), vec![1, 1]);
+    }
+
+    #[test]
+    fn projection_line_shows_the_date_or_why_not() {
+        let text = |level, date| -> String {
+            mastery_projection_line(level, date)
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect()
+        };
+        assert_eq!(
+            text(2, Some("2024-08-12")),
+            "Mastery by: ~Aug 12, 2024 if you keep succeeding"
+        );
+        assert_eq!(text(5, None), "Mastery by: Mastered");
+        assert_eq!(text(2, None), "Mastery by: Not projected");
     }
 }
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -7,6 +7,9 @@ use ratatui::{
     Frame,
 };
 
+use super::empty;
+use super::text::{pad_to_width, truncate};
+use crate::models::TopicRow;
 use crate::tui::App;
 
 pub fn draw(f: &mut Frame, app: &App, area: Rect) {
@@ -16,36 +19,42 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         " Topics ".to_string()
     };
 
+    let now = Utc::now();
     let items: Vec<ListItem> = app
         .topics
         .items
         .iter()
         .map(|twp| {
-            let mastery_bar = create_mastery_bar(twp.progress.mastery_level);
-            let skill_label = twp.progress.skill_level.label();
-            let next_review = format_next_review(&twp.progress.next_review);
-
-            let (next_color, next_text) = if is_overdue(&twp.progress.next_review) {
-                (Color::Red, format!("{} !", next_review))
+            let row = TopicRow::new(twp, now);
+            let (next_color, next_text) = if row.overdue {
+                (Color::Red, format!("{} !", row.next_review))
             } else {
-                (Color::White, next_review)
+                (Color::White, row.next_review)
             };
+            let health = twp.progress.health_score_with(now, &app.health_weights);
 
             ListItem::new(Line::from(vec![
                 Span::styled(
-                    format!("{:<30}", truncate(&twp.topic.name, 28)),
+                    pad_to_width(&truncate(&row.name, 28), 30),
                     Style::default().fg(Color::White),
                 ),
-                Span::styled(mastery_bar, Style::default().fg(Color::Green)),
+                Span::styled(row.mastery_bar, Style::default().fg(Color::Green)),
                 Span::styled(
-                    format!(" {} ", twp.progress.mastery_level),
+                    format!(" {} ", row.mastery_level),
                     Style::default().fg(Color::Yellow),
                 ),
                 Span::styled(
-                    format!("{:<12}", skill_label),
+                    format!("{:<12}", row.skill_label),
                     Style::default().fg(Color::Cyan),
                 ),
-                Span::styled(next_text, Style::default().fg(next_color)),
+                Span::styled(
+                    pad_to_width(&next_text, 14),
+                    Style::default().fg(next_color),
+                ),
+                Span::styled(
+                    format!("{:>6.0}", health),
+                    Style::default().fg(health_color(health)),
+                ),
             ]))
         })
         .collect();
@@ -55,6 +64,18 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
         .title(title)
         .title_style(Style::default().fg(Color::Cyan));
 
+    if items.is_empty() {
+        let hint = match &app.filter_tag {
+            Some(tag) => format!(
+                "No topics tagged '{}' — press <Esc> to clear the filter",
+                tag
+            ),
+            None => "No topics yet — add one with `feynman topic add`".to_string(),
+        };
+        empty::draw_hint(f, block, &hint, area);
+        return;
+    }
+
     // Header
     let header = Line::from(vec![
         Span::styled(
@@ -76,7 +97,13 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
-            "Next Review",
+            format!("{:<14}", "Next Review"),
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            "Health",
             Style::default()
                 .fg(Color::DarkGray)
                 .add_modifier(Modifier::BOLD),
@@ -115,42 +142,13 @@ pub fn draw(f: &mut Frame, app: &App, area: Rect) {
     f.render_stateful_widget(list, list_area, &mut state);
 }
 
-fn create_mastery_bar(level: i32) -> String {
-    let filled = level as usize;
-    let empty = 5 - filled;
-    format!("{}{}", "█".repeat(filled), "░".repeat(empty))
-}
-
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
+// Green when healthy, red when a topic needs attention
+fn health_color(score: f64) -> Color {
+    if score >= 70.0 {
+        Color::Green
+    } else if score >= 40.0 {
+        Color::Yellow
     } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
-    }
-}
-
-fn format_next_review(next_review: &Option<String>) -> String {
-    match next_review {
-        Some(date_str) => {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-                dt.format("%b %d").to_string()
-            } else {
-                "Unknown".to_string()
-            }
-        }
-        None => "Not set".to_string(),
-    }
-}
-
-fn is_overdue(next_review: &Option<String>) -> bool {
-    match next_review {
-        Some(date_str) => {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-                dt.with_timezone(&Utc) < Utc::now()
-            } else {
-                false
-            }
-        }
-        None => false,
+        Color::Red
     }
 }